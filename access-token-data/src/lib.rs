@@ -2,3 +2,5 @@ pub const SESSION_ID_JWT_CLAIM_NAME: &str = "session_id";
 pub const SESSION_KIND_JWT_CLAIM_NAME: &str = "session_kind";
 pub const SESSION_KIND_API: &str = "api";
 pub const SESSION_KIND_WEB: &str = "web";
+/// Space-separated scopes, absent from the unrestricted tokens
+pub const SCOPE_JWT_CLAIM_NAME: &str = "scope";
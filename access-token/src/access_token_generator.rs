@@ -1,5 +1,5 @@
-use access_token_data::{SESSION_ID_JWT_CLAIM_NAME, SESSION_KIND_API, SESSION_KIND_JWT_CLAIM_NAME, SESSION_KIND_WEB};
-use data::{SessionKind, UsernameStr};
+use access_token_data::{SCOPE_JWT_CLAIM_NAME, SESSION_ID_JWT_CLAIM_NAME, SESSION_KIND_API, SESSION_KIND_JWT_CLAIM_NAME, SESSION_KIND_WEB};
+use data::{format_scopes, Scope, SessionKind, UsernameStr};
 use josekit::jwk::Jwk;
 use josekit::jws::{EdDSA, JwsHeader};
 use josekit::{JoseError, jwt};
@@ -7,24 +7,36 @@ use josekit::jwt::JwtPayload;
 use log::{debug, log_enabled};
 use serde_json::Value;
 use thiserror::Error;
+use std::collections::BTreeSet;
 use std::io;
+use std::sync::{Arc, RwLock};
 use std::time::SystemTime;
 use josekit::jws::alg::eddsa::EddsaJwsSigner;
 use uuid::Uuid;
 
+/// Clones share the key, replaced for all of them at once
+#[derive(Clone)]
 pub struct AccessTokenGenerator {
-    signer: EddsaJwsSigner,
+    signer: Arc<RwLock<EddsaJwsSigner>>,
 }
 
 impl AccessTokenGenerator {
+    /// The key's `kid`, if any, is put into the tokens' headers
     pub fn from_jwk(key: &Jwk) -> Result<Self, AccessTokenGeneratorError> {
         Ok(
             AccessTokenGenerator {
-                signer: EdDSA.signer_from_jwk(key)?,
+                signer: Arc::new(RwLock::new(EdDSA.signer_from_jwk(key)?)),
             }
         )
     }
 
+    pub fn replace_key(&self, key: &Jwk) -> Result<(), AccessTokenGeneratorError> {
+        let signer = EdDSA.signer_from_jwk(key)?;
+        *self.signer.write().unwrap() = signer;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn generate_token(
         &self,
         session_id: Uuid,
@@ -32,6 +44,7 @@ impl AccessTokenGenerator {
         not_before: &SystemTime,
         expires_at: &SystemTime,
         session_kind: SessionKind,
+        scopes: Option<&BTreeSet<Scope>>,
     ) -> Result<String, AccessTokenGeneratorError> {
         let mut payload = JwtPayload::new();
         let subject = username.to_string();
@@ -56,13 +69,19 @@ impl AccessTokenGenerator {
                 }
             ),
         )?;
+        if let Some(scopes) = scopes {
+            payload.set_claim(
+                SCOPE_JWT_CLAIM_NAME,
+                Some(Value::from(format_scopes(scopes))),
+            )?;
+        }
         payload.set_not_before(not_before);
         payload.set_expires_at(expires_at);
 
         let token = jwt::encode_with_signer(
             &payload,
             &JwsHeader::new(),
-            &self.signer,
+            &*self.signer.read().unwrap(),
         )?;
         debug!(
             "access token generated with subject {subject}, \
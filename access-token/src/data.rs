@@ -1,6 +1,7 @@
+use std::collections::BTreeSet;
 use time::OffsetDateTime;
 use uuid::Uuid;
-use data::{SessionKind, UsernameString};
+use data::{Scope, SessionKind, UsernameString};
 
 #[derive(Debug, Clone)]
 pub struct AccessTokenData {
@@ -9,4 +10,6 @@ pub struct AccessTokenData {
     pub not_before: OffsetDateTime,
     pub expires_at: OffsetDateTime,
     pub session_kind: SessionKind,
+    /// `None` when the token isn't limited to some scopes
+    pub scopes: Option<BTreeSet<Scope>>,
 }
@@ -1,30 +1,55 @@
 use crate::data::AccessTokenData;
-use access_token_data::{SESSION_ID_JWT_CLAIM_NAME, SESSION_KIND_API, SESSION_KIND_JWT_CLAIM_NAME, SESSION_KIND_WEB};
-use data::{SessionKind, UsernameParseError, UsernameString};
-use josekit::jwk::Jwk;
+use access_token_data::{SCOPE_JWT_CLAIM_NAME, SESSION_ID_JWT_CLAIM_NAME, SESSION_KIND_API, SESSION_KIND_JWT_CLAIM_NAME, SESSION_KIND_WEB};
+use data::{parse_scopes, ScopeParseError, SessionKind, UsernameParseError, UsernameString};
+use josekit::jwk::{Jwk, JwkSet};
 use josekit::{JoseError, jwt};
 use log::info;
 use thiserror::Error;
 use std::io;
 use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
 use josekit::jws::alg::eddsa::EddsaJwsVerifier;
-use josekit::jws::EdDSA;
+use josekit::jws::{EdDSA, JwsVerifier};
+use crate::key_set::get_retired_until;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+/// Clones share the keys, replaced for all of them at once
+#[derive(Clone)]
 pub struct AccessTokenDecoder {
+    verifiers: Arc<RwLock<Vec<KeyVerifier>>>,
+}
+
+struct KeyVerifier {
     verifier: EddsaJwsVerifier,
+    retired_until: Option<SystemTime>,
 }
 
 impl AccessTokenDecoder {
     pub fn from_jwk(jwk: &Jwk) -> Result<Self, AccessTokenDecoderError> {
+        let mut keys = JwkSet::new();
+        keys.push_key(jwk.clone());
+        Self::from_jwk_set(&keys)
+    }
+
+    /// The tokens are verified by the key with the same `kid`, or by
+    /// the first key without one if the token has none. The retired
+    /// keys stop verifying anything once their time is up.
+    pub fn from_jwk_set(keys: &JwkSet) -> Result<Self, AccessTokenDecoderError> {
         Ok(
             AccessTokenDecoder {
-                verifier: EdDSA.verifier_from_jwk(jwk)?,
+                verifiers: Arc::new(RwLock::new(make_verifiers(keys)?)),
             }
         )
     }
 
+    pub fn replace_keys(&self, keys: &JwkSet) -> Result<(), AccessTokenDecoderError> {
+        let verifiers = make_verifiers(keys)?;
+        *self.verifiers.write().unwrap() = verifiers;
+        Ok(())
+    }
+
     /// Decode the access token.
     ///
     /// # Arguments
@@ -37,10 +62,19 @@ impl AccessTokenDecoder {
         token: impl AsRef<[u8]>,
     ) -> Result<AccessTokenData, AccessTokenDecoderError> {
         let token = token.as_ref();
-        let (payload, _) = jwt::decode_with_verifier(
+        let now = SystemTime::now();
+        let verifiers = self.verifiers.read().unwrap();
+        let (payload, _) = jwt::decode_with_verifier_selector(
             token,
-            &self.verifier,
+            |header| Ok(
+                verifiers
+                    .iter()
+                    .filter(|v| v.retired_until.is_none_or(|until| now < until))
+                    .find(|v| v.verifier.key_id() == header.key_id())
+                    .map(|v| &v.verifier as &dyn JwsVerifier)
+            ),
         )?;
+        drop(verifiers);
         let session_id = payload.claim(SESSION_ID_JWT_CLAIM_NAME)
             .map(|v| serde_json::from_value::<Uuid>(v.clone()))
             .transpose()
@@ -63,6 +97,13 @@ impl AccessTokenDecoder {
                 }
                 Err(e) => Err(e),
             })??;
+        let scopes = payload.claim(SCOPE_JWT_CLAIM_NAME)
+            .map(|v| v
+                .as_str()
+                .ok_or_else(|| ScopeParseError(v.to_string()))
+                .and_then(parse_scopes)
+            )
+            .transpose()?;
         let username = payload.subject()
             .map(UsernameString::from_str)
             .transpose()?
@@ -80,11 +121,29 @@ impl AccessTokenDecoder {
                 not_before,
                 expires_at,
                 session_kind,
+                scopes,
             }
         )
     }
 }
 
+fn make_verifiers(keys: &JwkSet) -> Result<Vec<KeyVerifier>, AccessTokenDecoderError> {
+    let verifiers = keys
+        .keys()
+        .into_iter()
+        .map(|key| Ok(
+            KeyVerifier {
+                verifier: EdDSA.verifier_from_jwk(key)?,
+                retired_until: get_retired_until(key),
+            }
+        ))
+        .collect::<Result<Vec<_>, JoseError>>()?;
+    if verifiers.is_empty() {
+        return Err(AccessTokenDecoderError::NoKeys)
+    }
+    Ok(verifiers)
+}
+
 fn missing_field(token: &[u8], part: &'static str) -> AccessTokenDecoderError {
     info!(
         "missing field {part} in access token {}",
@@ -114,4 +173,10 @@ pub enum AccessTokenDecoderError {
 
     #[error("invalid session kind: {0}")]
     InvalidTokenKind(String),
+
+    #[error("invalid scopes: {0}")]
+    InvalidScope(#[from] ScopeParseError),
+
+    #[error("the key set is empty")]
+    NoKeys,
 }
@@ -0,0 +1,51 @@
+use std::time::{Duration, SystemTime};
+use josekit::jwk::{Jwk, JwkSet};
+use josekit::{JoseError, Map, Value};
+
+// seconds since the epoch, set on the previous keys when rotating
+const RETIRED_UNTIL_JWK_PARAMETER_NAME: &str = "exp";
+
+/// Parses a JWK set, taking a single JWK as a set of one
+pub fn parse_jwk_set(
+    bytes: impl AsRef<[u8]>,
+) -> Result<JwkSet, JoseError> {
+    let map: Map<String, Value> = serde_json::from_slice(bytes.as_ref())
+        .map_err(|e| JoseError::InvalidJwkFormat(e.into()))?;
+    if map.contains_key("keys") {
+        JwkSet::from_map(map)
+    } else {
+        let mut set = JwkSet::new();
+        set.push_key(Jwk::from_map(map)?);
+        Ok(set)
+    }
+}
+
+/// Marks a public key as only verifying the tokens until `until`
+pub fn retire_key(
+    key: &mut Jwk,
+    until: SystemTime,
+) -> Result<(), JoseError> {
+    let until = until
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    key.set_parameter(
+        RETIRED_UNTIL_JWK_PARAMETER_NAME,
+        Some(Value::from(until)),
+    )
+}
+
+pub fn get_retired_until(key: &Jwk) -> Option<SystemTime> {
+    key.parameter(RETIRED_UNTIL_JWK_PARAMETER_NAME)
+        .map(|until| until
+            .as_u64()
+            // a malformed value is as good as expired
+            .map_or(SystemTime::UNIX_EPOCH, |until|
+                SystemTime::UNIX_EPOCH + Duration::from_secs(until)
+            )
+        )
+}
+
+pub fn is_key_expired(key: &Jwk, now: SystemTime) -> bool {
+    get_retired_until(key).is_some_and(|until| until <= now)
+}
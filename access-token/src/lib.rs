@@ -1,10 +1,12 @@
 mod access_token_generator;
 mod data;
 mod decoder;
+mod key_set;
 mod validator;
 
 pub use data::AccessTokenData;
 pub use decoder::AccessTokenDecoder;
 pub use decoder::AccessTokenDecoderError;
+pub use key_set::{get_retired_until, is_key_expired, parse_jwk_set, retire_key};
 pub use validator::{AccessTokenValidator, AccessTokenValidatorError};
 pub use access_token_generator::{AccessTokenGenerator, AccessTokenGeneratorError};
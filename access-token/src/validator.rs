@@ -41,7 +41,7 @@ impl AccessTokenValidator {
                 "expired valid token for user \"{}\"",
                 access_token.username,
             );
-            Err(AccessTokenValidatorError::ExpiredToken(access_token.clone()))
+            Err(AccessTokenValidatorError::ExpiredToken(Box::new(access_token.clone())))
         } else {
             trace!("valid token for user \"{}\"", access_token.username);
             Ok(())
@@ -55,5 +55,5 @@ pub enum AccessTokenValidatorError {
     InvalidToken(AccessTokenDecoderError),
 
     #[error("expired token")]
-    ExpiredToken(AccessTokenData),
+    ExpiredToken(Box<AccessTokenData>),
 }
@@ -2,6 +2,7 @@ pub mod header {
     use crate::http::status::Unauthorized;
     use rocket::http::Header;
     use rocket::Responder;
+    use std::time::Duration;
 
     pub struct WwwAuthenticate(Unauthorized);
 
@@ -32,6 +33,180 @@ pub mod header {
             }
         }
     }
+
+    pub struct RetryAfter(Duration);
+
+    impl From<RetryAfter> for Header<'static> {
+        fn from(value: RetryAfter) -> Self {
+            Header::new("Retry-After", value.0.as_secs().to_string())
+        }
+    }
+
+    #[derive(Responder)]
+    #[response(status = 429)]
+    pub struct TooManyRequestsResponse {
+        empty: (),
+        retry_after: RetryAfter,
+    }
+
+    impl From<Duration> for TooManyRequestsResponse {
+        fn from(value: Duration) -> Self {
+            TooManyRequestsResponse {
+                empty: Default::default(),
+                retry_after: RetryAfter(value),
+            }
+        }
+    }
+}
+
+pub mod query {
+    use std::fmt;
+    use std::str::FromStr;
+
+    use data::{NoteListOrder, NoteMetadata, NoteVersion, SyncToken};
+    use time::UtcDateTime;
+    use uuid::Uuid;
+
+    /// Position in a note list, opaque to the clients
+    #[derive(Clone, Copy, Debug)]
+    pub struct NoteListCursor(pub NoteMetadata);
+
+    #[derive(Debug)]
+    pub struct InvalidNoteListCursor;
+
+    impl fmt::Display for NoteListCursor {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}.{}", self.0.mtime.unix_timestamp(), self.0.id.simple())
+        }
+    }
+
+    impl FromStr for NoteListCursor {
+        type Err = InvalidNoteListCursor;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let (mtime, id) = s.split_once('.').ok_or(InvalidNoteListCursor)?;
+            Ok(
+                NoteListCursor(
+                    NoteMetadata {
+                        id: Uuid::try_parse(id).map_err(|_| InvalidNoteListCursor)?,
+                        mtime: mtime.parse::<i64>()
+                            .ok()
+                            .and_then(|v| UtcDateTime::from_unix_timestamp(v).ok())
+                            .ok_or(InvalidNoteListCursor)?,
+                    }
+                )
+            )
+        }
+    }
+
+    /// Sync token as handed to the clients, opaque to them
+    #[derive(Clone, Copy, Debug)]
+    pub struct SyncTokenParam(pub SyncToken);
+
+    #[derive(Debug)]
+    pub struct InvalidSyncToken;
+
+    const SNAPSHOT_SEPARATOR: &str = ".s";
+
+    impl fmt::Display for SyncTokenParam {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}.{}", self.0.generation.simple(), self.0.seq)?;
+            if let Some(start) = self.0.snapshot_start {
+                write!(f, "{SNAPSHOT_SEPARATOR}{start}")?;
+            }
+            Ok(())
+        }
+    }
+
+    impl FromStr for SyncTokenParam {
+        type Err = InvalidSyncToken;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let (s, snapshot_start) = match s.split_once(SNAPSHOT_SEPARATOR) {
+                Some((s, start)) => (
+                    s,
+                    Some(start.parse().map_err(|_| InvalidSyncToken)?),
+                ),
+                None => (s, None),
+            };
+            let (generation, seq) = s.split_once('.').ok_or(InvalidSyncToken)?;
+            Ok(
+                SyncTokenParam(
+                    SyncToken {
+                        generation: Uuid::try_parse(generation)
+                            .map_err(|_| InvalidSyncToken)?,
+                        seq: seq.parse().map_err(|_| InvalidSyncToken)?,
+                        snapshot_start,
+                    }
+                )
+            )
+        }
+    }
+
+    /// Note version as handed to the clients, opaque to them
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct NoteVersionParam(pub NoteVersion);
+
+    #[derive(Debug)]
+    pub struct InvalidNoteVersion;
+
+    impl fmt::Display for NoteVersionParam {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}.{}.{}", self.0.ino, self.0.ctime, self.0.ctime_nsec)
+        }
+    }
+
+    impl FromStr for NoteVersionParam {
+        type Err = InvalidNoteVersion;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let mut parts = s.splitn(3, '.');
+            let mut next = || parts.next().ok_or(InvalidNoteVersion);
+            Ok(
+                NoteVersionParam(
+                    NoteVersion {
+                        ino: next()?.parse().map_err(|_| InvalidNoteVersion)?,
+                        ctime: next()?.parse().map_err(|_| InvalidNoteVersion)?,
+                        ctime_nsec: next()?.parse().map_err(|_| InvalidNoteVersion)?,
+                    }
+                )
+            )
+        }
+    }
+
+    #[derive(Clone, Copy, Debug)]
+    pub struct NoteListOrderParam(pub NoteListOrder);
+
+    #[derive(Debug)]
+    pub struct InvalidNoteListOrder;
+
+    impl FromStr for NoteListOrderParam {
+        type Err = InvalidNoteListOrder;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "oldest" => Ok(NoteListOrderParam(NoteListOrder::OldestFirst)),
+                "newest" => Ok(NoteListOrderParam(NoteListOrder::NewestFirst)),
+                _ => Err(InvalidNoteListOrder),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum NoteFormatParam {
+        Protobuf,
+        Html,
+    }
+
+    #[derive(Debug)]
+    pub struct InvalidNoteFormat;
+
+    impl FromStr for NoteFormatParam {
+        type Err = InvalidNoteFormat;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "protobuf" => Ok(NoteFormatParam::Protobuf),
+                "html" => Ok(NoteFormatParam::Html),
+                _ => Err(InvalidNoteFormat),
+            }
+        }
+    }
 }
 
 #[allow(non_upper_case_globals)]
@@ -46,12 +221,14 @@ pub mod status {
         InvalidRequest = 499,
         InvalidToken = 498,
         InsufficientScope = 497,
+        SecondFactorRequired = 496,
     }
 
     pub trait StatusExt {
         const UnauthorizedInvalidRequest: Status = Status::new(Unauthorized::InvalidRequest as u16);
         const UnauthorizedInvalidToken: Status = Status::new(Unauthorized::InvalidToken as u16);
         const UnauthorizedInsufficientScope: Status = Status::new(Unauthorized::InsufficientScope as u16);
+        const UnauthorizedSecondFactorRequired: Status = Status::new(Unauthorized::SecondFactorRequired as u16);
     }
     impl StatusExt for Status {}
 
@@ -61,6 +238,7 @@ pub mod status {
                 Unauthorized::InvalidRequest => "invalid_request",
                 Unauthorized::InvalidToken => "invalid_token",
                 Unauthorized::InsufficientScope => "insufficient_scope",
+                Unauthorized::SecondFactorRequired => "second_factor_required",
             }
         }
     }
@@ -1,10 +1,16 @@
+use std::collections::BTreeSet;
+
 use time::UtcDateTime;
-use data::{Note, NoteInfo};
+use data::{AppPasswordInfo, AuditEvent, Note, NoteInfo, NoteMetadata, NoteRevisionInfo, NoteSearchResult, NoteShare, NoteTombstone, Scope, SessionDescription, ShareAccess, SharedNoteInfo, StorageUsage, TagCount, VersionedNote};
 use data::UsernameString;
 
+use crate::http::query::{NoteListCursor, NoteVersionParam, SyncTokenParam};
+
 pub struct LoginRequest {
     pub username: UsernameString,
     pub secret: LoginRequestSecret,
+    pub client_label: Option<String>,
+    pub second_factor: Option<String>,
 }
 
 #[derive(Clone)]
@@ -18,14 +24,101 @@ pub struct LoginResponse {
     pub access_token: String,
 }
 
+pub struct PasswordChangeRequest {
+    pub old_password: String,
+    pub new_password: String,
+    pub revoke_other_sessions: bool,
+}
+
+pub struct SessionListResponse {
+    pub sessions: Vec<SessionDescription>,
+}
+
+#[derive(Clone)]
+pub struct AppPasswordCreateRequest {
+    pub name: String,
+    pub scopes: BTreeSet<Scope>,
+}
+
+pub struct AppPasswordCreateResponse {
+    pub password: String,
+}
+
+pub struct AppPasswordListResponse {
+    pub app_passwords: Vec<AppPasswordInfo>,
+}
+
+pub struct ActivityResponse {
+    /// The newest first
+    pub events: Vec<AuditEvent>,
+}
+
 pub struct NoteListResponse {
     pub notes_info: Vec<NoteInfo>,
+    pub next_cursor: Option<NoteListCursor>,
+}
+
+pub struct NoteChangesResponse {
+    pub updated: Vec<NoteInfo>,
+    pub deleted: Vec<NoteTombstone>,
+    pub sync_token: SyncTokenParam,
+    pub more: bool,
+    pub reset: bool,
 }
 
-pub struct NoteResponse(pub Note);
+pub struct NoteSearchResponse {
+    pub results: Vec<NoteSearchResult>,
+    pub next_cursor: Option<NoteListCursor>,
+}
+
+pub struct NoteResponse {
+    pub note: Note,
+    /// Set for the current version of a note
+    pub version: Option<NoteVersionParam>,
+}
+
+impl From<VersionedNote> for NoteResponse {
+    fn from(value: VersionedNote) -> Self {
+        NoteResponse {
+            note: value.note,
+            version: Some(NoteVersionParam(value.version)),
+        }
+    }
+}
+
+pub struct NoteWriteResponse {
+    pub metadata: NoteMetadata,
+    pub version: NoteVersionParam,
+}
 
 pub struct NoteWriteRequest {
     pub mtime: UtcDateTime,
     pub name: Option<String>,
     pub contents: String,
+    pub base_version: Option<NoteVersionParam>,
+    pub tags: BTreeSet<String>,
+}
+
+pub struct NoteShareRequest {
+    pub access: ShareAccess,
+}
+
+pub struct NoteShareListResponse {
+    pub shares: Vec<NoteShare>,
+}
+
+pub struct NoteRevisionListResponse {
+    /// The most recently archived first
+    pub revisions: Vec<NoteRevisionInfo>,
+}
+
+pub struct SharedNoteListResponse {
+    /// The most recently modified first
+    pub notes_info: Vec<SharedNoteInfo>,
+}
+
+pub struct UsageResponse(pub StorageUsage);
+
+pub struct TagListResponse {
+    pub tags: Vec<TagCount>,
 }
@@ -1,7 +1,18 @@
 mod login;
 mod users_notes;
+mod note_search;
+mod note_revisions;
+mod note_changes;
+mod tag_list;
+mod usage;
 mod note;
 mod note_metadata;
+mod note_share;
+mod task_summary;
+mod password_change;
+mod session_list;
+mod app_password;
+mod activity;
 
 #[macro_export]
 macro_rules! protobuf_request {
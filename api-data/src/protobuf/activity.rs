@@ -0,0 +1,91 @@
+use std::net::IpAddr;
+use std::str::FromStr;
+use data::{AuditEvent, AuditEventKind, AuditOutcome, UsernameString};
+use protobuf_common::{MappingError, ProtobufRequestError};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::protobuf_response;
+use crate::model::ActivityResponse;
+use crate::bindings;
+
+impl From<ActivityResponse> for bindings::ActivityResponse {
+    fn from(value: ActivityResponse) -> Self {
+        bindings::ActivityResponse {
+            events: value.events
+                .into_iter()
+                .map(|event| {
+                    bindings::ActivityEvent {
+                        timestamp: event.timestamp.unix_timestamp(),
+                        username: event.username.into_string(),
+                        session_id: event.session_id
+                            .map(|session_id| session_id.into_bytes().to_vec()),
+                        client_address: event.client_address
+                            .map(|address| address.to_string()),
+                        kind: match event.event {
+                            AuditEventKind::Login => bindings::ActivityKind::Login,
+                            AuditEventKind::Logout => bindings::ActivityKind::Logout,
+                            AuditEventKind::TokenRefresh => bindings::ActivityKind::TokenRefresh,
+                            AuditEventKind::PasswordChange => bindings::ActivityKind::PasswordChange,
+                            AuditEventKind::SessionRevocation => bindings::ActivityKind::SessionRevocation,
+                            AuditEventKind::NoteDeletion => bindings::ActivityKind::NoteDeletion,
+                            AuditEventKind::NotePurge => bindings::ActivityKind::NotePurge,
+                        }.into(),
+                        success: event.outcome == AuditOutcome::Success,
+                        note_id: event.note_id
+                            .map(|note_id| note_id.into_bytes().to_vec()),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<bindings::ActivityResponse> for ActivityResponse {
+    type Error = ProtobufRequestError;
+
+    fn try_from(
+        value: bindings::ActivityResponse,
+    ) -> Result<Self, Self::Error> {
+        Ok(
+            ActivityResponse {
+                events: value.events
+                    .into_iter()
+                    .map(|event| {
+                        Ok::<_, ProtobufRequestError>(
+                            AuditEvent {
+                                timestamp: OffsetDateTime::from_unix_timestamp(event.timestamp)?,
+                                username: UsernameString::from_str(&event.username)?,
+                                session_id: event.session_id
+                                    .map(|session_id| Uuid::from_slice(&session_id))
+                                    .transpose()?,
+                                client_address: event.client_address
+                                    .map(|address| IpAddr::from_str(&address))
+                                    .transpose()
+                                    .map_err(|_| MappingError::invalid("client_address"))?,
+                                event: match event.kind.try_into()? {
+                                    bindings::ActivityKind::Login => AuditEventKind::Login,
+                                    bindings::ActivityKind::Logout => AuditEventKind::Logout,
+                                    bindings::ActivityKind::TokenRefresh => AuditEventKind::TokenRefresh,
+                                    bindings::ActivityKind::PasswordChange => AuditEventKind::PasswordChange,
+                                    bindings::ActivityKind::SessionRevocation => AuditEventKind::SessionRevocation,
+                                    bindings::ActivityKind::NoteDeletion => AuditEventKind::NoteDeletion,
+                                    bindings::ActivityKind::NotePurge => AuditEventKind::NotePurge,
+                                },
+                                outcome: match event.success {
+                                    true => AuditOutcome::Success,
+                                    false => AuditOutcome::Failure,
+                                },
+                                note_id: event.note_id
+                                    .map(|note_id| Uuid::from_slice(&note_id))
+                                    .transpose()?,
+                            }
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+            }
+        )
+    }
+}
+
+protobuf_response!(bindings::ActivityResponse, ActivityResponse);
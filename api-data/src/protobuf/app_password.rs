@@ -0,0 +1,114 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use data::{AppPasswordInfo, Scope};
+use protobuf_common::{MappingError, ProtobufRequestError};
+
+use crate::{protobuf_request, protobuf_response};
+use crate::model::{AppPasswordCreateRequest, AppPasswordCreateResponse, AppPasswordListResponse};
+use crate::bindings;
+
+impl TryFrom<bindings::AppPasswordCreateRequest> for AppPasswordCreateRequest {
+    type Error = ProtobufRequestError;
+
+    fn try_from(
+        value: bindings::AppPasswordCreateRequest,
+    ) -> Result<Self, Self::Error> {
+        Ok(
+            AppPasswordCreateRequest {
+                name: value.name,
+                scopes: parse_scopes(value.scopes)?,
+            }
+        )
+    }
+}
+
+impl From<AppPasswordCreateRequest> for bindings::AppPasswordCreateRequest {
+    fn from(value: AppPasswordCreateRequest) -> Self {
+        bindings::AppPasswordCreateRequest {
+            name: value.name,
+            scopes: format_scopes(value.scopes),
+        }
+    }
+}
+
+impl From<AppPasswordCreateResponse> for bindings::AppPasswordCreateResponse {
+    fn from(value: AppPasswordCreateResponse) -> Self {
+        bindings::AppPasswordCreateResponse {
+            password: value.password,
+        }
+    }
+}
+
+impl TryFrom<bindings::AppPasswordCreateResponse> for AppPasswordCreateResponse {
+    type Error = ProtobufRequestError;
+
+    fn try_from(
+        value: bindings::AppPasswordCreateResponse,
+    ) -> Result<Self, Self::Error> {
+        Ok(
+            AppPasswordCreateResponse {
+                password: value.password,
+            }
+        )
+    }
+}
+
+impl From<AppPasswordListResponse> for bindings::AppPasswordListResponse {
+    fn from(value: AppPasswordListResponse) -> Self {
+        bindings::AppPasswordListResponse {
+            app_passwords: value.app_passwords
+                .into_iter()
+                .map(|app_password| {
+                    bindings::AppPasswordInfo {
+                        name: app_password.name,
+                        scopes: format_scopes(app_password.scopes),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<bindings::AppPasswordListResponse> for AppPasswordListResponse {
+    type Error = ProtobufRequestError;
+
+    fn try_from(
+        value: bindings::AppPasswordListResponse,
+    ) -> Result<Self, Self::Error> {
+        Ok(
+            AppPasswordListResponse {
+                app_passwords: value.app_passwords
+                    .into_iter()
+                    .map(|app_password| {
+                        Ok::<_, ProtobufRequestError>(
+                            AppPasswordInfo {
+                                name: app_password.name,
+                                scopes: parse_scopes(app_password.scopes)?,
+                            }
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+            }
+        )
+    }
+}
+
+fn parse_scopes(scopes: Vec<String>) -> Result<BTreeSet<Scope>, ProtobufRequestError> {
+    scopes
+        .iter()
+        .map(|scope| Scope::from_str(scope))
+        .collect::<Result<_, _>>()
+        .map_err(|_| MappingError::invalid("scopes").into())
+}
+
+fn format_scopes(scopes: BTreeSet<Scope>) -> Vec<String> {
+    scopes
+        .into_iter()
+        .map(|scope| scope.as_str().to_owned())
+        .collect()
+}
+
+protobuf_request!(bindings::AppPasswordCreateRequest, AppPasswordCreateRequest);
+protobuf_response!(bindings::AppPasswordCreateResponse, AppPasswordCreateResponse);
+protobuf_response!(bindings::AppPasswordListResponse, AppPasswordListResponse);
@@ -19,7 +19,9 @@ impl TryFrom<bindings::LoginRequest> for LoginRequest {
                     PbSecret::Password(s) => LoginRequestSecret::Password(s),
                     PbSecret::RefreshToken(s) =>
                         LoginRequestSecret::RefreshToken(s),
-                }
+                },
+                client_label: pb.client_label,
+                second_factor: pb.second_factor,
             }
         )
     }
@@ -34,7 +36,9 @@ impl From<LoginRequest> for bindings::LoginRequest {
                     LoginRequestSecret::Password(p) => PbSecret::Password(p),
                     LoginRequestSecret::RefreshToken(rt) => PbSecret::RefreshToken(rt),
                 }
-            )
+            ),
+            client_label: value.client_label,
+            second_factor: value.second_factor,
         }
     }
 }
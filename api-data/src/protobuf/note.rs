@@ -1,8 +1,9 @@
-use data::Note;
+use data::{is_valid_tag, Note};
 use time::UtcDateTime;
 use crate::{protobuf_request, protobuf_response};
 use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
-use crate::model::{NoteResponse, NoteWriteRequest};
+use crate::http::query::NoteVersionParam;
+use crate::model::{NoteResponse, NoteWriteRequest, NoteWriteResponse};
 use crate::bindings;
 
 impl TryFrom<bindings::NoteResponse> for NoteResponse {
@@ -14,29 +15,38 @@ impl TryFrom<bindings::NoteResponse> for NoteResponse {
             .ok_or_mapping_error(MappingError::missing("metadata"))?
             .try_into()?;
         Ok(
-            NoteResponse(
-                Note {
+            NoteResponse {
+                note: Note {
                     metadata,
                     name: info.name,
+                    tags: info.tags.into_iter().collect(),
                     contents: value.contents,
-                }
-            )
+                },
+                version: value.version
+                    .map(|version| version.parse::<NoteVersionParam>())
+                    .transpose()
+                    .map_err(|_| MappingError::invalid("version"))?,
+            }
         )
     }
 }
 
 impl From<NoteResponse> for bindings::NoteResponse {
     fn from(value: NoteResponse) -> Self {
+        let NoteResponse { note, version } = value;
         bindings::NoteResponse {
             info: Some(
                 bindings::NoteInfo {
                     metadata: Some(
-                        value.0.metadata.into(),
+                        note.metadata.into(),
                     ),
-                    name: value.0.name,
+                    name: note.name,
+                    tags: note.tags.into_iter().collect(),
+                    tasks: None,
                 },
             ),
-            contents: value.0.contents,
+            contents: note.contents,
+            version: version.map(|version| version.to_string()),
         }
     }
 }
@@ -49,6 +59,14 @@ impl TryFrom<bindings::NoteWriteRequest> for NoteWriteRequest {
                 mtime: UtcDateTime::from_unix_timestamp(value.mtime)?,
                 name: value.name,
                 contents: value.contents,
+                base_version: value.base_version
+                    .map(|version| version.parse::<NoteVersionParam>())
+                    .transpose()
+                    .map_err(|_| MappingError::invalid("base_version"))?,
+                tags: match value.tags.iter().all(|tag| is_valid_tag(tag)) {
+                    true => value.tags.into_iter().collect(),
+                    false => return Err(MappingError::invalid("tags").into()),
+                },
             }
         )
     }
@@ -60,9 +78,37 @@ impl From<NoteWriteRequest> for bindings::NoteWriteRequest {
             mtime: value.mtime.unix_timestamp(),
             name: value.name,
             contents: value.contents,
+            base_version: value.base_version.map(|version| version.to_string()),
+            tags: value.tags.into_iter().collect(),
+        }
+    }
+}
+
+impl TryFrom<bindings::NoteWriteResponse> for NoteWriteResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::NoteWriteResponse) -> Result<Self, Self::Error> {
+        Ok(
+            NoteWriteResponse {
+                metadata: value.metadata
+                    .ok_or_mapping_error(MappingError::missing("metadata"))?
+                    .try_into()?,
+                version: value.version
+                    .parse()
+                    .map_err(|_| MappingError::invalid("version"))?,
+            }
+        )
+    }
+}
+
+impl From<NoteWriteResponse> for bindings::NoteWriteResponse {
+    fn from(value: NoteWriteResponse) -> Self {
+        bindings::NoteWriteResponse {
+            metadata: Some(value.metadata.into()),
+            version: value.version.to_string(),
         }
     }
 }
 
 protobuf_request!(bindings::NoteWriteRequest, NoteWriteRequest);
+protobuf_response!(bindings::NoteWriteResponse, NoteWriteResponse);
 protobuf_response!(bindings::NoteResponse, NoteResponse);
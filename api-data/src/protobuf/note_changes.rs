@@ -0,0 +1,87 @@
+use data::{NoteInfo, NoteTombstone};
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use time::UtcDateTime;
+use uuid::Uuid;
+
+use crate::protobuf_response;
+use crate::http::query::SyncTokenParam;
+use crate::model::NoteChangesResponse;
+use crate::bindings;
+
+impl From<NoteChangesResponse> for bindings::NoteChangesResponse {
+    fn from(value: NoteChangesResponse) -> Self {
+        bindings::NoteChangesResponse {
+            updated: value.updated
+                .into_iter()
+                .map(|info| {
+                    bindings::NoteInfo {
+                        metadata: Some(info.metadata.into()),
+                        name: info.name,
+                        tags: info.tags.into_iter().collect(),
+                        tasks: info.tasks.map(Into::into),
+                    }
+                })
+                .collect(),
+            deleted: value.deleted
+                .into_iter()
+                .map(|tombstone| {
+                    bindings::NoteTombstone {
+                        id: tombstone.id.into_bytes().to_vec(),
+                        deleted_at: tombstone.deleted_at.unix_timestamp(),
+                    }
+                })
+                .collect(),
+            sync_token: value.sync_token.to_string(),
+            more: value.more,
+            reset: value.reset,
+        }
+    }
+}
+
+impl TryFrom<bindings::NoteChangesResponse> for NoteChangesResponse {
+    type Error = ProtobufRequestError;
+
+    fn try_from(
+        value: bindings::NoteChangesResponse,
+    ) -> Result<Self, Self::Error> {
+        Ok(
+            NoteChangesResponse {
+                updated: value.updated
+                    .into_iter()
+                    .map(|ni| -> Result<_, ProtobufRequestError> {
+                        Ok(
+                            NoteInfo {
+                                metadata: ni.metadata
+                                    .ok_or_mapping_error(MappingError::missing("metadata"))
+                                    .and_then(|v| v.try_into())?,
+                                name: ni.name,
+                                tags: ni.tags.into_iter().collect(),
+                                tasks: ni.tasks.map(Into::into),
+                            }
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+                deleted: value.deleted
+                    .into_iter()
+                    .map(|tombstone| -> Result<_, ProtobufRequestError> {
+                        Ok(
+                            NoteTombstone {
+                                id: Uuid::from_slice(&tombstone.id)?,
+                                deleted_at: UtcDateTime::from_unix_timestamp(
+                                    tombstone.deleted_at,
+                                )?,
+                            }
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+                sync_token: value.sync_token
+                    .parse::<SyncTokenParam>()
+                    .map_err(|_| MappingError::invalid("sync_token"))?,
+                more: value.more,
+                reset: value.reset,
+            }
+        )
+    }
+}
+
+protobuf_response!(bindings::NoteChangesResponse, NoteChangesResponse);
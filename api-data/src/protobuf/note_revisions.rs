@@ -0,0 +1,64 @@
+use data::{NoteInfo, NoteRevisionInfo};
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use time::UtcDateTime;
+
+use crate::protobuf_response;
+use crate::model::NoteRevisionListResponse;
+use crate::bindings;
+
+impl From<NoteRevisionListResponse> for bindings::NoteRevisionListResponse {
+    fn from(value: NoteRevisionListResponse) -> Self {
+        bindings::NoteRevisionListResponse {
+            revisions: value.revisions
+                .into_iter()
+                .map(|revision| {
+                    bindings::NoteRevisionInfo {
+                        revision: revision.revision,
+                        archived_at: revision.archived_at.unix_timestamp(),
+                        info: Some(
+                            bindings::NoteInfo {
+                                metadata: Some(revision.info.metadata.into()),
+                                name: revision.info.name,
+                                tags: revision.info.tags.into_iter().collect(),
+                                tasks: revision.info.tasks.map(Into::into),
+                            }
+                        ),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<bindings::NoteRevisionListResponse> for NoteRevisionListResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::NoteRevisionListResponse) -> Result<Self, Self::Error> {
+        Ok(
+            NoteRevisionListResponse {
+                revisions: value.revisions
+                    .into_iter()
+                    .map(|revision| -> Result<_, ProtobufRequestError> {
+                        let info = revision.info
+                            .ok_or_mapping_error(MappingError::missing("info"))?;
+                        Ok(
+                            NoteRevisionInfo {
+                                revision: revision.revision,
+                                archived_at: UtcDateTime::from_unix_timestamp(revision.archived_at)?,
+                                info: NoteInfo {
+                                    metadata: info.metadata
+                                        .ok_or_mapping_error(MappingError::missing("metadata"))?
+                                        .try_into()?,
+                                    name: info.name,
+                                    tags: info.tags.into_iter().collect(),
+                                    tasks: info.tasks.map(Into::into),
+                                },
+                            }
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+            }
+        )
+    }
+}
+
+protobuf_response!(bindings::NoteRevisionListResponse, NoteRevisionListResponse);
@@ -0,0 +1,70 @@
+use data::{NoteInfo, NoteSearchResult};
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+
+use crate::protobuf_response;
+use crate::http::query::NoteListCursor;
+use crate::model::NoteSearchResponse;
+use crate::bindings;
+
+impl From<NoteSearchResponse> for bindings::NoteSearchResponse {
+    fn from(value: NoteSearchResponse) -> Self {
+        bindings::NoteSearchResponse {
+            results: value.results
+                .into_iter()
+                .map(|result| {
+                    bindings::NoteSearchResult {
+                        info: Some(
+                            bindings::NoteInfo {
+                                metadata: Some(result.info.metadata.into()),
+                                name: result.info.name,
+                                tags: result.info.tags.into_iter().collect(),
+                                tasks: result.info.tasks.map(Into::into),
+                            }
+                        ),
+                        snippets: result.snippets,
+                    }
+                })
+                .collect(),
+            next_cursor: value.next_cursor.as_ref().map(ToString::to_string),
+        }
+    }
+}
+
+impl TryFrom<bindings::NoteSearchResponse> for NoteSearchResponse {
+    type Error = ProtobufRequestError;
+
+    fn try_from(
+        value: bindings::NoteSearchResponse,
+    ) -> Result<Self, Self::Error> {
+        Ok(
+            NoteSearchResponse {
+                results: value.results
+                    .into_iter()
+                    .map(|result| -> Result<_, ProtobufRequestError> {
+                        let info = result.info
+                            .ok_or_mapping_error(MappingError::missing("info"))?;
+                        Ok(
+                            NoteSearchResult {
+                                info: NoteInfo {
+                                    metadata: info.metadata
+                                        .ok_or_mapping_error(MappingError::missing("metadata"))
+                                        .and_then(|v| v.try_into())?,
+                                    name: info.name,
+                                    tags: info.tags.into_iter().collect(),
+                                    tasks: info.tasks.map(Into::into),
+                                },
+                                snippets: result.snippets,
+                            }
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+                next_cursor: value.next_cursor
+                    .map(|cursor| cursor.parse::<NoteListCursor>())
+                    .transpose()
+                    .map_err(|_| MappingError::invalid("next_cursor"))?,
+            }
+        )
+    }
+}
+
+protobuf_response!(bindings::NoteSearchResponse, NoteSearchResponse);
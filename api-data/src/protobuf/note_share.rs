@@ -0,0 +1,141 @@
+use std::str::FromStr;
+
+use data::{NoteInfo, NoteShare, ShareAccess, SharedNoteInfo, UsernameString};
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+
+use crate::{protobuf_request, protobuf_response};
+use crate::model::{NoteShareListResponse, NoteShareRequest, SharedNoteListResponse};
+use crate::bindings;
+
+impl TryFrom<bindings::NoteShareRequest> for NoteShareRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::NoteShareRequest) -> Result<Self, Self::Error> {
+        Ok(
+            NoteShareRequest {
+                access: bindings::ShareAccess::try_from(value.access)?.into(),
+            }
+        )
+    }
+}
+
+impl From<NoteShareRequest> for bindings::NoteShareRequest {
+    fn from(value: NoteShareRequest) -> Self {
+        bindings::NoteShareRequest {
+            access: bindings::ShareAccess::from(value.access).into(),
+        }
+    }
+}
+
+impl From<NoteShareListResponse> for bindings::NoteShareListResponse {
+    fn from(value: NoteShareListResponse) -> Self {
+        bindings::NoteShareListResponse {
+            shares: value.shares
+                .into_iter()
+                .map(|share| {
+                    bindings::NoteShare {
+                        username: share.username.into_string(),
+                        access: bindings::ShareAccess::from(share.access).into(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<bindings::NoteShareListResponse> for NoteShareListResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::NoteShareListResponse) -> Result<Self, Self::Error> {
+        Ok(
+            NoteShareListResponse {
+                shares: value.shares
+                    .into_iter()
+                    .map(|share| -> Result<_, ProtobufRequestError> {
+                        Ok(
+                            NoteShare {
+                                username: UsernameString::from_str(&share.username)?,
+                                access: bindings::ShareAccess::try_from(share.access)?.into(),
+                            }
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+            }
+        )
+    }
+}
+
+impl From<SharedNoteListResponse> for bindings::SharedNoteListResponse {
+    fn from(value: SharedNoteListResponse) -> Self {
+        bindings::SharedNoteListResponse {
+            notes_info: value.notes_info
+                .into_iter()
+                .map(|note| {
+                    bindings::SharedNoteInfo {
+                        owner: note.owner.into_string(),
+                        access: bindings::ShareAccess::from(note.access).into(),
+                        info: Some(
+                            bindings::NoteInfo {
+                                metadata: Some(note.info.metadata.into()),
+                                name: note.info.name,
+                                tags: note.info.tags.into_iter().collect(),
+                                tasks: note.info.tasks.map(Into::into),
+                            }
+                        ),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<bindings::SharedNoteListResponse> for SharedNoteListResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::SharedNoteListResponse) -> Result<Self, Self::Error> {
+        Ok(
+            SharedNoteListResponse {
+                notes_info: value.notes_info
+                    .into_iter()
+                    .map(|note| -> Result<_, ProtobufRequestError> {
+                        let info = note.info
+                            .ok_or_mapping_error(MappingError::missing("info"))?;
+                        Ok(
+                            SharedNoteInfo {
+                                owner: UsernameString::from_str(&note.owner)?,
+                                access: bindings::ShareAccess::try_from(note.access)?.into(),
+                                info: NoteInfo {
+                                    metadata: info.metadata
+                                        .ok_or_mapping_error(MappingError::missing("metadata"))?
+                                        .try_into()?,
+                                    name: info.name,
+                                    tags: info.tags.into_iter().collect(),
+                                    tasks: info.tasks.map(Into::into),
+                                },
+                            }
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+            }
+        )
+    }
+}
+
+impl From<ShareAccess> for bindings::ShareAccess {
+    fn from(value: ShareAccess) -> Self {
+        match value {
+            ShareAccess::ReadOnly => bindings::ShareAccess::ReadOnly,
+            ShareAccess::ReadWrite => bindings::ShareAccess::ReadWrite,
+        }
+    }
+}
+
+impl From<bindings::ShareAccess> for ShareAccess {
+    fn from(value: bindings::ShareAccess) -> Self {
+        match value {
+            bindings::ShareAccess::ReadOnly => ShareAccess::ReadOnly,
+            bindings::ShareAccess::ReadWrite => ShareAccess::ReadWrite,
+        }
+    }
+}
+
+protobuf_request!(bindings::NoteShareRequest, NoteShareRequest);
+protobuf_response!(bindings::NoteShareListResponse, NoteShareListResponse);
+protobuf_response!(bindings::SharedNoteListResponse, SharedNoteListResponse);
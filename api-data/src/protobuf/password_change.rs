@@ -0,0 +1,33 @@
+use protobuf_common::ProtobufRequestError;
+
+use crate::protobuf_request;
+use crate::model::PasswordChangeRequest;
+use crate::bindings;
+
+impl TryFrom<bindings::PasswordChangeRequest> for PasswordChangeRequest {
+    type Error = ProtobufRequestError;
+
+    fn try_from(
+        value: bindings::PasswordChangeRequest,
+    ) -> Result<Self, Self::Error> {
+        Ok(
+            PasswordChangeRequest {
+                old_password: value.old_password,
+                new_password: value.new_password,
+                revoke_other_sessions: value.revoke_other_sessions,
+            }
+        )
+    }
+}
+
+impl From<PasswordChangeRequest> for bindings::PasswordChangeRequest {
+    fn from(value: PasswordChangeRequest) -> Self {
+        bindings::PasswordChangeRequest {
+            old_password: value.old_password,
+            new_password: value.new_password,
+            revoke_other_sessions: value.revoke_other_sessions,
+        }
+    }
+}
+
+protobuf_request!(bindings::PasswordChangeRequest, PasswordChangeRequest);
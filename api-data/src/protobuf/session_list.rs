@@ -0,0 +1,64 @@
+use data::{SessionDescription, SessionKind};
+use protobuf_common::ProtobufRequestError;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::protobuf_response;
+use crate::model::SessionListResponse;
+use crate::bindings;
+
+impl From<SessionListResponse> for bindings::SessionListResponse {
+    fn from(value: SessionListResponse) -> Self {
+        bindings::SessionListResponse {
+            sessions: value.sessions
+                .into_iter()
+                .map(|session| {
+                    bindings::SessionInfo {
+                        id: session.session_id.into_bytes().to_vec(),
+                        kind: match session.kind {
+                            SessionKind::Api => bindings::SessionKind::Api,
+                            SessionKind::Web => bindings::SessionKind::Web,
+                        }.into(),
+                        created_at: session.created_at.unix_timestamp(),
+                        expires_at: session.expires_at.unix_timestamp(),
+                        client_label: session.client_label,
+                        current: session.is_current,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<bindings::SessionListResponse> for SessionListResponse {
+    type Error = ProtobufRequestError;
+
+    fn try_from(
+        value: bindings::SessionListResponse,
+    ) -> Result<Self, Self::Error> {
+        Ok(
+            SessionListResponse {
+                sessions: value.sessions
+                    .into_iter()
+                    .map(|session| {
+                        Ok::<_, ProtobufRequestError>(
+                            SessionDescription {
+                                session_id: Uuid::from_slice(&session.id)?,
+                                kind: match session.kind.try_into()? {
+                                    bindings::SessionKind::Api => SessionKind::Api,
+                                    bindings::SessionKind::Web => SessionKind::Web,
+                                },
+                                created_at: OffsetDateTime::from_unix_timestamp(session.created_at)?,
+                                expires_at: OffsetDateTime::from_unix_timestamp(session.expires_at)?,
+                                client_label: session.client_label,
+                                is_current: session.current,
+                            }
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+            }
+        )
+    }
+}
+
+protobuf_response!(bindings::SessionListResponse, SessionListResponse);
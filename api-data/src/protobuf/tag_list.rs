@@ -0,0 +1,41 @@
+use data::TagCount;
+use protobuf_common::ProtobufRequestError;
+
+use crate::protobuf_response;
+use crate::model::TagListResponse;
+use crate::bindings;
+
+impl From<TagListResponse> for bindings::TagListResponse {
+    fn from(value: TagListResponse) -> Self {
+        bindings::TagListResponse {
+            tags: value.tags
+                .into_iter()
+                .map(|tag| {
+                    bindings::TagCount {
+                        tag: tag.tag,
+                        count: tag.count,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<bindings::TagListResponse> for TagListResponse {
+    type Error = ProtobufRequestError;
+
+    fn try_from(
+        value: bindings::TagListResponse,
+    ) -> Result<Self, Self::Error> {
+        Ok(
+            TagListResponse {
+                tags: value.tags
+                    .into_iter()
+                    .map(|tag| TagCount { tag: tag.tag, count: tag.count })
+                    .collect(),
+            }
+        )
+    }
+}
+
+protobuf_response!(bindings::TagListResponse, TagListResponse);
@@ -0,0 +1,20 @@
+use data::TaskSummary;
+use crate::bindings;
+
+impl From<TaskSummary> for bindings::TaskSummary {
+    fn from(value: TaskSummary) -> Self {
+        bindings::TaskSummary {
+            done: value.done,
+            total: value.total,
+        }
+    }
+}
+
+impl From<bindings::TaskSummary> for TaskSummary {
+    fn from(value: bindings::TaskSummary) -> Self {
+        TaskSummary {
+            done: value.done,
+            total: value.total,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use data::StorageUsage;
+use protobuf_common::ProtobufRequestError;
+
+use crate::protobuf_response;
+use crate::model::UsageResponse;
+use crate::bindings;
+
+impl From<UsageResponse> for bindings::UsageResponse {
+    fn from(value: UsageResponse) -> Self {
+        bindings::UsageResponse {
+            storage_size: value.0.storage_size,
+            notes: value.0.notes,
+            max_storage_size: value.0.max_storage_size,
+            max_notes: value.0.max_notes,
+        }
+    }
+}
+
+impl TryFrom<bindings::UsageResponse> for UsageResponse {
+    type Error = ProtobufRequestError;
+
+    fn try_from(
+        value: bindings::UsageResponse,
+    ) -> Result<Self, Self::Error> {
+        Ok(
+            UsageResponse(
+                StorageUsage {
+                    storage_size: value.storage_size,
+                    notes: value.notes,
+                    max_storage_size: value.max_storage_size,
+                    max_notes: value.max_notes,
+                }
+            )
+        )
+    }
+}
+
+protobuf_response!(bindings::UsageResponse, UsageResponse);
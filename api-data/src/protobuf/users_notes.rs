@@ -2,6 +2,7 @@ use data::{NoteInfo};
 use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
 
 use crate::protobuf_response;
+use crate::http::query::NoteListCursor;
 use crate::model::NoteListResponse;
 use crate::bindings;
 
@@ -19,9 +20,12 @@ impl From<NoteListResponse> for bindings::NoteListResponse {
                             }
                         ),
                         name: info.name,
+                        tags: info.tags.into_iter().collect(),
+                        tasks: info.tasks.map(Into::into),
                     }
                 })
-                .collect()
+                .collect(),
+            next_cursor: value.next_cursor.as_ref().map(ToString::to_string),
         }
     }
 }
@@ -43,10 +47,16 @@ impl TryFrom<bindings::NoteListResponse> for NoteListResponse {
                                     .ok_or_mapping_error(MappingError::missing("metadata"))
                                     .and_then(|v| v.try_into())?,
                                 name: ni.name,
+                                tags: ni.tags.into_iter().collect(),
+                                tasks: ni.tasks.map(Into::into),
                             }
                         )
                     })
-                    .collect::<Result<_, _>>()?
+                    .collect::<Result<_, _>>()?,
+                next_cursor: value.next_cursor
+                    .map(|cursor| cursor.parse::<NoteListCursor>())
+                    .transpose()
+                    .map_err(|_| MappingError::invalid("next_cursor"))?,
             }
         )
     }
@@ -6,6 +6,18 @@ pub mod model {
     pub mod login;
     pub mod refresh_token;
     pub mod logout;
+    pub mod change_password;
+    pub mod list_sessions;
+    pub mod revoke_session;
+    pub mod revoke_all_sessions;
+    pub mod check_session;
+    pub mod list_activity;
+    pub mod get_user_quota;
+    pub mod create_app_password;
+    pub mod list_app_passwords;
+    pub mod remove_app_password;
     pub mod session_kind;
     pub mod successful_login;
+    mod audit_event;
+    mod scopes;
 }
@@ -0,0 +1,95 @@
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+pub struct ChangePasswordRequest {
+    pub access_token: String,
+    pub old_password: String,
+    pub new_password: String,
+    pub revoke_other_sessions: bool,
+}
+
+pub struct ChangePasswordResponse(
+    pub Result<ChangePasswordSuccess, bindings::ChangePasswordError>
+);
+
+pub struct ChangePasswordSuccess {
+    pub revoked_session_ids: Vec<Uuid>,
+}
+
+impl TryFrom<bindings::ChangePasswordRequest> for ChangePasswordRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ChangePasswordRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ChangePasswordRequest {
+                access_token: value.access_token,
+                old_password: value.old_password,
+                new_password: value.new_password,
+                revoke_other_sessions: value.revoke_other_sessions,
+            }
+        )
+    }
+}
+
+impl From<ChangePasswordRequest> for bindings::ChangePasswordRequest {
+    fn from(value: ChangePasswordRequest) -> Self {
+        bindings::ChangePasswordRequest {
+            access_token: value.access_token,
+            old_password: value.old_password,
+            new_password: value.new_password,
+            revoke_other_sessions: value.revoke_other_sessions,
+        }
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ChangePasswordResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::change_password_response::Result;
+        let value = match value {
+            bindings::response::Response::ChangePassword(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ChangePasswordResponse(
+                match value.result.ok_or_mapping_error(MappingError::missing("result"))? {
+                    Result::Success(success) => Ok(
+                        ChangePasswordSuccess {
+                            revoked_session_ids: success.revoked_session_ids
+                                .iter()
+                                .map(|id| Uuid::from_slice(id))
+                                .collect::<std::result::Result<_, _>>()?,
+                        }
+                    ),
+                    Result::Error(error) => Err(error.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ChangePasswordResponse> for bindings::response::Response {
+    fn from(value: ChangePasswordResponse) -> Self {
+        bindings::response::Response::ChangePassword(
+            bindings::ChangePasswordResponse {
+                result: Some(
+                    match value.0 {
+                        Ok(success) =>
+                            bindings::change_password_response::Result::Success(
+                                bindings::ChangePasswordSuccess {
+                                    revoked_session_ids: success.revoked_session_ids
+                                        .into_iter()
+                                        .map(|id| id.as_bytes().to_vec())
+                                        .collect(),
+                                }
+                            ),
+                        Err(error) =>
+                            bindings::change_password_response::Result::Error(
+                                error.into()
+                            ),
+                    }
+                ),
+            }
+        )
+    }
+}
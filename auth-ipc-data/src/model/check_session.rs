@@ -0,0 +1,72 @@
+use std::str::FromStr;
+use data::UsernameString;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+pub struct CheckSessionRequest {
+    pub username: UsernameString,
+    pub session_id: Uuid,
+    pub xsrf_token: Option<Vec<u8>>,
+}
+
+pub struct CheckSessionResponse(pub Result<bool, bindings::SessionsError>);
+
+impl TryFrom<bindings::CheckSessionRequest> for CheckSessionRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::CheckSessionRequest) -> Result<Self, Self::Error> {
+        Ok(
+            CheckSessionRequest {
+                username: UsernameString::from_str(&value.username)?,
+                session_id: Uuid::from_slice(&value.session_id)?,
+                xsrf_token: value.xsrf_token,
+            }
+        )
+    }
+}
+
+impl From<CheckSessionRequest> for bindings::CheckSessionRequest {
+    fn from(value: CheckSessionRequest) -> Self {
+        bindings::CheckSessionRequest {
+            username: value.username.into_string(),
+            session_id: value.session_id.as_bytes().to_vec(),
+            xsrf_token: value.xsrf_token,
+        }
+    }
+}
+
+impl TryFrom<bindings::response::Response> for CheckSessionResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::check_session_response::Result;
+        let value = match value {
+            bindings::response::Response::CheckSession(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            CheckSessionResponse(
+                match value.result.ok_or_mapping_error(MappingError::missing("result"))? {
+                    Result::Alive(alive) => Ok(alive),
+                    Result::Error(error) => Err(error.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<CheckSessionResponse> for bindings::response::Response {
+    fn from(value: CheckSessionResponse) -> Self {
+        bindings::response::Response::CheckSession(
+            bindings::CheckSessionResponse {
+                result: Some(
+                    match value.0 {
+                        Ok(alive) => bindings::check_session_response::Result::Alive(alive),
+                        Err(error) => bindings::check_session_response::Result::Error(
+                            error.into()
+                        ),
+                    }
+                ),
+            }
+        )
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeSet;
+use data::Scope;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+use crate::model::scopes::{format_scopes, parse_scopes};
+
+pub struct CreateAppPasswordRequest {
+    pub access_token: String,
+    pub name: String,
+    pub scopes: BTreeSet<Scope>,
+}
+
+/// Holds the generated password
+pub struct CreateAppPasswordResponse(pub Result<String, bindings::AppPasswordsError>);
+
+impl TryFrom<bindings::CreateAppPasswordRequest> for CreateAppPasswordRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::CreateAppPasswordRequest) -> Result<Self, Self::Error> {
+        Ok(
+            CreateAppPasswordRequest {
+                access_token: value.access_token,
+                name: value.name,
+                scopes: parse_scopes(value.scopes)?,
+            }
+        )
+    }
+}
+
+impl From<CreateAppPasswordRequest> for bindings::CreateAppPasswordRequest {
+    fn from(value: CreateAppPasswordRequest) -> Self {
+        bindings::CreateAppPasswordRequest {
+            access_token: value.access_token,
+            name: value.name,
+            scopes: format_scopes(value.scopes),
+        }
+    }
+}
+
+impl TryFrom<bindings::response::Response> for CreateAppPasswordResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::create_app_password_response::Result;
+        let value = match value {
+            bindings::response::Response::CreateAppPassword(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            CreateAppPasswordResponse(
+                match value.result.ok_or_mapping_error(MappingError::missing("result"))? {
+                    Result::Password(password) => Ok(password),
+                    Result::Error(error) => Err(error.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<CreateAppPasswordResponse> for bindings::response::Response {
+    fn from(value: CreateAppPasswordResponse) -> Self {
+        bindings::response::Response::CreateAppPassword(
+            bindings::CreateAppPasswordResponse {
+                result: Some(
+                    match value.0 {
+                        Ok(password) =>
+                            bindings::create_app_password_response::Result::Password(password),
+                        Err(error) =>
+                            bindings::create_app_password_response::Result::Error(error.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
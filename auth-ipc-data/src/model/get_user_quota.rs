@@ -0,0 +1,75 @@
+use std::str::FromStr;
+use data::{UserQuota, UsernameString};
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+
+pub struct GetUserQuotaRequest {
+    pub username: UsernameString,
+}
+
+pub struct GetUserQuotaResponse(pub Result<UserQuota, bindings::SessionsError>);
+
+impl TryFrom<bindings::GetUserQuotaRequest> for GetUserQuotaRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::GetUserQuotaRequest) -> Result<Self, Self::Error> {
+        Ok(
+            GetUserQuotaRequest {
+                username: UsernameString::from_str(&value.username)?,
+            }
+        )
+    }
+}
+
+impl From<GetUserQuotaRequest> for bindings::GetUserQuotaRequest {
+    fn from(value: GetUserQuotaRequest) -> Self {
+        bindings::GetUserQuotaRequest {
+            username: value.username.into_string(),
+        }
+    }
+}
+
+impl TryFrom<bindings::response::Response> for GetUserQuotaResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::get_user_quota_response::Result;
+        let value = match value {
+            bindings::response::Response::GetUserQuota(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            GetUserQuotaResponse(
+                match value.result.ok_or_mapping_error(MappingError::missing("result"))? {
+                    Result::Quota(quota) => Ok(
+                        UserQuota {
+                            max_storage_size: quota.max_storage_size,
+                            max_notes: quota.max_notes,
+                        }
+                    ),
+                    Result::Error(error) => Err(error.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<GetUserQuotaResponse> for bindings::response::Response {
+    fn from(value: GetUserQuotaResponse) -> Self {
+        bindings::response::Response::GetUserQuota(
+            bindings::GetUserQuotaResponse {
+                result: Some(
+                    match value.0 {
+                        Ok(quota) => bindings::get_user_quota_response::Result::Quota(
+                            bindings::UserQuota {
+                                max_storage_size: quota.max_storage_size,
+                                max_notes: quota.max_notes,
+                            }
+                        ),
+                        Err(error) => bindings::get_user_quota_response::Result::Error(
+                            error.into()
+                        ),
+                    }
+                ),
+            }
+        )
+    }
+}
@@ -0,0 +1,84 @@
+use data::AuditEvent;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+
+pub struct ListActivityRequest {
+    pub access_token: String,
+    pub limit: u32,
+}
+
+/// The newest first
+pub struct ListActivityResponse(
+    pub Result<Vec<AuditEvent>, bindings::SessionsError>
+);
+
+impl TryFrom<bindings::ListActivityRequest> for ListActivityRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ListActivityRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ListActivityRequest {
+                access_token: value.access_token,
+                limit: value.limit,
+            }
+        )
+    }
+}
+
+impl From<ListActivityRequest> for bindings::ListActivityRequest {
+    fn from(value: ListActivityRequest) -> Self {
+        bindings::ListActivityRequest {
+            access_token: value.access_token,
+            limit: value.limit,
+        }
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ListActivityResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::list_activity_response::Result;
+        let value = match value {
+            bindings::response::Response::ListActivity(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ListActivityResponse(
+                match value.result.ok_or_mapping_error(MappingError::missing("result"))? {
+                    Result::Events(events) => Ok(
+                        events.events
+                            .into_iter()
+                            .map(TryInto::try_into)
+                            .collect::<std::result::Result<_, _>>()?
+                    ),
+                    Result::Error(error) => Err(error.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ListActivityResponse> for bindings::response::Response {
+    fn from(value: ListActivityResponse) -> Self {
+        bindings::response::Response::ListActivity(
+            bindings::ListActivityResponse {
+                result: Some(
+                    match value.0 {
+                        Ok(events) =>
+                            bindings::list_activity_response::Result::Events(
+                                bindings::AuditEventList {
+                                    events: events
+                                        .into_iter()
+                                        .map(Into::into)
+                                        .collect(),
+                                }
+                            ),
+                        Err(error) =>
+                            bindings::list_activity_response::Result::Error(
+                                error.into()
+                            ),
+                    }
+                ),
+            }
+        )
+    }
+}
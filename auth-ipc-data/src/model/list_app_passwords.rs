@@ -0,0 +1,102 @@
+use data::AppPasswordInfo;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+use crate::model::scopes::{format_scopes, parse_scopes};
+
+pub struct ListAppPasswordsRequest {
+    pub access_token: String,
+}
+
+pub struct ListAppPasswordsResponse(
+    pub Result<Vec<AppPasswordInfo>, bindings::AppPasswordsError>
+);
+
+impl TryFrom<bindings::ListAppPasswordsRequest> for ListAppPasswordsRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ListAppPasswordsRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ListAppPasswordsRequest {
+                access_token: value.access_token,
+            }
+        )
+    }
+}
+
+impl From<ListAppPasswordsRequest> for bindings::ListAppPasswordsRequest {
+    fn from(value: ListAppPasswordsRequest) -> Self {
+        bindings::ListAppPasswordsRequest {
+            access_token: value.access_token,
+        }
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ListAppPasswordsResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::list_app_passwords_response::Result;
+        let value = match value {
+            bindings::response::Response::ListAppPasswords(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ListAppPasswordsResponse(
+                match value.result.ok_or_mapping_error(MappingError::missing("result"))? {
+                    Result::AppPasswords(list) => Ok(
+                        list.app_passwords
+                            .into_iter()
+                            .map(TryInto::try_into)
+                            .collect::<std::result::Result<_, _>>()?
+                    ),
+                    Result::Error(error) => Err(error.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ListAppPasswordsResponse> for bindings::response::Response {
+    fn from(value: ListAppPasswordsResponse) -> Self {
+        bindings::response::Response::ListAppPasswords(
+            bindings::ListAppPasswordsResponse {
+                result: Some(
+                    match value.0 {
+                        Ok(app_passwords) =>
+                            bindings::list_app_passwords_response::Result::AppPasswords(
+                                bindings::AppPasswordList {
+                                    app_passwords: app_passwords
+                                        .into_iter()
+                                        .map(Into::into)
+                                        .collect(),
+                                }
+                            ),
+                        Err(error) =>
+                            bindings::list_app_passwords_response::Result::Error(
+                                error.into()
+                            ),
+                    }
+                ),
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::AppPasswordInfo> for AppPasswordInfo {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::AppPasswordInfo) -> Result<Self, Self::Error> {
+        Ok(
+            AppPasswordInfo {
+                name: value.name,
+                scopes: parse_scopes(value.scopes)?,
+            }
+        )
+    }
+}
+
+impl From<AppPasswordInfo> for bindings::AppPasswordInfo {
+    fn from(value: AppPasswordInfo) -> Self {
+        bindings::AppPasswordInfo {
+            name: value.name,
+            scopes: format_scopes(value.scopes),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use data::SessionDescription;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use crate::bindings;
+
+pub struct ListSessionsRequest {
+    pub access_token: String,
+}
+
+pub struct ListSessionsResponse(
+    pub Result<Vec<SessionDescription>, bindings::SessionsError>
+);
+
+impl TryFrom<bindings::ListSessionsRequest> for ListSessionsRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ListSessionsRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ListSessionsRequest {
+                access_token: value.access_token,
+            }
+        )
+    }
+}
+
+impl From<ListSessionsRequest> for bindings::ListSessionsRequest {
+    fn from(value: ListSessionsRequest) -> Self {
+        bindings::ListSessionsRequest {
+            access_token: value.access_token,
+        }
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ListSessionsResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::list_sessions_response::Result;
+        let value = match value {
+            bindings::response::Response::ListSessions(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ListSessionsResponse(
+                match value.result.ok_or_mapping_error(MappingError::missing("result"))? {
+                    Result::Sessions(sessions) => Ok(
+                        sessions.sessions
+                            .into_iter()
+                            .map(TryInto::try_into)
+                            .collect::<std::result::Result<_, _>>()?
+                    ),
+                    Result::Error(error) => Err(error.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ListSessionsResponse> for bindings::response::Response {
+    fn from(value: ListSessionsResponse) -> Self {
+        bindings::response::Response::ListSessions(
+            bindings::ListSessionsResponse {
+                result: Some(
+                    match value.0 {
+                        Ok(sessions) =>
+                            bindings::list_sessions_response::Result::Sessions(
+                                bindings::SessionList {
+                                    sessions: sessions
+                                        .into_iter()
+                                        .map(Into::into)
+                                        .collect(),
+                                }
+                            ),
+                        Err(error) =>
+                            bindings::list_sessions_response::Result::Error(
+                                error.into()
+                            ),
+                    }
+                ),
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::SessionDescription> for SessionDescription {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::SessionDescription) -> Result<Self, Self::Error> {
+        Ok(
+            SessionDescription {
+                session_id: Uuid::from_slice(&value.session_id)?,
+                kind: <_ as TryInto<bindings::SessionKind>>
+                    ::try_into(value.kind)?
+                    .into(),
+                created_at: OffsetDateTime::from_unix_timestamp(value.created_at)?,
+                expires_at: OffsetDateTime::from_unix_timestamp(value.expires_at)?,
+                client_label: value.client_label,
+                is_current: value.is_current,
+            }
+        )
+    }
+}
+
+impl From<SessionDescription> for bindings::SessionDescription {
+    fn from(value: SessionDescription) -> Self {
+        bindings::SessionDescription {
+            session_id: value.session_id.as_bytes().to_vec(),
+            kind: <_ as Into<bindings::SessionKind>>
+                ::into(value.kind)
+                .into(),
+            created_at: value.created_at.unix_timestamp(),
+            expires_at: value.expires_at.unix_timestamp(),
+            client_label: value.client_label,
+            is_current: value.is_current,
+        }
+    }
+}
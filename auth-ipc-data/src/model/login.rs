@@ -1,4 +1,6 @@
+use std::net::IpAddr;
 use std::str::FromStr;
+use std::time::Duration;
 use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
 use data::{SessionKind, UsernameString};
 use super::successful_login::SuccessfulLogin;
@@ -8,9 +10,27 @@ pub struct LoginRequest {
     pub username: UsernameString,
     pub password: String,
     pub session_kind: SessionKind,
+    pub client_label: Option<String>,
+    pub second_factor: Option<String>,
+    pub client_address: Option<IpAddr>,
 }
 
-pub struct LoginResponse(pub Result<SuccessfulLogin, bindings::LoginError>);
+pub struct LoginResponse(pub Result<SuccessfulLogin, LoginFailure>);
+
+pub struct LoginFailure {
+    pub error: bindings::LoginError,
+    /// Set with `LoginTooManyAttempts`
+    pub retry_after: Option<Duration>,
+}
+
+impl From<bindings::LoginError> for LoginFailure {
+    fn from(error: bindings::LoginError) -> Self {
+        LoginFailure {
+            error,
+            retry_after: None,
+        }
+    }
+}
 
 impl TryFrom<bindings::LoginRequest> for LoginRequest {
     type Error = ProtobufRequestError;
@@ -22,6 +42,12 @@ impl TryFrom<bindings::LoginRequest> for LoginRequest {
                 session_kind: <_ as TryInto<bindings::SessionKind>>
                     ::try_into(value.session_kind)?
                     .into(),
+                client_label: value.client_label,
+                second_factor: value.second_factor,
+                client_address: value.client_address
+                    .map(|address| IpAddr::from_str(&address))
+                    .transpose()
+                    .map_err(|_| MappingError::invalid("client_address"))?,
             }
         )
     }
@@ -39,7 +65,13 @@ impl TryFrom<bindings::response::Response> for LoginResponse {
             LoginResponse(
                 match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
                     Response::SuccessfulLogin(successful_login) => Ok(successful_login.try_into()?),
-                    Response::LoginError(login_error) => Err(login_error.try_into()?),
+                    Response::LoginError(login_error) => Err(
+                        LoginFailure {
+                            error: login_error.try_into()?,
+                            retry_after: value.retry_after_seconds
+                                .map(|seconds| Duration::from_secs(seconds.into())),
+                        }
+                    ),
                 }
             )
         )
@@ -48,20 +80,29 @@ impl TryFrom<bindings::response::Response> for LoginResponse {
 
 impl From<LoginResponse> for bindings::response::Response {
     fn from(value: LoginResponse) -> Self {
+        let (response, retry_after_seconds) = match value.0 {
+            Ok(successful_login) => (
+                bindings::login_response::Response::SuccessfulLogin(
+                    successful_login.into()
+                ),
+                None,
+            ),
+            Err(failure) => (
+                bindings::login_response::Response::LoginError(
+                    failure.error.into()
+                ),
+                // rounded up, not to invite retrying too early
+                failure.retry_after.map(|retry_after|
+                    (retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0))
+                        .try_into()
+                        .unwrap_or(u32::MAX)
+                ),
+            ),
+        };
         bindings::response::Response::Login(
             bindings::LoginResponse {
-                response: Some(
-                    match value.0 {
-                        Ok(successful_login) =>
-                            bindings::login_response::Response::SuccessfulLogin(
-                                successful_login.into()
-                            ),
-                        Err(error) =>
-                            bindings::login_response::Response::LoginError(
-                                error.into()
-                            ),
-                    }
-                ),
+                response: Some(response),
+                retry_after_seconds,
             }
         )
     }
@@ -75,6 +116,9 @@ impl From<LoginRequest> for bindings::LoginRequest {
             session_kind: <_ as Into<bindings::SessionKind>>
                 ::into(value.session_kind)
                 .into(),
+            client_label: value.client_label,
+            second_factor: value.second_factor,
+            client_address: value.client_address.map(|address| address.to_string()),
         }
     }
 }
@@ -0,0 +1,84 @@
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+pub struct RemoveAppPasswordRequest {
+    pub access_token: String,
+    pub name: String,
+}
+
+/// Holds the ids of the sessions revoked with the application password
+pub struct RemoveAppPasswordResponse(
+    pub Result<Vec<Uuid>, bindings::AppPasswordsError>
+);
+
+impl TryFrom<bindings::RemoveAppPasswordRequest> for RemoveAppPasswordRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::RemoveAppPasswordRequest) -> Result<Self, Self::Error> {
+        Ok(
+            RemoveAppPasswordRequest {
+                access_token: value.access_token,
+                name: value.name,
+            }
+        )
+    }
+}
+
+impl From<RemoveAppPasswordRequest> for bindings::RemoveAppPasswordRequest {
+    fn from(value: RemoveAppPasswordRequest) -> Self {
+        bindings::RemoveAppPasswordRequest {
+            access_token: value.access_token,
+            name: value.name,
+        }
+    }
+}
+
+impl TryFrom<bindings::response::Response> for RemoveAppPasswordResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::remove_app_password_response::Result;
+        let value = match value {
+            bindings::response::Response::RemoveAppPassword(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            RemoveAppPasswordResponse(
+                match value.result.ok_or_mapping_error(MappingError::missing("result"))? {
+                    Result::Revoked(revoked) => Ok(
+                        revoked.session_ids
+                            .iter()
+                            .map(|id| Uuid::from_slice(id))
+                            .collect::<std::result::Result<_, _>>()?
+                    ),
+                    Result::Error(error) => Err(error.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<RemoveAppPasswordResponse> for bindings::response::Response {
+    fn from(value: RemoveAppPasswordResponse) -> Self {
+        bindings::response::Response::RemoveAppPassword(
+            bindings::RemoveAppPasswordResponse {
+                result: Some(
+                    match value.0 {
+                        Ok(session_ids) =>
+                            bindings::remove_app_password_response::Result::Revoked(
+                                bindings::RevokedSessions {
+                                    session_ids: session_ids
+                                        .into_iter()
+                                        .map(|id| id.as_bytes().to_vec())
+                                        .collect(),
+                                }
+                            ),
+                        Err(error) =>
+                            bindings::remove_app_password_response::Result::Error(
+                                error.into()
+                            ),
+                    }
+                ),
+            }
+        )
+    }
+}
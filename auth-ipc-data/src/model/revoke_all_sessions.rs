@@ -0,0 +1,81 @@
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+pub struct RevokeAllSessionsRequest {
+    pub access_token: String,
+}
+
+/// Holds the ids of the revoked sessions
+pub struct RevokeAllSessionsResponse(
+    pub Result<Vec<Uuid>, bindings::SessionsError>
+);
+
+impl TryFrom<bindings::RevokeAllSessionsRequest> for RevokeAllSessionsRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::RevokeAllSessionsRequest) -> Result<Self, Self::Error> {
+        Ok(
+            RevokeAllSessionsRequest {
+                access_token: value.access_token,
+            }
+        )
+    }
+}
+
+impl From<RevokeAllSessionsRequest> for bindings::RevokeAllSessionsRequest {
+    fn from(value: RevokeAllSessionsRequest) -> Self {
+        bindings::RevokeAllSessionsRequest {
+            access_token: value.access_token,
+        }
+    }
+}
+
+impl TryFrom<bindings::response::Response> for RevokeAllSessionsResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::revoke_all_sessions_response::Result;
+        let value = match value {
+            bindings::response::Response::RevokeAllSessions(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            RevokeAllSessionsResponse(
+                match value.result.ok_or_mapping_error(MappingError::missing("result"))? {
+                    Result::Revoked(revoked) => Ok(
+                        revoked.session_ids
+                            .iter()
+                            .map(|id| Uuid::from_slice(id))
+                            .collect::<std::result::Result<_, _>>()?
+                    ),
+                    Result::Error(error) => Err(error.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<RevokeAllSessionsResponse> for bindings::response::Response {
+    fn from(value: RevokeAllSessionsResponse) -> Self {
+        bindings::response::Response::RevokeAllSessions(
+            bindings::RevokeAllSessionsResponse {
+                result: Some(
+                    match value.0 {
+                        Ok(session_ids) =>
+                            bindings::revoke_all_sessions_response::Result::Revoked(
+                                bindings::RevokedSessions {
+                                    session_ids: session_ids
+                                        .into_iter()
+                                        .map(|id| id.as_bytes().to_vec())
+                                        .collect(),
+                                }
+                            ),
+                        Err(error) =>
+                            bindings::revoke_all_sessions_response::Result::Error(
+                                error.into()
+                            ),
+                    }
+                ),
+            }
+        )
+    }
+}
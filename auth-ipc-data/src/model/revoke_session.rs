@@ -0,0 +1,59 @@
+use protobuf_common::{MappingError, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+pub struct RevokeSessionRequest {
+    pub access_token: String,
+    pub session_id: Uuid,
+}
+
+pub struct RevokeSessionResponse(pub Option<bindings::SessionsError>);
+
+impl TryFrom<bindings::RevokeSessionRequest> for RevokeSessionRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::RevokeSessionRequest) -> Result<Self, Self::Error> {
+        Ok(
+            RevokeSessionRequest {
+                access_token: value.access_token,
+                session_id: Uuid::from_slice(&value.session_id)?,
+            }
+        )
+    }
+}
+
+impl From<RevokeSessionRequest> for bindings::RevokeSessionRequest {
+    fn from(value: RevokeSessionRequest) -> Self {
+        bindings::RevokeSessionRequest {
+            access_token: value.access_token,
+            session_id: value.session_id.as_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<bindings::response::Response> for RevokeSessionResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        let value = match value {
+            bindings::response::Response::RevokeSession(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            RevokeSessionResponse(
+                match value.error {
+                    Some(e) => Some(e.try_into()?),
+                    None => None,
+                }
+            )
+        )
+    }
+}
+
+impl From<RevokeSessionResponse> for bindings::response::Response {
+    fn from(value: RevokeSessionResponse) -> Self {
+        bindings::response::Response::RevokeSession(
+            bindings::RevokeSessionResponse {
+                error: value.0.map(bindings::SessionsError::into),
+            }
+        )
+    }
+}
@@ -0,0 +1,19 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use data::Scope;
+use protobuf_common::{MappingError, ProtobufRequestError};
+
+pub(crate) fn parse_scopes(scopes: Vec<String>) -> Result<BTreeSet<Scope>, ProtobufRequestError> {
+    scopes
+        .iter()
+        .map(|scope| Scope::from_str(scope))
+        .collect::<Result<_, _>>()
+        .map_err(|_| MappingError::invalid("scopes").into())
+}
+
+pub(crate) fn format_scopes(scopes: BTreeSet<Scope>) -> Vec<String> {
+    scopes
+        .into_iter()
+        .map(|scope| scope.as_str().to_owned())
+        .collect()
+}
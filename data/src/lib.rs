@@ -1,12 +1,21 @@
+mod scope;
 mod username_string;
 
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+
 use argon2::PasswordHash;
 use kinded::Kinded;
+use serde::{Deserialize, Serialize};
 use time::{OffsetDateTime, UtcDateTime};
 use uuid::Uuid;
 
+pub use scope::*;
 pub use username_string::*;
 
+pub const MAX_NOTE_TAGS: usize = 32;
+pub const MAX_TAG_LEN: usize = 64;
+
 #[derive(Clone, Copy, Debug)]
 pub struct NoteMetadata {
     pub id: Uuid,
@@ -17,6 +26,138 @@ pub struct NoteMetadata {
 pub struct NoteInfo {
     pub metadata: NoteMetadata,
     pub name: Option<String>,
+    pub tags: BTreeSet<String>,
+    /// `None` where the note contents weren't read
+    pub tasks: Option<TaskSummary>,
+}
+
+/// Counts of the task list items (`- [ ]` and `- [x]`) of a note
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TaskSummary {
+    pub done: u32,
+    pub total: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NoteListOrder {
+    #[default]
+    OldestFirst,
+    NewestFirst,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NoteListQuery {
+    pub limit: Option<u32>,
+    /// The last note of the previous page, compared by mtime and then id
+    pub after: Option<NoteMetadata>,
+    pub modified_since: Option<UtcDateTime>,
+    pub order: NoteListOrder,
+    pub tag: Option<String>,
+}
+
+/// Search results come newest first, paged like [`NoteListQuery`] ones
+#[derive(Clone, Debug, Default)]
+pub struct NoteSearchQuery {
+    pub query: String,
+    pub limit: Option<u32>,
+    /// The last result of the previous page, compared by mtime and then id
+    pub after: Option<NoteMetadata>,
+}
+
+#[derive(Clone, Debug)]
+pub struct NoteListPage<T> {
+    pub notes: Vec<T>,
+    /// Set when there are more notes, to be passed as [`NoteListQuery::after`]
+    pub next: Option<NoteMetadata>,
+}
+
+/// Position in a user's change journal
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SyncToken {
+    /// Changes when the journal is recreated, invalidating older tokens
+    pub generation: Uuid,
+    pub seq: u64,
+    /// Set while paging through a full listing, to the position it started
+    /// at, the deletions after which are listed along
+    pub snapshot_start: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NoteTombstone {
+    pub id: Uuid,
+    pub deleted_at: UtcDateTime,
+}
+
+#[derive(Clone, Debug)]
+pub enum NoteEvent {
+    Created(NoteInfo),
+    Updated(NoteInfo),
+    Deleted(NoteTombstone),
+}
+
+#[derive(Clone, Debug)]
+pub struct NoteChanges {
+    pub updated: Vec<NoteInfo>,
+    pub deleted: Vec<NoteTombstone>,
+    pub sync_token: SyncToken,
+    /// Set when there are more changes after `sync_token`
+    pub more: bool,
+    /// Set when the changes are a full listing of the notes instead
+    /// of a delta, the notes missing from it are gone
+    pub reset: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: u32,
+}
+
+/// Tags are compared as is, they can't be blank, have surrounding
+/// whitespace or contain control characters
+pub fn is_valid_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag.chars().count() <= MAX_TAG_LEN
+        && tag.trim() == tag
+        && !tag.chars().any(char::is_control)
+}
+
+/// A previous version of a note
+#[derive(Clone, Debug)]
+pub struct NoteRevisionInfo {
+    /// Assigned when the version is archived, increasing with the archive time
+    pub revision: i64,
+    pub archived_at: UtcDateTime,
+    /// As the version was written, without the tags
+    pub info: NoteInfo,
+}
+
+#[derive(Clone, Debug)]
+pub struct NoteSearchResult {
+    pub info: NoteInfo,
+    pub snippets: Vec<String>,
+}
+
+/// What a user may do with a note shared with them, a read-write share
+/// also allows reading and deleting the note
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum ShareAccess {
+    ReadOnly,
+    ReadWrite,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NoteShare {
+    pub username: UsernameString,
+    pub access: ShareAccess,
+}
+
+/// A note another user shared with the user
+#[derive(Clone, Debug)]
+pub struct SharedNoteInfo {
+    pub owner: UsernameString,
+    pub access: ShareAccess,
+    pub info: NoteInfo,
 }
 
 // TODO: data is always validated for MAX_NOTE_LEN
@@ -24,13 +165,68 @@ pub struct NoteInfo {
 pub struct Note {
     pub metadata: NoteMetadata,
     pub name: Option<String>,
+    pub tags: BTreeSet<String>,
     pub contents: String,
 }
 
+/// Identifies a stored version of a note, assigned by the storage daemon
+/// on every write for the clients to base their edits on
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NoteVersion {
+    pub ino: u64,
+    pub ctime: i64,
+    pub ctime_nsec: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct VersionedNote {
+    pub note: Note,
+    pub version: NoteVersion,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct User {
     pub username: String,
     pub hash: PasswordHash,
+    pub quota: UserQuota,
+    pub locked: bool,
+    /// Base32-encoded, enabling the second factor
+    pub totp_secret: Option<String>,
+    /// Hashes of the unused ones
+    pub recovery_codes: Vec<String>,
+    pub app_passwords: Vec<AppPassword>,
+}
+
+/// A password logging a client into an API session limited to `scopes`,
+/// without the second factor
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppPassword {
+    pub name: String,
+    pub hash: String,
+    pub scopes: BTreeSet<Scope>,
+}
+
+/// An application password as listed to its user, without the hash
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AppPasswordInfo {
+    pub name: String,
+    pub scopes: BTreeSet<Scope>,
+}
+
+/// Per-user overrides of the configured quota, zero meaning unlimited
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct UserQuota {
+    pub max_storage_size: Option<u64>,
+    pub max_notes: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct StorageUsage {
+    pub storage_size: u64,
+    pub notes: u32,
+    /// `None` when unlimited
+    pub max_storage_size: Option<u64>,
+    pub max_notes: Option<u32>,
 }
 
 #[derive(Clone, Debug, Eq, Kinded, PartialEq)]
@@ -62,6 +258,9 @@ pub struct ApiSession {
     pub refresh_token: Vec<u8>,
     pub created_at: OffsetDateTime,
     pub expires_at: OffsetDateTime,
+    pub client_label: Option<String>,
+    /// The name of the application password the session was created with
+    pub app_password: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -71,4 +270,74 @@ pub struct WebSession {
     pub xsrf_token: Vec<u8>,
     pub created_at: OffsetDateTime,
     pub expires_at: OffsetDateTime,
+    pub client_label: Option<String>,
+}
+
+/// A session as listed to its user
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionDescription {
+    pub session_id: Uuid,
+    pub kind: SessionKind,
+    pub created_at: OffsetDateTime,
+    /// When the session ends unless it is refreshed
+    pub expires_at: OffsetDateTime,
+    pub client_label: Option<String>,
+    /// Whether it is the session of the listing request
+    pub is_current: bool,
+}
+
+/// A security-relevant event, as written to the audit log
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AuditEvent {
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub username: UsernameString,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_address: Option<IpAddr>,
+    pub event: AuditEventKind,
+    pub outcome: AuditOutcome,
+    /// Set for the note events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note_id: Option<Uuid>,
+}
+
+impl AuditEvent {
+    /// Without the optional details, to be filled with the struct update
+    /// syntax
+    pub fn now(
+        username: UsernameString,
+        event: AuditEventKind,
+        outcome: AuditOutcome,
+    ) -> Self {
+        AuditEvent {
+            timestamp: OffsetDateTime::now_utc(),
+            username,
+            session_id: None,
+            client_address: None,
+            event,
+            outcome,
+            note_id: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventKind {
+    Login,
+    Logout,
+    TokenRefresh,
+    PasswordChange,
+    SessionRevocation,
+    NoteDeletion,
+    NotePurge,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
 }
@@ -0,0 +1,62 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A part of the API an access token can be limited to
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Scope {
+    #[serde(rename = "notes:read")] NotesRead,
+    #[serde(rename = "notes:write")] NotesWrite,
+    /// Sessions, password and usage
+    #[serde(rename = "account")] Account,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 3] = [Scope::NotesRead, Scope::NotesWrite, Scope::Account];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::NotesRead => "notes:read",
+            Scope::NotesWrite => "notes:write",
+            Scope::Account => "account",
+        }
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = ScopeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| ScopeParseError(s.to_owned()))
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("unknown scope \"{0}\"")]
+pub struct ScopeParseError(pub String);
+
+/// Space-separated, as in OAuth
+pub fn format_scopes(scopes: &BTreeSet<Scope>) -> String {
+    scopes
+        .iter()
+        .map(|scope| scope.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn parse_scopes(s: &str) -> Result<BTreeSet<Scope>, ScopeParseError> {
+    s.split_ascii_whitespace()
+        .map(Scope::from_str)
+        .collect()
+}
@@ -1,5 +1,6 @@
 use std::path::PathBuf;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use data::{Scope, UsernameString};
 use dumbnotes::bin_constants::DEFAULT_CONFIG_FILE;
 
 #[derive(Clone, Debug, Eq, Parser, PartialEq)]
@@ -14,6 +15,87 @@ pub struct CliConfig {
     #[arg(group = "gen", long, default_value_t = false)]
     pub generate_jwt_key: bool,
 
+    /// Replace the jwt private key, keeping the tokens signed with the old
+    /// one valid until they expire
+    #[arg(group = "gen", long, default_value_t = false)]
+    pub rotate_jwt_key: bool,
+
     #[arg(group = "gen", long, default_value_t = false)]
     pub generate_pepper: bool,
+
+    #[arg(group = "gen", long, default_value_t = false)]
+    pub generate_note_key: bool,
+
+    #[command(subcommand)]
+    pub user_command: Option<UserCommand>,
+}
+
+/// Edits the user db, must be run as its owner
+#[derive(Clone, Debug, Eq, PartialEq, Subcommand)]
+pub enum UserCommand {
+    /// Add a user, asking for the password, and create their notes directory
+    AddUser {
+        username: UsernameString,
+    },
+
+    /// Remove a user along with their notes and the shares with them
+    RemoveUser {
+        username: UsernameString,
+
+        #[arg(long, default_value_t = false)]
+        keep_notes: bool,
+    },
+
+    /// List the users, marking the locked ones
+    ListUsers,
+
+    /// Ask for a new password of a user
+    ResetPassword {
+        username: UsernameString,
+    },
+
+    /// Forbid a user to log in or refresh their sessions
+    LockUser {
+        username: UsernameString,
+    },
+
+    /// Allow a locked user to log in again
+    UnlockUser {
+        username: UsernameString,
+    },
+
+    /// Require a TOTP code at login, printing a new secret and recovery
+    /// codes that replace the old ones
+    EnableTotp {
+        username: UsernameString,
+    },
+
+    /// Stop requiring a TOTP code at login
+    DisableTotp {
+        username: UsernameString,
+    },
+
+    /// Print a new password logging API clients in without the second
+    /// factor, limited to the scopes: notes:read, notes:write or account
+    AddAppPassword {
+        username: UsernameString,
+
+        name: String,
+
+        #[arg(long = "scope", required = true)]
+        scopes: Vec<Scope>,
+    },
+
+    /// Remove an application password, its sessions can't be refreshed
+    /// afterwards
+    RemoveAppPassword {
+        username: UsernameString,
+
+        name: String,
+    },
+
+    /// List the application passwords of a user with their scopes
+    ListAppPasswords {
+        username: UsernameString,
+    },
 }
@@ -1,8 +1,8 @@
-use std::fs::{OpenOptions, Permissions};
+use std::fs::{self, OpenOptions, Permissions};
 use std::io;
 use std::io::Write;
 use std::os::unix::fs::{MetadataExt, OpenOptionsExt, PermissionsExt};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use libc::{gid_t, uid_t};
 use dumbnotes::sandbox::user_group::get_user_and_group;
 use unix::{get_ids, ChownExt};
@@ -51,3 +51,28 @@ pub fn write(
     file.write_all(contents.as_ref().as_bytes())?;
     Ok(())
 }
+
+const TMP_SUFFIX: &str = ".tmp";
+
+/// Replaces the file atomically, keeping its ownership and mode
+pub fn replace(
+    path: &Path,
+    contents: impl AsRef<str>,
+) -> Result<(), io::Error> {
+    let metadata = fs::metadata(path)?;
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(TMP_SUFFIX);
+    let tmp_path = PathBuf::from(tmp_path);
+    let result = write(
+        &tmp_path,
+        contents,
+        Some(metadata.uid()),
+        Some(metadata.gid()),
+        Some(metadata.mode() & 0o777),
+    )
+        .and_then(|()| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
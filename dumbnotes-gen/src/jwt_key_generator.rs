@@ -1,10 +1,16 @@
+use std::fs;
 use std::io;
 use std::path::Path;
+use std::time::SystemTime;
+use access_token::{get_retired_until, is_key_expired, parse_jwk_set, retire_key};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use dumbnotes::bin_constants::{JWT_KEY_ID_LENGTH, RETIRED_JWT_KEY_VALIDITY_TIME};
 use josekit::JoseError;
 use josekit::jwk::alg::ed::EdCurve;
-use josekit::jwk::Jwk;
+use josekit::jwk::{Jwk, JwkSet};
+use rand::Rng;
 use thiserror::Error;
-use file_write::{get_ids_for_chown, write};
+use file_write::{get_ids_for_chown, replace, write};
 use crate::file_write;
 
 #[derive(Debug, Error)]
@@ -12,6 +18,9 @@ pub enum MakeJwtKeyError {
     #[error("failed generating jwt key")]
     Generation(#[from] JoseError),
 
+    #[error("invalid current public keys: {0}")]
+    InvalidPublicKeys(JoseError),
+
     #[error("jwt key serialization failed")]
     Serialization(#[from] serde_json::Error),
 
@@ -24,8 +33,8 @@ pub fn make_jwt_key(
     jwt_public_key: &Path,
     owner_user_group: Option<&str>,
 ) -> Result<(), MakeJwtKeyError> {
-    let private_key = Jwk::generate_ed_key(EdCurve::Ed25519)?;
-    let public_key = private_key.to_public_key()?;
+    let private_key = generate_key()?;
+    let public_key = get_public_key(&private_key)?;
     let (uid, gid) = get_ids_for_chown(owner_user_group)?;
     write(
         jwt_private_key,
@@ -43,3 +52,55 @@ pub fn make_jwt_key(
     )?;
     Ok(())
 }
+
+/// Replaces the private key, keeping the previous public keys in the set
+/// until the tokens they signed expire
+pub fn rotate_jwt_key(
+    jwt_private_key: &Path,
+    jwt_public_key: &Path,
+) -> Result<(), MakeJwtKeyError> {
+    let now = SystemTime::now();
+    let previous_keys = parse_jwk_set(fs::read(jwt_public_key)?)
+        .map_err(MakeJwtKeyError::InvalidPublicKeys)?;
+    let private_key = generate_key()?;
+    let mut public_keys = JwkSet::new();
+    public_keys.push_key(get_public_key(&private_key)?);
+    for key in previous_keys.keys() {
+        if is_key_expired(key, now) {
+            continue
+        }
+        let mut key = key.clone();
+        if get_retired_until(&key).is_none() {
+            retire_key(&mut key, now + RETIRED_JWT_KEY_VALIDITY_TIME)?;
+        }
+        public_keys.push_key(key);
+    }
+
+    // the daemons have to know the new public key before it signs anything
+    replace(
+        jwt_public_key,
+        serde_json::to_string_pretty(public_keys.as_ref())? + "\n",
+    )?;
+    replace(
+        jwt_private_key,
+        serde_json::to_string_pretty(&private_key)? + "\n",
+    )?;
+    Ok(())
+}
+
+fn generate_key() -> Result<Jwk, JoseError> {
+    let mut key = Jwk::generate_ed_key(EdCurve::Ed25519)?;
+    let mut key_id = [0u8; JWT_KEY_ID_LENGTH];
+    rand::rng().fill_bytes(&mut key_id);
+    key.set_key_id(Base64UrlUnpadded::encode_string(&key_id));
+    Ok(key)
+}
+
+fn get_public_key(private_key: &Jwk) -> Result<Jwk, JoseError> {
+    let mut public_key = private_key.to_public_key()?;
+    // not copied by josekit
+    if let Some(key_id) = private_key.key_id() {
+        public_key.set_key_id(key_id);
+    }
+    Ok(public_key)
+}
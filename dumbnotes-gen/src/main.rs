@@ -1,24 +1,34 @@
 use std::io::stdin;
-use crate::cli::CliConfig;
+use std::str::FromStr;
+use argon2::PasswordHash;
+use crate::cli::{CliConfig, UserCommand};
 use clap::Parser;
 use dumbnotes::config::app_config::AppConfig;
 use dumbnotes::config::read::read_app_config;
 use util::error_exit;
 use dumbnotes::hasher::{Hasher, ProductionHasher, ProductionHasherConfig};
-#[cfg(target_os = "openbsd")] use dumbnotes::sandbox::pledge::{pledge_gen_init, pledge_gen_key, pledge_gen_hash};
+use data::format_scopes;
+use dumbnotes::app_password::{generate_app_password, hash_app_password};
+use dumbnotes::totp;
+#[cfg(target_os = "openbsd")] use dumbnotes::sandbox::pledge::{pledge_gen_init, pledge_gen_key, pledge_gen_hash, pledge_gen_users};
+#[cfg(target_os = "openbsd")] use dumbnotes::bin_constants::NOTES_DIRECTORY_PATH;
 #[cfg(target_os = "openbsd")] use dumbnotes::sandbox::unveil::{Permissions, unveil, seal_unveil};
-use jwt_key_generator::make_jwt_key;
+use jwt_key_generator::{make_jwt_key, rotate_jwt_key};
 use log::warn;
 use rpassword::prompt_password;
 use dumbnotes::logging::init_tool_logging;
 use unix::set_umask;
 use crate::pepper_generator::make_pepper;
+use crate::note_key_generator::make_note_key;
+use crate::user_admin::UserAdmin;
 
 mod cli;
 mod config;
 mod jwt_key_generator;
 mod pepper_generator;
+mod note_key_generator;
 mod file_write;
+mod user_admin;
 
 fn main() {
     #[cfg(target_os = "openbsd")] pledge_gen_init();
@@ -45,10 +55,16 @@ fn main() {
             error_exit!("finishing due to a configuration error: {e}");
         });
 
-    if cli_config.generate_jwt_key {
+    if let Some(ref user_command) = cli_config.user_command {
+        run_user_command(&cli_config, user_command, app_config)
+    } else if cli_config.generate_jwt_key {
         generate_jwt_key(app_config)
+    } else if cli_config.rotate_jwt_key {
+        rotate_jwt_keys(app_config)
     } else if cli_config.generate_pepper {
         generate_pepper(app_config)
+    } else if cli_config.generate_note_key {
+        generate_note_key(app_config)
     } else {
         generate_hash(cli_config, app_config)
     }
@@ -78,6 +94,30 @@ fn generate_jwt_key(
         .unwrap_or_else(|e| error_exit!("could not generate a jwt key: {e}"));
 }
 
+fn rotate_jwt_keys(
+    app_config: AppConfig,
+) {
+    #[cfg(target_os = "openbsd")] {
+        // the keys are replaced with new files
+        for key_path in [&app_config.jwt_private_key, &app_config.jwt_public_key] {
+            if let Some(key_dir) = key_path.parent() {
+                unveil(
+                    key_dir,
+                    Permissions::R | Permissions::W | Permissions::C,
+                );
+            }
+        }
+        seal_unveil();
+        pledge_gen_key();
+    }
+
+    rotate_jwt_key(
+        &app_config.jwt_private_key,
+        &app_config.jwt_public_key,
+    )
+        .unwrap_or_else(|e| error_exit!("could not rotate the jwt key: {e}"));
+}
+
 fn generate_pepper(
     app_config: AppConfig,
 ) {
@@ -97,6 +137,29 @@ fn generate_pepper(
         .unwrap_or_else(|e| error_exit!("could not generate pepper: {e}"));
 }
 
+fn generate_note_key(
+    app_config: AppConfig,
+) {
+    let Some(note_key_path) = app_config.note_encryption_key else {
+        error_exit!("note_encryption_key is not set in the configuration")
+    };
+
+    #[cfg(target_os = "openbsd")] {
+        unveil(
+            &note_key_path,
+            Permissions::C | Permissions::W,
+        );
+        seal_unveil();
+        pledge_gen_key();
+    }
+
+    make_note_key(
+        &note_key_path,
+        app_config.storage_user_group.as_deref(),
+    )
+        .unwrap_or_else(|e| error_exit!("could not generate note encryption key: {e}"));
+}
+
 fn generate_hash(
     cli_config: CliConfig,
     app_config: AppConfig,
@@ -114,20 +177,138 @@ fn generate_hash(
         pledge_gen_hash();
     }
 
+    let hasher = make_hasher(&app_config);
+    let read_value = read_password(cli_config.no_repeat);
+    let hash = hasher.generate_hash(&read_value)
+        .unwrap_or_else(|e| error_exit!("could not generate hash: {}", e));
+    println!("{}", hash);
+}
+
+fn run_user_command(
+    cli_config: &CliConfig,
+    user_command: &UserCommand,
+    app_config: AppConfig,
+) {
+    #[cfg(target_os = "openbsd")] {
+        unveil(
+            &app_config.hasher_config.pepper_path,
+            Permissions::R,
+        );
+        // the db is replaced with a new file on writes
+        if let Some(user_db_dir) = app_config.user_db.parent() {
+            unveil(
+                user_db_dir,
+                Permissions::R | Permissions::W | Permissions::C,
+            );
+        }
+        unveil(
+            app_config.data_directory.join(NOTES_DIRECTORY_PATH),
+            Permissions::R | Permissions::W | Permissions::C,
+        );
+        unveil(
+            "/dev/tty",
+            Permissions::R | Permissions::W,
+        );
+        seal_unveil();
+        pledge_gen_users();
+    }
+
+    let user_admin = UserAdmin {
+        user_db: &app_config.user_db,
+        data_directory: &app_config.data_directory,
+        storage_user_group: app_config.storage_user_group.as_deref(),
+    };
+    let make_hash = || {
+        let hash = make_hasher(&app_config)
+            .generate_hash(&read_password(cli_config.no_repeat))
+            .unwrap_or_else(|e| error_exit!("could not generate hash: {e}"));
+        PasswordHash::from_str(&hash)
+            .unwrap_or_else(|e| error_exit!("generated an invalid hash: {e}"))
+    };
+    let result = match user_command {
+        UserCommand::AddUser { username } =>
+            user_admin.add_user(username, make_hash()),
+        UserCommand::RemoveUser { username, keep_notes } =>
+            user_admin.remove_user(username, *keep_notes),
+        UserCommand::ListUsers => user_admin.list_users()
+            .map(|users|
+                for (username, locked) in users {
+                    if locked {
+                        println!("{username} (locked)");
+                    } else {
+                        println!("{username}");
+                    }
+                }
+            ),
+        UserCommand::ResetPassword { username } =>
+            user_admin.reset_password(username, make_hash()),
+        UserCommand::LockUser { username } =>
+            user_admin.set_user_locked(username, true),
+        UserCommand::UnlockUser { username } =>
+            user_admin.set_user_locked(username, false),
+        UserCommand::EnableTotp { username } => {
+            let secret = totp::generate_secret();
+            let recovery_codes = totp::generate_recovery_codes();
+            user_admin
+                .set_user_totp(
+                    username,
+                    Some(secret.clone()),
+                    recovery_codes
+                        .iter()
+                        .map(|code| totp::hash_recovery_code(code))
+                        .collect(),
+                )
+                .map(|()| {
+                    println!("secret: {secret}");
+                    println!("uri: {}", totp::make_otpauth_uri(username, &secret));
+                    println!("recovery codes, each usable once:");
+                    for code in recovery_codes {
+                        println!("{code}");
+                    }
+                })
+        },
+        UserCommand::DisableTotp { username } =>
+            user_admin.set_user_totp(username, None, Vec::new()),
+        UserCommand::AddAppPassword { username, name, scopes } => {
+            let password = generate_app_password();
+            user_admin
+                .add_app_password(
+                    username,
+                    name,
+                    hash_app_password(&password),
+                    scopes.iter().copied().collect(),
+                )
+                .map(|()| println!("{password}"))
+        },
+        UserCommand::RemoveAppPassword { username, name } =>
+            user_admin.remove_app_password(username, name),
+        UserCommand::ListAppPasswords { username } => user_admin.list_app_passwords(username)
+            .map(|app_passwords|
+                for (name, scopes) in app_passwords {
+                    println!("{name}: {}", format_scopes(&scopes));
+                }
+            ),
+    };
+    result.unwrap_or_else(|e| error_exit!("{e}"));
+}
+
+fn make_hasher(app_config: &AppConfig) -> ProductionHasher {
     let hasher_config = app_config.hasher_config.make_params()
         .unwrap_or_else(|e| error_exit!("hasher config is invalid: {e}"));
-    let hasher = ProductionHasher
+    ProductionHasher
         ::new(
             ProductionHasherConfig {
                 argon2_params: hasher_config,
-                pepper: app_config.hasher_config.pepper_path,
+                pepper: app_config.hasher_config.pepper_path.clone(),
             },
         )
         .unwrap_or_else(|e|
             error_exit!("invalid hasher configuration: {e}")
-        );
+        )
+}
 
-    let read_value = if cli_config.no_repeat {
+fn read_password(no_repeat: bool) -> String {
+    let read_value = if no_repeat {
         let mut read_value = String::new();
         stdin().read_line(&mut read_value)
             .unwrap_or_else(|e| error_exit!("cannot read from stdin: {e}"));
@@ -155,7 +336,5 @@ fn generate_hash(
         warn!("the password has leading or trailing whitespace characters");
     }
 
-    let hash = hasher.generate_hash(&read_value)
-        .unwrap_or_else(|e| error_exit!("could not generate hash: {}", e));
-    println!("{}", hash);
+    read_value
 }
@@ -0,0 +1,31 @@
+use std::io;
+use std::path::Path;
+use base64ct::{Base64, Encoding};
+use rand::Rng;
+use thiserror::Error;
+use dumbnotes::bin_constants::NOTE_ENCRYPTION_KEY_LENGTH;
+use file_write::{get_ids_for_chown, write};
+use crate::file_write;
+
+#[derive(Debug, Error)]
+pub enum MakeNoteKeyError {
+    #[error("failed writing generated note encryption key: {0}")]
+    Io(#[from] io::Error),
+}
+
+pub fn make_note_key(
+    note_key_path: &Path,
+    owner_user_group: Option<&str>,
+) -> Result<(), MakeNoteKeyError> {
+    let mut key = [0u8; NOTE_ENCRYPTION_KEY_LENGTH];
+    rand::rng().fill_bytes(&mut key);
+    let (uid, gid) = get_ids_for_chown(owner_user_group)?;
+    write(
+        note_key_path,
+        Base64::encode_string(&key) + "\n",
+        uid,
+        gid,
+        Some(0o440),
+    )?;
+    Ok(())
+}
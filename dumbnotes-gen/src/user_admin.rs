@@ -0,0 +1,298 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::fs::DirBuilder;
+use std::io;
+use std::io::ErrorKind;
+use std::os::unix::fs::{chown, DirBuilderExt};
+use std::path::{Path, PathBuf};
+use argon2::PasswordHash;
+use data::{Scope, UsernameStr, UsernameString};
+use dumbnotes::bin_constants::{NOTES_DIRECTORY_PATH, SHARES_DIRECTORY_PATH};
+use dumbnotesd_auth_data::user_db::{AppPasswordData, UserData, UsersData};
+use thiserror::Error;
+use unix::check_secret_file_rw_access;
+use unix::errors::CheckAccessError;
+use file_write::{get_ids_for_chown, replace};
+use crate::file_write;
+
+#[derive(Debug, Error)]
+pub enum UserAdminError {
+    #[error("cannot access the user db: {0}")]
+    CheckAccess(#[from] CheckAccessError),
+
+    #[error("failed to parse the user db: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("failed to serialize the user db: {0}")]
+    Serialize(#[from] toml::ser::Error),
+
+    #[error("user \"{0}\" already exists")]
+    UserExists(UsernameString),
+
+    #[error("user \"{0}\" does not exist")]
+    UserNotFound(UsernameString),
+
+    #[error("username is empty")]
+    EmptyUsername,
+
+    #[error("application password \"{0}\" already exists")]
+    AppPasswordExists(String),
+
+    #[error("application password \"{0}\" does not exist")]
+    AppPasswordNotFound(String),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+pub struct UserAdmin<'a> {
+    pub user_db: &'a Path,
+    pub data_directory: &'a Path,
+    pub storage_user_group: Option<&'a str>,
+}
+
+impl UserAdmin<'_> {
+    /// Returns the usernames with whether they are locked
+    pub fn list_users(&self) -> Result<Vec<(String, bool)>, UserAdminError> {
+        Ok(
+            self.read_users()?
+                .users
+                .into_iter()
+                .map(|user| (user.username, user.locked))
+                .collect()
+        )
+    }
+
+    pub fn add_user(
+        &self,
+        username: &UsernameStr,
+        hash: PasswordHash,
+    ) -> Result<(), UserAdminError> {
+        if username.is_empty() {
+            return Err(UserAdminError::EmptyUsername)
+        }
+        let mut users = self.read_users()?;
+        if users.users.iter().any(|user| user.username == **username) {
+            return Err(UserAdminError::UserExists(username.to_owned()))
+        }
+        // created first, so that a user never exists without one
+        self.create_notes_dir(username)?;
+        users.users.push(
+            UserData {
+                username: username.to_string(),
+                hash,
+                max_storage_size: None,
+                max_notes: None,
+                locked: false,
+                totp_secret: None,
+                recovery_codes: Vec::new(),
+                app_passwords: Vec::new(),
+            }
+        );
+        self.write_users(&users)
+    }
+
+    pub fn remove_user(
+        &self,
+        username: &UsernameStr,
+        keep_notes: bool,
+    ) -> Result<(), UserAdminError> {
+        if username.is_empty() {
+            return Err(UserAdminError::EmptyUsername)
+        }
+        let mut users = self.read_users()?;
+        let count = users.users.len();
+        users.users.retain(|user| user.username != **username);
+        if users.users.len() == count {
+            return Err(UserAdminError::UserNotFound(username.to_owned()))
+        }
+        self.write_users(&users)?;
+        // not to share the notes with a new user of the same name
+        self.remove_from_shares(username)?;
+        if !keep_notes {
+            match fs::remove_dir_all(self.get_notes_dir(username)) {
+                Ok(()) => {},
+                Err(e) if e.kind() == ErrorKind::NotFound => {},
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn reset_password(
+        &self,
+        username: &UsernameStr,
+        hash: PasswordHash,
+    ) -> Result<(), UserAdminError> {
+        self.update_user(username, |user| {
+            user.hash = hash;
+            Ok(())
+        })
+    }
+
+    pub fn set_user_locked(
+        &self,
+        username: &UsernameStr,
+        locked: bool,
+    ) -> Result<(), UserAdminError> {
+        self.update_user(username, |user| {
+            user.locked = locked;
+            Ok(())
+        })
+    }
+
+    pub fn set_user_totp(
+        &self,
+        username: &UsernameStr,
+        secret: Option<String>,
+        recovery_code_hashes: Vec<String>,
+    ) -> Result<(), UserAdminError> {
+        self.update_user(username, |user| {
+            user.totp_secret = secret;
+            user.recovery_codes = recovery_code_hashes;
+            Ok(())
+        })
+    }
+
+    /// Returns the names with the scopes
+    pub fn list_app_passwords(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Vec<(String, BTreeSet<Scope>)>, UserAdminError> {
+        self.read_users()?
+            .users
+            .into_iter()
+            .find(|user| user.username == **username)
+            .map(|user| user.app_passwords
+                .into_iter()
+                .map(|app_password| (app_password.name, app_password.scopes))
+                .collect()
+            )
+            .ok_or_else(|| UserAdminError::UserNotFound(username.to_owned()))
+    }
+
+    pub fn add_app_password(
+        &self,
+        username: &UsernameStr,
+        name: &str,
+        hash: String,
+        scopes: BTreeSet<Scope>,
+    ) -> Result<(), UserAdminError> {
+        self.update_user(username, |user| {
+            if user.app_passwords.iter().any(|app_password| app_password.name == name) {
+                return Err(UserAdminError::AppPasswordExists(name.to_owned()))
+            }
+            user.app_passwords.push(
+                AppPasswordData {
+                    name: name.to_owned(),
+                    hash,
+                    scopes,
+                }
+            );
+            Ok(())
+        })
+    }
+
+    pub fn remove_app_password(
+        &self,
+        username: &UsernameStr,
+        name: &str,
+    ) -> Result<(), UserAdminError> {
+        self.update_user(username, |user| {
+            let count = user.app_passwords.len();
+            user.app_passwords.retain(|app_password| app_password.name != name);
+            if user.app_passwords.len() == count {
+                return Err(UserAdminError::AppPasswordNotFound(name.to_owned()))
+            }
+            Ok(())
+        })
+    }
+
+    fn update_user(
+        &self,
+        username: &UsernameStr,
+        update: impl FnOnce(&mut UserData) -> Result<(), UserAdminError>,
+    ) -> Result<(), UserAdminError> {
+        let mut users = self.read_users()?;
+        let user = users.users
+            .iter_mut()
+            .find(|user| user.username == **username)
+            .ok_or_else(|| UserAdminError::UserNotFound(username.to_owned()))?;
+        update(user)?;
+        self.write_users(&users)
+    }
+
+    fn read_users(&self) -> Result<UsersData, UserAdminError> {
+        check_secret_file_rw_access(self.user_db)?;
+        Ok(toml::from_str(&fs::read_to_string(self.user_db)?)?)
+    }
+
+    fn write_users(&self, users: &UsersData) -> Result<(), UserAdminError> {
+        Ok(replace(self.user_db, toml::to_string(users)?)?)
+    }
+
+    fn create_notes_dir(&self, username: &UsernameStr) -> Result<(), UserAdminError> {
+        let path = self.get_notes_dir(username);
+        match DirBuilder::new().mode(0o700).create(&path) {
+            Ok(()) => {},
+            // left by a user removed with their notes kept
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+        let (uid, gid) = get_ids_for_chown(self.storage_user_group)?;
+        if uid.is_some() || gid.is_some() {
+            chown(&path, uid, gid)?;
+        }
+        Ok(())
+    }
+
+    // Shares of a note are kept by the storage daemon in
+    // `<owner dir>/.shares/<note id>`, a `<username> <access>` line per user
+    fn remove_from_shares(&self, username: &UsernameStr) -> Result<(), UserAdminError> {
+        let owners = match fs::read_dir(self.data_directory.join(NOTES_DIRECTORY_PATH)) {
+            Ok(owners) => owners,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        for owner in owners {
+            let shares = match fs::read_dir(owner?.path().join(SHARES_DIRECTORY_PATH)) {
+                Ok(shares) => shares,
+                Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) =>
+                    continue,
+                Err(e) => return Err(e.into()),
+            };
+            for entry in shares {
+                let entry = entry?;
+                // tmp files of the daemon's writes
+                if entry.file_name().as_encoded_bytes().starts_with(b".") {
+                    continue
+                }
+                let path = entry.path();
+                let data = fs::read_to_string(&path)?;
+                let lines = data.lines().collect::<Vec<_>>();
+                let kept = lines
+                    .iter()
+                    .filter(|line| line
+                        .rsplit_once(' ')
+                        .is_none_or(|(share_username, _)| share_username != &**username)
+                    )
+                    .map(|line| line.to_string() + "\n")
+                    .collect::<Vec<_>>();
+                if kept.len() == lines.len() {
+                    continue
+                }
+                match kept.is_empty() {
+                    true => fs::remove_file(&path)?,
+                    false => replace(&path, kept.concat())?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get_notes_dir(&self, username: &UsernameStr) -> PathBuf {
+        self.data_directory
+            .join(NOTES_DIRECTORY_PATH)
+            .join(username as &str)
+    }
+}
@@ -11,10 +11,14 @@ use std::process::{Command, Stdio};
 use argon2::{Algorithm, Argon2, PasswordHash, PasswordVerifier, Version};
 use base64ct::{Base64, Encoding as Base64Encoding};
 use boolean_enums::gen_boolean_enum;
-use josekit::jwk::Jwk;
+use josekit::jwk::{Jwk, JwkSet};
 use rexpect::session::PtySession;
 use dumbnotes::config::hasher_config::ProductionHasherConfigData;
-use test_utils::{new_configured_command, setup_basic_config, setup_basic_config_with_keys, ChildKillOnDropExt, PtySessionExt};
+use dumbnotes::app_password::hash_app_password;
+use dumbnotes::totp;
+use std::os::unix::fs::PermissionsExt;
+use dumbnotesd_auth_data::user_db::UsersData;
+use test_utils::{new_configured_command, setup_basic_config, setup_basic_config_with_keys, setup_basic_config_with_keys_and_data, ChildKillOnDropExt, GEN_BIN_PATH, PtySessionExt};
 use test_utils::data::MOCK_PEPPER;
 use test_utils::predicates::file_mode;
 
@@ -41,11 +45,46 @@ fn create_jwt_key() -> Result<(), Box<dyn Error>> {
     let private_key = Jwk::from_bytes(fs::read_to_string(&private_key)?)?;
     let public_key = Jwk::from_bytes(fs::read_to_string(&public_key)?)?;
     assert_ne!(private_key.to_public_key()?, private_key);
-    assert_eq!(private_key.to_public_key()?, public_key);
+    assert!(private_key.key_id().is_some());
+    assert_eq!(get_public_key(&private_key)?, public_key);
 
     Ok(())
 }
 
+#[test]
+fn rotate_jwt_key() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config();
+
+    call_create(&dir, "--generate-jwt-key")?;
+    let private_key = dir.child("etc/dumbnotes/private/jwt_private_key.json");
+    let public_key = dir.child("etc/dumbnotes/jwt_public_key.json");
+    let old_public_key = Jwk::from_bytes(fs::read_to_string(&public_key)?)?;
+
+    call_create(&dir, "--rotate-jwt-key")?;
+    private_key
+        .assert(
+            predicates::path::is_file()
+                .and(file_mode(0o400, 0o337))
+        );
+    let private_key = Jwk::from_bytes(fs::read_to_string(&private_key)?)?;
+    let public_keys = JwkSet::from_bytes(fs::read_to_string(&public_key)?)?;
+    let public_keys = public_keys.keys();
+    assert_eq!(public_keys.len(), 2);
+    assert_eq!(*public_keys[0], get_public_key(&private_key)?);
+    assert_ne!(private_key.key_id(), old_public_key.key_id());
+    assert_eq!(public_keys[1].key_id(), old_public_key.key_id());
+    assert!(public_keys[1].parameter("exp").is_some());
+    assert!(public_keys[0].parameter("exp").is_none());
+
+    Ok(())
+}
+
+fn get_public_key(private_key: &Jwk) -> Result<Jwk, Box<dyn Error>> {
+    let mut public_key = private_key.to_public_key()?;
+    public_key.set_key_id(private_key.key_id().ok_or("no key id")?);
+    Ok(public_key)
+}
+
 #[test]
 fn create_pepper() -> Result<(), Box<dyn Error>> {
     let dir = setup_basic_config();
@@ -246,6 +285,141 @@ fn hash_password_spaces_impl(
 }
 gen_boolean_enum!(MustWarn);
 
+#[test]
+fn manage_users() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let user_db = dir.child("etc/dumbnotes/private/users.toml");
+    fs::set_permissions(&user_db, fs::Permissions::from_mode(0o600))?;
+    fs::set_permissions(
+        dir.child("etc/dumbnotes/private"),
+        fs::Permissions::from_mode(0o700),
+    )?;
+    let notes_dir = dir.child("var/dumbnotes/notes/new");
+
+    call_user_command(&dir, &["add-user", "new"], Some("123"))?;
+    notes_dir.assert(predicates::path::is_dir());
+    let users: UsersData = toml::from_str(&fs::read_to_string(&user_db)?)?;
+    let new_user = users.users.iter()
+        .find(|user| user.username == "new")
+        .expect("the user was not added");
+    validate_hash(&new_user.hash.to_string(), "123")?;
+    user_db.assert(predicates::path::is_file().and(file_mode(0o600, 0o177)));
+    assert!(call_user_command(&dir, &["add-user", "new"], Some("123")).is_err());
+    assert!(call_user_command(&dir, &["add-user", "a/b"], Some("123")).is_err());
+
+    call_user_command(&dir, &["lock-user", "new"], None)?;
+    let users = call_user_command(&dir, &["list-users"], None)?;
+    assert_eq!(users.lines().collect::<Vec<_>>(), ["abc", "abcdef", "new (locked)"]);
+    call_user_command(&dir, &["unlock-user", "new"], None)?;
+    let users = call_user_command(&dir, &["list-users"], None)?;
+    assert_eq!(users.lines().collect::<Vec<_>>(), ["abc", "abcdef", "new"]);
+
+    call_user_command(&dir, &["reset-password", "new"], Some("456"))?;
+    let users: UsersData = toml::from_str(&fs::read_to_string(&user_db)?)?;
+    let new_user = users.users.iter()
+        .find(|user| user.username == "new")
+        .expect("the user was removed");
+    validate_hash(&new_user.hash.to_string(), "456")?;
+
+    let output = call_user_command(&dir, &["enable-totp", "new"], None)?;
+    let secret = output.lines().next()
+        .and_then(|line| line.strip_prefix("secret: "))
+        .expect("no secret printed");
+    assert!(output.contains(&format!("otpauth://totp/dumbnotes:new?secret={secret}&")));
+    let recovery_codes: Vec<_> = output
+        .lines()
+        .skip_while(|line| !line.starts_with("recovery codes"))
+        .skip(1)
+        .map(totp::hash_recovery_code)
+        .collect();
+    let users: UsersData = toml::from_str(&fs::read_to_string(&user_db)?)?;
+    let new_user = users.users.iter()
+        .find(|user| user.username == "new")
+        .expect("the user was removed");
+    assert_eq!(new_user.totp_secret.as_deref(), Some(secret));
+    assert_eq!(new_user.recovery_codes, recovery_codes);
+    assert_eq!(recovery_codes.len(), 10);
+    call_user_command(&dir, &["disable-totp", "new"], None)?;
+    let users: UsersData = toml::from_str(&fs::read_to_string(&user_db)?)?;
+    let new_user = users.users.iter()
+        .find(|user| user.username == "new")
+        .expect("the user was removed");
+    assert_eq!(new_user.totp_secret, None);
+    assert!(new_user.recovery_codes.is_empty());
+
+    let app_password = call_user_command(
+        &dir,
+        &["add-app-password", "new", "backup", "--scope", "notes:read", "--scope", "account"],
+        None,
+    )?;
+    let users: UsersData = toml::from_str(&fs::read_to_string(&user_db)?)?;
+    let new_user = users.users.iter()
+        .find(|user| user.username == "new")
+        .expect("the user was removed");
+    assert_eq!(new_user.app_passwords.len(), 1);
+    assert_eq!(new_user.app_passwords[0].hash, hash_app_password(app_password.trim()));
+    for args in [
+        ["add-app-password", "new", "backup", "--scope", "account"],
+        ["add-app-password", "new", "other", "--scope", "notes"],
+    ] {
+        assert!(call_user_command(&dir, &args, None).is_err());
+    }
+    let app_passwords = call_user_command(&dir, &["list-app-passwords", "new"], None)?;
+    assert_eq!(app_passwords.lines().collect::<Vec<_>>(), ["backup: notes:read account"]);
+    call_user_command(&dir, &["remove-app-password", "new", "backup"], None)?;
+    let app_passwords = call_user_command(&dir, &["list-app-passwords", "new"], None)?;
+    assert!(app_passwords.is_empty());
+    assert!(call_user_command(&dir, &["remove-app-password", "new", "backup"], None).is_err());
+
+    let shares_dir = dir.child("var/dumbnotes/notes/abc/.shares");
+    shares_dir.create_dir_all()?;
+    let shared = shares_dir.child("00000000-0000-0000-0000-000000000001");
+    shared.write_str("new write\nabcdef read\n")?;
+    let shared_only = shares_dir.child("00000000-0000-0000-0000-000000000002");
+    shared_only.write_str("new read\n")?;
+    call_user_command(&dir, &["remove-user", "new"], None)?;
+    notes_dir.assert(predicates::path::missing());
+    shared.assert("abcdef read\n");
+    shared_only.assert(predicates::path::missing());
+    let users = call_user_command(&dir, &["list-users"], None)?;
+    assert_eq!(users.lines().collect::<Vec<_>>(), ["abc", "abcdef"]);
+    assert!(call_user_command(&dir, &["remove-user", "new"], None).is_err());
+
+    Ok(())
+}
+
+fn call_user_command(
+    dir: &TempDir,
+    args: &[&str],
+    password: Option<&str>,
+) -> Result<String, Box<dyn Error>> {
+    // the user db access checks need the integration test build
+    let mut child = new_configured_command(&GEN_BIN_PATH, dir)
+        .arg("--no-repeat")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?
+        .kill_on_drop();
+    let mut stdin = child.stdin.take().expect("failed to get stdin");
+    if let Some(password) = password {
+        stdin.write_all(password.as_bytes())?;
+    }
+    drop(stdin);
+    let result = child.into_child().wait_with_output()?;
+    if !result.status.success() {
+        return Err(
+            format!(
+                "status: {}, stderr: {}",
+                result.status,
+                String::from_utf8_lossy(&result.stderr),
+            ).into()
+        )
+    }
+    Ok(String::from_utf8(result.stdout)?)
+}
+
 fn call_create(
     dir: &TempDir,
     arg: &str,
@@ -0,0 +1,23 @@
+//! Application passwords, given to the API clients instead of the
+//! user's own password
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use openssl::sha::sha256;
+use rand::Rng;
+
+const APP_PASSWORD_SIZE: usize = 18;
+
+pub fn generate_app_password() -> String {
+    let mut bytes = [0u8; APP_PASSWORD_SIZE];
+    rand::rng().fill_bytes(&mut bytes);
+    Base64UrlUnpadded::encode_string(&bytes)
+}
+
+/// Hex-encoded SHA-256, as the passwords are random enough not to need
+/// a password hash
+pub fn hash_app_password(password: &str) -> String {
+    sha256(password.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
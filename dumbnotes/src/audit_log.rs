@@ -0,0 +1,150 @@
+//! Append-only log of the security-relevant events, one JSON object
+//! per line
+
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use data::{AuditEvent, UsernameStr};
+use log::{error, info, warn};
+use thiserror::Error;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+#[derive(Clone, Debug)]
+pub struct AuditLogConfig {
+    pub path: PathBuf,
+    /// The file is rotated when it would grow over this, zero disables
+    /// the rotation
+    pub max_size: u64,
+    /// Rotated files kept next to the current one, as `<path>.1` being
+    /// the newest
+    pub max_files: u32,
+}
+
+#[derive(Debug, Error)]
+pub enum AuditLogError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("invalid audit log entry: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+pub struct AuditLog {
+    config: AuditLogConfig,
+    file: Mutex<OpenFile>,
+}
+
+struct OpenFile {
+    file: File,
+    size: u64,
+}
+
+impl AuditLog {
+    pub async fn open(config: AuditLogConfig) -> Result<Self, AuditLogError> {
+        let file = open_file(&config.path).await?;
+        Ok(
+            AuditLog {
+                config,
+                file: Mutex::new(file),
+            }
+        )
+    }
+
+    /// Failures are only logged, as they should not fail the audited
+    /// operation
+    pub async fn record(&self, event: &AuditEvent) {
+        if let Err(e) = self.record_impl(event).await {
+            error!(
+                "failed to write {:?} event of user \"{}\" to the audit log: {e}",
+                event.event,
+                event.username,
+            );
+        }
+    }
+
+    async fn record_impl(&self, event: &AuditEvent) -> Result<(), AuditLogError> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().await;
+        let max_size = self.config.max_size;
+        if max_size > 0 && file.size > 0 && file.size + line.len() as u64 > max_size {
+            self.rotate().await?;
+            *file = open_file(&self.config.path).await?;
+        }
+        file.file.write_all(&line).await?;
+        file.file.flush().await?;
+        file.size += line.len() as u64;
+        Ok(())
+    }
+
+    async fn rotate(&self) -> Result<(), AuditLogError> {
+        info!("rotating the audit log at {}", self.config.path.display());
+        if self.config.max_files == 0 {
+            tokio::fs::remove_file(&self.config.path).await?;
+            return Ok(())
+        }
+        for index in (1..self.config.max_files).rev() {
+            let from = self.rotated_path(index);
+            match tokio::fs::rename(&from, self.rotated_path(index + 1)).await {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                _ => (),
+            }
+        }
+        tokio::fs::rename(&self.config.path, self.rotated_path(1)).await?;
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: u32) -> PathBuf {
+        let mut path = OsString::from(self.config.path.as_os_str());
+        path.push(format!(".{index}"));
+        path.into()
+    }
+
+    /// The newest first, including the rotated files
+    pub async fn read_user_events(
+        &self,
+        username: &UsernameStr,
+        limit: usize,
+    ) -> Result<Vec<AuditEvent>, AuditLogError> {
+        // keeps the files from being rotated while reading
+        let _file = self.file.lock().await;
+        let paths = std::iter::once(self.config.path.clone())
+            .chain((1..=self.config.max_files).map(|index| self.rotated_path(index)));
+        let mut events = Vec::new();
+        for path in paths {
+            let contents = match tokio::fs::read_to_string(&path).await {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => break,
+                Err(e) => return Err(e.into()),
+            };
+            for line in contents.lines().rev() {
+                if events.len() >= limit {
+                    return Ok(events)
+                }
+                let event: AuditEvent = match serde_json::from_str(line) {
+                    Ok(event) => event,
+                    Err(e) => {
+                        warn!("skipping audit log line in {}: {e}", path.display());
+                        continue
+                    },
+                };
+                if event.username.as_username_str() == username {
+                    events.push(event);
+                }
+            }
+        }
+        Ok(events)
+    }
+}
+
+async fn open_file(path: &Path) -> Result<OpenFile, AuditLogError> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    let size = file.metadata().await?.len();
+    Ok(OpenFile { file, size })
+}
@@ -1,12 +1,26 @@
+use std::time::Duration;
 use crate::lib_constants::{DEFAULT_MAX_NOTE_LEN, DEFAULT_MAX_NOTE_NAME_LEN};
 
 pub const DEFAULT_CONFIG_FILE: &str = "/etc/dumbnotes/dumbnotes.toml";
 pub const DEFAULT_USER_DB: &str = "/etc/dumbnotes/private/users.toml";
 pub const DEFAULT_DATA_DIR: &str = "/var/dumbnotes";
+pub const DEFAULT_AUDIT_LOG_DIR: &str = "/var/log/dumbnotes";
+// relative to the audit log directory
+pub const AUTH_AUDIT_LOG_PATH: &str = "auth.jsonl";
+pub const STORAGE_AUDIT_LOG_PATH: &str = "storage.jsonl";
+// relative to the data directory
+pub const NOTES_DIRECTORY_PATH: &str = "notes";
+// relative to a user's notes directory, the user management cleans up
+// the shares of the removed users
+pub const SHARES_DIRECTORY_PATH: &str = ".shares";
 pub const DEFAULT_JWT_PRIVATE_KEY: &str = "/etc/dumbnotes/private/jwt_private_key.json";
 pub const DEFAULT_JWT_PUBLIC_KEY: &str = "/etc/dumbnotes/jwt_public_key.json";
+// outlives the web access tokens, the longest-lived ones
+pub const RETIRED_JWT_KEY_VALIDITY_TIME: Duration = Duration::from_secs(6 * 7 * 24 * 60 * 60);
+pub const JWT_KEY_ID_LENGTH: usize = 64 / 8;
 pub const DEFAULT_PEPPER_PATH: &str = "/etc/dumbnotes/private/pepper.b64";
 pub const PEPPER_LENGTH: usize = 128 / 8;
+pub const NOTE_ENCRYPTION_KEY_LENGTH: usize = 256 / 8;
 pub const APP_CONFIG_API_ENV_PREFIX: &str = "DUMBNOTES_API_";
 pub const APP_CONFIG_WEB_ENV_PREFIX: &str = "DUMBNOTES_ENV_";
 
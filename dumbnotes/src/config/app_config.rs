@@ -13,8 +13,21 @@ pub struct AppConfig {
     pub user_db: PathBuf,
     pub jwt_private_key: PathBuf,
     pub jwt_public_key: PathBuf,
+    pub note_encryption_key: Option<PathBuf>,
     pub max_note_size: u64,
     pub max_note_name_size: u64,
+    pub max_note_revisions: u32,
+    pub max_note_revision_age_days: u32,
+    pub trash_retention_days: u32,
+    pub max_user_storage_size: u64,
+    pub max_user_notes: u32,
+    pub login_lockout_threshold: u32,
+    pub address_login_lockout_threshold: u32,
+    pub login_lockout_seconds: u32,
+    pub max_login_lockout_seconds: u32,
+    pub audit_log_directory: PathBuf,
+    pub audit_log_max_size: u64,
+    pub audit_log_max_files: u32,
     pub hasher_config: ProductionHasherConfigData,
     pub api_rocket_config: Option<PathBuf>,
     pub web_rocket_config: Option<PathBuf>,
@@ -32,8 +45,21 @@ impl From<AppConfigData> for AppConfig {
             user_db: value.user_db,
             jwt_private_key: value.jwt_private_key,
             jwt_public_key: value.jwt_public_key,
+            note_encryption_key: value.note_encryption_key,
             max_note_size: value.max_note_size,
             max_note_name_size: value.max_note_name_size,
+            max_note_revisions: value.max_note_revisions,
+            max_note_revision_age_days: value.max_note_revision_age_days,
+            trash_retention_days: value.trash_retention_days,
+            max_user_storage_size: value.max_user_storage_size,
+            max_user_notes: value.max_user_notes,
+            login_lockout_threshold: value.login_lockout_threshold,
+            address_login_lockout_threshold: value.address_login_lockout_threshold,
+            login_lockout_seconds: value.login_lockout_seconds,
+            max_login_lockout_seconds: value.max_login_lockout_seconds,
+            audit_log_directory: value.audit_log_directory,
+            audit_log_max_size: value.audit_log_max_size,
+            audit_log_max_files: value.audit_log_max_files,
             hasher_config: value.hasher_config,
             api_rocket_config: value.api_rocket_config,
             web_rocket_config: value.web_rocket_config,
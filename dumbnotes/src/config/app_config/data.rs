@@ -1,6 +1,6 @@
-use crate::bin_constants::{DEFAULT_DATA_DIR, DEFAULT_JWT_PRIVATE_KEY, DEFAULT_JWT_PUBLIC_KEY, DEFAULT_USER_DB};
+use crate::bin_constants::{DEFAULT_AUDIT_LOG_DIR, DEFAULT_DATA_DIR, DEFAULT_JWT_PRIVATE_KEY, DEFAULT_JWT_PUBLIC_KEY, DEFAULT_USER_DB};
 use crate::config::hasher_config::ProductionHasherConfigData;
-use crate::lib_constants::{DEFAULT_MAX_NOTE_LEN, DEFAULT_MAX_NOTE_NAME_LEN};
+use crate::lib_constants::{DEFAULT_MAX_NOTE_LEN, DEFAULT_MAX_NOTE_NAME_LEN, DEFAULT_MAX_NOTE_REVISIONS, DEFAULT_MAX_NOTE_REVISION_AGE_DAYS, DEFAULT_MAX_USER_NOTES, DEFAULT_MAX_USER_STORAGE_SIZE, DEFAULT_TRASH_RETENTION_DAYS, DEFAULT_LOGIN_LOCKOUT_THRESHOLD, DEFAULT_ADDRESS_LOGIN_LOCKOUT_THRESHOLD, DEFAULT_LOGIN_LOCKOUT_SECONDS, DEFAULT_MAX_LOGIN_LOCKOUT_SECONDS, DEFAULT_AUDIT_LOG_MAX_SIZE, DEFAULT_AUDIT_LOG_MAX_FILES};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -28,17 +28,76 @@ pub struct AppConfigData {
     #[serde(default = "app_config_default_jwt_public_key")]
     pub jwt_public_key: PathBuf,
 
+    /// Notes are encrypted at rest with this key if set, see
+    /// `dumbnotes-gen --generate-note-key`
+    #[serde(default)]
+    pub note_encryption_key: Option<PathBuf>,
+
     #[serde(default = "app_config_default_max_note_size")]
     pub max_note_size: u64,
 
     #[serde(default = "app_config_default_max_note_name_size")]
     pub max_note_name_size: u64,
 
+    #[serde(default = "app_config_default_max_note_revisions")]
+    pub max_note_revisions: u32,
+
+    #[serde(default = "app_config_default_max_note_revision_age_days")]
+    pub max_note_revision_age_days: u32,
+
+    /// Zero keeps deleted notes until purged manually
+    #[serde(default = "app_config_default_trash_retention_days")]
+    pub trash_retention_days: u32,
+
+    /// Total size of a user's files including the trash, the revisions
+    /// and the search index, zero means unlimited, overridable per user in the user db
+    #[serde(default = "app_config_default_max_user_storage_size")]
+    pub max_user_storage_size: u64,
+
+    /// Number of a user's notes not in the trash, zero means
+    /// unlimited, overridable per user in the user db
+    #[serde(default = "app_config_default_max_user_notes")]
+    pub max_user_notes: u32,
+
+    /// Failed logins of a username after which its logins are refused
+    /// for a while, zero disables the lockout
+    #[serde(default = "app_config_default_login_lockout_threshold")]
+    pub login_lockout_threshold: u32,
+
+    /// Same for the failed logins from a client address, counting all
+    /// the usernames tried
+    #[serde(default = "app_config_default_address_login_lockout_threshold")]
+    pub address_login_lockout_threshold: u32,
+
+    /// The first lockout, doubled with each further failure
+    #[serde(default = "app_config_default_login_lockout_seconds")]
+    pub login_lockout_seconds: u32,
+
+    /// Failures are also forgotten after this long
+    #[serde(default = "app_config_default_max_login_lockout_seconds")]
+    pub max_login_lockout_seconds: u32,
+
+    /// Holds the audit logs of the auth and storage daemons, writable
+    /// by both of their users
+    #[serde(default = "app_config_default_audit_log_directory")]
+    pub audit_log_directory: PathBuf,
+
+    /// Size at which an audit log is rotated, zero disables the rotation
+    #[serde(default = "app_config_default_audit_log_max_size")]
+    pub audit_log_max_size: u64,
+
+    /// Rotated audit log files kept
+    #[serde(default = "app_config_default_audit_log_max_files")]
+    pub audit_log_max_files: u32,
+
     #[serde(default, flatten)]
     pub hasher_config: ProductionHasherConfigData,
 
+    /// Rocket config file of the api, which can set `ip_header` to
+    /// the client address header of a trusted reverse proxy
     pub api_rocket_config: Option<PathBuf>,
 
+    /// Same for the web ui
     pub web_rocket_config: Option<PathBuf>,
 
     #[serde(default)]
@@ -72,6 +131,54 @@ pub fn app_config_default_max_note_name_size() -> u64 {
     DEFAULT_MAX_NOTE_NAME_LEN
 }
 
+pub fn app_config_default_max_note_revisions() -> u32 {
+    DEFAULT_MAX_NOTE_REVISIONS
+}
+
+pub fn app_config_default_max_note_revision_age_days() -> u32 {
+    DEFAULT_MAX_NOTE_REVISION_AGE_DAYS
+}
+
+pub fn app_config_default_trash_retention_days() -> u32 {
+    DEFAULT_TRASH_RETENTION_DAYS
+}
+
+pub fn app_config_default_max_user_storage_size() -> u64 {
+    DEFAULT_MAX_USER_STORAGE_SIZE
+}
+
+pub fn app_config_default_max_user_notes() -> u32 {
+    DEFAULT_MAX_USER_NOTES
+}
+
+pub fn app_config_default_login_lockout_threshold() -> u32 {
+    DEFAULT_LOGIN_LOCKOUT_THRESHOLD
+}
+
+pub fn app_config_default_address_login_lockout_threshold() -> u32 {
+    DEFAULT_ADDRESS_LOGIN_LOCKOUT_THRESHOLD
+}
+
+pub fn app_config_default_login_lockout_seconds() -> u32 {
+    DEFAULT_LOGIN_LOCKOUT_SECONDS
+}
+
+pub fn app_config_default_max_login_lockout_seconds() -> u32 {
+    DEFAULT_MAX_LOGIN_LOCKOUT_SECONDS
+}
+
+pub fn app_config_default_audit_log_directory() -> PathBuf {
+    DEFAULT_AUDIT_LOG_DIR.into()
+}
+
+pub fn app_config_default_audit_log_max_size() -> u64 {
+    DEFAULT_AUDIT_LOG_MAX_SIZE
+}
+
+pub fn app_config_default_audit_log_max_files() -> u32 {
+    DEFAULT_AUDIT_LOG_MAX_FILES
+}
+
 impl Default for AppConfigData {
     fn default() -> Self {
         AppConfigData {
@@ -82,8 +189,21 @@ impl Default for AppConfigData {
             user_db: DEFAULT_USER_DB.into(),
             jwt_private_key: DEFAULT_JWT_PRIVATE_KEY.into(),
             jwt_public_key: DEFAULT_JWT_PUBLIC_KEY.into(),
+            note_encryption_key: Default::default(),
             max_note_size: DEFAULT_MAX_NOTE_LEN,
             max_note_name_size: DEFAULT_MAX_NOTE_NAME_LEN,
+            max_note_revisions: DEFAULT_MAX_NOTE_REVISIONS,
+            max_note_revision_age_days: DEFAULT_MAX_NOTE_REVISION_AGE_DAYS,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            max_user_storage_size: DEFAULT_MAX_USER_STORAGE_SIZE,
+            max_user_notes: DEFAULT_MAX_USER_NOTES,
+            login_lockout_threshold: DEFAULT_LOGIN_LOCKOUT_THRESHOLD,
+            address_login_lockout_threshold: DEFAULT_ADDRESS_LOGIN_LOCKOUT_THRESHOLD,
+            login_lockout_seconds: DEFAULT_LOGIN_LOCKOUT_SECONDS,
+            max_login_lockout_seconds: DEFAULT_MAX_LOGIN_LOCKOUT_SECONDS,
+            audit_log_directory: DEFAULT_AUDIT_LOG_DIR.into(),
+            audit_log_max_size: DEFAULT_AUDIT_LOG_MAX_SIZE,
+            audit_log_max_files: DEFAULT_AUDIT_LOG_MAX_FILES,
             hasher_config: Default::default(),
             api_rocket_config: Default::default(),
             web_rocket_config: Default::default(),
@@ -11,7 +11,7 @@ use async_stream::try_stream;
 use log::{debug, error, log_enabled, trace};
 use tokio_stream::StreamExt;
 use tokio_stream::wrappers::BroadcastStream;
-use crate::app_constants::FILE_WATCHER_DEBOUNCE_TIME;
+use crate::lib_constants::FILE_WATCHER_DEBOUNCE_TIME;
 use crate::file_watcher::{Event, FileWatchGuard, FileWatcher, FileWatcherError};
 
 const FILE_WATCHER_BUFFER_SIZE: usize = 16;
@@ -77,7 +77,7 @@ impl<W: notify::Watcher> FileWatcherImpl<W> {
                     Mutex::new(
                         FileWatcherInternal {
                             watcher: new_debouncer_opt(
-                                FILE_WATCHER_DEBOUNCE_TIME.unsigned_abs(),
+                                FILE_WATCHER_DEBOUNCE_TIME,
                                 None,
                                 Callback(sender.clone()),
                                 Default::default(),
@@ -130,13 +130,17 @@ impl<W: notify::Watcher> Drop for FileWatchGuardImpl<W> {
             .lock().expect("failed locking the file watcher")
             .watcher
             .unwatch(&self.path)
-            .unwrap_or_else(|e|
-                error!("failed to unwatch {}: {e}", self.path.display())
-            )
+            .unwrap_or_else(|e| match e.kind {
+                // the watch goes with a file replaced by renaming another
+                // one over it, until the event for that rewatches the path
+                notify::ErrorKind::WatchNotFound =>
+                    debug!("{} is not watched anymore", self.path.display()),
+                _ => error!("failed to unwatch {}: {e}", self.path.display()),
+            })
     }
 }
 
-impl<W: notify::Watcher + Send + Sync> FileWatchGuard for FileWatchGuardImpl<W> {
+impl<W: notify::Watcher + Send + Sync + 'static> FileWatchGuard for FileWatchGuardImpl<W> {
     fn get_events(&self) -> impl Stream<Item=Result<Event, FileWatcherError>> + Send + 'static {
         let lock = self.file_watcher
             .lock().expect("failed locking the file watcher");
@@ -156,6 +160,8 @@ impl<W: notify::Watcher + Send + Sync> FileWatchGuard for FileWatchGuardImpl<W>
             Cow::Borrowed("")
         };
         let skip_modification = self.skip_modification.clone();
+        let file_watcher = self.file_watcher.clone();
+        let watched_path = self.path.clone();
         try_stream! {
             let mut do_drop_one = false;
 
@@ -187,6 +193,17 @@ impl<W: notify::Watcher + Send + Sync> FileWatchGuard for FileWatchGuardImpl<W>
                     },
                 }?;
                 if let Some(e) = event {
+                    // a file replaced by renaming another one over it is
+                    // a new inode, the watch stays with the old one
+                    let rewatch_result = {
+                        let mut inner = file_watcher
+                            .lock().expect("failed locking the file watcher");
+                        let _ = inner.watcher.unwatch(&watched_path);
+                        inner.watcher
+                            .watch(&watched_path, RecursiveMode::NonRecursive)
+                    };
+                    rewatch_result.map_err(FileWatcherError::WatchStart)?;
+
                     if do_drop_one {
                         do_drop_one = false;
                         trace!("dropping event {e:?}");
@@ -3,6 +3,7 @@ use std::marker::PhantomData;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
+use futures::stream::BoxStream;
 use futures::{pin_mut, Stream};
 use log::{error, trace, warn};
 use scc::HashMap;
@@ -11,11 +12,13 @@ use thiserror::Error;
 use tokio::io::AsyncWriteExt;
 use tokio::net::unix::OwnedWriteHalf;
 use tokio::net::UnixStream;
-use tokio::sync::{oneshot, Mutex};
+use tokio::sync::{mpsc, oneshot, Mutex};
 use tokio_stream::StreamExt;
+use tokio_stream::wrappers::ReceiverStream;
 use util::error_exit;
 use crate::ipc::message_stream;
 use crate::ipc::data::{IpcInputContainerWrapper, IpcOutput};
+use crate::lib_constants::IPC_SUBSCRIPTION_BUFFER_LEN;
 use protobuf_common::ProtobufRequestError;
 
 pub trait Caller<
@@ -28,20 +31,32 @@ pub trait Caller<
         &self,
         command: CommandWrapper,
     ) -> impl Future<Output = Result<Response, CallerError>> + Send;
+
+    /// Executes a command answered with a stream of responses, dropping
+    /// the returned stream cancels the command
+    fn subscribe(
+        &self,
+        command: CommandWrapper,
+    ) -> impl Future<Output = Result<Subscription<Response>, CallerError>> + Send;
 }
 
+pub type Subscription<Response> = BoxStream<'static, Result<Response, CallerError>>;
+
 pub struct CallerImpl<
     Response: Send + Sync,
     ResponseContainer: prost::Message,
     ResponseContainerWrapper: IpcInputContainerWrapper<Response, ResponseContainer>,
 > where
 {
-    write_socket: Mutex<OwnedWriteHalf>,
+    write_socket: Arc<Mutex<OwnedWriteHalf>>,
     next_request_id: AtomicU64,
     read_task: tokio::task::AbortHandle,
     active_requests: Arc<
         HashMap<u64, oneshot::Sender<ResponseContainerWrapper>>
     >,
+    active_subscriptions: Arc<
+        HashMap<u64, mpsc::Sender<ResponseContainerWrapper>>
+    >,
     max_message_size: usize,
     _phantom: PhantomData<(Response, ResponseContainer)>,
 }
@@ -73,22 +88,26 @@ where
         let (read_socket, write_socket) = socket.into_split();
         let stored_active_requests = Arc::new(HashMap::new());
         let active_requests = stored_active_requests.clone();
+        let stored_active_subscriptions = Arc::new(HashMap::new());
+        let active_subscriptions = stored_active_subscriptions.clone();
         let responses = message_stream::stream(read_socket, max_message_size)
             .map(IpcInputContainerWrapper::wrap);
         let (shutdown_transmitter, shutdown_receiver) = oneshot::channel();
         let read_task = tokio::task::spawn(
             Self::process_responses(
                 active_requests,
+                active_subscriptions,
                 responses,
                 shutdown_transmitter,
             )
         );
         (
             CallerImpl {
-                write_socket: Mutex::new(write_socket),
+                write_socket: Arc::new(Mutex::new(write_socket)),
                 next_request_id: AtomicU64::new(0),
                 read_task: read_task.abort_handle(),
                 active_requests: stored_active_requests,
+                active_subscriptions: stored_active_subscriptions,
                 max_message_size,
                 _phantom: Default::default(),
             },
@@ -108,6 +127,7 @@ where
 {
     async fn process_responses(
         active_requests: Arc<HashMap<u64, oneshot::Sender<ResponseContainerWrapper>>>,
+        active_subscriptions: Arc<HashMap<u64, mpsc::Sender<ResponseContainerWrapper>>>,
         responses: impl Stream<Item=ResponseContainerWrapper>,
         shutdown_notice: oneshot::Sender<()>,
     ) {
@@ -115,6 +135,17 @@ where
         while let Some(response) = responses.next().await {
             trace!("received response: {response:?}");
             let command_id = response.get_id();
+            if let Some(sender) = active_subscriptions.read_sync(&command_id, |_, s| s.clone()) {
+                // dropping the sender ends the subscriber's stream
+                if response.is_empty() {
+                    trace!("subscription {command_id} ended");
+                    active_subscriptions.remove_sync(&command_id);
+                } else if let Err(e) = sender.try_send(response) {
+                    warn!("dropping subscription {command_id}: {e}");
+                    active_subscriptions.remove_sync(&command_id);
+                }
+                continue
+            }
             let Some((_, sender)) = active_requests.remove_sync(&command_id) else {
                 warn!(
                     "received response to unknown or dropped request, id {}",
@@ -174,18 +205,75 @@ where
         let request_guard = guard(self.active_requests.clone(), |ar| {
             ar.remove_sync(&request_id);
         });
-        let mut socket = self.write_socket.lock().await;
-        socket.write_u64(command.len() as u64).await?;
-        socket.write_all(command).await
-            .unwrap_or_else(|e|
-                error_exit!("failed marshalling an auth command: {e}")
-            );
+        write_message(&self.write_socket, command).await?;
         let (_, response) = receiver.await?.into_id_and_input();
         drop(request_guard);
         let response = response?;
         trace!("successfully awaited response: {response:?}");
         Ok(response)
     }
+
+    async fn subscribe(
+        &self,
+        command: CommandWrapper,
+    ) -> Result<Subscription<Response>, CallerError> {
+        trace!("subscribing with command {command:?}");
+        let request_id = self.get_next_request_id();
+        let command = command.into_container(request_id).encode_to_vec();
+        if command.len() > self.max_message_size {
+            return Err(
+                CallerError::MessageTooBig {
+                    length: command.len(),
+                    max: self.max_message_size,
+                }
+            );
+        }
+        let (sender, receiver) = mpsc::channel(IPC_SUBSCRIPTION_BUFFER_LEN);
+        self.active_subscriptions.insert_sync(request_id, sender)
+            .unwrap_or_else(|_|
+                error_exit!("found previous instance of subscription with id {request_id}")
+            );
+        let active_subscriptions = self.active_subscriptions.clone();
+        let write_socket = self.write_socket.clone();
+        let subscription_guard = guard((), move |_| {
+            // still active means the daemon has to be told to stop
+            if active_subscriptions.remove_sync(&request_id).is_some() {
+                trace!("cancelling subscription {request_id}");
+                let cancellation = CommandWrapper::empty_container(request_id)
+                    .encode_to_vec();
+                tokio::spawn(async move {
+                    if let Err(e) = write_message(&write_socket, &cancellation).await {
+                        error!("failed to cancel subscription {request_id}: {e}");
+                    }
+                });
+            }
+        });
+        write_message(&self.write_socket, &command).await?;
+        Ok(
+            Box::pin(
+                ReceiverStream::new(receiver)
+                    .map(move |response: ResponseContainerWrapper| {
+                        // dropped along with the stream
+                        let _ = &subscription_guard;
+                        let (_, response) = response.into_id_and_input();
+                        Ok(response?)
+                    })
+            )
+        )
+    }
+}
+
+async fn write_message(
+    write_socket: &Mutex<OwnedWriteHalf>,
+    message: &[u8],
+) -> Result<(), io::Error> {
+    let mut socket = write_socket.lock().await;
+    socket.write_u64(message.len() as u64).await?;
+    socket.write_all(message).await
+        .unwrap_or_else(|e|
+            error_exit!("failed marshalling an auth command: {e}")
+        );
+    Ok(())
 }
 
 #[derive(Debug, Error)]
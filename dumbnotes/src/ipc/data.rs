@@ -14,6 +14,10 @@ pub trait IpcInputContainerWrapper<
         (self.get_id(), self.get_input())
     }
 
+    /// Containers without a payload end a response stream when received
+    /// by a caller and cancel it when received by an event loop
+    fn is_empty(&self) -> bool;
+
     fn wrap(wrapped: W) -> Self;
 }
 
@@ -22,6 +26,8 @@ pub trait IpcOutput<
     W: prost::Message + Sized,
 >: Send + Sync + 'static {
     fn into_container(self, command_id: u64) -> W;
+
+    fn empty_container(command_id: u64) -> W;
 }
 
 pub enum LoopInputMessage<T> {
@@ -1,14 +1,29 @@
 use std::{io, sync::Arc};
 
+use futures::future::AbortHandle;
+use futures::stream::{Abortable, BoxStream};
 use futures::{Stream, StreamExt, pin_mut};
 use log::{debug, error, info, trace, warn};
 use protobuf_common::ProtobufRequestError;
-use scc::HashSet;
+use scc::HashMap;
 use thiserror::Error;
 use tokio::{io::AsyncWriteExt, net::unix::OwnedWriteHalf, sync::Mutex};
 use util::error_exit;
 use crate::{ipc::data::{IpcInputContainerWrapper, IpcOutput, LoopInputMessage}, lib_constants::BIN_SHUTDOWN_TIMEOUT};
 
+/// What a dispatcher answers a command with: a single response, or a stream
+/// of them running until it ends or the caller cancels it
+pub enum Dispatched<T> {
+    Response(T),
+    Stream(BoxStream<'static, T>),
+}
+
+impl<T> From<T> for Dispatched<T> {
+    fn from(value: T) -> Self {
+        Dispatched::Response(value)
+    }
+}
+
 pub async fn process_commands<
     Command: Send + Sync + 'static,
     CommandContainer: prost::Message,
@@ -40,20 +55,21 @@ where
         + Sync
         + 'static,
     DispatcherOutput: Future<
-        Output = Result<LResponse, DispatcherError>
+        Output = Result<Dispatched<LResponse>, DispatcherError>
     > + Send + 'static,
 {
     info!("{} listening to commands", loop_name.as_ref());
 
     struct InnerState {
         write_socket: Mutex<OwnedWriteHalf>,
-        active_request_ids: HashSet<u64>,
+        // the handles only matter for streams
+        active_requests: HashMap<u64, AbortHandle>,
     }
 
     let owned_inner_state = Arc::new(
         InnerState {
             write_socket: Mutex::new(write_socket),
-            active_request_ids: HashSet::<u64>::new(),
+            active_requests: HashMap::<u64, AbortHandle>::new(),
         }
     );
     let owned_dispatcher = Arc::new(dispatcher);
@@ -71,9 +87,22 @@ where
 
         trace!("received command: {l_command:?}");
 
+        if l_command.is_empty() {
+            let command_id = l_command.get_id();
+            match owned_inner_state.active_requests.remove_sync(&command_id) {
+                Some((_, abort_handle)) => {
+                    debug!("cancelling command {command_id}");
+                    abort_handle.abort();
+                },
+                None => trace!("ignoring cancellation of finished command {command_id}"),
+            }
+            continue
+        }
+
         let (command_id, command) = l_command.into_id_and_input();
 
-        if owned_inner_state.active_request_ids.insert_sync(command_id).is_err() {
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        if owned_inner_state.active_requests.insert_sync(command_id, abort_handle).is_err() {
             error!("duplicate command id: {}", command_id);
             continue
         }
@@ -81,7 +110,7 @@ where
             Ok(command) => command,
             Err(e) => {
                 error!("failed to parse command with id {command_id}: {e}");
-                owned_inner_state.active_request_ids.remove_sync(&command_id);
+                owned_inner_state.active_requests.remove_sync(&command_id);
                 continue
             },
         };
@@ -90,37 +119,70 @@ where
         let owned_dispatcher = owned_dispatcher.clone();
         let inner_state = owned_inner_state.clone();
         tokio::spawn(async move {
+            // an oversized response is answered with an empty one, which
+            // the caller takes for an error, returns whether it was sent
+            let write = async |response| {
+                let mut write_socket = inner_state.write_socket.lock().await;
+                match write_response(&mut write_socket, response, max_message_len).await {
+                    Ok(()) => true,
+                    Err(e @ DispatchCommandError::MessageTooBig { .. }) => {
+                        error!("failed to send the response to command {command_id}: {e}");
+                        write_response(
+                            &mut write_socket,
+                            LResponse::empty_container(command_id),
+                            max_message_len,
+                        ).await
+                            .unwrap_or_else(|e|
+                                error_exit!("error writing to the control socket: {e}")
+                            );
+                        false
+                    },
+                    Err(e) => error_exit!("error writing to the control socket: {e}"),
+                }
+            };
             match owned_dispatcher(command, state).await {
-                Ok(response) => {
+                Ok(Dispatched::Response(response)) => {
                     debug!("command {command_id} executed successfully");
-                    write_response(
-                        &mut *inner_state.write_socket.lock().await,
-                        response.into_container(command_id),
-                        max_message_len,
-                    ).await
-                        .unwrap_or_else(|e|
-                            error_exit!("error writing to the control socket: {e}")
-                        );
+                    write(response.into_container(command_id)).await;
+                },
+                Ok(Dispatched::Stream(responses)) => {
+                    debug!("streaming responses to command {command_id}");
+                    // only the stream is aborted, never a write in progress
+                    let mut responses = Abortable::new(responses, abort_registration);
+                    let mut sent = true;
+                    while sent && let Some(response) = responses.next().await {
+                        sent = write(response.into_container(command_id)).await;
+                    }
+                    if sent && !responses.is_aborted() {
+                        debug!("response stream to command {command_id} ended");
+                        write(LResponse::empty_container(command_id)).await;
+                    }
                 },
                 Err(e) => {
                     error!("failed executing command {command_id}: {e}");
                 }
             };
 
-            inner_state.active_request_ids.remove_sync(&command_id);
+            inner_state.active_requests.remove_sync(&command_id);
         });
     }
 
     debug!("command connection closed");
 
-    let active_count = owned_inner_state.active_request_ids.len();
+    // streams have no natural end, so they're cut off right away
+    owned_inner_state.active_requests.iter_sync(|_, abort_handle| {
+        abort_handle.abort();
+        true
+    });
+    tokio::task::yield_now().await;
+    let active_count = owned_inner_state.active_requests.len();
     if active_count == 0 {
         return
     }
 
     warn!("waiting for {active_count} active requests to finish");
     tokio::time::sleep(BIN_SHUTDOWN_TIMEOUT).await;
-    let active_count = owned_inner_state.active_request_ids.len();
+    let active_count = owned_inner_state.active_requests.len();
     if active_count != 0 {
         error!("dropping {active_count} active requests after timeout");
     }
@@ -30,6 +30,10 @@ macro_rules! gen_proto_ipc_wrappers {
                 )
             }
 
+            fn is_empty(&self) -> bool {
+                self.0.$input_name.is_none()
+            }
+
             fn wrap(wrapped: $input_container_binding) -> Self {
                 $input_container_wrapper(wrapped)
             }
@@ -51,6 +55,13 @@ macro_rules! gen_proto_ipc_wrappers {
                     $output_name: Some(self.0),
                 }
             }
+
+            fn empty_container(command_id: u64) -> $output_container_binding {
+                $output_container_binding {
+                    command_id,
+                    $output_name: None,
+                }
+            }
         }
     };
 }
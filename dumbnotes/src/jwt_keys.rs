@@ -0,0 +1,110 @@
+use std::error::Error;
+use std::io;
+use std::path::Path;
+use std::pin::pin;
+use std::time::Duration;
+use access_token::{parse_jwk_set, AccessTokenDecoder, AccessTokenGenerator};
+use futures::StreamExt;
+use josekit::JoseError;
+use josekit::jwk::{Jwk, JwkSet};
+use log::{error, info};
+use thiserror::Error;
+use unix::check_secret_file_ro_access;
+use unix::errors::CheckAccessError;
+use crate::file_watcher::{Event, FileWatchGuard, FileWatcher, FileWatcherError, ProductionFileWatcher};
+use crate::lib_constants::JWT_KEY_ACTIVATION_DELAY;
+
+#[derive(Debug, Error)]
+pub enum JwtKeyError {
+    #[error("cannot access the key file: {0}")]
+    CheckAccess(#[from] CheckAccessError),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("invalid key: {0}")]
+    Parse(#[from] JoseError),
+}
+
+pub fn read_jwt_private_key(path: &Path) -> Result<Jwk, JwtKeyError> {
+    check_secret_file_ro_access(path)?;
+    Ok(Jwk::from_bytes(std::fs::read(path)?)?)
+}
+
+/// The file is either a JWK set or a single JWK
+pub fn read_jwt_public_keys(path: &Path) -> Result<JwkSet, JwtKeyError> {
+    Ok(parse_jwk_set(std::fs::read(path)?)?)
+}
+
+/// Keeps the generator signing with the key at `path`, switching to a new
+/// one only after the other daemons had the time to load its public part
+pub fn watch_jwt_private_key(
+    file_watcher: &ProductionFileWatcher,
+    path: &Path,
+    token_generator: AccessTokenGenerator,
+) -> Result<(), FileWatcherError> {
+    watch_key_file(
+        file_watcher,
+        path,
+        JWT_KEY_ACTIVATION_DELAY,
+        move |path| {
+            token_generator.replace_key(&read_jwt_private_key(path)?)?;
+            Ok(())
+        },
+    )
+}
+
+pub fn watch_jwt_public_keys(
+    file_watcher: &ProductionFileWatcher,
+    path: &Path,
+    token_decoder: AccessTokenDecoder,
+) -> Result<(), FileWatcherError> {
+    watch_key_file(
+        file_watcher,
+        path,
+        Duration::ZERO,
+        move |path| {
+            token_decoder.replace_keys(&read_jwt_public_keys(path)?)?;
+            Ok(())
+        },
+    )
+}
+
+fn watch_key_file(
+    file_watcher: &ProductionFileWatcher,
+    path: &Path,
+    activation_delay: Duration,
+    mut reload: impl FnMut(&Path) -> Result<(), Box<dyn Error>> + Send + 'static,
+) -> Result<(), FileWatcherError> {
+    let file_watch_guard = file_watcher.watch(path)?;
+    let path = path.to_owned();
+    tokio::spawn(async move {
+        let mut events = pin!(file_watch_guard.get_events());
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(Event::Any) | Err(FileWatcherError::Overflow(_)) => {
+                    if !activation_delay.is_zero() {
+                        info!(
+                            "reading updated jwt key at \"{}\" in {}s",
+                            path.display(),
+                            activation_delay.as_secs(),
+                        );
+                        tokio::time::sleep(activation_delay).await;
+                    }
+                    info!("reading updated jwt key at \"{}\"", path.display());
+                    if let Err(e) = reload(&path) {
+                        error!(
+                            "failed to reload jwt key at \"{}\", keeping the old one: {e}",
+                            path.display(),
+                        );
+                    }
+                },
+                Err(e) => error!(
+                    "failed to watch jwt key updates at \"{}\": {e}",
+                    path.display(),
+                ),
+            }
+        }
+    });
+    Ok(())
+}
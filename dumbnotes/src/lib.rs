@@ -1,11 +1,16 @@
 // TODO: split into more crates (for example, userdb is irrelevant
 //  for local editing)
 
+pub mod app_password;
+pub mod audit_log;
 pub mod config;
+pub mod file_watcher;
 mod lib_constants;
 pub mod bin_constants;
 pub mod hasher;
 pub mod logging;
 pub mod ipc;
+pub mod jwt_keys;
 pub mod sandbox;
+pub mod totp;
 #[cfg(test)] pub mod test;
@@ -5,6 +5,17 @@ use std::time::Duration;
 // TODO: validate the limits to match each other on startup
 pub const DEFAULT_MAX_NOTE_LEN: u64 = 128 * 1024;
 pub const DEFAULT_MAX_NOTE_NAME_LEN: u64 = 256;
+pub const DEFAULT_MAX_NOTE_REVISIONS: u32 = 20;
+pub const DEFAULT_MAX_NOTE_REVISION_AGE_DAYS: u32 = 90;
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+pub const DEFAULT_MAX_USER_STORAGE_SIZE: u64 = 0;
+pub const DEFAULT_MAX_USER_NOTES: u32 = 0;
+pub const DEFAULT_LOGIN_LOCKOUT_THRESHOLD: u32 = 10;
+pub const DEFAULT_ADDRESS_LOGIN_LOCKOUT_THRESHOLD: u32 = 20;
+pub const DEFAULT_LOGIN_LOCKOUT_SECONDS: u32 = 30;
+pub const DEFAULT_MAX_LOGIN_LOCKOUT_SECONDS: u32 = 60 * 60;
+pub const DEFAULT_AUDIT_LOG_MAX_SIZE: u64 = 8 * 1024 * 1024;
+pub const DEFAULT_AUDIT_LOG_MAX_FILES: u32 = 4;
 
 // the defaults are taken from the argon2 crate itself
 // TODO: check that the defaults are sane
@@ -13,5 +24,13 @@ pub const DEFAULT_ARGON2_T_COST: u32 = 2;
 pub const DEFAULT_ARGON2_P_COST: u32 = 1;
 pub const DEFAULT_ARGON2_OUTPUT_LEN: Option<usize> = Some(32);
 
+// responses a subscriber may lag behind before the subscription is dropped
+pub const IPC_SUBSCRIPTION_BUFFER_LEN: usize = 64;
+
+pub const FILE_WATCHER_DEBOUNCE_TIME: Duration = Duration::from_secs(10);
+// the other daemons load the new public key after a debounce, so the tokens
+// signed with the new private key are not rejected by them
+pub const JWT_KEY_ACTIVATION_DELAY: Duration = Duration::from_secs(20);
+
 // TODO: to the config
 pub const BIN_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
@@ -88,10 +88,19 @@ pub fn pledge_gen_hash() {
     )
 }
 
+pub fn pledge_gen_users() {
+    trace!("pledging for editing the user db");
+    pledge(
+        // wpath is needed since 7.9 for rpassword to function
+        Some("stdio rpath wpath cpath tty getpw chown fattr"),
+        None,
+    )
+}
+
 // unix is for initializing syslog
 pub fn pledge_storage_init() {
     pledge(
-        Some("stdio rpath wpath cpath unix getpw unveil"),
+        Some("stdio rpath wpath cpath fattr unix getpw unveil"),
         None,
     )
 }
@@ -99,7 +108,7 @@ pub fn pledge_storage_init() {
 pub fn pledge_storage_normal() {
     trace!("pledging for continuous operation");
     pledge(
-        Some("stdio rpath wpath cpath"),
+        Some("stdio rpath wpath cpath fattr"),
         None,
     )
 }
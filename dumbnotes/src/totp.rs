@@ -0,0 +1,173 @@
+//! RFC 6238 one-time passwords with the parameters authenticator apps
+//! assume by default, and the recovery codes replacing them
+
+#[cfg(test)] mod tests;
+
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::memcmp;
+use openssl::pkey::PKey;
+use openssl::sha::sha256;
+use openssl::sign::Signer;
+use rand::Rng;
+use thiserror::Error;
+
+const SECRET_SIZE: usize = 20;
+const TIME_STEP_SECONDS: u64 = 30;
+const CODE_DIGITS: usize = 6;
+// steps accepted on each side of the current one, for clock drift
+const ALLOWED_DRIFT_STEPS: u64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_GROUP_SIZE: usize = 5;
+const ISSUER: &str = "dumbnotes";
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[derive(Debug, Error)]
+pub enum TotpError {
+    #[error("invalid TOTP secret encoding")]
+    SecretDecode,
+
+    #[error("failed to compute TOTP code: {0}")]
+    Hmac(#[from] ErrorStack),
+}
+
+/// A random secret, base32-encoded
+pub fn generate_secret() -> String {
+    let mut secret = [0u8; SECRET_SIZE];
+    rand::rng().fill_bytes(&mut secret);
+    encode_base32(&secret)
+}
+
+/// The enrollment URI for authenticator apps, usually shown as a QR code
+pub fn make_otpauth_uri(username: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{ISSUER}:{}?secret={secret}&issuer={ISSUER}\
+            &algorithm=SHA1&digits={CODE_DIGITS}&period={TIME_STEP_SECONDS}",
+        percent_encode(username),
+    )
+}
+
+/// Returns the time step the code was generated for, if it's valid at
+/// `unix_time`
+pub fn check_code(
+    secret: &str,
+    code: &str,
+    unix_time: u64,
+) -> Result<Option<u64>, TotpError> {
+    if !is_totp_code(code) {
+        return Ok(None)
+    }
+    let key = decode_base32(secret).ok_or(TotpError::SecretDecode)?;
+    let current_step = unix_time / TIME_STEP_SECONDS;
+    for step in current_step.saturating_sub(ALLOWED_DRIFT_STEPS)
+        ..=current_step + ALLOWED_DRIFT_STEPS
+    {
+        let expected = make_code(&key, step)?;
+        if memcmp::eq(expected.as_bytes(), code.as_bytes()) {
+            return Ok(Some(step))
+        }
+    }
+    Ok(None)
+}
+
+/// The code an authenticator app shows at `unix_time`
+pub fn generate_code(secret: &str, unix_time: u64) -> Result<String, TotpError> {
+    let key = decode_base32(secret).ok_or(TotpError::SecretDecode)?;
+    make_code(&key, unix_time / TIME_STEP_SECONDS)
+}
+
+/// Whether the second factor looks like a TOTP code rather than
+/// a recovery code
+pub fn is_totp_code(code: &str) -> bool {
+    code.len() == CODE_DIGITS && code.bytes().all(|c| c.is_ascii_digit())
+}
+
+/// Codes formatted for the user, to be stored hashed
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; RECOVERY_CODE_GROUP_SIZE * 2 * 5 / 8];
+            rand::rng().fill_bytes(&mut bytes);
+            let code = encode_base32(&bytes).to_ascii_lowercase();
+            let (first, second) = code.split_at(RECOVERY_CODE_GROUP_SIZE);
+            format!("{first}-{second}")
+        })
+        .collect()
+}
+
+/// Hex-encoded SHA-256 of the code, ignoring its case and separators;
+/// the codes are random enough not to need a password hash
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    sha256(normalized.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn make_code(key: &[u8], step: u64) -> Result<String, TotpError> {
+    let key = PKey::hmac(key)?;
+    let mut signer = Signer::new(MessageDigest::sha1(), &key)?;
+    signer.update(&step.to_be_bytes())?;
+    let hmac = signer.sign_to_vec()?;
+    let offset = (hmac[hmac.len() - 1] & 0xf) as usize;
+    let truncated = u32::from_be_bytes(
+        hmac[offset..offset + 4].try_into().unwrap()
+    ) & 0x7fff_ffff;
+    let code = truncated % 10u32.pow(CODE_DIGITS as u32);
+    Ok(format!("{code:0CODE_DIGITS$}"))
+}
+
+fn encode_base32(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            result.push(BASE32_ALPHABET[(buffer >> bits) as usize & 0x1f] as char);
+        }
+    }
+    if bits > 0 {
+        result.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 0x1f] as char);
+    }
+    result
+}
+
+// lenient, as the secrets may be typed in by hand
+fn decode_base32(data: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::with_capacity(data.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in data.bytes() {
+        if c == b'=' || c.is_ascii_whitespace() {
+            continue
+        }
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a == c.to_ascii_uppercase())?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            result.push((buffer >> bits) as u8);
+        }
+    }
+    (!result.is_empty()).then_some(result)
+}
+
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~'
+            => (b as char).to_string(),
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
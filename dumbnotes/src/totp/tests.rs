@@ -0,0 +1,58 @@
+use super::*;
+
+// RFC 6238 appendix B, truncated to six digits
+const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+#[test]
+fn rfc_test_vectors() {
+    for (time, code) in [
+        (59, "287082"),
+        (1111111109, "081804"),
+        (1111111111, "050471"),
+        (1234567890, "005924"),
+        (2000000000, "279037"),
+    ] {
+        assert_eq!(
+            check_code(RFC_SECRET, code, time).unwrap(),
+            Some(time / TIME_STEP_SECONDS),
+        );
+    }
+}
+
+#[test]
+fn clock_drift() {
+    let time = 1111111109;
+    let step = time / TIME_STEP_SECONDS;
+    assert_eq!(check_code(RFC_SECRET, "081804", time + 30).unwrap(), Some(step));
+    assert_eq!(check_code(RFC_SECRET, "081804", time - 30).unwrap(), Some(step));
+    assert_eq!(check_code(RFC_SECRET, "081804", time + 90).unwrap(), None);
+    assert_eq!(check_code(RFC_SECRET, "08180", time).unwrap(), None);
+    assert_eq!(check_code(RFC_SECRET, "abcdef", time).unwrap(), None);
+}
+
+#[test]
+fn base32_roundtrip() {
+    assert_eq!(encode_base32(b"12345678901234567890"), RFC_SECRET);
+    assert_eq!(
+        decode_base32(&RFC_SECRET.to_ascii_lowercase()).unwrap(),
+        b"12345678901234567890",
+    );
+    let secret = generate_secret();
+    assert_eq!(decode_base32(&secret).unwrap().len(), SECRET_SIZE);
+    assert!(decode_base32("not base32!").is_none());
+}
+
+#[test]
+fn recovery_codes() {
+    let codes = generate_recovery_codes();
+    assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+    for code in &codes {
+        assert_eq!(code.len(), RECOVERY_CODE_GROUP_SIZE * 2 + 1);
+        assert!(!is_totp_code(code));
+        assert_eq!(
+            hash_recovery_code(code),
+            hash_recovery_code(&code.replace('-', " ").to_ascii_uppercase()),
+        );
+    }
+    assert_ne!(hash_recovery_code(&codes[0]), hash_recovery_code(&codes[1]));
+}
@@ -1,19 +1,35 @@
 use access_token::{AccessTokenValidator, AccessTokenValidatorError};
 use auth_ipc_data::model::successful_login::SuccessfulLogin;
-use data::{SessionKind, UsernameStr};
+use data::{AppPasswordInfo, AuditEvent, Scope, SessionDescription, SessionKind, UsernameStr};
 use dumbnotes::bin_constants::IPC_MESSAGE_MAX_SIZE;
 use dumbnotes::gen_proto_ipc_wrappers;
 use dumbnotes::ipc::data::IpcOutput;
-use tokio::sync::oneshot;
+use tokio::sync::{broadcast, oneshot};
+use std::collections::{BTreeSet, HashMap};
 use std::marker::PhantomData;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Instant;
 use async_trait::async_trait;
 use log::{debug, error, trace};
 use tokio::net::UnixStream;
 use dumbnotes::ipc::caller::{Caller, CallerImpl};
-use auth_ipc_data::model::login::{LoginRequest, LoginResponse};
+use auth_ipc_data::model::login::{LoginFailure, LoginRequest, LoginResponse};
 use auth_ipc_data::model::logout::{LogoutRequest, LogoutResponse};
 use auth_ipc_data::model::refresh_token::{RefreshTokenRequest, RefreshTokenResponse};
-use auth_ipc_data::bindings::{self, LoginError, LogoutError};
+use auth_ipc_data::model::change_password::{ChangePasswordRequest, ChangePasswordResponse, ChangePasswordSuccess};
+use auth_ipc_data::model::list_sessions::{ListSessionsRequest, ListSessionsResponse};
+use auth_ipc_data::model::revoke_session::{RevokeSessionRequest, RevokeSessionResponse};
+use auth_ipc_data::model::revoke_all_sessions::{RevokeAllSessionsRequest, RevokeAllSessionsResponse};
+use auth_ipc_data::model::check_session::{CheckSessionRequest, CheckSessionResponse};
+use auth_ipc_data::model::list_activity::{ListActivityRequest, ListActivityResponse};
+use auth_ipc_data::model::create_app_password::{CreateAppPasswordRequest, CreateAppPasswordResponse};
+use auth_ipc_data::model::list_app_passwords::{ListAppPasswordsRequest, ListAppPasswordsResponse};
+use auth_ipc_data::model::remove_app_password::{RemoveAppPasswordRequest, RemoveAppPasswordResponse};
+use auth_ipc_data::bindings::{self, AppPasswordsError, ChangePasswordError, LoginError, LogoutError, SessionsError};
+use uuid::Uuid;
+
+use crate::app_constants::{LIVE_SESSIONS_CACHE_CAPACITY, LOGOUT_NOTICES_CAPACITY, SESSION_CHECK_CACHE_TIME};
 
 mod errors;
 mod model;
@@ -32,6 +48,9 @@ pub trait AccessGranter: Send + Sync + 'static {
         &self,
         username: &UsernameStr,
         password: &str,
+        second_factor: Option<&str>,
+        client_label: Option<&str>,
+        client_address: Option<IpAddr>,
     ) -> Result<LoginResult, AccessGranterError>;
 
     async fn refresh_user_token(
@@ -44,6 +63,68 @@ pub trait AccessGranter: Send + Sync + 'static {
         &self,
         access_token: &str,
     ) -> Result<(), AccessGranterError>;
+
+    async fn change_password(
+        &self,
+        access_token: &str,
+        old_password: &str,
+        new_password: &str,
+        revoke_other_sessions: bool,
+    ) -> Result<(), AccessGranterError>;
+
+    async fn list_sessions(
+        &self,
+        access_token: &str,
+    ) -> Result<Vec<SessionDescription>, AccessGranterError>;
+
+    async fn revoke_session(
+        &self,
+        access_token: &str,
+        session_id: Uuid,
+    ) -> Result<(), AccessGranterError>;
+
+    /// Revokes the session of the token too
+    async fn revoke_all_sessions(
+        &self,
+        access_token: &str,
+    ) -> Result<(), AccessGranterError>;
+
+    /// The user's account events from the audit log, the newest first
+    async fn list_activity(
+        &self,
+        access_token: &str,
+        limit: u32,
+    ) -> Result<Vec<AuditEvent>, AccessGranterError>;
+
+    /// Returns the generated password
+    async fn create_app_password(
+        &self,
+        access_token: &str,
+        name: &str,
+        scopes: BTreeSet<Scope>,
+    ) -> Result<String, AccessGranterError>;
+
+    async fn list_app_passwords(
+        &self,
+        access_token: &str,
+    ) -> Result<Vec<AppPasswordInfo>, AccessGranterError>;
+
+    /// Revokes the sessions logged in with the application password too
+    async fn remove_app_password(
+        &self,
+        access_token: &str,
+        name: &str,
+    ) -> Result<(), AccessGranterError>;
+
+    /// Whether the session still exists in the auth daemon, cached for
+    /// `SESSION_CHECK_CACHE_TIME`
+    async fn check_session(
+        &self,
+        session: &KnownSession,
+    ) -> Result<bool, AccessGranterError>;
+
+    /// Ids of the sessions logged out from now on
+    fn subscribe_logouts(&self) -> broadcast::Receiver<Uuid>;
 }
 
 pub struct AccessGranterImpl<
@@ -55,6 +136,9 @@ pub struct AccessGranterImpl<
 > {
     access_token_validator: AccessTokenValidator,
     caller: C,
+    logouts: broadcast::Sender<Uuid>,
+    // when each session was last confirmed alive
+    live_sessions: Mutex<HashMap<Uuid, Instant>>,
     _phantom: PhantomData<(Command, CommandContainer, CommandWrapper, Response)>,
 }
 
@@ -89,6 +173,8 @@ impl ProductionAccessGranter {
             AccessGranterImpl {
                 access_token_validator,
                 caller,
+                logouts: broadcast::Sender::new(LOGOUT_NOTICES_CAPACITY),
+                live_sessions: Mutex::new(HashMap::new()),
                 _phantom: Default::default(),
             },
             shutdown_notice,
@@ -117,15 +203,19 @@ impl<
         }
 
         match self.access_token_validator.check_access_token(token) {
-            Ok(parsed_token) => Ok(
-                SessionInfo::Valid(
-                    KnownSession {
-                        raw_token: token.to_owned(),
-                        session_id: parsed_token.session_id,
-                        username: parsed_token.username,
-                    }
-                )
-            ),
+            Ok(parsed_token) => {
+                let session = KnownSession {
+                    raw_token: token.to_owned(),
+                    session_id: parsed_token.session_id,
+                    username: parsed_token.username,
+                    scopes: parsed_token.scopes,
+                };
+                if self.check_session(&session).await? {
+                    Ok(SessionInfo::Valid(session))
+                } else {
+                    Err(AccessGranterError::InvalidToken)
+                }
+            },
             Err(AccessTokenValidatorError::InvalidToken(_)) =>
                 Err(AccessGranterError::InvalidToken),
             Err(AccessTokenValidatorError::ExpiredToken(parsed_token)) => Ok(
@@ -134,6 +224,7 @@ impl<
                         raw_token: token.to_owned(),
                         session_id: parsed_token.session_id,
                         username: parsed_token.username,
+                        scopes: parsed_token.scopes,
                     }
                 )
             )
@@ -144,6 +235,9 @@ impl<
         &self,
         username: &UsernameStr,
         password: &str,
+        second_factor: Option<&str>,
+        client_label: Option<&str>,
+        client_address: Option<IpAddr>,
     ) -> Result<LoginResult, AccessGranterError> {
         debug!("logging user \"{username}\" in");
         let response: LoginResponse = self.caller
@@ -154,6 +248,9 @@ impl<
                             username: username.to_owned(),
                             password: password.to_owned(),
                             session_kind: SessionKind::Api,
+                            client_label: client_label.map(str::to_owned),
+                            second_factor: second_factor.map(str::to_owned),
+                            client_address,
                         }.into()
                     )
                 )
@@ -172,9 +269,17 @@ impl<
                 Err(AccessGranterError::AuthDaemonInternalError)
             },
 
-            Err(e) => Err(
-                match e {
+            Err(LoginFailure { error, retry_after }) => Err(
+                match error {
                     LoginError::LoginInvalidCredentials => AccessGranterError::InvalidCredentials,
+                    LoginError::LoginSecondFactorRequired => AccessGranterError::SecondFactorRequired,
+                    LoginError::LoginTooManyAttempts => match retry_after {
+                        Some(retry_after) => AccessGranterError::TooManyAttempts { retry_after },
+                        None => {
+                            error!("received a lockout without its duration");
+                            AccessGranterError::AuthDaemonInternalError
+                        },
+                    },
                     LoginError::LoginInternalError => AccessGranterError::AuthDaemonInternalError,
                 }
             )
@@ -214,7 +319,9 @@ impl<
 
             Err(e) => Err(
                 match e {
-                    LoginError::LoginInvalidCredentials => AccessGranterError::InvalidCredentials,
+                    LoginError::LoginInvalidCredentials |
+                    LoginError::LoginSecondFactorRequired |
+                    LoginError::LoginTooManyAttempts => AccessGranterError::InvalidCredentials,
                     LoginError::LoginInternalError => AccessGranterError::AuthDaemonInternalError,
                 }
             )
@@ -246,7 +353,299 @@ impl<
                     LogoutError::LogoutInternalError => AccessGranterError::AuthDaemonInternalError,
                 }
             ),
-            None => Ok(())
+            None => {
+                match self.access_token_validator.check_access_token(access_token) {
+                    Ok(parsed_token) => self.notify_logout(parsed_token.session_id),
+                    Err(AccessTokenValidatorError::ExpiredToken(parsed_token)) =>
+                        self.notify_logout(parsed_token.session_id),
+                    Err(AccessTokenValidatorError::InvalidToken(_)) => {},
+                }
+                Ok(())
+            },
+        }
+    }
+
+    async fn change_password(
+        &self,
+        access_token: &str,
+        old_password: &str,
+        new_password: &str,
+        revoke_other_sessions: bool,
+    ) -> Result<(), AccessGranterError> {
+        trace!("changing password for token \"{access_token}\"");
+        let response: ChangePasswordResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ChangePassword(
+                        ChangePasswordRequest {
+                            access_token: access_token.to_owned(),
+                            old_password: old_password.to_owned(),
+                            new_password: new_password.to_owned(),
+                            revoke_other_sessions,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        match response.0 {
+            Ok(ChangePasswordSuccess { revoked_session_ids }) => {
+                for session_id in revoked_session_ids {
+                    self.notify_logout(session_id);
+                }
+                Ok(())
+            },
+            Err(e) => Err(
+                match e {
+                    ChangePasswordError::ChangePasswordInvalidCredentials => AccessGranterError::InvalidToken,
+                    ChangePasswordError::ChangePasswordWrongPassword => AccessGranterError::InvalidCredentials,
+                    ChangePasswordError::ChangePasswordInvalidNewPassword => AccessGranterError::InvalidNewPassword,
+                    ChangePasswordError::ChangePasswordInternalError => AccessGranterError::AuthDaemonInternalError,
+                }
+            ),
+        }
+    }
+
+    async fn list_sessions(
+        &self,
+        access_token: &str,
+    ) -> Result<Vec<SessionDescription>, AccessGranterError> {
+        trace!("listing sessions for token \"{access_token}\"");
+        let response: ListSessionsResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ListSessions(
+                        ListSessionsRequest {
+                            access_token: access_token.to_owned(),
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        response.0.map_err(map_sessions_error)
+    }
+
+    async fn revoke_session(
+        &self,
+        access_token: &str,
+        session_id: Uuid,
+    ) -> Result<(), AccessGranterError> {
+        trace!("revoking session {session_id} for token \"{access_token}\"");
+        let response: RevokeSessionResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::RevokeSession(
+                        RevokeSessionRequest {
+                            access_token: access_token.to_owned(),
+                            session_id,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        match response.0 {
+            Some(error) => Err(map_sessions_error(error)),
+            None => {
+                self.notify_logout(session_id);
+                Ok(())
+            },
+        }
+    }
+
+    async fn revoke_all_sessions(
+        &self,
+        access_token: &str,
+    ) -> Result<(), AccessGranterError> {
+        trace!("revoking all sessions for token \"{access_token}\"");
+        let response: RevokeAllSessionsResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::RevokeAllSessions(
+                        RevokeAllSessionsRequest {
+                            access_token: access_token.to_owned(),
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        for session_id in response.0.map_err(map_sessions_error)? {
+            self.notify_logout(session_id);
+        }
+        Ok(())
+    }
+
+    async fn list_activity(
+        &self,
+        access_token: &str,
+        limit: u32,
+    ) -> Result<Vec<AuditEvent>, AccessGranterError> {
+        trace!("listing activity for token \"{access_token}\"");
+        let response: ListActivityResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ListActivity(
+                        ListActivityRequest {
+                            access_token: access_token.to_owned(),
+                            limit,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        response.0.map_err(map_sessions_error)
+    }
+
+    async fn create_app_password(
+        &self,
+        access_token: &str,
+        name: &str,
+        scopes: BTreeSet<Scope>,
+    ) -> Result<String, AccessGranterError> {
+        trace!("creating app password \"{name}\" for token \"{access_token}\"");
+        let response: CreateAppPasswordResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::CreateAppPassword(
+                        CreateAppPasswordRequest {
+                            access_token: access_token.to_owned(),
+                            name: name.to_owned(),
+                            scopes,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        response.0.map_err(map_app_passwords_error)
+    }
+
+    async fn list_app_passwords(
+        &self,
+        access_token: &str,
+    ) -> Result<Vec<AppPasswordInfo>, AccessGranterError> {
+        trace!("listing app passwords for token \"{access_token}\"");
+        let response: ListAppPasswordsResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ListAppPasswords(
+                        ListAppPasswordsRequest {
+                            access_token: access_token.to_owned(),
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        response.0.map_err(map_app_passwords_error)
+    }
+
+    async fn remove_app_password(
+        &self,
+        access_token: &str,
+        name: &str,
+    ) -> Result<(), AccessGranterError> {
+        trace!("removing app password \"{name}\" for token \"{access_token}\"");
+        let response: RemoveAppPasswordResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::RemoveAppPassword(
+                        RemoveAppPasswordRequest {
+                            access_token: access_token.to_owned(),
+                            name: name.to_owned(),
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        for session_id in response.0.map_err(map_app_passwords_error)? {
+            self.notify_logout(session_id);
+        }
+        Ok(())
+    }
+
+    async fn check_session(
+        &self,
+        session: &KnownSession,
+    ) -> Result<bool, AccessGranterError> {
+        let is_cached = self.live_sessions
+            .lock()
+            .unwrap()
+            .get(&session.session_id)
+            .is_some_and(|checked_at| checked_at.elapsed() < SESSION_CHECK_CACHE_TIME);
+        if is_cached {
+            return Ok(true)
+        }
+
+        trace!("checking session {} of user \"{}\"", session.session_id, session.username);
+        let response: CheckSessionResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::CheckSession(
+                        CheckSessionRequest {
+                            username: session.username.clone(),
+                            session_id: session.session_id,
+                            xsrf_token: None,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        let is_alive = response.0.map_err(map_sessions_error)?;
+        let mut live_sessions = self.live_sessions.lock().unwrap();
+        if is_alive {
+            if live_sessions.len() >= LIVE_SESSIONS_CACHE_CAPACITY {
+                live_sessions.retain(|_, checked_at| {
+                    checked_at.elapsed() < SESSION_CHECK_CACHE_TIME
+                });
+            }
+            live_sessions.insert(session.session_id, Instant::now());
+        } else {
+            debug!("session {} of user \"{}\" was revoked", session.session_id, session.username);
+            live_sessions.remove(&session.session_id);
         }
+        Ok(is_alive)
+    }
+
+    fn subscribe_logouts(&self) -> broadcast::Receiver<Uuid> {
+        self.logouts.subscribe()
+    }
+}
+
+impl<
+    Command: Send + Sync + 'static,
+    CommandContainer: prost::Message + 'static,
+    CommandWrapper: IpcOutput<Command, CommandContainer>,
+    Response: Send + Sync + 'static,
+    C: Caller<Command, CommandContainer, CommandWrapper, Response>,
+> AccessGranterImpl<Command, CommandContainer, CommandWrapper, Response, C> {
+    fn notify_logout(&self, session_id: Uuid) {
+        self.live_sessions.lock().unwrap().remove(&session_id);
+        // fails only when nobody's listening
+        let _ = self.logouts.send(session_id);
+    }
+}
+
+fn map_sessions_error(error: SessionsError) -> AccessGranterError {
+    match error {
+        SessionsError::SessionsInvalidCredentials => AccessGranterError::InvalidToken,
+        SessionsError::SessionsNotFound => AccessGranterError::SessionNotFound,
+        SessionsError::SessionsInternalError => AccessGranterError::AuthDaemonInternalError,
+    }
+}
+
+fn map_app_passwords_error(error: AppPasswordsError) -> AccessGranterError {
+    match error {
+        AppPasswordsError::AppPasswordsInvalidCredentials => AccessGranterError::InvalidToken,
+        AppPasswordsError::AppPasswordsNotFound => AccessGranterError::AppPasswordNotFound,
+        AppPasswordsError::AppPasswordsAlreadyExists => AccessGranterError::AppPasswordExists,
+        AppPasswordsError::AppPasswordsInvalidRequest => AccessGranterError::InvalidAppPassword,
+        AppPasswordsError::AppPasswordsForbiddenScopes => AccessGranterError::ForbiddenScopes,
+        AppPasswordsError::AppPasswordsInternalError => AccessGranterError::AuthDaemonInternalError,
     }
 }
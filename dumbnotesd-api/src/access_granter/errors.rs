@@ -1,3 +1,4 @@
+use std::time::Duration;
 use thiserror::Error;
 use dumbnotes::ipc::caller::CallerError;
 use protobuf_common::ProtobufRequestError;
@@ -13,6 +14,32 @@ pub enum AccessGranterError {
     #[error("invalid credentials")]
     InvalidCredentials,
 
+    #[error("second authentication factor required")]
+    SecondFactorRequired,
+
+    #[error("too many failed logins, retry after {retry_after:?}")]
+    TooManyAttempts {
+        retry_after: Duration,
+    },
+
+    #[error("invalid new password")]
+    InvalidNewPassword,
+
+    #[error("session not found")]
+    SessionNotFound,
+
+    #[error("application password not found")]
+    AppPasswordNotFound,
+
+    #[error("application password already exists")]
+    AppPasswordExists,
+
+    #[error("invalid application password name or scopes")]
+    InvalidAppPassword,
+
+    #[error("scopes not granted to the session")]
+    ForbiddenScopes,
+
     #[error("calling the auth daemon failed")]
     Caller(#[from] CallerError),
 
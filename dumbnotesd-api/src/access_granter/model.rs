@@ -1,5 +1,6 @@
+use std::collections::BTreeSet;
 use uuid::Uuid;
-use data::UsernameString;
+use data::{Scope, UsernameString};
 
 pub struct LoginResult {
     pub refresh_token: Vec<u8>,
@@ -17,4 +18,14 @@ pub struct KnownSession {
     pub raw_token: String,
     pub session_id: Uuid,
     pub username: UsernameString,
+    /// `None` when unrestricted
+    pub scopes: Option<BTreeSet<Scope>>,
+}
+
+impl KnownSession {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.contains(&scope))
+    }
 }
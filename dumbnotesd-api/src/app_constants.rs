@@ -1,3 +1,12 @@
+use std::time::Duration;
+
 pub const API_PREFIX: &str = "/";
 pub const API_VERSION: &str = "1";
 pub const DEFAULT_API_PORT: u16 = 8081;
+pub const LOGOUT_NOTICES_CAPACITY: usize = 64;
+/// How long a session confirmed by the auth daemon is trusted without
+/// asking again, bounding the delay of revocations made elsewhere
+pub const SESSION_CHECK_CACHE_TIME: Duration = Duration::from_secs(5);
+pub const LIVE_SESSIONS_CACHE_CAPACITY: usize = 1024;
+pub const DEFAULT_ACTIVITY_LIMIT: u32 = 50;
+pub const MAX_ACTIVITY_LIMIT: u32 = 500;
@@ -5,18 +5,18 @@ use futures::future::{join_all, select_all};
 use storage_ipc_sdk::{ProductionStorageAccessor, StorageAccessor};
 use async_trait::async_trait;
 use access_token::{AccessTokenDecoder, AccessTokenValidator};
+use dumbnotes::file_watcher::ProductionFileWatcher;
 use dumbnotes::ipc::socket::discover_socket;
+use dumbnotes::jwt_keys::{read_jwt_public_keys, watch_jwt_public_keys};
 #[cfg(target_os = "openbsd")] use dumbnotes::sandbox::pledge::pledge_apid_liftoff;
 #[cfg(target_os = "openbsd")] use dumbnotes::sandbox::unveil::{Permissions, unveil, seal_unveil};
-use josekit::jwk::Jwk;
 use log::error;
 use rocket::fairing::{Fairing, Info};
 use rocket::{Build, Orbit, Rocket};
 use tokio::sync::{Mutex, oneshot};
 use util::error_exit;
-use std::error::Error;
 use std::os::fd::RawFd;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 pub struct AppSetupFairing {
@@ -93,16 +93,30 @@ impl Fairing for AppSetupFairing {
         *self.storage_daemon_failure_notice.lock().await =
             Some(storage_accessor_shutdown_notice);
         let storage_accessor: Box<dyn StorageAccessor> = Box::new(storage_accessor);
-        let jwt_public_key = ok_or_bail!(
+        let jwt_public_keys = ok_or_bail!(
             rocket,
-            read_jwt_key(&self.jwt_public_key),
-            |e| error!("failed reading the public jwt key: {e}")
+            read_jwt_public_keys(&self.jwt_public_key),
+            |e| error!("failed reading the public jwt keys: {e}")
         );
         let access_token_decoder = ok_or_bail!(
             rocket,
-            AccessTokenDecoder::from_jwk(&jwt_public_key),
+            AccessTokenDecoder::from_jwk_set(&jwt_public_keys),
             |e| error!("could not initialize access token decoder: {e}")
         );
+        let file_watcher = ok_or_bail!(
+            rocket,
+            ProductionFileWatcher::new(),
+            |e| error!("failed to create file watcher: {e}")
+        );
+        ok_or_bail!(
+            rocket,
+            watch_jwt_public_keys(
+                &file_watcher,
+                &self.jwt_public_key,
+                access_token_decoder.clone(),
+            ),
+            |e| error!("failed to watch the public jwt keys: {e}")
+        );
         let access_token_validator = AccessTokenValidator::new(
             access_token_decoder,
         );
@@ -154,7 +168,3 @@ impl Fairing for AppSetupFairing {
         });
     }
 }
-
-fn read_jwt_key(path: &Path) -> Result<Jwk, Box<dyn Error>> {
-    Ok(Jwk::from_bytes(std::fs::read(path)?)?)
-}
@@ -22,6 +22,10 @@ fn main() {
     let rocket_defaults = Figment::from(
         rocket::Config {
             cli_colors: !cli_config.is_daemonizing(),
+            // the client addresses the logins are throttled by can't be
+            // taken from a header unless the rocket config names the one
+            // set by a trusted proxy
+            ip_header: None,
             port: DEFAULT_API_PORT,
             .. Default::default()
         }
@@ -1,21 +1,32 @@
 pub mod authentication_guard;
+pub mod user_agent;
 
 use crate::access_granter::AccessGranter;
 use crate::access_granter::AccessGranterError;
 use crate::access_granter::LoginResult;
-use crate::app_constants::API_PREFIX;
-use api_data::http::header::UnauthorizedResponse;
+use crate::app_constants::{API_PREFIX, DEFAULT_ACTIVITY_LIMIT, MAX_ACTIVITY_LIMIT, SESSION_CHECK_CACHE_TIME};
+use api_data::http::header::{TooManyRequestsResponse, UnauthorizedResponse};
+use api_data::http::query::{NoteFormatParam, NoteListCursor, NoteListOrderParam, NoteVersionParam, SyncTokenParam};
 use api_data::http::status::{StatusExt, Unauthorized};
 use storage_ipc_sdk::StorageAccessor;
 use storage_ipc_sdk::errors::StorageAccessorError;
-use crate::routes::authentication_guard::{Authenticated, Unauthenticated};
-use api_data::model::{LoginRequest, LoginRequestSecret, LoginResponse, NoteListResponse, NoteResponse, NoteWriteRequest};
+use crate::routes::authentication_guard::{Authenticated, ManageAccount, ReadNotes, Unauthenticated, WriteNotes};
+use crate::routes::user_agent::UserAgent;
+use api_data::model::{ActivityResponse, AppPasswordCreateRequest, AppPasswordCreateResponse, AppPasswordListResponse, LoginRequest, LoginRequestSecret, LoginResponse, NoteChangesResponse, NoteListResponse, NoteResponse, NoteRevisionListResponse, NoteSearchResponse, NoteShareListResponse, NoteShareRequest, NoteWriteRequest, NoteWriteResponse, PasswordChangeRequest, SessionListResponse, SharedNoteListResponse, TagListResponse, UsageResponse};
 use log::{debug, error};
-use rocket::http::Status;
-use rocket::response::content::RawText;
-use rocket::{catch, catchers, delete, get, post, put, routes, Build, Rocket, State};
+use markdown_renderer::render_markdown;
+use rocket::http::{Accept, Status};
+use rocket::response::content::{RawHtml, RawText};
+use rocket::response::stream::{Event, EventStream};
+use rocket::{catch, catchers, delete, get, post, put, routes, Build, Responder, Rocket, Shutdown, State};
+use std::cmp::Reverse;
+use std::net::IpAddr;
+use std::str::FromStr;
+use time::UtcDateTime;
 use uuid::Uuid;
-use data::{Note, NoteMetadata};
+use data::{is_valid_tag, Note, NoteEvent, NoteListQuery, NoteMetadata, NoteSearchQuery, NoteShare, UsernameString};
+use futures::StreamExt;
+use tokio::sync::broadcast::error::RecvError;
 use util::send_fut_lifetime_workaround;
 
 #[get("/version")]
@@ -27,12 +38,21 @@ fn version() -> RawText<&'static str> {
 async fn login(
     request: LoginRequest,
     _unauthenticated: Unauthenticated,
+    user_agent: UserAgent,
+    client_address: Option<IpAddr>,
     access_granter: &State<Box<dyn AccessGranter>>,
-) -> Result<LoginResponse, Status> {
+) -> Result<LoginResponse, LoginFailure> {
     match request.secret {
         LoginRequestSecret::Password(password) => {
+            let client_label = request.client_label.or(user_agent.0);
             match access_granter
-                .login_user(&request.username, &password)
+                .login_user(
+                    &request.username,
+                    &password,
+                    request.second_factor.as_deref(),
+                    client_label.as_deref(),
+                    client_address,
+                )
                 .await
             {
                 Ok(LoginResult { refresh_token, access_token }) => Ok(
@@ -41,7 +61,7 @@ async fn login(
                         access_token,
                     }
                 ),
-                Err(e) => Err(process_login_error(e))
+                Err(e) => Err(process_login_failure(e))
             }
         }
         LoginRequestSecret::RefreshToken(token) => {
@@ -55,12 +75,27 @@ async fn login(
                         access_token,
                     }
                 ),
-                Err(e) => Err(process_login_error(e))
+                Err(e) => Err(process_login_failure(e))
             }
         }
     }
 }
 
+#[derive(Responder)]
+enum LoginFailure {
+    TooManyAttempts(TooManyRequestsResponse),
+    Status(Status),
+}
+
+fn process_login_failure(e: AccessGranterError) -> LoginFailure {
+    match e {
+        AccessGranterError::TooManyAttempts { retry_after }
+        => LoginFailure::TooManyAttempts(retry_after.into()),
+
+        e => LoginFailure::Status(process_login_error(e)),
+    }
+}
+
 fn process_login_error(e: AccessGranterError) -> Status {
     match e {
         AccessGranterError::HeaderFormatError
@@ -70,6 +105,18 @@ fn process_login_error(e: AccessGranterError) -> Status {
         AccessGranterError::InvalidCredentials
         => Status::UnauthorizedInvalidToken,
 
+        AccessGranterError::SecondFactorRequired
+        => Status::UnauthorizedSecondFactorRequired,
+
+        AccessGranterError::TooManyAttempts { .. }
+        => Status::TooManyRequests,
+
+        AccessGranterError::InvalidNewPassword |
+        AccessGranterError::SessionNotFound |
+        AccessGranterError::AppPasswordNotFound |
+        AccessGranterError::AppPasswordExists |
+        AccessGranterError::InvalidAppPassword |
+        AccessGranterError::ForbiddenScopes |
         AccessGranterError::ProtobufError(_) |
         AccessGranterError::Caller(_) |
         AccessGranterError::AuthDaemonInternalError
@@ -94,20 +141,110 @@ async fn logout(
     }
 }
 
-#[get("/notes")]
+#[post("/account/password", data = "<request>")]
+async fn change_password(
+    authenticated: Authenticated<ManageAccount>,
+    request: PasswordChangeRequest,
+    access_granter: &State<Box<dyn AccessGranter>>,
+) -> Result<(), Status> {
+    if request.new_password.is_empty() {
+        return Err(Status::BadRequest)
+    }
+    let result = access_granter
+        .change_password(
+            &authenticated.0.raw_token,
+            &request.old_password,
+            &request.new_password,
+            request.revoke_other_sessions,
+        )
+        .await;
+    match result {
+        Ok(()) => Ok(()),
+        Err(AccessGranterError::InvalidCredentials) => Err(Status::Forbidden),
+        Err(AccessGranterError::InvalidNewPassword) => Err(Status::BadRequest),
+        Err(e) => Err(process_login_error(e)),
+    }
+}
+
+#[get("/sessions")]
+async fn get_sessions(
+    authenticated: Authenticated<ManageAccount>,
+    access_granter: &State<Box<dyn AccessGranter>>,
+) -> Result<SessionListResponse, Status> {
+    match access_granter.list_sessions(&authenticated.0.raw_token).await {
+        Ok(sessions) => Ok(SessionListResponse { sessions }),
+        Err(e) => Err(process_login_error(e)),
+    }
+}
+
+#[delete("/sessions/<session_id>")]
+async fn revoke_session(
+    authenticated: Authenticated<ManageAccount>,
+    session_id: Uuid,
+    access_granter: &State<Box<dyn AccessGranter>>,
+) -> Result<(), Status> {
+    let result = access_granter
+        .revoke_session(&authenticated.0.raw_token, session_id)
+        .await;
+    match result {
+        Ok(()) => Ok(()),
+        Err(AccessGranterError::SessionNotFound) => {
+            debug!(
+                "no session found with id {session_id} for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(e) => Err(process_login_error(e)),
+    }
+}
+
+#[delete("/sessions")]
+async fn revoke_all_sessions(
+    authenticated: Authenticated<ManageAccount>,
+    access_granter: &State<Box<dyn AccessGranter>>,
+) -> Result<(), Status> {
+    access_granter
+        .revoke_all_sessions(&authenticated.0.raw_token)
+        .await
+        .map_err(process_login_error)
+}
+
+#[get("/notes?<limit>&<cursor>&<modified_since>&<order>&<tag>")]
 async fn get_users_notes(
-    authenticated: Authenticated,
+    authenticated: Authenticated<ReadNotes>,
     note_storage: &State<Box<dyn StorageAccessor>>,
+    limit: Option<&str>,
+    cursor: Option<&str>,
+    modified_since: Option<&str>,
+    order: Option<&str>,
+    tag: Option<String>,
 ) -> Result<NoteListResponse, Status> {
+    if tag.as_deref().is_some_and(|tag| !is_valid_tag(tag)) {
+        return Err(Status::BadRequest)
+    }
+    // parsed by hand, as Rocket turns invalid optional values into `None`
+    let query = NoteListQuery {
+        limit: parse_query_param(limit)?,
+        after: parse_query_param::<NoteListCursor>(cursor)?
+            .map(|cursor| cursor.0),
+        modified_since: parse_query_param(modified_since)?
+            .map(UtcDateTime::from_unix_timestamp)
+            .transpose()
+            .map_err(|_| Status::BadRequest)?,
+        order: parse_query_param::<NoteListOrderParam>(order)?
+            .map(|order| order.0)
+            .unwrap_or_default(),
+        tag,
+    };
     let result = note_storage
-        .get_users_notes(authenticated.0.raw_token)
+        .get_users_notes(authenticated.0.raw_token, query)
         .await;
     match result {
-        Ok(notes_info) => Ok(
+        Ok(page) => Ok(
             NoteListResponse {
-                notes_info: notes_info
-                    .into_iter()
-                    .collect(),
+                notes_info: page.notes,
+                next_cursor: page.next.map(NoteListCursor),
             }
         ),
         Err(e) => {
@@ -117,19 +254,180 @@ async fn get_users_notes(
     }
 }
 
-#[get("/notes/<note_id>")]
+fn parse_query_param<T: FromStr>(value: Option<&str>) -> Result<Option<T>, Status> {
+    value
+        .map(str::parse)
+        .transpose()
+        .map_err(|_| Status::BadRequest)
+}
+
+#[get("/notes/search?<q>&<limit>&<cursor>")]
+async fn search_notes(
+    authenticated: Authenticated<ReadNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    q: String,
+    limit: Option<&str>,
+    cursor: Option<&str>,
+) -> Result<NoteSearchResponse, Status> {
+    let query = NoteSearchQuery {
+        query: q,
+        limit: parse_query_param(limit)?,
+        after: parse_query_param::<NoteListCursor>(cursor)?
+            .map(|cursor| cursor.0),
+    };
+    let result = note_storage
+        .search_notes(authenticated.0.raw_token, query)
+        .await;
+    match result {
+        Ok(page) => Ok(
+            NoteSearchResponse {
+                results: page.notes,
+                next_cursor: page.next.map(NoteListCursor),
+            }
+        ),
+        Err(StorageAccessorError::TooBig) => Err(Status::PayloadTooLarge),
+        Err(e) => {
+            error!("error searching notes: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[get("/notes/changes?<since>&<limit>")]
+async fn get_note_changes(
+    authenticated: Authenticated<ReadNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    since: Option<&str>,
+    limit: Option<&str>,
+) -> Result<NoteChangesResponse, Status> {
+    let since = parse_query_param::<SyncTokenParam>(since)?
+        .map(|since| since.0);
+    let result = note_storage
+        .get_note_changes(authenticated.0.raw_token, since, parse_query_param(limit)?)
+        .await;
+    match result {
+        Ok(changes) => Ok(
+            NoteChangesResponse {
+                updated: changes.updated,
+                deleted: changes.deleted,
+                sync_token: SyncTokenParam(changes.sync_token),
+                more: changes.more,
+                reset: changes.reset,
+            }
+        ),
+        Err(e) => {
+            error!("error fetching note changes: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+/// Streams `created`, `updated` and `deleted` events with the note id as
+/// the data, until the access token expires or the session is logged out
+/// or revoked
+#[get("/notes/events")]
+async fn note_events<'r>(
+    authenticated: Authenticated<ReadNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    access_granter: &'r State<Box<dyn AccessGranter>>,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![Event + 'r], Status> {
+    // before subscribing, not to miss a logout in between
+    let mut logouts = access_granter.subscribe_logouts();
+    let session = authenticated.0;
+    let mut events = note_storage
+        .subscribe_note_events(session.raw_token.clone())
+        .await
+        .map_err(|e| {
+            error!("error subscribing to note events: {}", e);
+            Status::InternalServerError
+        })?;
+    // revocations made through the web daemon aren't broadcast here
+    let mut session_checks = tokio::time::interval_at(
+        tokio::time::Instant::now() + SESSION_CHECK_CACHE_TIME,
+        SESSION_CHECK_CACHE_TIME,
+    );
+    let username = session.username.clone();
+    Ok(
+        EventStream! {
+            loop {
+                let event = tokio::select! {
+                    event = events.next() => match event {
+                        Some(Ok(event)) => event,
+                        Some(Err(e)) => {
+                            error!("error receiving note events: {}", e);
+                            break
+                        },
+                        None => break,
+                    },
+                    logout = logouts.recv() => match logout {
+                        Ok(id) if id != session.session_id => continue,
+                        Ok(_) | Err(RecvError::Lagged(_)) | Err(RecvError::Closed) => {
+                            debug!("ending note events for user \"{username}\": logged out");
+                            break
+                        },
+                    },
+                    _ = session_checks.tick() => match access_granter.check_session(&session).await {
+                        Ok(true) => continue,
+                        Ok(false) => {
+                            debug!("ending note events for user \"{username}\": session revoked");
+                            break
+                        },
+                        Err(e) => {
+                            error!("error checking session for note events: {}", e);
+                            break
+                        },
+                    },
+                    _ = &mut shutdown => break,
+                };
+                yield match event {
+                    NoteEvent::Created(info) => Event::data(info.metadata.id.to_string())
+                        .event("created"),
+                    NoteEvent::Updated(info) => Event::data(info.metadata.id.to_string())
+                        .event("updated"),
+                    NoteEvent::Deleted(tombstone) => Event::data(tombstone.id.to_string())
+                        .event("deleted"),
+                }
+            }
+        }
+    )
+}
+
+#[derive(Responder)]
+enum NoteRepresentation {
+    Protobuf(NoteResponse),
+    Html(RawHtml<String>),
+}
+
+/// `owner` is set for the notes shared by another user
+#[get("/notes/<note_id>?<format>&<owner>")]
 async fn get_note(
-    authenticated: Authenticated,
+    authenticated: Authenticated<ReadNotes>,
     note_storage: &State<Box<dyn StorageAccessor>>,
     note_id: Uuid,
-) -> Result<NoteResponse, Status> {
+    format: Option<&str>,
+    owner: Option<&str>,
+    accept: Option<&Accept>,
+) -> Result<NoteRepresentation, Status> {
+    let format = match parse_query_param::<NoteFormatParam>(format)? {
+        Some(format) => format,
+        None if accept.is_some_and(|accept| accept.preferred().is_html())
+            => NoteFormatParam::Html,
+        None => NoteFormatParam::Protobuf,
+    };
+    let owner = parse_query_param::<UsernameString>(owner)?;
     let result =
         send_fut_lifetime_workaround(
-            note_storage.get_note(authenticated.0.raw_token, note_id)
+            note_storage.get_note(authenticated.0.raw_token, note_id, owner)
         )
         .await;
     match result {
-        Ok(note) => Ok(NoteResponse(note)),
+        Ok(note) => Ok(match format {
+            NoteFormatParam::Protobuf => NoteRepresentation::Protobuf(note.into()),
+            NoteFormatParam::Html => NoteRepresentation::Html(
+                RawHtml(render_markdown(&note.note.contents))
+            ),
+        }),
         Err(e) => match e {
             StorageAccessorError::NotFound => {
                 debug!(
@@ -146,46 +444,94 @@ async fn get_note(
     }
 }
 
-#[put("/notes/<note_id>", data = "<note>")]
+#[derive(Responder)]
+enum WriteNoteFailure {
+    #[response(status = 409)]
+    Conflict(NoteResponse),
+    Status(Status),
+}
+
+#[put("/notes/<note_id>?<owner>", data = "<note>")]
 async fn write_note(
-    authenticated: Authenticated,
+    authenticated: Authenticated<WriteNotes>,
     note_id: Uuid,
+    owner: Option<&str>,
     note: NoteWriteRequest,
     note_storage: &State<Box<dyn StorageAccessor>>,
-) -> Result<(), Status> {
+) -> Result<NoteWriteResponse, WriteNoteFailure> {
+    let owner = parse_query_param::<UsernameString>(owner)
+        .map_err(WriteNoteFailure::Status)?;
     let result = note_storage
         .write_note(
             authenticated.0.raw_token,
             Note {
                 metadata: NoteMetadata {
                     id: note_id,
-                    mtime: note.mtime, // TODO: validate when we start writing it
+                    mtime: note.mtime,
                 },
                 name: note.name,
+                tags: note.tags,
                 contents: note.contents,
-            }
+            },
+            note.base_version.map(|version| version.0),
+            owner,
         )
         .await;
     match result {
-        Ok(_) => Ok(()),
+        Ok((metadata, version)) => Ok(
+            NoteWriteResponse {
+                metadata,
+                version: NoteVersionParam(version),
+            }
+        ),
+        Err(StorageAccessorError::Conflict(current)) => {
+            debug!(
+                "stale write of note {note_id} for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(WriteNoteFailure::Conflict((*current).into()))
+        },
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "no note found with id {note_id} for user \"{}\" to update",
+                authenticated.0.username,
+            );
+            Err(WriteNoteFailure::Status(Status::NotFound))
+        },
+        Err(StorageAccessorError::TooBig) =>
+            Err(WriteNoteFailure::Status(Status::PayloadTooLarge)),
+        Err(StorageAccessorError::QuotaExceeded) =>
+            Err(WriteNoteFailure::Status(Status::InsufficientStorage)),
+        Err(StorageAccessorError::Forbidden) =>
+            Err(WriteNoteFailure::Status(Status::Forbidden)),
         Err(e) => {
             error!("error writing note: {}", e);
-            Err(Status::InternalServerError)
+            Err(WriteNoteFailure::Status(Status::InternalServerError))
         }
     }
 }
 
-#[delete("/notes/<note_id>")]
+#[delete("/notes/<note_id>?<owner>")]
 async fn delete_note(
-    authenticated: Authenticated,
+    authenticated: Authenticated<WriteNotes>,
     note_id: Uuid,
+    owner: Option<&str>,
     note_storage: &State<Box<dyn StorageAccessor>>,
 ) -> Result<(), Status> {
+    let owner = parse_query_param::<UsernameString>(owner)?;
     let result = note_storage
-        .delete_note(authenticated.0.raw_token, note_id)
+        .delete_note(authenticated.0.raw_token, note_id, owner)
         .await;
     match result {
         Ok(_) => Ok(()),
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "no note found with id {note_id} for user \"{}\" to delete",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(StorageAccessorError::Forbidden) => Err(Status::Forbidden),
         Err(e) => {
             error!("error deleting note: {}", e);
             Err(Status::InternalServerError)
@@ -193,6 +539,430 @@ async fn delete_note(
     }
 }
 
+#[post("/notes/<note_id>/tasks/<task_index>/toggle")]
+async fn toggle_note_task(
+    authenticated: Authenticated<WriteNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    note_id: Uuid,
+    task_index: u32,
+) -> Result<NoteResponse, Status> {
+    let result = note_storage
+        .toggle_note_task(authenticated.0.raw_token, note_id, task_index)
+        .await;
+    match result {
+        Ok(note) => Ok(note.into()),
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "no task {task_index} found in note {note_id} for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(StorageAccessorError::QuotaExceeded) => Err(Status::InsufficientStorage),
+        Err(e) => {
+            error!("error toggling note task: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[get("/notes/<note_id>/shares")]
+async fn get_note_shares(
+    authenticated: Authenticated<ReadNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    note_id: Uuid,
+) -> Result<NoteShareListResponse, Status> {
+    let result = note_storage
+        .list_note_shares(authenticated.0.raw_token, note_id)
+        .await;
+    match result {
+        Ok(shares) => Ok(NoteShareListResponse { shares }),
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "no note found with id {note_id} for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(e) => {
+            error!("error listing note shares: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[put("/notes/<note_id>/shares/<username>", data = "<request>")]
+async fn share_note(
+    authenticated: Authenticated<WriteNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    note_id: Uuid,
+    username: &str,
+    request: NoteShareRequest,
+) -> Result<(), Status> {
+    let username = UsernameString::from_str(username)
+        .map_err(|_| Status::BadRequest)?;
+    if username == authenticated.0.username {
+        return Err(Status::BadRequest)
+    }
+    let result = note_storage
+        .grant_note_share(
+            authenticated.0.raw_token,
+            note_id,
+            NoteShare { username, access: request.access },
+        )
+        .await;
+    match result {
+        Ok(()) => Ok(()),
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "no note {note_id} or no user to share it with found \
+                    for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(StorageAccessorError::TooBig) => Err(Status::PayloadTooLarge),
+        Err(e) => {
+            error!("error sharing note: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[delete("/notes/<note_id>/shares/<username>")]
+async fn unshare_note(
+    authenticated: Authenticated<WriteNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    note_id: Uuid,
+    username: &str,
+) -> Result<(), Status> {
+    let username = UsernameString::from_str(username)
+        .map_err(|_| Status::BadRequest)?;
+    let result = note_storage
+        .revoke_note_share(authenticated.0.raw_token, note_id, username)
+        .await;
+    match result {
+        Ok(()) => Ok(()),
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "note {note_id} of user \"{}\" isn't shared with the user",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(e) => {
+            error!("error revoking note share: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[get("/notes/<note_id>/revisions")]
+async fn get_note_revisions(
+    authenticated: Authenticated<ReadNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    note_id: Uuid,
+) -> Result<NoteRevisionListResponse, Status> {
+    let result = note_storage
+        .get_note_revisions(authenticated.0.raw_token, note_id)
+        .await;
+    match result {
+        Ok(revisions) => Ok(NoteRevisionListResponse { revisions }),
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "no note found with id {note_id} for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(e) => {
+            error!("error fetching note revisions: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[get("/notes/<note_id>/revisions/<revision>")]
+async fn get_note_revision(
+    authenticated: Authenticated<ReadNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    note_id: Uuid,
+    revision: i64,
+) -> Result<NoteResponse, Status> {
+    let result =
+        send_fut_lifetime_workaround(
+            note_storage.get_note_revision(
+                authenticated.0.raw_token,
+                note_id,
+                revision,
+            )
+        )
+        .await;
+    match result {
+        Ok(note) => Ok(NoteResponse { note, version: None }),
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "no revision {revision} found of note {note_id} for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(e) => {
+            error!("error fetching note revision: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[post("/notes/<note_id>/revisions/<revision>/restore")]
+async fn restore_note_revision(
+    authenticated: Authenticated<WriteNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    note_id: Uuid,
+    revision: i64,
+) -> Result<NoteWriteResponse, Status> {
+    let result = note_storage
+        .restore_note_revision(authenticated.0.raw_token, note_id, revision)
+        .await;
+    match result {
+        Ok((metadata, version)) => Ok(
+            NoteWriteResponse {
+                metadata,
+                version: NoteVersionParam(version),
+            }
+        ),
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "no revision {revision} found of note {note_id} for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(StorageAccessorError::QuotaExceeded) => Err(Status::InsufficientStorage),
+        Err(e) => {
+            error!("error restoring note revision: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[get("/shared")]
+async fn get_shared_notes(
+    authenticated: Authenticated<ReadNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+) -> Result<SharedNoteListResponse, Status> {
+    let result = note_storage
+        .list_shared_notes(authenticated.0.raw_token)
+        .await;
+    match result {
+        Ok(notes_info) => Ok(SharedNoteListResponse { notes_info }),
+        Err(e) => {
+            error!("error fetching shared notes: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[get("/trash")]
+async fn get_trashed_notes(
+    authenticated: Authenticated<ReadNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+) -> Result<NoteListResponse, Status> {
+    let result = note_storage
+        .get_trashed_notes(authenticated.0.raw_token)
+        .await;
+    match result {
+        Ok(notes_info) => Ok(NoteListResponse { notes_info, next_cursor: None }),
+        Err(e) => {
+            error!("error fetching trashed notes: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[post("/trash/<note_id>/restore")]
+async fn restore_trashed_note(
+    authenticated: Authenticated<WriteNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    note_id: Uuid,
+) -> Result<NoteWriteResponse, Status> {
+    let result = note_storage
+        .restore_trashed_note(authenticated.0.raw_token, note_id)
+        .await;
+    match result {
+        Ok((metadata, version)) => Ok(
+            NoteWriteResponse {
+                metadata,
+                version: NoteVersionParam(version),
+            }
+        ),
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "no trashed note found with id {note_id} for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(StorageAccessorError::AlreadyExists) => {
+            debug!(
+                "note {note_id} for user \"{}\" was recreated since \
+                    the deletion, not restoring",
+                authenticated.0.username,
+            );
+            Err(Status::Conflict)
+        },
+        Err(StorageAccessorError::QuotaExceeded) => Err(Status::InsufficientStorage),
+        Err(e) => {
+            error!("error restoring trashed note: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[delete("/trash/<note_id>")]
+async fn purge_trashed_note(
+    authenticated: Authenticated<WriteNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    note_id: Uuid,
+) -> Result<(), Status> {
+    let result = note_storage
+        .purge_trashed_note(authenticated.0.raw_token, note_id)
+        .await;
+    match result {
+        Ok(()) => Ok(()),
+        Err(StorageAccessorError::NotFound) => {
+            debug!(
+                "no trashed note found with id {note_id} for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(e) => {
+            error!("error purging trashed note: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[get("/tags")]
+async fn get_tags(
+    authenticated: Authenticated<ReadNotes>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+) -> Result<TagListResponse, Status> {
+    let result = note_storage
+        .list_tags(authenticated.0.raw_token)
+        .await;
+    match result {
+        Ok(tags) => Ok(TagListResponse { tags }),
+        Err(e) => {
+            error!("error listing tags: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[get("/account/usage")]
+async fn get_usage(
+    authenticated: Authenticated<ManageAccount>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+) -> Result<UsageResponse, Status> {
+    let result = note_storage
+        .get_usage(authenticated.0.raw_token)
+        .await;
+    match result {
+        Ok(usage) => Ok(UsageResponse(usage)),
+        Err(e) => {
+            error!("error measuring storage usage: {}", e);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+/// Merges the events of the auth and storage daemons
+#[get("/account/activity?<limit>")]
+async fn get_activity(
+    authenticated: Authenticated<ManageAccount>,
+    access_granter: &State<Box<dyn AccessGranter>>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+    limit: Option<&str>,
+) -> Result<ActivityResponse, Status> {
+    let limit = parse_query_param::<u32>(limit)?
+        .unwrap_or(DEFAULT_ACTIVITY_LIMIT)
+        .min(MAX_ACTIVITY_LIMIT);
+    let mut events = access_granter
+        .list_activity(&authenticated.0.raw_token, limit)
+        .await
+        .map_err(process_login_error)?;
+    let result = note_storage
+        .list_activity(authenticated.0.raw_token, limit)
+        .await;
+    match result {
+        Ok(note_events) => events.extend(note_events),
+        Err(e) => {
+            error!("error listing note activity: {}", e);
+            return Err(Status::InternalServerError)
+        },
+    }
+    events.sort_by_key(|event| Reverse(event.timestamp));
+    events.truncate(limit as usize);
+    Ok(ActivityResponse { events })
+}
+
+#[post("/account/app-passwords", data = "<request>")]
+async fn create_app_password(
+    authenticated: Authenticated<ManageAccount>,
+    request: AppPasswordCreateRequest,
+    access_granter: &State<Box<dyn AccessGranter>>,
+) -> Result<AppPasswordCreateResponse, Status> {
+    let result = access_granter
+        .create_app_password(
+            &authenticated.0.raw_token,
+            &request.name,
+            request.scopes,
+        )
+        .await;
+    match result {
+        Ok(password) => Ok(AppPasswordCreateResponse { password }),
+        Err(AccessGranterError::AppPasswordExists) => Err(Status::Conflict),
+        Err(AccessGranterError::InvalidAppPassword) => Err(Status::BadRequest),
+        Err(AccessGranterError::ForbiddenScopes) => Err(Status::Forbidden),
+        Err(e) => Err(process_login_error(e)),
+    }
+}
+
+#[get("/account/app-passwords")]
+async fn get_app_passwords(
+    authenticated: Authenticated<ManageAccount>,
+    access_granter: &State<Box<dyn AccessGranter>>,
+) -> Result<AppPasswordListResponse, Status> {
+    match access_granter.list_app_passwords(&authenticated.0.raw_token).await {
+        Ok(app_passwords) => Ok(AppPasswordListResponse { app_passwords }),
+        Err(e) => Err(process_login_error(e)),
+    }
+}
+
+#[delete("/account/app-passwords/<name>")]
+async fn remove_app_password(
+    authenticated: Authenticated<ManageAccount>,
+    name: &str,
+    access_granter: &State<Box<dyn AccessGranter>>,
+) -> Result<(), Status> {
+    let result = access_granter
+        .remove_app_password(&authenticated.0.raw_token, name)
+        .await;
+    match result {
+        Ok(()) => Ok(()),
+        Err(AccessGranterError::AppPasswordNotFound) => {
+            debug!(
+                "no application password \"{name}\" found for user \"{}\"",
+                authenticated.0.username,
+            );
+            Err(Status::NotFound)
+        },
+        Err(e) => Err(process_login_error(e)),
+    }
+}
+
 #[catch(499)]
 fn catch_unauthorized_invalid_request() -> UnauthorizedResponse {
     assert_eq!(Status::UnauthorizedInvalidRequest.code, 499);
@@ -211,6 +981,12 @@ fn catch_unauthorized_insufficient_scope() -> UnauthorizedResponse {
     Unauthorized::InsufficientScope.into()
 }
 
+#[catch(496)]
+fn catch_unauthorized_second_factor_required() -> UnauthorizedResponse {
+    assert_eq!(Status::UnauthorizedSecondFactorRequired.code, 496);
+    Unauthorized::SecondFactorRequired.into()
+}
+
 pub trait ApiRocketBuildExt {
     fn install_dumbnotes_api(self) -> Self;
 }
@@ -224,10 +1000,34 @@ impl ApiRocketBuildExt for Rocket<Build> {
                     version,
                     login,
                     logout,
+                    change_password,
+                    get_sessions,
+                    revoke_session,
+                    revoke_all_sessions,
                     get_users_notes,
+                    search_notes,
+                    get_note_changes,
+                    note_events,
                     get_note,
                     write_note,
                     delete_note,
+                    toggle_note_task,
+                    get_note_shares,
+                    share_note,
+                    unshare_note,
+                    get_note_revisions,
+                    get_note_revision,
+                    restore_note_revision,
+                    get_shared_notes,
+                    get_trashed_notes,
+                    restore_trashed_note,
+                    purge_trashed_note,
+                    get_tags,
+                    get_usage,
+                    get_activity,
+                    create_app_password,
+                    get_app_passwords,
+                    remove_app_password,
                 ],
             )
             .register(
@@ -236,6 +1036,7 @@ impl ApiRocketBuildExt for Rocket<Build> {
                     catch_unauthorized_invalid_request,
                     catch_unauthorized_invalid_token,
                     catch_unauthorized_insufficient_scope,
+                    catch_unauthorized_second_factor_required,
                 ]
             )
     }
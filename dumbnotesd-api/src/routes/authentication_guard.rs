@@ -1,5 +1,7 @@
+use std::marker::PhantomData;
 use async_trait::async_trait;
-use log::error;
+use data::Scope;
+use log::{error, info};
 use rocket::{Request, State};
 use rocket::http::hyper::header;
 use rocket::http::Status;
@@ -12,8 +14,41 @@ use api_data::http::status::StatusExt;
 #[derive(Debug)]
 pub struct Unauthenticated;
 
+/// Rejects the tokens limited to scopes other than `S::SCOPE`
 #[derive(Debug)]
-pub struct Authenticated(pub KnownSession);
+pub struct Authenticated<S: RequiredScope = AnyScope>(pub KnownSession, PhantomData<S>);
+
+pub trait RequiredScope: Send + Sync + 'static {
+    const SCOPE: Option<Scope>;
+}
+
+#[derive(Debug)]
+pub struct AnyScope;
+
+impl RequiredScope for AnyScope {
+    const SCOPE: Option<Scope> = None;
+}
+
+#[derive(Debug)]
+pub struct ReadNotes;
+
+impl RequiredScope for ReadNotes {
+    const SCOPE: Option<Scope> = Some(Scope::NotesRead);
+}
+
+#[derive(Debug)]
+pub struct WriteNotes;
+
+impl RequiredScope for WriteNotes {
+    const SCOPE: Option<Scope> = Some(Scope::NotesWrite);
+}
+
+#[derive(Debug)]
+pub struct ManageAccount;
+
+impl RequiredScope for ManageAccount {
+    const SCOPE: Option<Scope> = Some(Scope::Account);
+}
 
 #[derive(Debug)]
 pub enum MaybeAuthenticated {
@@ -38,12 +73,22 @@ impl<'r> FromRequest<'r> for Unauthenticated {
 }
 
 #[async_trait]
-impl<'r> FromRequest<'r> for Authenticated {
+impl<'r, S: RequiredScope> FromRequest<'r> for Authenticated<S> {
     type Error = ();
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
         match try_outcome!(request.guard::<MaybeAuthenticated>().await) {
-            MaybeAuthenticated::Valid(session) => Outcome::Success(Authenticated(session)),
+            MaybeAuthenticated::Valid(session) => match S::SCOPE {
+                Some(scope) if !session.has_scope(scope) => {
+                    info!(
+                        "session {} of user \"{}\" lacks scope \"{scope}\"",
+                        session.session_id,
+                        session.username,
+                    );
+                    Outcome::Error((Status::UnauthorizedInsufficientScope, ()))
+                },
+                _ => Outcome::Success(Authenticated(session, PhantomData)),
+            },
             MaybeAuthenticated::Expired(_) => Outcome::Error((Status::UnauthorizedInvalidToken, ())),
             MaybeAuthenticated::InvalidRequest => Outcome::Error((Status::UnauthorizedInvalidRequest, ())),
             MaybeAuthenticated::InvalidToken => Outcome::Error((Status::UnauthorizedInvalidToken, ())),
@@ -73,6 +118,14 @@ impl<'r> FromRequest<'r> for MaybeAuthenticated {
                 AccessGranterError::InvalidCredentials
                 => Outcome::Success(MaybeAuthenticated::InvalidToken),
 
+                AccessGranterError::SecondFactorRequired |
+                AccessGranterError::TooManyAttempts { .. } |
+                AccessGranterError::InvalidNewPassword |
+                AccessGranterError::SessionNotFound |
+                AccessGranterError::AppPasswordNotFound |
+                AccessGranterError::AppPasswordExists |
+                AccessGranterError::InvalidAppPassword |
+                AccessGranterError::ForbiddenScopes |
                 AccessGranterError::ProtobufError(_) |
                 AccessGranterError::Caller(_) |
                 AccessGranterError::AuthDaemonInternalError
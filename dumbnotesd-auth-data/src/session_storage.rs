@@ -33,6 +33,12 @@ pub enum UserSessionData {
 
         #[serde(with = "time::serde::rfc3339")]
         expires_at: OffsetDateTime,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        client_label: Option<String>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        app_password: Option<String>,
     },
     #[serde(rename = "web")] Web {
         session_id: Uuid,
@@ -45,5 +51,8 @@ pub enum UserSessionData {
 
         #[serde(with = "time::serde::rfc3339")]
         expires_at: OffsetDateTime,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        client_label: Option<String>,
     }
 }
@@ -1,5 +1,6 @@
+use std::collections::BTreeSet;
 use argon2::PasswordHash;
-use data::User;
+use data::{AppPassword, Scope, User, UserQuota};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -9,6 +10,40 @@ pub struct UserData {
 
     #[serde(with = "crate::serde::password_hash")]
     pub hash: PasswordHash,
+
+    /// Overrides `max_user_storage_size` of the app config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_storage_size: Option<u64>,
+
+    /// Overrides `max_user_notes` of the app config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_notes: Option<u32>,
+
+    /// Locked users can't log in or refresh their sessions
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+
+    /// Base32-encoded, enables the second factor at login
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp_secret: Option<String>,
+
+    /// Hex-encoded SHA-256 hashes of the unused recovery codes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recovery_codes: Vec<String>,
+
+    #[serde(default, rename = "app_password", skip_serializing_if = "Vec::is_empty")]
+    pub app_passwords: Vec<AppPasswordData>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AppPasswordData {
+    pub name: String,
+
+    /// Hex-encoded SHA-256 hash
+    pub hash: String,
+
+    pub scopes: BTreeSet<Scope>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -23,6 +58,27 @@ impl From<UserData> for User {
         User {
             username: value.username,
             hash: value.hash,
+            quota: UserQuota {
+                max_storage_size: value.max_storage_size,
+                max_notes: value.max_notes,
+            },
+            locked: value.locked,
+            totp_secret: value.totp_secret,
+            recovery_codes: value.recovery_codes,
+            app_passwords: value.app_passwords
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<AppPasswordData> for AppPassword {
+    fn from(value: AppPasswordData) -> Self {
+        AppPassword {
+            name: value.name,
+            hash: value.hash,
+            scopes: value.scopes,
         }
     }
 }
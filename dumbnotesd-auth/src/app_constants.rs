@@ -2,13 +2,16 @@ use time::Duration;
 
 // relative to the data directory
 pub const SESSION_STORAGE_PATH: &str = "private/session.toml";
+// relative to the data directory
+pub const USER_DB_FALLBACK_TMP_DIR: &str = "private";
 pub const SESSION_STORAGE_READ_BUF_SIZE: usize = 1024 * 128;
 pub const REFRESH_TOKEN_SIZE: usize = 128 / 8;
 pub const XSRF_TOKEN_SIZE: usize = 128 / 8;
+pub const MAX_CLIENT_LABEL_LENGTH: usize = 256;
+pub const MAX_APP_PASSWORD_NAME_LENGTH: usize = 256;
+pub const LOGIN_THROTTLE_PRUNE_THRESHOLD: usize = 1024;
 pub const REFRESH_TOKEN_VALIDITY_TIME: Duration = Duration::weeks(5);
 pub const API_ACCESS_TOKEN_VALIDITY_TIME: Duration = Duration::minutes(15);
 pub const WEB_ACCESS_TOKEN_VALIDITY_TIME: Duration = Duration::weeks(5);
 
-pub const FILE_WATCHER_DEBOUNCE_TIME: Duration = Duration::seconds(10);
-
 pub const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(1200);
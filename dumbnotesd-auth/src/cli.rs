@@ -19,6 +19,9 @@ pub struct CliConfig {
     #[arg(long)]
     pub private_key_file: PathBuf,
 
+    #[arg(long)]
+    pub public_key_file: PathBuf,
+
     #[arg(long)]
     pub data_directory: PathBuf,
     
@@ -27,6 +30,27 @@ pub struct CliConfig {
 
     #[arg(long)]
     pub hasher_config: String,
+
+    #[arg(long)]
+    pub login_lockout_threshold: u32,
+
+    #[arg(long)]
+    pub address_login_lockout_threshold: u32,
+
+    #[arg(long)]
+    pub login_lockout_seconds: u32,
+
+    #[arg(long)]
+    pub max_login_lockout_seconds: u32,
+
+    #[arg(long)]
+    pub audit_log_directory: PathBuf,
+
+    #[arg(long)]
+    pub audit_log_max_size: u64,
+
+    #[arg(long)]
+    pub audit_log_max_files: u32,
 }
 
 impl CliConfig {
@@ -7,8 +7,10 @@ use crate::session_storage::SessionStorage;
 use crate::user_db::UserDb;
 use tokio::net::unix::OwnedWriteHalf;
 use futures::Stream;
-use dumbnotes::{bin_constants::IPC_MESSAGE_MAX_SIZE, gen_proto_ipc_wrappers, ipc::data::{LoopInputMessage, LoopStreamExt}};
+use dumbnotes::audit_log::AuditLog;
+use dumbnotes::{bin_constants::IPC_MESSAGE_MAX_SIZE, gen_proto_ipc_wrappers, ipc::data::{LoopInputMessage, LoopStreamExt}, ipc::eventloop::Dispatched};
 use crate::processors;
+use crate::login_throttle::LoginThrottle;
 use auth_ipc_data::bindings;
 
 pub struct State<U: UserDb, S: SessionStorage> {
@@ -16,6 +18,8 @@ pub struct State<U: UserDb, S: SessionStorage> {
     pub user_db: U,
     pub session_storage: S,
     pub access_token_validator: AccessTokenValidator,
+    pub login_throttle: LoginThrottle,
+    pub audit_log: AuditLog,
 }
 
 pub async fn process_commands<U, S>(
@@ -45,27 +49,89 @@ where
 async fn dispatch_command<U: UserDb, S: SessionStorage>(
     command: bindings::command::Command,
     state: Arc<State<U, S>>,
-) -> Result<Response, ProtobufRequestError> {
+) -> Result<Dispatched<Response>, ProtobufRequestError> {
     use bindings::command::Command as CE;
     let response = match command {
         CE::Login(request) => processors::process_login(
             &state.user_db,
             &state.session_storage,
             &state.token_generator,
+            &state.login_throttle,
+            &state.audit_log,
             request.try_into()?,
         ).await,
         CE::RefreshToken(request) => processors::process_refresh_token(
+            &state.user_db,
             &state.session_storage,
             &state.token_generator,
+            &state.audit_log,
             request.try_into()?,
         ).await,
         CE::Logout(request) => processors::process_logout(
             &state.session_storage,
             &state.access_token_validator,
+            &state.audit_log,
+            request.try_into()?,
+        ).await,
+        CE::ChangePassword(request) => processors::process_change_password(
+            &state.user_db,
+            &state.session_storage,
+            &state.access_token_validator,
+            &state.audit_log,
+            request.try_into()?,
+        ).await,
+        CE::ListSessions(request) => processors::process_list_sessions(
+            &state.session_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::RevokeSession(request) => processors::process_revoke_session(
+            &state.session_storage,
+            &state.access_token_validator,
+            &state.audit_log,
+            request.try_into()?,
+        ).await,
+        CE::RevokeAllSessions(request) => processors::process_revoke_all_sessions(
+            &state.session_storage,
+            &state.access_token_validator,
+            &state.audit_log,
+            request.try_into()?,
+        ).await,
+        CE::CheckSession(request) => processors::process_check_session(
+            &state.session_storage,
+            request.try_into()?,
+        ).await,
+        CE::ListActivity(request) => processors::process_list_activity(
+            &state.session_storage,
+            &state.access_token_validator,
+            &state.audit_log,
+            request.try_into()?,
+        ).await,
+        CE::GetUserQuota(request) => processors::process_get_user_quota(
+            &state.user_db,
+            request.try_into()?,
+        ).await,
+        CE::CreateAppPassword(request) => processors::process_create_app_password(
+            &state.user_db,
+            &state.session_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::ListAppPasswords(request) => processors::process_list_app_passwords(
+            &state.user_db,
+            &state.session_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::RemoveAppPassword(request) => processors::process_remove_app_password(
+            &state.user_db,
+            &state.session_storage,
+            &state.access_token_validator,
+            &state.audit_log,
             request.try_into()?,
         ).await,
     };
-    Ok(Response(response))
+    Ok(Response(response).into())
 }
 
 gen_proto_ipc_wrappers!(
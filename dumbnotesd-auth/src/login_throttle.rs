@@ -0,0 +1,209 @@
+#[cfg(test)] mod tests;
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use data::{UsernameStr, UsernameString};
+use log::warn;
+use crate::app_constants::LOGIN_THROTTLE_PRUNE_THRESHOLD;
+
+pub struct LoginThrottleConfig {
+    /// Zero disables the lockout
+    pub username_threshold: u32,
+    pub address_threshold: u32,
+    pub lockout: Duration,
+    pub max_lockout: Duration,
+}
+
+/// Counts the failed logins per username and per client address,
+/// refusing further attempts for an exponentially growing time after
+/// a threshold
+pub struct LoginThrottle {
+    config: LoginThrottleConfig,
+    failures: Mutex<HashMap<ThrottleKey, Failures>>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum ThrottleKey {
+    Username(UsernameString),
+    Address(IpAddr),
+}
+
+struct Failures {
+    count: u32,
+    // the attempts let through and not registered yet
+    in_flight: u32,
+    last_failure: Instant,
+}
+
+/// A login attempt let through by the throttle, which counts against
+/// the thresholds as a failure until it's registered or dropped, so that
+/// concurrent attempts can't get past them
+pub struct LoginAttempt<'a> {
+    throttle: &'a LoginThrottle,
+    keys: Vec<ThrottleKey>,
+}
+
+impl LoginAttempt<'_> {
+    pub fn register_failure(self) {
+        self.throttle.register_failure(&self.keys);
+    }
+
+    /// Forgets the failures of the username, but not of the address, so
+    /// that logging into an own account doesn't help guessing others
+    pub fn register_success(self) {
+        self.throttle.register_success(&self.keys);
+    }
+}
+
+impl Drop for LoginAttempt<'_> {
+    fn drop(&mut self) {
+        self.throttle.release(&self.keys);
+    }
+}
+
+impl LoginThrottle {
+    pub fn new(config: LoginThrottleConfig) -> Self {
+        LoginThrottle {
+            config,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Lets the login attempt through, or tells how long it has to wait
+    /// if it's locked out
+    pub fn check(
+        &self,
+        username: &UsernameStr,
+        address: Option<IpAddr>,
+    ) -> Result<LoginAttempt<'_>, Duration> {
+        let now = Instant::now();
+        let keys: Vec<_> = Self::keys(username, address).collect();
+        let mut failures = self.failures.lock().unwrap();
+        let wait = keys
+            .iter()
+            .filter_map(|key| {
+                let threshold = self.get_threshold(key);
+                self.get_wait(failures.get(key)?, threshold, now)
+            })
+            .max();
+        if let Some(wait) = wait {
+            return Err(wait)
+        }
+        for key in &keys {
+            failures
+                .entry(key.clone())
+                .or_insert(Failures { count: 0, in_flight: 0, last_failure: now })
+                .in_flight += 1;
+        }
+        Ok(LoginAttempt { throttle: self, keys })
+    }
+
+    fn register_failure(&self, keys: &[ThrottleKey]) {
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap();
+        if failures.len() >= LOGIN_THROTTLE_PRUNE_THRESHOLD {
+            failures.retain(|_, failures|
+                failures.in_flight > 0 || !self.is_forgotten(failures, now)
+            );
+        }
+        for key in keys {
+            let failures = failures
+                .entry(key.clone())
+                .and_modify(|failures| {
+                    if self.is_forgotten(failures, now) {
+                        failures.count = 0;
+                    }
+                })
+                .or_insert(Failures { count: 0, in_flight: 0, last_failure: now });
+            failures.count = failures.count.saturating_add(1);
+            failures.last_failure = now;
+            if failures.count == self.get_threshold(key) {
+                warn!("locking out logins for {key:?} after {} failures", failures.count);
+            }
+        }
+    }
+
+    fn register_success(&self, keys: &[ThrottleKey]) {
+        let mut failures = self.failures.lock().unwrap();
+        for key in keys {
+            if let ThrottleKey::Username(_) = key
+                && let Some(failures) = failures.get_mut(key)
+            {
+                failures.count = 0;
+            }
+        }
+    }
+
+    // ends the attempt, after it's registered, if it was
+    fn release(&self, keys: &[ThrottleKey]) {
+        let mut failures = self.failures.lock().unwrap();
+        for key in keys {
+            if let Entry::Occupied(mut entry) = failures.entry(key.clone()) {
+                let failures = entry.get_mut();
+                failures.in_flight = failures.in_flight.saturating_sub(1);
+                if failures.in_flight == 0 && failures.count == 0 {
+                    entry.remove();
+                }
+            }
+        }
+    }
+
+    fn keys(
+        username: &UsernameStr,
+        address: Option<IpAddr>,
+    ) -> impl Iterator<Item = ThrottleKey> {
+        std::iter::once(ThrottleKey::Username(username.to_owned()))
+            .chain(address.map(|address| ThrottleKey::Address(normalize_address(address))))
+    }
+
+    fn get_threshold(&self, key: &ThrottleKey) -> u32 {
+        match key {
+            ThrottleKey::Username(_) => self.config.username_threshold,
+            ThrottleKey::Address(_) => self.config.address_threshold,
+        }
+    }
+
+    fn get_wait(
+        &self,
+        failures: &Failures,
+        threshold: u32,
+        now: Instant,
+    ) -> Option<Duration> {
+        if threshold == 0 {
+            return None
+        }
+        let count = if self.is_forgotten(failures, now) { 0 } else { failures.count };
+        if count >= threshold {
+            let lockout = 2u32
+                .checked_pow(count - threshold)
+                .and_then(|factor| self.config.lockout.checked_mul(factor))
+                .map_or(self.config.max_lockout, |lockout| lockout.min(self.config.max_lockout));
+            let wait = (failures.last_failure + lockout).checked_duration_since(now);
+            if wait.is_some() {
+                return wait
+            }
+        }
+        // the attempts in progress may all fail, and once a lockout runs
+        // out, a single one is let through at a time
+        let allowed = threshold.saturating_sub(count).max(1);
+        (failures.in_flight >= allowed).then_some(self.config.lockout)
+    }
+
+    fn is_forgotten(&self, failures: &Failures, now: Instant) -> bool {
+        now.duration_since(failures.last_failure) >= self.config.max_lockout
+    }
+}
+
+// a single IPv6 client usually has the whole /64 network
+fn normalize_address(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V4(_) => address,
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => IpAddr::V6((u128::from(v6) & !((1u128 << 64) - 1)).into()),
+        },
+    }
+}
@@ -0,0 +1,60 @@
+use std::str::FromStr;
+use std::sync::Barrier;
+use std::thread;
+use super::*;
+
+const THRESHOLD: u32 = 3;
+
+fn make_throttle() -> LoginThrottle {
+    LoginThrottle::new(
+        LoginThrottleConfig {
+            username_threshold: THRESHOLD,
+            address_threshold: 0,
+            lockout: Duration::from_secs(60),
+            max_lockout: Duration::from_secs(3600),
+        }
+    )
+}
+
+fn username() -> UsernameString {
+    UsernameString::from_str("abc").unwrap()
+}
+
+#[test]
+fn attempts_in_flight_count_as_failures() {
+    let throttle = make_throttle();
+    let username = username();
+    let attempts: Vec<_> = (0..THRESHOLD)
+        .map(|_| throttle.check(&username, None).unwrap())
+        .collect();
+    assert!(throttle.check(&username, None).is_err());
+    drop(attempts);
+    throttle.check(&username, None).unwrap().register_success();
+    assert!(throttle.check(&username, None).is_ok());
+}
+
+#[test]
+fn concurrent_attempts_stop_at_threshold() {
+    const ATTEMPTS: usize = 16;
+
+    let throttle = make_throttle();
+    let username = username();
+    let checked = Barrier::new(ATTEMPTS);
+    let let_through = thread::scope(|scope| {
+        let threads: Vec<_> = (0..ATTEMPTS)
+            .map(|_| scope.spawn(|| {
+                let attempt = throttle.check(&username, None);
+                // all the checks happen before any failure is registered
+                checked.wait();
+                attempt.map(LoginAttempt::register_failure).is_ok()
+            }))
+            .collect();
+        threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .filter(|let_through| *let_through)
+            .count()
+    });
+    assert_eq!(let_through, THRESHOLD as usize);
+    assert!(throttle.check(&username, None).is_err());
+}
@@ -4,26 +4,27 @@ mod processors;
 pub mod session_storage;
 mod app_constants;
 pub mod user_db;
-pub mod file_watcher;
+mod login_throttle;
 
 use crate::app_constants::SHUTDOWN_TIMEOUT;
 use crate::cli::CliConfig;
 use clap::{crate_name, Parser};
 use access_token::{AccessTokenDecoder, AccessTokenGenerator, AccessTokenValidator};
 use dumbnotes::config::hasher_config::ProductionHasherConfigData;
-use dumbnotes::bin_constants::IPC_MESSAGE_MAX_SIZE;
+use dumbnotes::audit_log::{AuditLog, AuditLogConfig};
+use dumbnotes::bin_constants::{AUTH_AUDIT_LOG_PATH, IPC_MESSAGE_MAX_SIZE};
 use dumbnotes::ipc::launch_event_loops::launch_event_loops;
 use util::error_exit;
 use dumbnotes::hasher::{ProductionHasher, ProductionHasherConfig};
 use dumbnotes::logging::init_daemon_logging;
 #[cfg(target_os = "openbsd")] use dumbnotes::sandbox::pledge::{pledge_authd_init, pledge_authd_normal};
-use file_watcher::ProductionFileWatcher;
-use josekit::jwk::Jwk;
+use dumbnotes::file_watcher::ProductionFileWatcher;
+use dumbnotes::jwt_keys::{read_jwt_private_key, read_jwt_public_keys, watch_jwt_private_key, watch_jwt_public_keys};
 use log::info;
+use login_throttle::{LoginThrottle, LoginThrottleConfig};
 use session_storage::ProductionSessionStorage;
-use std::error::Error;
-use std::path::Path;
-use unix::{check_secret_file_ro_access, set_umask};
+use std::time::Duration;
+use unix::set_umask;
 use user_db::ProductionUserDb;
 
 #[tokio::main]
@@ -45,9 +46,25 @@ async fn main() {
             Permissions::R,
         );
         unveil(
-            &config.user_db_path,
+            &config.public_key_file,
             Permissions::R,
         );
+        unveil(
+            &config.user_db_path,
+            Permissions::R | Permissions::W,
+        );
+        // the db is replaced by a file made next to it, or in the fallback
+        // directory if that fails
+        if let Some(user_db_directory) = config.user_db_path.parent() {
+            unveil(
+                user_db_directory,
+                Permissions::R | Permissions::W | Permissions::C,
+            );
+        }
+        unveil(
+            &ProductionUserDb::get_fallback_tmp_dir(&config.data_directory),
+            Permissions::R | Permissions::W | Permissions::C,
+        );
         unveil(
             &hasher_config.pepper_path,
             Permissions::R,
@@ -56,6 +73,10 @@ async fn main() {
             &ProductionSessionStorage::get_storage_path(&config.data_directory),
             Permissions::R | Permissions::W | Permissions::C,
         );
+        unveil(
+            &config.audit_log_directory,
+            Permissions::R | Permissions::W | Permissions::C,
+        );
         seal_unveil();
     }
 
@@ -69,19 +90,17 @@ async fn main() {
         async move || {
             let watcher = ProductionFileWatcher::new()
                 .unwrap_or_else(|e| error_exit!("failed to create file watcher: {e}"));
-            let jwt_private_key = read_jwt_key(&config.private_key_file)
-                .unwrap_or_else(|e| error_exit!("failed to read the jwt private key: {e}"));
-            let jwt_public_key = jwt_private_key.to_public_key()
-                .unwrap_or_else(|e| error_exit!("failed to derive jwt public key: {e}"));
             eventloop::State {
-                token_generator: make_token_generator(&jwt_private_key),
+                token_generator: make_token_generator(&config, &watcher),
+                access_token_validator: make_access_token_validator(&config, &watcher),
                 user_db: make_user_db(
                     &config,
                     make_hasher(&hasher_config),
                     watcher.clone(),
                 ).await,
                 session_storage: make_session_storage(&config, watcher).await,
-                access_token_validator: make_access_token_validator(&jwt_public_key).await,
+                login_throttle: make_login_throttle(&config),
+                audit_log: make_audit_log(&config).await,
             }
         },
         |state, stream, write_socket|
@@ -96,6 +115,32 @@ async fn main() {
     ).await;
 }
 
+fn make_login_throttle(config: &CliConfig) -> LoginThrottle {
+    LoginThrottle::new(
+        LoginThrottleConfig {
+            username_threshold: config.login_lockout_threshold,
+            address_threshold: config.address_login_lockout_threshold,
+            lockout: Duration::from_secs(config.login_lockout_seconds.into()),
+            max_lockout: Duration::from_secs(config.max_login_lockout_seconds.into()),
+        }
+    )
+}
+
+async fn make_audit_log(config: &CliConfig) -> AuditLog {
+    AuditLog
+        ::open(
+            AuditLogConfig {
+                path: config.audit_log_directory.join(AUTH_AUDIT_LOG_PATH),
+                max_size: config.audit_log_max_size,
+                max_files: config.audit_log_max_files,
+            }
+        )
+        .await
+        .unwrap_or_else(|e|
+            error_exit!("could not open the audit log: {e}")
+        )
+}
+
 fn parse_hasher_config(config: &CliConfig) -> ProductionHasherConfigData {
     serde_json
     ::from_str(&config.hasher_config)
@@ -124,23 +169,18 @@ fn make_hasher(
 }
 
 fn make_token_generator(
-    jwt_private_key: &Jwk,
+    config: &CliConfig,
+    watcher: &ProductionFileWatcher,
 ) -> AccessTokenGenerator {
-    AccessTokenGenerator::from_jwk(&jwt_private_key)
+    let jwt_private_key = read_jwt_private_key(&config.private_key_file)
+        .unwrap_or_else(|e| error_exit!("failed to read the jwt private key: {e}"));
+    let generator = AccessTokenGenerator::from_jwk(&jwt_private_key)
         .unwrap_or_else(|e|
             error_exit!("could not initialize access token access_token_generator: {e}")
-        )
-}
-
-fn read_jwt_key(
-    path: &Path,
-) -> Result<Jwk, Box<dyn Error>> {
-    check_secret_file_ro_access(path)?;
-    Ok(
-        Jwk::from_bytes(
-            std::fs::read(path)?
-        )?
-    )
+        );
+    watch_jwt_private_key(watcher, &config.private_key_file, generator.clone())
+        .unwrap_or_else(|e| error_exit!("failed to watch the jwt private key: {e}"));
+    generator
 }
 
 async fn make_user_db(
@@ -151,6 +191,7 @@ async fn make_user_db(
     ProductionUserDb
         ::new(
             &config.user_db_path,
+            &config.data_directory,
             hasher,
             watcher.clone(),
         )
@@ -175,13 +216,17 @@ async fn make_session_storage(
         )
 }
 
-async fn make_access_token_validator(
-    jwt_public_key: &Jwk,
+fn make_access_token_validator(
+    config: &CliConfig,
+    watcher: &ProductionFileWatcher,
 ) -> AccessTokenValidator {
-    AccessTokenValidator::new(
-        AccessTokenDecoder::from_jwk(jwt_public_key)
-            .unwrap_or_else(|e|
-                error_exit!("could not initialize access token decoder: {e}")
-            )
-    )
+    let jwt_public_keys = read_jwt_public_keys(&config.public_key_file)
+        .unwrap_or_else(|e| error_exit!("failed to read the jwt public keys: {e}"));
+    let decoder = AccessTokenDecoder::from_jwk_set(&jwt_public_keys)
+        .unwrap_or_else(|e|
+            error_exit!("could not initialize access token decoder: {e}")
+        );
+    watch_jwt_public_keys(watcher, &config.public_key_file, decoder.clone())
+        .unwrap_or_else(|e| error_exit!("failed to watch the jwt public keys: {e}"));
+    AccessTokenValidator::new(decoder)
 }
@@ -1,7 +1,52 @@
 mod login;
 mod refresh_token;
 mod logout;
+mod change_password;
+mod list_sessions;
+mod revoke_session;
+mod revoke_all_sessions;
+mod check_session;
+mod list_activity;
+mod get_user_quota;
+mod create_app_password;
+mod list_app_passwords;
+mod remove_app_password;
 
 pub use login::process_login;
 pub use refresh_token::process_refresh_token;
 pub use logout::process_logout;
+pub use change_password::process_change_password;
+pub use list_sessions::process_list_sessions;
+pub use revoke_session::process_revoke_session;
+pub use revoke_all_sessions::process_revoke_all_sessions;
+pub use check_session::process_check_session;
+pub use list_activity::process_list_activity;
+pub use get_user_quota::process_get_user_quota;
+pub use create_app_password::process_create_app_password;
+pub use list_app_passwords::process_list_app_passwords;
+pub use remove_app_password::process_remove_app_password;
+
+use access_token::AccessTokenData;
+use log::warn;
+use crate::session_storage::{SessionStorage, SessionStorageError};
+
+// access tokens stay valid after their session ends, so the requests
+// managing the account check that it still exists
+async fn is_session_alive(
+    session_storage: &impl SessionStorage,
+    token_data: &AccessTokenData,
+) -> Result<bool, SessionStorageError> {
+    let session = session_storage
+        .get_session_by_id(token_data.session_id)
+        .await?;
+    let is_alive = session
+        .is_some_and(|session| session.get_username() == token_data.username);
+    if !is_alive {
+        warn!(
+            "session {} of user \"{}\" no longer exists",
+            token_data.session_id,
+            token_data.username,
+        );
+    }
+    Ok(is_alive)
+}
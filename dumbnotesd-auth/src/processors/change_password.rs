@@ -0,0 +1,146 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use data::{AuditEvent, AuditEventKind, AuditOutcome, SessionKind};
+use dumbnotes::audit_log::AuditLog;
+use dumbnotes::check_access_token;
+use log::{debug, error, info, warn};
+use thiserror::Error;
+use crate::processors::is_session_alive;
+use crate::session_storage::{SessionStorage, SessionStorageError};
+use crate::user_db::{UserDb, UserDbError};
+use auth_ipc_data::model::change_password::{ChangePasswordRequest, ChangePasswordResponse, ChangePasswordSuccess};
+use auth_ipc_data::bindings::ChangePasswordError;
+
+pub async fn process_change_password(
+    user_db: &impl UserDb,
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: ChangePasswordRequest,
+) -> auth_ipc_data::bindings::response::Response {
+    process_change_password_impl(
+        user_db,
+        session_storage,
+        access_token_validator,
+        audit_log,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing change password request: {e}");
+            ChangePasswordResponse(Err(ChangePasswordError::ChangePasswordInternalError))
+        })
+        .into()
+}
+
+async fn process_change_password_impl(
+    user_db: &impl UserDb,
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: ChangePasswordRequest,
+) -> Result<ChangePasswordResponse, ChangePasswordProcessorError> {
+    let ChangePasswordRequest {
+        access_token,
+        old_password,
+        new_password,
+        revoke_other_sessions,
+    } = request;
+
+    let token_data = check_access_token!(
+        "change password",
+        access_token_validator,
+        access_token,
+        SessionKind::Api,
+        ChangePasswordResponse(Err(ChangePasswordError::ChangePasswordInvalidCredentials)),
+    );
+    if !is_session_alive(session_storage, &token_data).await? {
+        return Ok(
+            ChangePasswordResponse(
+                Err(ChangePasswordError::ChangePasswordInvalidCredentials)
+            )
+        )
+    }
+    let AccessTokenData { session_id, username, .. } = token_data;
+
+    if new_password.is_empty() {
+        warn!("rejecting empty new password for user \"{username}\"");
+        return Ok(
+            ChangePasswordResponse(
+                Err(ChangePasswordError::ChangePasswordInvalidNewPassword)
+            )
+        )
+    }
+    debug!("changing password of user \"{username}\"");
+    let failure_event = AuditEvent {
+        session_id: Some(session_id),
+        ..AuditEvent::now(username.clone(), AuditEventKind::PasswordChange, AuditOutcome::Failure)
+    };
+    if !user_db.check_user_credentials(&username, &old_password).await? {
+        warn!("invalid old password for user \"{username}\"");
+        audit_log.record(&failure_event).await;
+        return Ok(
+            ChangePasswordResponse(
+                Err(ChangePasswordError::ChangePasswordWrongPassword)
+            )
+        )
+    }
+    if !user_db.set_user_password(&username, &new_password).await? {
+        warn!("user \"{username}\" removed while changing their password");
+        return Ok(
+            ChangePasswordResponse(
+                Err(ChangePasswordError::ChangePasswordInvalidCredentials)
+            )
+        )
+    }
+    audit_log
+        .record(
+            &AuditEvent {
+                outcome: AuditOutcome::Success,
+                ..failure_event
+            }
+        )
+        .await;
+    let revoked_session_ids = if revoke_other_sessions {
+        let revoked = session_storage
+            .delete_user_sessions(&username, Some(session_id))
+            .await?;
+        info!(
+            "revoked {} other sessions of user \"{username}\"",
+            revoked.len(),
+        );
+        for revoked_session_id in &revoked {
+            audit_log
+                .record(
+                    &AuditEvent {
+                        session_id: Some(*revoked_session_id),
+                        ..AuditEvent::now(
+                            username.clone(),
+                            AuditEventKind::SessionRevocation,
+                            AuditOutcome::Success,
+                        )
+                    }
+                )
+                .await;
+        }
+        revoked
+    } else {
+        Vec::new()
+    };
+    Ok(
+        ChangePasswordResponse(
+            Ok(
+                ChangePasswordSuccess {
+                    revoked_session_ids,
+                }
+            )
+        )
+    )
+}
+
+#[derive(Debug, Error)]
+enum ChangePasswordProcessorError {
+    #[error("user database error: {0}")]
+    UserDb(#[from] UserDbError),
+
+    #[error("session storage error: {0}")]
+    SessionStorage(#[from] SessionStorageError),
+}
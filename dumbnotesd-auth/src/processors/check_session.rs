@@ -0,0 +1,50 @@
+use log::{debug, error, warn};
+use thiserror::Error;
+use crate::session_storage::{Session, SessionStorage, SessionStorageError};
+use auth_ipc_data::model::check_session::{CheckSessionRequest, CheckSessionResponse};
+use auth_ipc_data::bindings::SessionsError;
+
+pub async fn process_check_session(
+    session_storage: &impl SessionStorage,
+    request: CheckSessionRequest,
+) -> auth_ipc_data::bindings::response::Response {
+    process_check_session_impl(session_storage, request)
+        .await
+        .unwrap_or_else(|e| {
+            error!("error processing check session request: {e}");
+            CheckSessionResponse(Err(SessionsError::SessionsInternalError))
+        })
+        .into()
+}
+
+async fn process_check_session_impl(
+    session_storage: &impl SessionStorage,
+    request: CheckSessionRequest,
+) -> Result<CheckSessionResponse, CheckSessionProcessorError> {
+    let CheckSessionRequest { username, session_id, xsrf_token } = request;
+
+    let session = session_storage
+        .get_session_by_id(session_id)
+        .await?
+        .filter(|session| session.get_username() == username);
+    let is_alive = match (session.as_deref(), xsrf_token) {
+        (None, _) => {
+            debug!("session {session_id} of user \"{username}\" no longer exists");
+            false
+        },
+        (Some(_), None) => true,
+        (Some(Session::Web(web_session)), Some(xsrf_token))
+            if web_session.xsrf_token == xsrf_token => true,
+        (Some(_), Some(_)) => {
+            warn!("invalid xsrf token received for session {session_id}");
+            false
+        },
+    };
+    Ok(CheckSessionResponse(Ok(is_alive)))
+}
+
+#[derive(Debug, Error)]
+enum CheckSessionProcessorError {
+    #[error("session storage error: {0}")]
+    SessionStorage(#[from] SessionStorageError),
+}
@@ -0,0 +1,100 @@
+use access_token::AccessTokenValidator;
+use data::{format_scopes, SessionKind};
+use dumbnotes::app_password::generate_app_password;
+use dumbnotes::check_access_token;
+use log::{debug, error, warn};
+use thiserror::Error;
+use crate::app_constants::MAX_APP_PASSWORD_NAME_LENGTH;
+use crate::processors::is_session_alive;
+use crate::session_storage::{SessionStorage, SessionStorageError};
+use crate::user_db::{UserDb, UserDbError};
+use auth_ipc_data::model::create_app_password::{CreateAppPasswordRequest, CreateAppPasswordResponse};
+use auth_ipc_data::bindings::AppPasswordsError;
+
+pub async fn process_create_app_password(
+    user_db: &impl UserDb,
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: CreateAppPasswordRequest,
+) -> auth_ipc_data::bindings::response::Response {
+    process_create_app_password_impl(
+        user_db,
+        session_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing create app password request: {e}");
+            CreateAppPasswordResponse(Err(AppPasswordsError::AppPasswordsInternalError))
+        })
+        .into()
+}
+
+async fn process_create_app_password_impl(
+    user_db: &impl UserDb,
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: CreateAppPasswordRequest,
+) -> Result<CreateAppPasswordResponse, CreateAppPasswordProcessorError> {
+    let CreateAppPasswordRequest { access_token, name, scopes } = request;
+
+    let token_data = check_access_token!(
+        "create app password",
+        access_token_validator,
+        access_token,
+        SessionKind::Api,
+        CreateAppPasswordResponse(Err(AppPasswordsError::AppPasswordsInvalidCredentials)),
+    );
+    if !is_session_alive(session_storage, &token_data).await? {
+        return Ok(
+            CreateAppPasswordResponse(Err(AppPasswordsError::AppPasswordsInvalidCredentials))
+        )
+    }
+    let username = token_data.username;
+
+    if name.is_empty()
+        || name.len() > MAX_APP_PASSWORD_NAME_LENGTH
+        || name.chars().any(char::is_control)
+        || scopes.is_empty()
+    {
+        warn!("rejecting invalid application password \"{name}\" of user \"{username}\"");
+        return Ok(
+            CreateAppPasswordResponse(Err(AppPasswordsError::AppPasswordsInvalidRequest))
+        )
+    }
+    // a session logged in with an application password can't hand out
+    // more than it was given
+    if let Some(session_scopes) = &token_data.scopes
+        && !scopes.is_subset(session_scopes)
+    {
+        warn!(
+            "session {} of user \"{username}\" limited to \"{}\" can't create \
+                application password \"{name}\" for \"{}\"",
+            token_data.session_id,
+            format_scopes(session_scopes),
+            format_scopes(&scopes),
+        );
+        return Ok(
+            CreateAppPasswordResponse(Err(AppPasswordsError::AppPasswordsForbiddenScopes))
+        )
+    }
+
+    debug!("creating application password \"{name}\" of user \"{username}\"");
+    let password = generate_app_password();
+    if !user_db.add_app_password(&username, &name, &password, scopes).await? {
+        warn!("user \"{username}\" has an application password \"{name}\" already");
+        return Ok(
+            CreateAppPasswordResponse(Err(AppPasswordsError::AppPasswordsAlreadyExists))
+        )
+    }
+    Ok(CreateAppPasswordResponse(Ok(password)))
+}
+
+#[derive(Debug, Error)]
+enum CreateAppPasswordProcessorError {
+    #[error("user database error: {0}")]
+    UserDb(#[from] UserDbError),
+
+    #[error("session storage error: {0}")]
+    SessionStorage(#[from] SessionStorageError),
+}
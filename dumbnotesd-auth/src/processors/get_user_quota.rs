@@ -0,0 +1,33 @@
+use log::error;
+use thiserror::Error;
+use crate::user_db::{UserDb, UserDbError};
+use auth_ipc_data::model::get_user_quota::{GetUserQuotaRequest, GetUserQuotaResponse};
+use auth_ipc_data::bindings::SessionsError;
+
+pub async fn process_get_user_quota(
+    user_db: &impl UserDb,
+    request: GetUserQuotaRequest,
+) -> auth_ipc_data::bindings::response::Response {
+    process_get_user_quota_impl(user_db, request)
+        .await
+        .unwrap_or_else(|e| {
+            error!("error processing get user quota request: {e}");
+            GetUserQuotaResponse(Err(SessionsError::SessionsInternalError))
+        })
+        .into()
+}
+
+async fn process_get_user_quota_impl(
+    user_db: &impl UserDb,
+    request: GetUserQuotaRequest,
+) -> Result<GetUserQuotaResponse, GetUserQuotaProcessorError> {
+    let GetUserQuotaRequest { username } = request;
+
+    Ok(GetUserQuotaResponse(Ok(user_db.get_user_quota(&username).await?)))
+}
+
+#[derive(Debug, Error)]
+enum GetUserQuotaProcessorError {
+    #[error("user database error: {0}")]
+    UserDb(#[from] UserDbError),
+}
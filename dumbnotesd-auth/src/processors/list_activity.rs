@@ -0,0 +1,62 @@
+use access_token::AccessTokenValidator;
+use data::SessionKind;
+use dumbnotes::audit_log::{AuditLog, AuditLogError};
+use dumbnotes::check_access_token;
+use log::{debug, error};
+use thiserror::Error;
+use crate::processors::is_session_alive;
+use crate::session_storage::{SessionStorage, SessionStorageError};
+use auth_ipc_data::model::list_activity::{ListActivityRequest, ListActivityResponse};
+use auth_ipc_data::bindings::SessionsError;
+
+pub async fn process_list_activity(
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: ListActivityRequest,
+) -> auth_ipc_data::bindings::response::Response {
+    process_list_activity_impl(session_storage, access_token_validator, audit_log, request)
+        .await
+        .unwrap_or_else(|e| {
+            error!("error processing list activity request: {e}");
+            ListActivityResponse(Err(SessionsError::SessionsInternalError))
+        })
+        .into()
+}
+
+async fn process_list_activity_impl(
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: ListActivityRequest,
+) -> Result<ListActivityResponse, ListActivityProcessorError> {
+    let ListActivityRequest { access_token, limit } = request;
+
+    let token_data = check_access_token!(
+        "list activity",
+        access_token_validator,
+        access_token,
+        SessionKind::Api,
+        ListActivityResponse(Err(SessionsError::SessionsInvalidCredentials)),
+    );
+    if !is_session_alive(session_storage, &token_data).await? {
+        return Ok(
+            ListActivityResponse(Err(SessionsError::SessionsInvalidCredentials))
+        )
+    }
+
+    debug!("listing activity of user \"{}\"", token_data.username);
+    let events = audit_log
+        .read_user_events(&token_data.username, limit as usize)
+        .await?;
+    Ok(ListActivityResponse(Ok(events)))
+}
+
+#[derive(Debug, Error)]
+enum ListActivityProcessorError {
+    #[error("session storage error: {0}")]
+    SessionStorage(#[from] SessionStorageError),
+
+    #[error("audit log error: {0}")]
+    AuditLog(#[from] AuditLogError),
+}
@@ -0,0 +1,65 @@
+use access_token::AccessTokenValidator;
+use data::SessionKind;
+use dumbnotes::check_access_token;
+use log::{debug, error};
+use thiserror::Error;
+use crate::processors::is_session_alive;
+use crate::session_storage::{SessionStorage, SessionStorageError};
+use crate::user_db::{UserDb, UserDbError};
+use auth_ipc_data::model::list_app_passwords::{ListAppPasswordsRequest, ListAppPasswordsResponse};
+use auth_ipc_data::bindings::AppPasswordsError;
+
+pub async fn process_list_app_passwords(
+    user_db: &impl UserDb,
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListAppPasswordsRequest,
+) -> auth_ipc_data::bindings::response::Response {
+    process_list_app_passwords_impl(
+        user_db,
+        session_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing list app passwords request: {e}");
+            ListAppPasswordsResponse(Err(AppPasswordsError::AppPasswordsInternalError))
+        })
+        .into()
+}
+
+async fn process_list_app_passwords_impl(
+    user_db: &impl UserDb,
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListAppPasswordsRequest,
+) -> Result<ListAppPasswordsResponse, ListAppPasswordsProcessorError> {
+    let ListAppPasswordsRequest { access_token } = request;
+
+    let token_data = check_access_token!(
+        "list app passwords",
+        access_token_validator,
+        access_token,
+        SessionKind::Api,
+        ListAppPasswordsResponse(Err(AppPasswordsError::AppPasswordsInvalidCredentials)),
+    );
+    if !is_session_alive(session_storage, &token_data).await? {
+        return Ok(
+            ListAppPasswordsResponse(Err(AppPasswordsError::AppPasswordsInvalidCredentials))
+        )
+    }
+
+    debug!("listing application passwords of user \"{}\"", token_data.username);
+    let mut app_passwords = user_db.list_app_passwords(&token_data.username).await?;
+    app_passwords.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(ListAppPasswordsResponse(Ok(app_passwords)))
+}
+
+#[derive(Debug, Error)]
+enum ListAppPasswordsProcessorError {
+    #[error("user database error: {0}")]
+    UserDb(#[from] UserDbError),
+
+    #[error("session storage error: {0}")]
+    SessionStorage(#[from] SessionStorageError),
+}
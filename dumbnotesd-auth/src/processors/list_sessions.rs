@@ -0,0 +1,81 @@
+use access_token::AccessTokenValidator;
+use data::{Session, SessionDescription, SessionKind};
+use dumbnotes::check_access_token;
+use log::{debug, error};
+use thiserror::Error;
+use time::OffsetDateTime;
+use crate::app_constants::REFRESH_TOKEN_VALIDITY_TIME;
+use crate::processors::is_session_alive;
+use crate::session_storage::{SessionStorage, SessionStorageError};
+use auth_ipc_data::model::list_sessions::{ListSessionsRequest, ListSessionsResponse};
+use auth_ipc_data::bindings::SessionsError;
+
+pub async fn process_list_sessions(
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListSessionsRequest,
+) -> auth_ipc_data::bindings::response::Response {
+    process_list_sessions_impl(session_storage, access_token_validator, request)
+        .await
+        .unwrap_or_else(|e| {
+            error!("error processing list sessions request: {e}");
+            ListSessionsResponse(Err(SessionsError::SessionsInternalError))
+        })
+        .into()
+}
+
+async fn process_list_sessions_impl(
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListSessionsRequest,
+) -> Result<ListSessionsResponse, ListSessionsProcessorError> {
+    let ListSessionsRequest { access_token } = request;
+
+    let token_data = check_access_token!(
+        "list sessions",
+        access_token_validator,
+        access_token,
+        SessionKind::Api,
+        ListSessionsResponse(Err(SessionsError::SessionsInvalidCredentials)),
+    );
+    if !is_session_alive(session_storage, &token_data).await? {
+        return Ok(
+            ListSessionsResponse(Err(SessionsError::SessionsInvalidCredentials))
+        )
+    }
+
+    debug!("listing sessions of user \"{}\"", token_data.username);
+    let now = OffsetDateTime::now_utc();
+    let mut sessions: Vec<_> = session_storage
+        .get_user_sessions(&token_data.username)
+        .await?
+        .iter()
+        .map(|session| match **session {
+            Session::Api(ref session) => SessionDescription {
+                session_id: session.session_id,
+                kind: SessionKind::Api,
+                created_at: session.created_at,
+                expires_at: session.expires_at + REFRESH_TOKEN_VALIDITY_TIME,
+                client_label: session.client_label.clone(),
+                is_current: session.session_id == token_data.session_id,
+            },
+            Session::Web(ref session) => SessionDescription {
+                session_id: session.session_id,
+                kind: SessionKind::Web,
+                created_at: session.created_at,
+                expires_at: session.expires_at,
+                client_label: session.client_label.clone(),
+                is_current: session.session_id == token_data.session_id,
+            },
+        })
+        .filter(|session| session.expires_at > now)
+        .collect();
+    sessions.sort_by_key(|session| (session.created_at, session.session_id));
+    Ok(ListSessionsResponse(Ok(sessions)))
+}
+
+#[derive(Debug, Error)]
+enum ListSessionsProcessorError {
+    #[error("session storage error: {0}")]
+    SessionStorage(#[from] SessionStorageError),
+}
@@ -1,12 +1,14 @@
 use access_token::{AccessTokenGenerator, AccessTokenGeneratorError};
-use data::{ApiSession, Session, SessionKind, WebSession};
+use data::{format_scopes, ApiSession, AppPassword, AuditEvent, AuditEventKind, AuditOutcome, Session, SessionKind, WebSession};
+use dumbnotes::audit_log::AuditLog;
 use thiserror::Error;
-use crate::app_constants::{API_ACCESS_TOKEN_VALIDITY_TIME, WEB_ACCESS_TOKEN_VALIDITY_TIME};
+use crate::app_constants::{API_ACCESS_TOKEN_VALIDITY_TIME, MAX_CLIENT_LABEL_LENGTH, WEB_ACCESS_TOKEN_VALIDITY_TIME};
+use crate::login_throttle::LoginThrottle;
 use crate::session_storage::{SessionStorage, SessionStorageError};
 use crate::user_db::{UserDb, UserDbError};
 use log::{debug, error, info, warn};
 use time::OffsetDateTime;
-use auth_ipc_data::model::login::{LoginRequest, LoginResponse};
+use auth_ipc_data::model::login::{LoginFailure, LoginRequest, LoginResponse};
 use auth_ipc_data::model::successful_login::SuccessfulLogin;
 use auth_ipc_data::bindings::LoginError;
 
@@ -14,17 +16,21 @@ pub async fn process_login(
     user_db: &impl UserDb,
     session_storage: &impl SessionStorage,
     token_generator: &AccessTokenGenerator,
+    login_throttle: &LoginThrottle,
+    audit_log: &AuditLog,
     request: LoginRequest,
 ) -> auth_ipc_data::bindings::response::Response {
     process_login_impl(
         user_db,
         session_storage,
         token_generator,
+        login_throttle,
+        audit_log,
         request,
     ).await
         .unwrap_or_else(|e| {
             error!("error processing login request: {e}");
-            LoginResponse(Err(LoginError::LoginInternalError))
+            LoginResponse(Err(LoginError::LoginInternalError.into()))
         })
         .into()
 }
@@ -33,64 +39,156 @@ async fn process_login_impl(
     user_db: &impl UserDb,
     session_storage: &impl SessionStorage,
     token_generator: &AccessTokenGenerator,
+    login_throttle: &LoginThrottle,
+    audit_log: &AuditLog,
     request: LoginRequest,
 ) -> Result<LoginResponse, LoginProcessorError> {
-    let LoginRequest { username, password, session_kind } = request;
+    let LoginRequest {
+        username,
+        password,
+        session_kind,
+        client_label,
+        second_factor,
+        client_address,
+    } = request;
     let session_kind: SessionKind = session_kind.into();
     debug!("logging user \"{username}\" in");
-    if user_db.check_user_credentials(&username, &password).await? {
-        let now = OffsetDateTime::now_utc();
-        let expires_at = match session_kind {
-            SessionKind::Api => now + API_ACCESS_TOKEN_VALIDITY_TIME,
-            SessionKind::Web => now + WEB_ACCESS_TOKEN_VALIDITY_TIME,
-        };
-        let session = session_storage
-            .create_session(
-                &username,
-                now,
-                expires_at,
-                session_kind,
+    let failure_event = AuditEvent {
+        client_address,
+        ..AuditEvent::now(username.clone(), AuditEventKind::Login, AuditOutcome::Failure)
+    };
+    // checked first, so that the locked out attempts don't try passwords
+    let login_attempt = match login_throttle.check(&username, client_address) {
+        Ok(login_attempt) => login_attempt,
+        Err(retry_after) => {
+            warn!(
+                "refusing login of user \"{username}\" from {client_address:?} \
+                    for {retry_after:?} more",
+            );
+            audit_log.record(&failure_event).await;
+            return Ok(
+                LoginResponse(
+                    Err(
+                        LoginFailure {
+                            error: LoginError::LoginTooManyAttempts,
+                            retry_after: Some(retry_after),
+                        }
+                    )
+                )
             )
-            .await?;
-        let access_token = token_generator
-            .generate_token(
-                session.get_session_id(),
-                &session.get_username(),
-                &now.into(),
-                &expires_at.into(),
-                session_kind,
-            )?;
+        },
+    };
+    let app_password = match session_kind {
+        SessionKind::Api => user_db.check_app_password(&username, &password).await?,
+        // the browsers get the user's own password
+        SessionKind::Web => None,
+    };
+    if let Some(AppPassword { name, scopes, .. }) = &app_password {
         info!(
-            "logged user \"{username}\" in with session \"{}\"",
-            session.get_session_id(),
+            "user \"{username}\" logs in with application password \"{name}\" \
+                limited to \"{}\"",
+            format_scopes(scopes),
         );
-        Ok(
+    } else if !user_db.check_user_credentials(&username, &password).await? {
+        warn!("invalid credentials for user \"{}\"", username);
+        login_attempt.register_failure();
+        audit_log.record(&failure_event).await;
+        return Ok(
             LoginResponse(
-                Ok(
-                    match session {
-                        Session::Api(ApiSession { refresh_token, .. })
-                        => SuccessfulLogin::Api {
-                            access_token,
-                            refresh_token,
-                        },
-
-                        Session::Web(WebSession { xsrf_token, .. })
-                        => SuccessfulLogin::Web {
-                            access_token,
-                            xsrf_token,
-                        },
-                    }
+                Err(LoginError::LoginInvalidCredentials.into())
+            )
+        )
+    }
+    // the application passwords are for the unattended clients
+    if app_password.is_none() && user_db.is_second_factor_enabled(&username).await? {
+        let Some(second_factor) = second_factor else {
+            info!("user \"{username}\" needs a second factor to log in");
+            return Ok(
+                LoginResponse(
+                    Err(LoginError::LoginSecondFactorRequired.into())
+                )
+            )
+        };
+        if !user_db.check_second_factor(&username, &second_factor).await? {
+            warn!("invalid second factor for user \"{username}\"");
+            login_attempt.register_failure();
+            audit_log.record(&failure_event).await;
+            return Ok(
+                LoginResponse(
+                    Err(LoginError::LoginInvalidCredentials.into())
                 )
             )
+        }
+    }
+    login_attempt.register_success();
+    let now = OffsetDateTime::now_utc();
+    let expires_at = match session_kind {
+        SessionKind::Api => now + API_ACCESS_TOKEN_VALIDITY_TIME,
+        SessionKind::Web => now + WEB_ACCESS_TOKEN_VALIDITY_TIME,
+    };
+    let session = session_storage
+        .create_session(
+            &username,
+            now,
+            expires_at,
+            session_kind,
+            client_label.as_deref().and_then(sanitize_client_label),
+            app_password.as_ref().map(|app_password| app_password.name.clone()),
         )
-    } else {
-        warn!("invalid credentials for user \"{}\"", username);
-        Ok(
-            LoginResponse(
-                Err(LoginError::LoginInvalidCredentials)
+        .await?;
+    let access_token = token_generator
+        .generate_token(
+            session.get_session_id(),
+            &session.get_username(),
+            &now.into(),
+            &expires_at.into(),
+            session_kind,
+            app_password.as_ref().map(|app_password| &app_password.scopes),
+        )?;
+    info!(
+        "logged user \"{username}\" in with session \"{}\"",
+        session.get_session_id(),
+    );
+    audit_log
+        .record(
+            &AuditEvent {
+                timestamp: now,
+                session_id: Some(session.get_session_id()),
+                outcome: AuditOutcome::Success,
+                ..failure_event
+            }
+        )
+        .await;
+    Ok(
+        LoginResponse(
+            Ok(
+                match session {
+                    Session::Api(ApiSession { refresh_token, .. })
+                    => SuccessfulLogin::Api {
+                        access_token,
+                        refresh_token,
+                    },
+
+                    Session::Web(WebSession { xsrf_token, .. })
+                    => SuccessfulLogin::Web {
+                        access_token,
+                        xsrf_token,
+                    },
+                }
             )
         )
-    }
+    )
+}
+
+// the label comes straight from the client
+fn sanitize_client_label(label: &str) -> Option<String> {
+    let label: String = label
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_CLIENT_LABEL_LENGTH)
+        .collect();
+    let label = label.trim();
+    (!label.is_empty()).then(|| label.to_owned())
 }
 
 #[derive(Debug, Error)]
@@ -1,5 +1,6 @@
 use access_token::{AccessTokenData, AccessTokenValidator};
-use data::SessionKind;
+use data::{AuditEvent, AuditEventKind, AuditOutcome, SessionKind};
+use dumbnotes::audit_log::AuditLog;
 use dumbnotes::check_access_token;
 use log::{debug, error, info, warn};
 use thiserror::Error;
@@ -10,9 +11,10 @@ use auth_ipc_data::bindings::LogoutError;
 pub async fn process_logout(
     session_storage: &impl SessionStorage,
     access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
     request: LogoutRequest,
 ) -> auth_ipc_data::bindings::response::Response {
-    process_logout_impl(session_storage, access_token_validator, request)
+    process_logout_impl(session_storage, access_token_validator, audit_log, request)
         .await
         .unwrap_or_else(|e| {
             error!("error processing logout request: {}", e);
@@ -24,11 +26,12 @@ pub async fn process_logout(
 async fn process_logout_impl(
     session_storage: &impl SessionStorage,
     access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
     request: LogoutRequest,
 ) -> Result<LogoutResponse, LogoutProcessorError> {
     let LogoutRequest { access_token, xsrf_token } = request;
 
-    let AccessTokenData { session_id, .. } = check_access_token!(
+    let AccessTokenData { session_id, username, .. } = check_access_token!(
         "logout",
         access_token_validator,
         access_token,
@@ -45,6 +48,14 @@ async fn process_logout_impl(
         .await?;
     if did_exist {
         info!("session {session_id} deleted");
+        audit_log
+            .record(
+                &AuditEvent {
+                    session_id: Some(session_id),
+                    ..AuditEvent::now(username, AuditEventKind::Logout, AuditOutcome::Success)
+                }
+            )
+            .await;
     } else {
         warn!("attempting to delete nonexistent session {session_id}");
     }
@@ -1,22 +1,28 @@
 use access_token::{AccessTokenGenerator, AccessTokenGeneratorError};
-use data::SessionKind;
+use data::{AuditEvent, AuditEventKind, AuditOutcome, SessionKind};
+use dumbnotes::audit_log::AuditLog;
 use log::{debug, error, info, warn};
 use thiserror::Error;
 use time::OffsetDateTime;
 use crate::app_constants::API_ACCESS_TOKEN_VALIDITY_TIME;
 use crate::session_storage::{SessionStorage, SessionStorageError};
+use crate::user_db::{UserDb, UserDbError};
 use auth_ipc_data::model::refresh_token::{RefreshTokenRequest, RefreshTokenResponse};
 use auth_ipc_data::model::successful_login::SuccessfulLogin;
 use auth_ipc_data::bindings::LoginError;
 
 pub async fn process_refresh_token(
+    user_db: &impl UserDb,
     session_storage: &impl SessionStorage,
     token_generator: &AccessTokenGenerator,
+    audit_log: &AuditLog,
     request: RefreshTokenRequest,
 ) -> auth_ipc_data::bindings::response::Response {
     process_refresh_token_impl(
+        user_db,
         session_storage,
         token_generator,
+        audit_log,
         request,
     ).await
         .unwrap_or_else(|e| {
@@ -27,8 +33,10 @@ pub async fn process_refresh_token(
 }
 
 async fn process_refresh_token_impl(
+    user_db: &impl UserDb,
     session_storage: &impl SessionStorage,
     token_generator: &AccessTokenGenerator,
+    audit_log: &AuditLog,
     request: RefreshTokenRequest,
 ) -> Result<RefreshTokenResponse, RefreshTokenProcessorError> {
     let RefreshTokenRequest { username, refresh_token } = request;
@@ -36,19 +44,54 @@ async fn process_refresh_token_impl(
     let session = session_storage
         .get_api_session_by_token(&refresh_token)
         .await?;
-    if let Some(session) = session
+    let failure_event = AuditEvent {
+        session_id: session.as_ref().map(|session| session.session_id),
+        ..AuditEvent::now(username.clone(), AuditEventKind::TokenRefresh, AuditOutcome::Failure)
+    };
+    if let Some(session) = &session
         && session.username.as_username_str() != username.as_username_str()
     {
         warn!(
             "attempt to refresh access token for nonexisting \
                 or mismatched user \"{username}\""
         );
+        audit_log.record(&failure_event).await;
+        return Ok(
+            RefreshTokenResponse(
+                Err(LoginError::LoginInvalidCredentials)
+            )
+        )
+    }
+    if user_db.is_user_locked(&username).await? {
+        warn!("attempt to refresh access token for locked user \"{username}\"");
+        audit_log.record(&failure_event).await;
         return Ok(
             RefreshTokenResponse(
                 Err(LoginError::LoginInvalidCredentials)
             )
         )
     }
+    let app_password = session
+        .as_ref()
+        .and_then(|session| session.app_password.as_deref());
+    let scopes = match app_password {
+        Some(name) => match user_db.get_app_password_scopes(&username, name).await? {
+            Some(scopes) => Some(scopes),
+            None => {
+                warn!(
+                    "not refreshing session of user \"{username}\" \
+                        with removed application password \"{name}\"",
+                );
+                audit_log.record(&failure_event).await;
+                return Ok(
+                    RefreshTokenResponse(
+                        Err(LoginError::LoginInvalidCredentials)
+                    )
+                )
+            },
+        },
+        None => None,
+    };
     let now = OffsetDateTime::now_utc();
     let session = session_storage
         .refresh_session(
@@ -58,17 +101,30 @@ async fn process_refresh_token_impl(
         .await;
     let session = match session {
         Ok(session) => session,
-        Err(SessionStorageError::SessionNotFound) => return Ok(
-            RefreshTokenResponse(
-                Err(LoginError::LoginInvalidCredentials)
+        Err(SessionStorageError::SessionNotFound) => {
+            audit_log.record(&failure_event).await;
+            return Ok(
+                RefreshTokenResponse(
+                    Err(LoginError::LoginInvalidCredentials)
+                )
             )
-        ),
+        },
         Err(e) => return Err(e.into()),
     };
     info!(
         "refreshed session {} for user \"{username}\"",
         session.session_id,
     );
+    audit_log
+        .record(
+            &AuditEvent {
+                timestamp: now,
+                session_id: Some(session.session_id),
+                outcome: AuditOutcome::Success,
+                ..failure_event
+            }
+        )
+        .await;
     let access_token = token_generator
         .generate_token(
             session.session_id,
@@ -76,6 +132,7 @@ async fn process_refresh_token_impl(
             &now.into(),
             &session.expires_at.into(),
             SessionKind::Api,
+            scopes.as_ref(),
         )?;
     Ok(
         RefreshTokenResponse(
@@ -91,6 +148,9 @@ async fn process_refresh_token_impl(
 
 #[derive(Debug, Error)]
 enum RefreshTokenProcessorError {
+    #[error("user database error: {0}")]
+    UserDb(#[from] UserDbError),
+
     #[error("session storage error: {0}")]
     SessionStorage(#[from] SessionStorageError),
 
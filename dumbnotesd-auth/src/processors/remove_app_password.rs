@@ -0,0 +1,104 @@
+use access_token::AccessTokenValidator;
+use data::{AuditEvent, AuditEventKind, AuditOutcome, Session, SessionKind};
+use dumbnotes::audit_log::AuditLog;
+use dumbnotes::check_access_token;
+use log::{error, info, warn};
+use thiserror::Error;
+use crate::processors::is_session_alive;
+use crate::session_storage::{SessionStorage, SessionStorageError};
+use crate::user_db::{UserDb, UserDbError};
+use auth_ipc_data::model::remove_app_password::{RemoveAppPasswordRequest, RemoveAppPasswordResponse};
+use auth_ipc_data::bindings::AppPasswordsError;
+
+pub async fn process_remove_app_password(
+    user_db: &impl UserDb,
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: RemoveAppPasswordRequest,
+) -> auth_ipc_data::bindings::response::Response {
+    process_remove_app_password_impl(
+        user_db,
+        session_storage,
+        access_token_validator,
+        audit_log,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing remove app password request: {e}");
+            RemoveAppPasswordResponse(Err(AppPasswordsError::AppPasswordsInternalError))
+        })
+        .into()
+}
+
+async fn process_remove_app_password_impl(
+    user_db: &impl UserDb,
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: RemoveAppPasswordRequest,
+) -> Result<RemoveAppPasswordResponse, RemoveAppPasswordProcessorError> {
+    let RemoveAppPasswordRequest { access_token, name } = request;
+
+    let token_data = check_access_token!(
+        "remove app password",
+        access_token_validator,
+        access_token,
+        SessionKind::Api,
+        RemoveAppPasswordResponse(Err(AppPasswordsError::AppPasswordsInvalidCredentials)),
+    );
+    if !is_session_alive(session_storage, &token_data).await? {
+        return Ok(
+            RemoveAppPasswordResponse(Err(AppPasswordsError::AppPasswordsInvalidCredentials))
+        )
+    }
+    let username = token_data.username;
+
+    if !user_db.remove_app_password(&username, &name).await? {
+        warn!("user \"{username}\" attempted to remove unknown application password \"{name}\"");
+        return Ok(RemoveAppPasswordResponse(Err(AppPasswordsError::AppPasswordsNotFound)))
+    }
+    // their refreshes would fail anyway, but the access tokens would
+    // stay usable until they expire
+    let sessions = session_storage.get_user_sessions(&username).await?;
+    let session_ids = sessions
+        .iter()
+        .filter_map(|session| match **session {
+            Session::Api(ref session) if session.app_password.as_ref() == Some(&name) =>
+                Some(session.session_id),
+            _ => None,
+        });
+    let mut revoked_session_ids = Vec::new();
+    for session_id in session_ids {
+        if !session_storage.delete_user_session(&username, session_id).await? {
+            continue
+        }
+        revoked_session_ids.push(session_id);
+        info!(
+            "revoked session {session_id} of user \"{username}\" \
+                with removed application password \"{name}\""
+        );
+        audit_log
+            .record(
+                &AuditEvent {
+                    session_id: Some(session_id),
+                    ..AuditEvent::now(
+                        username.clone(),
+                        AuditEventKind::SessionRevocation,
+                        AuditOutcome::Success,
+                    )
+                }
+            )
+            .await;
+    }
+    Ok(RemoveAppPasswordResponse(Ok(revoked_session_ids)))
+}
+
+#[derive(Debug, Error)]
+enum RemoveAppPasswordProcessorError {
+    #[error("user database error: {0}")]
+    UserDb(#[from] UserDbError),
+
+    #[error("session storage error: {0}")]
+    SessionStorage(#[from] SessionStorageError),
+}
@@ -0,0 +1,74 @@
+use access_token::AccessTokenValidator;
+use data::{AuditEvent, AuditEventKind, AuditOutcome, SessionKind};
+use dumbnotes::audit_log::AuditLog;
+use dumbnotes::check_access_token;
+use log::{error, info};
+use thiserror::Error;
+use crate::processors::is_session_alive;
+use crate::session_storage::{SessionStorage, SessionStorageError};
+use auth_ipc_data::model::revoke_all_sessions::{RevokeAllSessionsRequest, RevokeAllSessionsResponse};
+use auth_ipc_data::bindings::SessionsError;
+
+pub async fn process_revoke_all_sessions(
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: RevokeAllSessionsRequest,
+) -> auth_ipc_data::bindings::response::Response {
+    process_revoke_all_sessions_impl(session_storage, access_token_validator, audit_log, request)
+        .await
+        .unwrap_or_else(|e| {
+            error!("error processing revoke all sessions request: {e}");
+            RevokeAllSessionsResponse(Err(SessionsError::SessionsInternalError))
+        })
+        .into()
+}
+
+async fn process_revoke_all_sessions_impl(
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: RevokeAllSessionsRequest,
+) -> Result<RevokeAllSessionsResponse, RevokeAllSessionsProcessorError> {
+    let RevokeAllSessionsRequest { access_token } = request;
+
+    let token_data = check_access_token!(
+        "revoke all sessions",
+        access_token_validator,
+        access_token,
+        SessionKind::Api,
+        RevokeAllSessionsResponse(Err(SessionsError::SessionsInvalidCredentials)),
+    );
+    if !is_session_alive(session_storage, &token_data).await? {
+        return Ok(
+            RevokeAllSessionsResponse(Err(SessionsError::SessionsInvalidCredentials))
+        )
+    }
+
+    let username = token_data.username;
+    let revoked = session_storage
+        .delete_user_sessions(&username, None)
+        .await?;
+    info!("user \"{username}\" revoked all their {} sessions", revoked.len());
+    for session_id in &revoked {
+        audit_log
+            .record(
+                &AuditEvent {
+                    session_id: Some(*session_id),
+                    ..AuditEvent::now(
+                        username.clone(),
+                        AuditEventKind::SessionRevocation,
+                        AuditOutcome::Success,
+                    )
+                }
+            )
+            .await;
+    }
+    Ok(RevokeAllSessionsResponse(Ok(revoked)))
+}
+
+#[derive(Debug, Error)]
+enum RevokeAllSessionsProcessorError {
+    #[error("session storage error: {0}")]
+    SessionStorage(#[from] SessionStorageError),
+}
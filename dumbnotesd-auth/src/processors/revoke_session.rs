@@ -0,0 +1,79 @@
+use access_token::AccessTokenValidator;
+use data::{AuditEvent, AuditEventKind, AuditOutcome, SessionKind};
+use dumbnotes::audit_log::AuditLog;
+use dumbnotes::check_access_token;
+use log::{error, info, warn};
+use thiserror::Error;
+use crate::processors::is_session_alive;
+use crate::session_storage::{SessionStorage, SessionStorageError};
+use auth_ipc_data::model::revoke_session::{RevokeSessionRequest, RevokeSessionResponse};
+use auth_ipc_data::bindings::SessionsError;
+
+pub async fn process_revoke_session(
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: RevokeSessionRequest,
+) -> auth_ipc_data::bindings::response::Response {
+    process_revoke_session_impl(session_storage, access_token_validator, audit_log, request)
+        .await
+        .unwrap_or_else(|e| {
+            error!("error processing revoke session request: {e}");
+            RevokeSessionResponse(Some(SessionsError::SessionsInternalError))
+        })
+        .into()
+}
+
+async fn process_revoke_session_impl(
+    session_storage: &impl SessionStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: RevokeSessionRequest,
+) -> Result<RevokeSessionResponse, RevokeSessionProcessorError> {
+    let RevokeSessionRequest { access_token, session_id } = request;
+
+    let token_data = check_access_token!(
+        "revoke session",
+        access_token_validator,
+        access_token,
+        SessionKind::Api,
+        RevokeSessionResponse(Some(SessionsError::SessionsInvalidCredentials)),
+    );
+    if !is_session_alive(session_storage, &token_data).await? {
+        return Ok(
+            RevokeSessionResponse(Some(SessionsError::SessionsInvalidCredentials))
+        )
+    }
+
+    let username = token_data.username;
+    let is_revoked = session_storage.delete_user_session(&username, session_id).await?;
+    if is_revoked {
+        info!("user \"{username}\" revoked session {session_id}");
+    } else {
+        warn!("user \"{username}\" attempted to revoke unknown session {session_id}");
+    }
+    // the revoked session, rather than the revoking one
+    audit_log
+        .record(
+            &AuditEvent {
+                session_id: Some(session_id),
+                ..AuditEvent::now(
+                    username,
+                    AuditEventKind::SessionRevocation,
+                    if is_revoked { AuditOutcome::Success } else { AuditOutcome::Failure },
+                )
+            }
+        )
+        .await;
+    if is_revoked {
+        Ok(RevokeSessionResponse(None))
+    } else {
+        Ok(RevokeSessionResponse(Some(SessionsError::SessionsNotFound)))
+    }
+}
+
+#[derive(Debug, Error)]
+enum RevokeSessionProcessorError {
+    #[error("session storage error: {0}")]
+    SessionStorage(#[from] SessionStorageError),
+}
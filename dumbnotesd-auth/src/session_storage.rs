@@ -19,6 +19,9 @@ pub trait SessionStorage: Send + Sync {
         created_at: OffsetDateTime,
         expires_at: OffsetDateTime,
         session_kind: SessionKind,
+        client_label: Option<String>,
+        // only kept for the API sessions
+        app_password: Option<String>,
     ) -> Result<Session, SessionStorageError>;
 
     async fn refresh_session(
@@ -33,11 +36,32 @@ pub trait SessionStorage: Send + Sync {
         xsrf_token: Option<Vec<u8>>,
     ) -> Result<bool, SessionStorageError>;
 
+    /// Unlike `delete_session`, deletes sessions of any kind, as long as
+    /// they belong to the user
+    async fn delete_user_session(
+        &self,
+        username: &UsernameStr,
+        session_id: Uuid,
+    ) -> Result<bool, SessionStorageError>;
+
+    /// Returns the ids of the deleted sessions
+    async fn delete_user_sessions(
+        &self,
+        username: &UsernameStr,
+        except_session_id: Option<Uuid>,
+    ) -> Result<Vec<Uuid>, SessionStorageError>;
+
     async fn get_session_by_id(
         &self,
         session_id: Uuid,
     ) -> Result<Option<Arc<Session>>, SessionStorageError>;
 
+    /// Includes the expired sessions not cleaned up yet
+    async fn get_user_sessions(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Vec<Arc<Session>>, SessionStorageError>;
+
     async fn get_api_session_by_token(
         &self,
         refresh_token: &[u8],
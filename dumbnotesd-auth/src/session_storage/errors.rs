@@ -2,7 +2,7 @@ use std::io::Error as IoError;
 use data::SessionKind;
 use thiserror::Error;
 use unix::errors::CheckAccessError;
-use crate::file_watcher::FileWatcherError;
+use dumbnotes::file_watcher::FileWatcherError;
 
 #[derive(Debug, Error)]
 pub enum SessionStorageError {
@@ -14,9 +14,9 @@ use tokio::spawn;
 use tokio::sync::{oneshot, RwLock, RwLockWriteGuard};
 use uuid::Uuid;
 use unix::check_secret_file_rw_access;
-use crate::file_watcher::{FileWatchGuard, FileWatcher, ProductionFileWatcher};
-use crate::file_watcher::Event;
-use crate::file_watcher::FileWatcherError;
+use dumbnotes::file_watcher::{FileWatchGuard, FileWatcher, ProductionFileWatcher};
+use dumbnotes::file_watcher::Event;
+use dumbnotes::file_watcher::FileWatcherError;
 use crate::app_constants::{REFRESH_TOKEN_VALIDITY_TIME, SESSION_STORAGE_PATH};
 use crate::session_storage::internal::io_trait::{ProductionSessionStorageIo, SessionStorageIo};
 use crate::session_storage::{Session, SessionStorage, SessionStorageError};
@@ -68,12 +68,16 @@ impl From<SessionsData> for State {
                                     refresh_token,
                                     created_at,
                                     expires_at,
+                                    client_label,
+                                    app_password,
                                 } => Session::Api(ApiSession {
                                     session_id,
                                     username: user_data.username.clone(),
                                     refresh_token,
                                     created_at,
                                     expires_at,
+                                    client_label,
+                                    app_password,
                                 }),
 
                                 UserSessionData::Web {
@@ -81,12 +85,14 @@ impl From<SessionsData> for State {
                                     xsrf_token,
                                     created_at,
                                     expires_at,
+                                    client_label,
                                 } => Session::Web(WebSession {
                                     session_id,
                                     username: user_data.username.clone(),
                                     xsrf_token,
                                     created_at,
                                     expires_at,
+                                    client_label,
                                 }),
                             }
                         })
@@ -259,11 +265,15 @@ impl<Io: SessionStorageIo, W: FileWatcher> SessionStorageImpl<Io, W> {
                 refresh_token,
                 created_at,
                 expires_at,
+                client_label,
+                app_password,
             }) => UserSessionData::Api {
                 session_id: *session_id,
                 refresh_token: refresh_token.clone(),
                 created_at: *created_at,
                 expires_at: *expires_at,
+                client_label: client_label.clone(),
+                app_password: app_password.clone(),
             },
             Session::Web(WebSession {
                 session_id,
@@ -271,11 +281,13 @@ impl<Io: SessionStorageIo, W: FileWatcher> SessionStorageImpl<Io, W> {
                 xsrf_token,
                 created_at,
                 expires_at,
+                client_label,
             }) => UserSessionData::Web {
                 session_id: *session_id,
                 xsrf_token: xsrf_token.clone(),
                 created_at: *created_at,
                 expires_at: *expires_at,
+                client_label: client_label.clone(),
             },
         }
     }
@@ -289,6 +301,8 @@ impl<Io: SessionStorageIo, W: FileWatcher> SessionStorage for SessionStorageImpl
         created_at: OffsetDateTime,
         expires_at: OffsetDateTime,
         session_kind: SessionKind,
+        client_label: Option<String>,
+        app_password: Option<String>,
     ) -> Result<Session, SessionStorageError> {
         let session_id = self.io.generate_uuid();
         info!(
@@ -303,6 +317,8 @@ impl<Io: SessionStorageIo, W: FileWatcher> SessionStorage for SessionStorageImpl
                     refresh_token: self.io.gen_refresh_token(),
                     created_at,
                     expires_at,
+                    client_label,
+                    app_password,
                 })
             },
             SessionKind::Web => {
@@ -312,6 +328,7 @@ impl<Io: SessionStorageIo, W: FileWatcher> SessionStorage for SessionStorageImpl
                     xsrf_token: self.io.gen_xsrf_token(),
                     created_at,
                     expires_at,
+                    client_label,
                 })
             }
         };
@@ -355,6 +372,8 @@ impl<Io: SessionStorageIo, W: FileWatcher> SessionStorage for SessionStorageImpl
             refresh_token: new_refresh_token.clone(),
             created_at: session.created_at,
             expires_at,
+            client_label: session.client_label.clone(),
+            app_password: session.app_password.clone(),
         };
         let new_session_arc = Arc::new(Session::Api(new_session.clone()));
         let name_to_sessions = state.name_to_sessions
@@ -432,6 +451,57 @@ impl<Io: SessionStorageIo, W: FileWatcher> SessionStorage for SessionStorageImpl
         }
     }
 
+    async fn delete_user_session(
+        &self,
+        username: &UsernameStr,
+        session_id: Uuid,
+    ) -> Result<bool, SessionStorageError> {
+        let mut state = self.state.write().await;
+        let Some(users_sessions) = state.name_to_sessions.get_mut(username) else {
+            return Ok(false)
+        };
+        let Some(index) = users_sessions
+            .iter()
+            .position(|s| s.get_session_id() == session_id)
+        else {
+            return Ok(false)
+        };
+        info!("terminating session {session_id} for user \"{username}\"");
+        users_sessions.remove(index);
+        self.write_state(state).await?;
+        Ok(true)
+    }
+
+    async fn delete_user_sessions(
+        &self,
+        username: &UsernameStr,
+        except_session_id: Option<Uuid>,
+    ) -> Result<Vec<Uuid>, SessionStorageError> {
+        let mut state = self.state.write().await;
+        let Some(users_sessions) = state.name_to_sessions.get_mut(username) else {
+            return Ok(Vec::new())
+        };
+        let mut deleted = Vec::new();
+        users_sessions.retain(|session| {
+            let session_id = session.get_session_id();
+            if Some(session_id) == except_session_id {
+                true
+            } else {
+                deleted.push(session_id);
+                false
+            }
+        });
+        if deleted.is_empty() {
+            return Ok(deleted)
+        }
+        info!(
+            "terminating {} sessions for user \"{username}\"",
+            deleted.len(),
+        );
+        self.write_state(state).await?;
+        Ok(deleted)
+    }
+
     async fn get_session_by_id(
         &self,
         session_id: Uuid,
@@ -446,6 +516,21 @@ impl<Io: SessionStorageIo, W: FileWatcher> SessionStorage for SessionStorageImpl
         )
     }
 
+    async fn get_user_sessions(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Vec<Arc<Session>>, SessionStorageError> {
+        Ok(
+            self.state
+                .read()
+                .await
+                .name_to_sessions
+                .get(username)
+                .cloned()
+                .unwrap_or_default(),
+        )
+    }
+
     async fn get_api_session_by_token(
         &self,
         refresh_token: &[u8],
@@ -1,8 +1,9 @@
 use std::io::Error as IoError;
 use thiserror::Error;
 use dumbnotes::hasher::HasherError;
+use dumbnotes::totp::TotpError;
 use unix::errors::CheckAccessError;
-use crate::file_watcher::FileWatcherError;
+use dumbnotes::file_watcher::FileWatcherError;
 
 #[derive(Debug, Error)]
 pub enum UserDbError {
@@ -11,6 +12,9 @@ pub enum UserDbError {
 
     #[error("invalid user db file contents: {0}")]
     Parsing(#[from] toml::de::Error),
+
+    #[error("failed to serialize the user db: {0}")]
+    Serializing(#[from] toml::ser::Error),
     
     #[error("failed to watch the db file: {0}")]
     Watch(#[from] FileWatcherError),
@@ -20,4 +24,7 @@ pub enum UserDbError {
     
     #[error("hashing failed: {0}")]
     Hashing(#[from] HasherError),
+
+    #[error(transparent)]
+    Totp(#[from] TotpError),
 }
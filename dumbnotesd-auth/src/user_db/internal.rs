@@ -1,14 +1,23 @@
-use std::path::Path;
-use std::sync::Arc;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use argon2::PasswordHash;
 use async_trait::async_trait;
-use log::trace;
+use log::{info, trace, warn};
 use tokio::task::spawn_blocking;
-use dumbnotes::hasher::{Hasher, ProductionHasher};
-use ::data::UsernameStr;
-use unix::check_secret_file_ro_access;
-use crate::file_watcher::ProductionFileWatcher;
+use dumbnotes::hasher::{Hasher, HasherError, ProductionHasher};
+use dumbnotes::app_password::hash_app_password;
+use dumbnotes::totp;
+use time::OffsetDateTime;
+use ::data::{AppPassword, AppPasswordInfo, Scope, UserQuota, UsernameStr};
+use dumbnotesd_auth_data::user_db::AppPasswordData;
+use unix::{check_secret_file_ro_access, check_secret_file_rw_access};
+use unix::errors::CheckAccessError;
+use dumbnotes::file_watcher::ProductionFileWatcher;
 use crate::user_db::internal::io_trait::{ProductionUserDbIo, UserDbIo};
 use crate::user_db::UserDbError;
+use crate::app_constants::USER_DB_FALLBACK_TMP_DIR;
 
 mod io_trait;
 #[cfg(test)] mod tests;
@@ -20,12 +29,86 @@ pub trait UserDb: Send + Sync {
         username: &UsernameStr,
         password: &str,
     ) -> Result<bool, UserDbError>;
+
+    /// The default quota for unknown users
+    async fn get_user_quota(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<UserQuota, UserDbError>;
+
+    /// Unknown users aren't locked
+    async fn is_user_locked(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<bool, UserDbError>;
+
+    /// Returns false for unknown users
+    async fn set_user_password(
+        &self,
+        username: &UsernameStr,
+        password: &str,
+    ) -> Result<bool, UserDbError>;
+
+    /// Whether the login needs a second factor after the password
+    async fn is_second_factor_enabled(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<bool, UserDbError>;
+
+    /// Checks a TOTP code, accepting each one once, or consumes
+    /// a recovery code
+    async fn check_second_factor(
+        &self,
+        username: &UsernameStr,
+        code: &str,
+    ) -> Result<bool, UserDbError>;
+
+    /// The application password matching `password`, unless the user
+    /// is locked
+    async fn check_app_password(
+        &self,
+        username: &UsernameStr,
+        password: &str,
+    ) -> Result<Option<AppPassword>, UserDbError>;
+
+    /// `None` once the application password is removed
+    async fn get_app_password_scopes(
+        &self,
+        username: &UsernameStr,
+        name: &str,
+    ) -> Result<Option<BTreeSet<Scope>>, UserDbError>;
+
+    /// Returns false for unknown users and when the user has
+    /// an application password with the name already
+    async fn add_app_password(
+        &self,
+        username: &UsernameStr,
+        name: &str,
+        password: &str,
+        scopes: BTreeSet<Scope>,
+    ) -> Result<bool, UserDbError>;
+
+    async fn list_app_passwords(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Vec<AppPasswordInfo>, UserDbError>;
+
+    /// Returns false when the user has no application password
+    /// with the name
+    async fn remove_app_password(
+        &self,
+        username: &UsernameStr,
+        name: &str,
+    ) -> Result<bool, UserDbError>;
 }
 
 #[allow(private_bounds)]
 pub struct UserDbImpl<H: Hasher + 'static, Io: UserDbIo> {
     hasher: Arc<H>,
     io: Io,
+    // the last time step a code was accepted for, against replays; kept
+    // in memory only, as restarts take longer than a code stays valid
+    last_totp_steps: Mutex<HashMap<String, u64>>,
 }
 
 #[async_trait]
@@ -44,6 +127,10 @@ impl<H: Hasher, Io: UserDbIo> UserDb for UserDbImpl<H, Io> {
                 trace!("user \"{username}\" not authenticated");
                 Ok(false)
             },
+            Some(user) if user.locked => {
+                info!("rejecting login of locked user \"{username}\"");
+                Ok(false)
+            },
             Some(user) => {
                 trace!("user \"{username}\" correctly authenticated");
                 let hasher = self.hasher.clone();
@@ -60,6 +147,213 @@ impl<H: Hasher, Io: UserDbIo> UserDb for UserDbImpl<H, Io> {
             }
         }
     }
+
+    async fn get_user_quota(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<UserQuota, UserDbError> {
+        Ok(
+            self.io
+                .get_user(username)
+                .await?
+                .map(|user| user.quota)
+                .unwrap_or_default()
+        )
+    }
+
+    async fn is_user_locked(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<bool, UserDbError> {
+        Ok(
+            self.io
+                .get_user(username)
+                .await?
+                .is_some_and(|user| user.locked)
+        )
+    }
+
+    async fn set_user_password(
+        &self,
+        username: &UsernameStr,
+        password: &str,
+    ) -> Result<bool, UserDbError> {
+        trace!("hashing new password for \"{username}\"");
+        let hasher = self.hasher.clone();
+        let password = password.to_string();
+        let hash = spawn_blocking(move || hasher.generate_hash(&password))
+            .await
+            .unwrap()?;
+        let hash = PasswordHash::from_str(&hash)
+            .map_err(|e| HasherError::Hash(Box::new(e)))?;
+        let is_found = self.io
+            .update_user(username, move |user| {
+                user.hash = hash;
+                true
+            })
+            .await?;
+        if is_found {
+            info!("changed password of user \"{username}\"");
+        }
+        Ok(is_found)
+    }
+
+    async fn is_second_factor_enabled(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<bool, UserDbError> {
+        Ok(
+            self.io
+                .get_user(username)
+                .await?
+                .is_some_and(|user| user.totp_secret.is_some())
+        )
+    }
+
+    async fn check_second_factor(
+        &self,
+        username: &UsernameStr,
+        code: &str,
+    ) -> Result<bool, UserDbError> {
+        let Some(secret) = self.io
+            .get_user(username)
+            .await?
+            .and_then(|user| user.totp_secret)
+        else {
+            return Ok(false)
+        };
+
+        if totp::is_totp_code(code) {
+            let now = OffsetDateTime::now_utc().unix_timestamp() as u64;
+            let Some(step) = totp::check_code(&secret, code, now)? else {
+                trace!("invalid TOTP code for \"{username}\"");
+                return Ok(false)
+            };
+            let mut last_totp_steps = self.last_totp_steps.lock().unwrap();
+            if last_totp_steps.get::<str>(username).is_some_and(|last| *last >= step) {
+                warn!("rejecting a reused TOTP code of user \"{username}\"");
+                return Ok(false)
+            }
+            last_totp_steps.insert(username.to_string(), step);
+            Ok(true)
+        } else {
+            let hash = totp::hash_recovery_code(code);
+            let is_found = self.io
+                .update_user(username, move |user| {
+                    let count = user.recovery_codes.len();
+                    user.recovery_codes.retain(|code| *code != hash);
+                    user.recovery_codes.len() != count
+                })
+                .await?;
+            if is_found {
+                info!("user \"{username}\" used a recovery code");
+            }
+            Ok(is_found)
+        }
+    }
+
+    async fn check_app_password(
+        &self,
+        username: &UsernameStr,
+        password: &str,
+    ) -> Result<Option<AppPassword>, UserDbError> {
+        let Some(user) = self.io
+            .get_user(username)
+            .await?
+            .filter(|user| !user.locked)
+        else {
+            return Ok(None)
+        };
+        let hash = hash_app_password(password);
+        Ok(
+            user.app_passwords
+                .into_iter()
+                .find(|app_password| app_password.hash == hash)
+        )
+    }
+
+    async fn get_app_password_scopes(
+        &self,
+        username: &UsernameStr,
+        name: &str,
+    ) -> Result<Option<BTreeSet<Scope>>, UserDbError> {
+        Ok(
+            self.io
+                .get_user(username)
+                .await?
+                .and_then(|user| user.app_passwords
+                    .into_iter()
+                    .find(|app_password| app_password.name == name)
+                )
+                .map(|app_password| app_password.scopes)
+        )
+    }
+
+    async fn add_app_password(
+        &self,
+        username: &UsernameStr,
+        name: &str,
+        password: &str,
+        scopes: BTreeSet<Scope>,
+    ) -> Result<bool, UserDbError> {
+        let app_password = AppPasswordData {
+            name: name.to_owned(),
+            hash: hash_app_password(password),
+            scopes,
+        };
+        let is_added = self.io
+            .update_user(username, move |user| {
+                if user.app_passwords.iter().any(|existing| existing.name == app_password.name) {
+                    return false
+                }
+                user.app_passwords.push(app_password);
+                true
+            })
+            .await?;
+        if is_added {
+            info!("added application password \"{name}\" of user \"{username}\"");
+        }
+        Ok(is_added)
+    }
+
+    async fn list_app_passwords(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Vec<AppPasswordInfo>, UserDbError> {
+        Ok(
+            self.io
+                .get_user(username)
+                .await?
+                .map(|user| user.app_passwords
+                    .into_iter()
+                    .map(|app_password| AppPasswordInfo {
+                        name: app_password.name,
+                        scopes: app_password.scopes,
+                    })
+                    .collect()
+                )
+                .unwrap_or_default()
+        )
+    }
+
+    async fn remove_app_password(
+        &self,
+        username: &UsernameStr,
+        name: &str,
+    ) -> Result<bool, UserDbError> {
+        let name_owned = name.to_owned();
+        let is_removed = self.io
+            .update_user(username, move |user| {
+                let count = user.app_passwords.len();
+                user.app_passwords.retain(|app_password| app_password.name != name_owned);
+                user.app_passwords.len() != count
+            })
+            .await?;
+        if is_removed {
+            info!("removed application password \"{name}\" of user \"{username}\"");
+        }
+        Ok(is_removed)
+    }
 }
 
 pub type ProductionUserDb = UserDbImpl<ProductionHasher, ProductionUserDbIo>;
@@ -67,18 +361,33 @@ pub type ProductionUserDb = UserDbImpl<ProductionHasher, ProductionUserDbIo>;
 impl ProductionUserDb {
     pub async fn new(
         user_db_path: &Path,
+        data_directory: &Path,
         hasher: ProductionHasher,
         file_watcher: ProductionFileWatcher,
     ) -> Result<ProductionUserDb, UserDbError> {
-        check_secret_file_ro_access(user_db_path)?;
+        // a writable db also allows users to change their passwords
+        check_secret_file_ro_access(user_db_path)
+            .or_else(|e| match e {
+                CheckAccessError::FileTooPermissive =>
+                    check_secret_file_rw_access(user_db_path),
+                e => Err(e),
+            })?;
         Ok(
             UserDbImpl {
                 hasher: Arc::new(hasher),
                 io: ProductionUserDbIo::new(
                     user_db_path,
+                    Self::get_fallback_tmp_dir(data_directory),
                     file_watcher,
                 ).await?,
+                last_totp_steps: Mutex::new(HashMap::new()),
             }
         )
     }
+
+    /// Where the db is written before replacing it, if its own directory
+    /// isn't writable
+    pub fn get_fallback_tmp_dir(data_directory: &Path) -> PathBuf {
+        data_directory.join(USER_DB_FALLBACK_TMP_DIR)
+    }
 }
@@ -1,29 +1,72 @@
-use crate::file_watcher::{Event, FileWatchGuard, FileWatcher, FileWatcherError, ProductionFileWatcher};
+use dumbnotes::file_watcher::{Event, FileWatchGuard, FileWatcher, FileWatcherError, ProductionFileWatcher};
 use async_trait::async_trait;
 use data::{User, UsernameStr};
-use dumbnotesd_auth_data::user_db::UsersData;
+use dumbnotesd_auth_data::user_db::{UserData, UsersData};
 use futures::StreamExt;
 use log::{debug, error, info, trace};
 use std::collections::HashMap;
+use std::fs::Permissions;
+use std::io::{self, ErrorKind};
 use std::mem::ManuallyDrop;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
 use std::path::{Path, PathBuf};
 use std::pin::pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 use tokio::sync::{oneshot, RwLock, RwLockWriteGuard};
 use crate::user_db::UserDbError;
 
+const USER_DB_TMP_SUFFIX: &str = ".tmp";
+
 #[async_trait]
 pub trait UserDbIo: Send + Sync {
     async fn get_user(
         &self,
         username: &UsernameStr,
     ) -> Result<Option<User>, UserDbError>;
+
+    /// Writes the db if `update` returns true, returns false for unknown
+    /// users or when nothing changed
+    async fn update_user<F>(
+        &self,
+        username: &UsernameStr,
+        update: F,
+    ) -> Result<bool, UserDbError>
+    where
+        F: FnOnce(&mut UserData) -> bool + Send + 'static;
 }
 
 pub struct ProductionUserDbIo {
     die_notice: ManuallyDrop<oneshot::Sender<()>>,
     users: Arc<RwLock<HashMap<String, User>>>,
+    user_db_path: PathBuf,
+    // the new db is made here when its own directory isn't writable
+    fallback_tmp_dir: PathBuf,
+    // the file last written by the daemon, which the watcher doesn't reload
+    own_write: Arc<Mutex<Option<FileIdentity>>>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct FileIdentity {
+    ino: u64,
+    size: u64,
+    mtime: i64,
+    mtime_nsec: i64,
+}
+
+impl FileIdentity {
+    async fn of(path: &Path) -> io::Result<FileIdentity> {
+        let metadata = fs::metadata(path).await?;
+        Ok(
+            FileIdentity {
+                ino: metadata.ino(),
+                size: metadata.size(),
+                mtime: metadata.mtime(),
+                mtime_nsec: metadata.mtime_nsec(),
+            }
+        )
+    }
 }
 
 impl Drop for ProductionUserDbIo {
@@ -36,6 +79,7 @@ impl Drop for ProductionUserDbIo {
 impl ProductionUserDbIo {
     pub async fn new(
         user_db_path: impl AsRef<Path> + Send,
+        fallback_tmp_dir: impl AsRef<Path> + Send,
         file_watcher: ProductionFileWatcher,
     ) -> Result<Self, UserDbError> {
         trace!("creating user storage");
@@ -45,12 +89,14 @@ impl ProductionUserDbIo {
         let data = Self::read_data(&user_db_path).await?;
 
         let users = Arc::new(RwLock::new(data));
+        let own_write = Arc::new(Mutex::new(None));
         let (die_notice_sender, die_notice_receiver) = oneshot::channel();
         let file_watch_guard = file_watcher.watch(&user_db_path)?;
         tokio::spawn(
             Self::file_updates_watcher(
-                user_db_path,
+                user_db_path.clone(),
                 users.clone(),
+                own_write.clone(),
                 die_notice_receiver,
                 file_watch_guard,
             )
@@ -60,6 +106,9 @@ impl ProductionUserDbIo {
             ProductionUserDbIo {
                 die_notice: ManuallyDrop::new(die_notice_sender),
                 users,
+                user_db_path,
+                fallback_tmp_dir: fallback_tmp_dir.as_ref().to_owned(),
+                own_write,
             }
         )
     }
@@ -67,6 +116,7 @@ impl ProductionUserDbIo {
     async fn file_updates_watcher(
         user_db_path: PathBuf,
         users: Arc<RwLock<HashMap<String, User>>>,
+        own_write: Arc<Mutex<Option<FileIdentity>>>,
         mut die_notice: oneshot::Receiver<()>,
         file_watch_guard: <ProductionFileWatcher as FileWatcher>::Guard,
     ) {
@@ -80,7 +130,8 @@ impl ProductionUserDbIo {
                         FileWatcherError::Overflow(_) => Self
                             ::read_and_replace(
                                 users.write().await,
-                                &user_db_path
+                                &own_write,
+                                &user_db_path,
                             )
                             .await,
                         _ => {
@@ -93,6 +144,7 @@ impl ProductionUserDbIo {
                     Ok(Event::Any) => Self
                         ::read_and_replace(
                             users.write().await,
+                            &own_write,
                             &user_db_path,
                         )
                         .await,
@@ -107,8 +159,19 @@ impl ProductionUserDbIo {
 
     async fn read_and_replace(
         mut users: RwLockWriteGuard<'_, HashMap<String, User>>,
+        own_write: &Mutex<Option<FileIdentity>>,
         user_db_path: &Path,
     ) {
+        // checked under the users lock, which the writes hold until they
+        // have recorded the file
+        let written = *own_write.lock().unwrap();
+        if written.is_some() && FileIdentity::of(user_db_path).await.ok() == written {
+            trace!(
+                "user db at \"{}\" is the one written by the daemon",
+                user_db_path.display(),
+            );
+            return;
+        }
         info!("reading updated user db at \"{}\"", user_db_path.display());
         let data = match Self::read_data(user_db_path).await {
             Ok(d) => d,
@@ -126,19 +189,73 @@ impl ProductionUserDbIo {
     async fn read_data(
         user_db_path: &Path,
     ) -> Result<HashMap<String, User>, UserDbError> {
+        Ok(Self::map_data(Self::read_users_data(user_db_path).await?))
+    }
+
+    async fn read_users_data(
+        user_db_path: &Path,
+    ) -> Result<UsersData, UserDbError> {
         trace!("reading user db at \"{}\"", user_db_path.display());
         let db_str = fs::read_to_string(&user_db_path).await?;
         trace!("read user db data at \"{}\": {db_str}", user_db_path.display());
         let parsed = toml::from_str::<UsersData>(&db_str)?;
         trace!("parsed user db data at \"{}\": {parsed:?}", user_db_path.display());
-        Ok(
-            HashMap::from_iter(
-                parsed.users
-                    .into_iter()
-                    .map(|u|
-                        (u.username.clone(), u.into())
-                    )
-            )
+        Ok(parsed)
+    }
+
+    // next to the db when its directory is writable, otherwise in the
+    // fallback directory, which has to be on the same file system
+    async fn write_tmp_file(
+        &self,
+        contents: &str,
+        mode: u32,
+    ) -> Result<PathBuf, UserDbError> {
+        let mut tmp_name = self.user_db_path
+            .file_name()
+            .unwrap_or_default()
+            .to_owned();
+        tmp_name.push(USER_DB_TMP_SUFFIX);
+        let tmp_path = self.user_db_path.with_file_name(&tmp_name);
+        match Self::write_file(&tmp_path, contents, mode).await {
+            Ok(()) => return Ok(tmp_path),
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => debug!(
+                "can't write \"{}\", using \"{}\"",
+                tmp_path.display(),
+                self.fallback_tmp_dir.display(),
+            ),
+            Err(e) => return Err(e.into()),
+        }
+        let tmp_path = self.fallback_tmp_dir.join(&tmp_name);
+        Self::write_file(&tmp_path, contents, mode).await?;
+        Ok(tmp_path)
+    }
+
+    async fn write_file(
+        path: &Path,
+        contents: &str,
+        mode: u32,
+    ) -> io::Result<()> {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(mode)
+            .open(path)
+            .await?;
+        // left from an interrupted write, it may have another mode
+        file.set_permissions(Permissions::from_mode(mode)).await?;
+        file.write_all(contents.as_bytes()).await?;
+        // on disk before it replaces the db
+        file.sync_all().await
+    }
+
+    fn map_data(data: UsersData) -> HashMap<String, User> {
+        HashMap::from_iter(
+            data.users
+                .into_iter()
+                .map(|u|
+                    (u.username.clone(), u.into())
+                )
         )
     }
 }
@@ -151,4 +268,40 @@ impl UserDbIo for ProductionUserDbIo {
     ) -> Result<Option<User>, UserDbError> {
         Ok(self.users.read().await.get::<str>(username).cloned())
     }
+
+    async fn update_user<F>(
+        &self,
+        username: &UsernameStr,
+        update: F,
+    ) -> Result<bool, UserDbError>
+    where
+        F: FnOnce(&mut UserData) -> bool + Send + 'static,
+    {
+        // held for the whole update, so that writes don't interleave
+        let mut users = self.users.write().await;
+        // the file, not the cache, keeps the fields the daemon doesn't use
+        let mut data = Self::read_users_data(&self.user_db_path).await?;
+        let Some(user) = data.users
+            .iter_mut()
+            .find(|user| user.username == **username)
+        else {
+            return Ok(false)
+        };
+        if !update(user) {
+            return Ok(false)
+        }
+        let serialized = toml::to_string(&data)?;
+        debug!("writing user db at \"{}\"", self.user_db_path.display());
+        // replaced as a whole, so that readers never see a partial db
+        let mode = fs::metadata(&self.user_db_path).await?.mode() & 0o777;
+        let tmp_path = self.write_tmp_file(&serialized, mode).await?;
+        if let Err(e) = fs::rename(&tmp_path, &self.user_db_path).await {
+            let _ = fs::remove_file(&tmp_path).await;
+            return Err(e.into())
+        }
+        *users = Self::map_data(data);
+        let written = FileIdentity::of(&self.user_db_path).await.ok();
+        *self.own_write.lock().unwrap() = written;
+        Ok(true)
+    }
 }
@@ -1,6 +1,20 @@
 use std::time::Duration;
 
-// relative to the data directory
-pub const NOTES_DIRECTORY_PATH: &str = "notes";
+// relative to a user's notes directory
+pub const REVISIONS_DIRECTORY_PATH: &str = ".revisions";
+pub const TRASH_DIRECTORY_PATH: &str = ".trash";
+pub const SEARCH_INDEX_PATH: &str = ".search-index";
+pub const JOURNAL_PATH: &str = ".journal";
+pub const TAGS_DIRECTORY_PATH: &str = ".tags";
+
+pub const MAX_NOTE_LIST_LIMIT: u32 = 1000;
+pub const MAX_NOTE_SEARCH_LIMIT: u32 = 100;
+
+pub const JOURNAL_TOMBSTONE_MAX_AGE: time::Duration = time::Duration::days(90);
+
+// events a subscriber may lag behind before being cut off
+pub const NOTE_EVENTS_CAPACITY: usize = 256;
+
+pub const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_millis(1200);
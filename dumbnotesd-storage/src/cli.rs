@@ -13,9 +13,13 @@ pub struct CliConfig {
     #[arg(long, short = 'D', default_value_t = false)]
     pub daemonize: bool,
 
-    #[arg(long, required = true, value_delimiter = ',')]
+    #[arg(long, required_unless_present = "encrypt_notes", value_delimiter = ',')]
     pub socket_fds: Vec<RawFd>,
 
+    /// For looking up the quotas of the users
+    #[arg(long, required_unless_present = "encrypt_notes")]
+    pub auth_socket_fd: Option<RawFd>,
+
     #[arg(long)]
     pub public_key_file: PathBuf,
 
@@ -27,6 +31,37 @@ pub struct CliConfig {
 
     #[arg(long)]
     pub max_note_name_len: u64,
+
+    #[arg(long)]
+    pub max_note_revisions: u32,
+
+    #[arg(long)]
+    pub max_note_revision_age_days: u32,
+
+    #[arg(long)]
+    pub trash_retention_days: u32,
+
+    #[arg(long)]
+    pub max_user_storage_size: u64,
+
+    #[arg(long)]
+    pub max_user_notes: u32,
+
+    #[arg(long)]
+    pub note_key_file: Option<PathBuf>,
+
+    #[arg(long)]
+    pub audit_log_directory: PathBuf,
+
+    #[arg(long)]
+    pub audit_log_max_size: u64,
+
+    #[arg(long)]
+    pub audit_log_max_files: u32,
+
+    /// Encrypt the plaintext note files in place and exit
+    #[arg(long, default_value_t = false, requires = "note_key_file")]
+    pub encrypt_notes: bool,
 }
 
 impl CliConfig {
@@ -2,17 +2,21 @@ use std::sync::Arc;
 
 use access_token::AccessTokenValidator;
 use clap::crate_name;
-use dumbnotes::{bin_constants::IPC_STORAGE_MESSAGE_MAX_SIZE, gen_proto_ipc_wrappers, ipc::data::{LoopInputMessage, LoopStreamExt}};
+use dumbnotes::audit_log::AuditLog;
+use dumbnotes::{bin_constants::IPC_STORAGE_MESSAGE_MAX_SIZE, gen_proto_ipc_wrappers, ipc::data::{LoopInputMessage, LoopStreamExt}, ipc::eventloop::Dispatched};
+use futures::StreamExt;
 use futures::stream::BoxStream;
 use protobuf_common::ProtobufRequestError;
 use storage_ipc_data::bindings;
 use tokio::net::unix::OwnedWriteHalf;
 
-use crate::{processors::{process_delete_note, process_get_note_details, process_list_notes, process_read_note, process_write_note}, storage::NoteStorage};
+use crate::{processors::{process_delete_note, process_get_note_details, process_list_note_revisions, process_list_notes, process_read_note, process_read_note_revision, process_restore_note_revision, process_write_note, process_list_trash, process_restore_trashed_note, process_purge_trashed_note, process_search_notes, process_list_note_changes, process_subscribe_note_events, process_list_tags, process_get_usage, process_list_activity, process_toggle_note_task, process_grant_note_share, process_revoke_note_share, process_list_note_shares, process_list_shared_notes}, storage::NoteStorage, user_quotas::UserQuotas};
 
 pub struct State {
-    pub note_storage: NoteStorage,
+    pub note_storage: Arc<NoteStorage>,
     pub access_token_validator: AccessTokenValidator,
+    pub audit_log: AuditLog,
+    pub user_quotas: UserQuotas,
 }
 
 pub async fn process_commands(
@@ -35,7 +39,7 @@ pub async fn process_commands(
 async fn dispatch_command(
     command: bindings::command::Command,
     state: Arc<State>,
-) -> Result<Response, ProtobufRequestError> {
+) -> Result<Dispatched<Response>, ProtobufRequestError> {
     use bindings::command::Command as CE;
     let response = match command {
         CE::ReadNote(request) => process_read_note(
@@ -46,6 +50,7 @@ async fn dispatch_command(
         CE::WriteNote(request) => process_write_note(
             &state.note_storage,
             &state.access_token_validator,
+            &state.user_quotas,
             request.try_into()?,
         ).await,
         CE::ListNotes(request) => process_list_notes(
@@ -61,10 +66,107 @@ async fn dispatch_command(
         CE::DeleteNote(request) => process_delete_note(
             &state.note_storage,
             &state.access_token_validator,
+            &state.audit_log,
             request.try_into()?,
         ).await,
+        CE::ListNoteRevisions(request) => process_list_note_revisions(
+            &state.note_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::ReadNoteRevision(request) => process_read_note_revision(
+            &state.note_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::RestoreNoteRevision(request) => process_restore_note_revision(
+            &state.note_storage,
+            &state.access_token_validator,
+            &state.user_quotas,
+            request.try_into()?,
+        ).await,
+        CE::ToggleNoteTask(request) => process_toggle_note_task(
+            &state.note_storage,
+            &state.access_token_validator,
+            &state.user_quotas,
+            request.try_into()?,
+        ).await,
+        CE::GrantNoteShare(request) => process_grant_note_share(
+            &state.note_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::RevokeNoteShare(request) => process_revoke_note_share(
+            &state.note_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::ListNoteShares(request) => process_list_note_shares(
+            &state.note_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::ListSharedNotes(request) => process_list_shared_notes(
+            &state.note_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::ListTrash(request) => process_list_trash(
+            &state.note_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::RestoreTrashedNote(request) => process_restore_trashed_note(
+            &state.note_storage,
+            &state.access_token_validator,
+            &state.user_quotas,
+            request.try_into()?,
+        ).await,
+        CE::PurgeTrashedNote(request) => process_purge_trashed_note(
+            &state.note_storage,
+            &state.access_token_validator,
+            &state.audit_log,
+            request.try_into()?,
+        ).await,
+        CE::SearchNotes(request) => process_search_notes(
+            &state.note_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::ListNoteChanges(request) => process_list_note_changes(
+            &state.note_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::ListTags(request) => process_list_tags(
+            &state.note_storage,
+            &state.access_token_validator,
+            request.try_into()?,
+        ).await,
+        CE::GetUsage(request) => process_get_usage(
+            &state.note_storage,
+            &state.access_token_validator,
+            &state.user_quotas,
+            request.try_into()?,
+        ).await,
+        CE::ListActivity(request) => process_list_activity(
+            &state.access_token_validator,
+            &state.audit_log,
+            request.try_into()?,
+        ).await,
+        CE::SubscribeNoteEvents(request) => return Ok(
+            Dispatched::Stream(
+                process_subscribe_note_events(
+                    &state.note_storage,
+                    &state.access_token_validator,
+                    request.try_into()?,
+                ).await
+                    .map(Response)
+                    .boxed()
+            )
+        ),
     };
-    Ok(Response(response))
+    Ok(Response(response).into())
 }
 
 gen_proto_ipc_wrappers!(
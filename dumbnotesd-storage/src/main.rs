@@ -3,21 +3,22 @@ mod cli;
 mod eventloop;
 mod processors;
 mod storage;
+mod user_quotas;
 mod util;
 
-use std::{error::Error, path::Path};
+use std::{error::Error, path::Path, sync::Arc};
 
 use access_token::{AccessTokenDecoder, AccessTokenValidator};
+use base64ct::{Base64, Encoding};
 use clap::{Parser, crate_name};
-use dumbnotes::{bin_constants::IPC_STORAGE_MESSAGE_MAX_SIZE, ipc::launch_event_loops::launch_event_loops, logging::init_daemon_logging};
+use dumbnotes::{audit_log::{AuditLog, AuditLogConfig}, bin_constants::{IPC_STORAGE_MESSAGE_MAX_SIZE, STORAGE_AUDIT_LOG_PATH}, file_watcher::ProductionFileWatcher, ipc::{launch_event_loops::launch_event_loops, socket::discover_socket}, jwt_keys::{read_jwt_public_keys, watch_jwt_public_keys}, logging::init_daemon_logging};
 #[cfg(target_os = "openbsd")] use dumbnotes::sandbox::pledge::{pledge_storage_init, pledge_storage_normal};
-use josekit::jwk::Jwk;
-use log::info;
-use storage::{errors::*, NoteStorage};
-use unix::set_umask;
+use log::{error, info};
+use storage::{errors::*, NoteCipher, NoteStorage, QuotaLimits, RevisionLimits};
+use unix::{check_secret_file_ro_access, set_umask};
 use ::util::error_exit;
 
-use crate::{app_constants::SHUTDOWN_TIMEOUT, cli::CliConfig};
+use crate::{app_constants::{SHUTDOWN_TIMEOUT, TRASH_PURGE_INTERVAL}, cli::CliConfig, user_quotas::UserQuotas};
 
 async fn async_main() -> i32 {
     #[cfg(target_os = "openbsd")] pledge_storage_init();
@@ -35,10 +36,17 @@ async fn async_main() -> i32 {
             &config.public_key_file,
             Permissions::R,
         );
+        if let Some(ref note_key_file) = config.note_key_file {
+            unveil(note_key_file, Permissions::R);
+        }
         unveil(
             &NoteStorage::get_notes_dir(&config.data_directory),
             Permissions::R | Permissions::W | Permissions::C,
         );
+        unveil(
+            &config.audit_log_directory,
+            Permissions::R | Permissions::W | Permissions::C,
+        );
         seal_unveil();
     }
 
@@ -46,23 +54,51 @@ async fn async_main() -> i32 {
 
     info!("{} starting up", crate_name!());
 
+    if config.encrypt_notes {
+        return encrypt_notes(&config).await
+    }
+
     launch_event_loops(
         crate_name!(),
-        config.socket_fds,
+        config.socket_fds.clone(),
         async move || {
-            eventloop::State {
-                note_storage: make_note_storage(
+            let note_storage = Arc::new(
+                make_note_storage(
                     &config.data_directory,
                     config.max_note_len,
                     config.max_note_name_len,
-                ).await,
-                access_token_validator: make_access_token_validator(
-                    &read_jwt_key(&config.public_key_file)
-                        .unwrap_or_else(|e|
-                            error_exit!(
-                                "failed to read the jwt private key: {e}",
-                            )
+                    RevisionLimits {
+                        max_count: config.max_note_revisions,
+                        max_age: time::Duration::days(
+                            config.max_note_revision_age_days.into(),
                         ),
+                    },
+                    QuotaLimits {
+                        max_storage_size: config.max_user_storage_size,
+                        max_notes: config.max_user_notes,
+                    },
+                    config.note_key_file.as_deref().map(make_note_cipher),
+                ).await
+            );
+            if config.trash_retention_days > 0 {
+                tokio::spawn(
+                    purge_trash_periodically(
+                        note_storage.clone(),
+                        time::Duration::days(config.trash_retention_days.into()),
+                    )
+                );
+            }
+            eventloop::State {
+                note_storage,
+                access_token_validator: make_access_token_validator(
+                    &config.public_key_file,
+                ),
+                audit_log: make_audit_log(&config).await,
+                user_quotas: UserQuotas::new(
+                    discover_socket(
+                        config.auth_socket_fd
+                            .unwrap_or_else(|| error_exit!("missing the auth socket")),
+                    ),
                 ).await,
             }
         },
@@ -77,12 +113,18 @@ async fn make_note_storage(
     data_directory: impl AsRef<Path>,
     max_note_len: u64,
     max_note_name_len: u64,
+    revision_limits: RevisionLimits,
+    default_quota: QuotaLimits,
+    note_cipher: Option<NoteCipher>,
 ) -> NoteStorage {
     NoteStorage
         ::new(
             &data_directory,
             max_note_len,
             max_note_name_len,
+            revision_limits,
+            default_quota,
+            note_cipher,
         )
         .await
         .unwrap_or_else(|e|
@@ -93,19 +135,92 @@ async fn make_note_storage(
         )
 }
 
-fn read_jwt_key(path: &Path) -> Result<Jwk, Box<dyn Error>> {
-    Ok(Jwk::from_bytes(std::fs::read(path)?)?)
+async fn encrypt_notes(config: &CliConfig) -> i32 {
+    let note_storage = make_note_storage(
+        &config.data_directory,
+        config.max_note_len,
+        config.max_note_name_len,
+        RevisionLimits {
+            max_count: config.max_note_revisions,
+            max_age: time::Duration::days(
+                config.max_note_revision_age_days.into(),
+            ),
+        },
+        QuotaLimits {
+            max_storage_size: config.max_user_storage_size,
+            max_notes: config.max_user_notes,
+        },
+        config.note_key_file.as_deref().map(make_note_cipher),
+    ).await;
+    match note_storage.encrypt_plaintext_notes().await {
+        Ok(count) => {
+            info!("encrypted {count} note files");
+            0
+        },
+        Err(e) => {
+            error!("failed to encrypt the notes: {e}");
+            1
+        },
+    }
 }
 
-async fn make_access_token_validator(
-    jwt_public_key: &Jwk,
+async fn purge_trash_periodically(
+    note_storage: Arc<NoteStorage>,
+    retention: time::Duration,
+) {
+    let mut interval = tokio::time::interval(TRASH_PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = note_storage.purge_expired_trash(retention).await {
+            error!("failed to purge expired trash: {e}");
+        }
+    }
+}
+
+async fn make_audit_log(config: &CliConfig) -> AuditLog {
+    AuditLog
+        ::open(
+            AuditLogConfig {
+                path: config.audit_log_directory.join(STORAGE_AUDIT_LOG_PATH),
+                max_size: config.audit_log_max_size,
+                max_files: config.audit_log_max_files,
+            }
+        )
+        .await
+        .unwrap_or_else(|e|
+            error_exit!("could not open the audit log: {e}")
+        )
+}
+
+fn make_note_cipher(path: &Path) -> NoteCipher {
+    read_note_cipher(path)
+        .unwrap_or_else(|e|
+            error_exit!("failed to read the note encryption key: {e}")
+        )
+}
+
+fn read_note_cipher(path: &Path) -> Result<NoteCipher, Box<dyn Error>> {
+    check_secret_file_ro_access(path)?;
+    let key = Base64::decode_vec(
+        std::fs::read_to_string(path)?.trim_ascii_end()
+    )?;
+    Ok(NoteCipher::new(&key)?)
+}
+
+fn make_access_token_validator(
+    jwt_public_key_path: &Path,
 ) -> AccessTokenValidator {
-    AccessTokenValidator::new(
-        AccessTokenDecoder::from_jwk(jwt_public_key)
-            .unwrap_or_else(|e|
-                error_exit!("could not initialize access token decoder: {e}")
-            )
-    )
+    let jwt_public_keys = read_jwt_public_keys(jwt_public_key_path)
+        .unwrap_or_else(|e| error_exit!("failed to read the jwt public keys: {e}"));
+    let decoder = AccessTokenDecoder::from_jwk_set(&jwt_public_keys)
+        .unwrap_or_else(|e|
+            error_exit!("could not initialize access token decoder: {e}")
+        );
+    let watcher = ProductionFileWatcher::new()
+        .unwrap_or_else(|e| error_exit!("failed to create file watcher: {e}"));
+    watch_jwt_public_keys(&watcher, jwt_public_key_path, decoder.clone())
+        .unwrap_or_else(|e| error_exit!("failed to watch the jwt public keys: {e}"));
+    AccessTokenValidator::new(decoder)
 }
 
 fn main() {
@@ -3,9 +3,43 @@ mod write_note;
 mod list_notes;
 mod get_note_details;
 mod delete_note;
+mod list_note_revisions;
+mod read_note_revision;
+mod restore_note_revision;
+mod list_trash;
+mod restore_trashed_note;
+mod purge_trashed_note;
+mod search_notes;
+mod list_note_changes;
+mod subscribe_note_events;
+mod list_tags;
+mod get_usage;
+mod list_activity;
+mod toggle_note_task;
+mod grant_note_share;
+mod revoke_note_share;
+mod list_note_shares;
+mod list_shared_notes;
 
 pub use read_note::process_read_note;
 pub use write_note::process_write_note;
 pub use list_notes::process_list_notes;
 pub use get_note_details::process_get_note_details;
 pub use delete_note::process_delete_note;
+pub use list_note_revisions::process_list_note_revisions;
+pub use read_note_revision::process_read_note_revision;
+pub use restore_note_revision::process_restore_note_revision;
+pub use list_trash::process_list_trash;
+pub use restore_trashed_note::process_restore_trashed_note;
+pub use purge_trashed_note::process_purge_trashed_note;
+pub use search_notes::process_search_notes;
+pub use list_note_changes::process_list_note_changes;
+pub use subscribe_note_events::process_subscribe_note_events;
+pub use list_tags::process_list_tags;
+pub use get_usage::process_get_usage;
+pub use list_activity::process_list_activity;
+pub use toggle_note_task::process_toggle_note_task;
+pub use grant_note_share::process_grant_note_share;
+pub use revoke_note_share::process_revoke_note_share;
+pub use list_note_shares::process_list_note_shares;
+pub use list_shared_notes::process_list_shared_notes;
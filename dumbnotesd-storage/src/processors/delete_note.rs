@@ -1,4 +1,6 @@
 use access_token::{AccessTokenData, AccessTokenValidator};
+use data::{AuditEvent, AuditEventKind, AuditOutcome, ShareAccess};
+use dumbnotes::audit_log::AuditLog;
 use dumbnotes::check_access_token;
 use log::{error, trace};
 use storage_ipc_data::model::delete_note::{DeleteNoteRequest, DeleteNoteResponse};
@@ -12,11 +14,13 @@ use crate::storage::NoteStorage;
 pub async fn process_delete_note(
     note_storage: &NoteStorage,
     access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
     request: DeleteNoteRequest,
 ) -> bindings::response::Response {
     process_delete_note_impl(
         note_storage,
         access_token_validator,
+        audit_log,
         request,
     ).await
         .unwrap_or_else(|e| {
@@ -29,11 +33,12 @@ pub async fn process_delete_note(
 async fn process_delete_note_impl(
     note_storage: &NoteStorage,
     access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
     request: DeleteNoteRequest,
 ) -> Result<DeleteNoteResponse, DeleteNoteError> {
-    let DeleteNoteRequest { access_token, note_id } = request;
+    let DeleteNoteRequest { access_token, note_id, owner } = request;
 
-    let AccessTokenData { username, .. } = check_access_token!(
+    let AccessTokenData { session_id, username, .. } = check_access_token!(
         "delete note",
         access_token_validator,
         access_token,
@@ -41,9 +46,29 @@ async fn process_delete_note_impl(
     );
 
     trace!("deleting note \"{note_id}\" for user \"{username}\"");
-    match note_storage.delete_note(&username, note_id).await {
+    let result = async {
+        let owner = note_storage
+            .resolve_note_access(&username, owner, note_id, ShareAccess::ReadWrite)
+            .await?;
+        note_storage.delete_note(&owner, &username, note_id).await
+    }.await;
+    let outcome = match result {
+        Ok(()) => AuditOutcome::Success,
+        Err(_) => AuditOutcome::Failure,
+    };
+    audit_log
+        .record(
+            &AuditEvent {
+                session_id: Some(session_id),
+                note_id: Some(note_id),
+                ..AuditEvent::now(username, AuditEventKind::NoteDeletion, outcome)
+            }
+        )
+        .await;
+    match result {
         Ok(()) => Ok(DeleteNoteResponse(None)),
         Err(SE::NoteNotFound) => Ok(DeleteNoteResponse(Some(StorageError::NotFound))),
+        Err(SE::Forbidden) => Ok(DeleteNoteResponse(Some(StorageError::Forbidden))),
         Err(e) => Err(e.into()),
     }
 }
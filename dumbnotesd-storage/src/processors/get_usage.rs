@@ -0,0 +1,59 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::get_usage::{GetUsageRequest, GetUsageResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+use crate::user_quotas::{UserQuotas, UserQuotasError};
+
+pub async fn process_get_usage(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    user_quotas: &UserQuotas,
+    request: GetUsageRequest,
+) -> bindings::response::Response {
+    process_get_usage_impl(
+        note_storage,
+        access_token_validator,
+        user_quotas,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing get usage request: {e}");
+            GetUsageResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_get_usage_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    user_quotas: &UserQuotas,
+    request: GetUsageRequest,
+) -> Result<GetUsageResponse, GetUsageError> {
+    let GetUsageRequest { access_token } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "get usage",
+        access_token_validator,
+        access_token,
+        GetUsageResponse(Err(StorageError::InvalidCredentials)),
+    );
+    let quota = user_quotas.get_user_quota(&username).await?;
+
+    trace!("measuring storage usage of user \"{username}\"");
+    Ok(GetUsageResponse(Ok(note_storage.get_usage(&username, quota).await?)))
+}
+
+#[derive(Debug, Error)]
+enum GetUsageError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+
+    #[error("user quota lookup error: {0}")]
+    UserQuotas(#[from] UserQuotasError),
+}
@@ -0,0 +1,61 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::grant_note_share::{GrantNoteShareRequest, GrantNoteShareResponse};
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+use thiserror::Error;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_grant_note_share(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: GrantNoteShareRequest,
+) -> bindings::response::Response {
+    process_grant_note_share_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing grant note share request: {e}");
+            GrantNoteShareResponse(Some(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_grant_note_share_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: GrantNoteShareRequest,
+) -> Result<GrantNoteShareResponse, GrantNoteShareError> {
+    let GrantNoteShareRequest { access_token, note_id, share } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "grant note share",
+        access_token_validator,
+        access_token,
+        GrantNoteShareResponse(Some(StorageError::InvalidCredentials)),
+    );
+
+    trace!(
+        "sharing note \"{note_id}\" of user \"{username}\" with user \"{}\"",
+        share.username,
+    );
+    match note_storage.grant_note_share(&username, note_id, share).await {
+        Ok(()) => Ok(GrantNoteShareResponse(None)),
+        Err(SE::NoteNotFound | SE::UserNotFound) =>
+            Ok(GrantNoteShareResponse(Some(StorageError::NotFound))),
+        Err(SE::Forbidden) => Ok(GrantNoteShareResponse(Some(StorageError::Forbidden))),
+        Err(SE::TooBig) => Ok(GrantNoteShareResponse(Some(StorageError::TooBig))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Error)]
+enum GrantNoteShareError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
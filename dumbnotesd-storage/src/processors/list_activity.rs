@@ -0,0 +1,53 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::audit_log::{AuditLog, AuditLogError};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::list_activity::{ListActivityRequest, ListActivityResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+pub async fn process_list_activity(
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: ListActivityRequest,
+) -> bindings::response::Response {
+    process_list_activity_impl(
+        access_token_validator,
+        audit_log,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing list activity request: {e}");
+            ListActivityResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_list_activity_impl(
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: ListActivityRequest,
+) -> Result<ListActivityResponse, ListActivityError> {
+    let ListActivityRequest { access_token, limit } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "list activity",
+        access_token_validator,
+        access_token,
+        ListActivityResponse(Err(StorageError::InvalidCredentials)),
+    );
+
+    trace!("listing note activity of user \"{username}\"");
+    Ok(
+        ListActivityResponse(
+            Ok(audit_log.read_user_events(&username, limit as usize).await?)
+        )
+    )
+}
+
+#[derive(Debug, Error)]
+enum ListActivityError {
+    #[error("audit log error: {0}")]
+    AuditLog(#[from] AuditLogError),
+}
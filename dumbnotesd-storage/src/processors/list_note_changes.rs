@@ -0,0 +1,52 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::list_note_changes::{ListNoteChangesRequest, ListNoteChangesResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_list_note_changes(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListNoteChangesRequest,
+) -> bindings::response::Response {
+    process_list_note_changes_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing list note changes request: {e}");
+            ListNoteChangesResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_list_note_changes_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListNoteChangesRequest,
+) -> Result<ListNoteChangesResponse, ListNoteChangesError> {
+    let ListNoteChangesRequest { access_token, since, limit } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "list note changes",
+        access_token_validator,
+        access_token,
+        ListNoteChangesResponse(Err(StorageError::InvalidCredentials)),
+    );
+
+    trace!("listing note changes since {since:?} for user \"{username}\"");
+    let changes = note_storage.list_changes(&username, since, limit).await?;
+    Ok(ListNoteChangesResponse(Ok(changes)))
+}
+
+#[derive(Debug, Error)]
+enum ListNoteChangesError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
@@ -0,0 +1,55 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::list_note_revisions::{ListNoteRevisionsRequest, ListNoteRevisionsResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_list_note_revisions(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListNoteRevisionsRequest,
+) -> bindings::response::Response {
+    process_list_note_revisions_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing list note revisions request: {e}");
+            ListNoteRevisionsResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_list_note_revisions_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListNoteRevisionsRequest,
+) -> Result<ListNoteRevisionsResponse, ListNoteRevisionsError> {
+    let ListNoteRevisionsRequest { access_token, note_id } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "list note revisions",
+        access_token_validator,
+        access_token,
+        ListNoteRevisionsResponse(Err(StorageError::InvalidCredentials)),
+    );
+
+    trace!("listing revisions of note \"{note_id}\" for user \"{username}\"");
+    match note_storage.list_revisions(&username, note_id).await {
+        Ok(revisions) => Ok(ListNoteRevisionsResponse(Ok(revisions))),
+        Err(SE::NoteNotFound) => Ok(ListNoteRevisionsResponse(Err(StorageError::NotFound))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Error)]
+enum ListNoteRevisionsError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
@@ -0,0 +1,55 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::list_note_shares::{ListNoteSharesRequest, ListNoteSharesResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_list_note_shares(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListNoteSharesRequest,
+) -> bindings::response::Response {
+    process_list_note_shares_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing list note shares request: {e}");
+            ListNoteSharesResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_list_note_shares_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListNoteSharesRequest,
+) -> Result<ListNoteSharesResponse, ListNoteSharesError> {
+    let ListNoteSharesRequest { access_token, note_id } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "list note shares",
+        access_token_validator,
+        access_token,
+        ListNoteSharesResponse(Err(StorageError::InvalidCredentials)),
+    );
+
+    trace!("listing shares of note \"{note_id}\" for user \"{username}\"");
+    match note_storage.list_note_shares(&username, note_id).await {
+        Ok(shares) => Ok(ListNoteSharesResponse(Ok(shares))),
+        Err(SE::NoteNotFound) => Ok(ListNoteSharesResponse(Err(StorageError::NotFound))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Error)]
+enum ListNoteSharesError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
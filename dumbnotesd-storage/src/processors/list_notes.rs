@@ -31,7 +31,7 @@ async fn process_list_notes_impl(
     access_token_validator: &AccessTokenValidator,
     request: ListNotesRequest,
 ) -> Result<ListNotesResponse, ListNotesError> {
-    let ListNotesRequest { access_token } = request;
+    let ListNotesRequest { access_token, query } = request;
 
     let AccessTokenData { username, .. } = check_access_token!(
         "list notes",
@@ -42,7 +42,9 @@ async fn process_list_notes_impl(
 
     trace!("listing notes for user \"{username}\"");
     Ok(
-        ListNotesResponse::Notes(note_storage.list_notes(&username).await?)
+        ListNotesResponse::Notes(
+            note_storage.list_notes(&username, &query).await?
+        )
     )
 }
 
@@ -0,0 +1,51 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::list_shared_notes::{ListSharedNotesRequest, ListSharedNotesResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_list_shared_notes(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListSharedNotesRequest,
+) -> bindings::response::Response {
+    process_list_shared_notes_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing list shared notes request: {e}");
+            ListSharedNotesResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_list_shared_notes_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListSharedNotesRequest,
+) -> Result<ListSharedNotesResponse, ListSharedNotesError> {
+    let ListSharedNotesRequest { access_token } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "list shared notes",
+        access_token_validator,
+        access_token,
+        ListSharedNotesResponse(Err(StorageError::InvalidCredentials)),
+    );
+
+    trace!("listing notes shared with user \"{username}\"");
+    Ok(ListSharedNotesResponse(Ok(note_storage.list_shared_notes(&username).await?)))
+}
+
+#[derive(Debug, Error)]
+enum ListSharedNotesError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
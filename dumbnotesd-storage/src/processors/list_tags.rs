@@ -0,0 +1,51 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::list_tags::{ListTagsRequest, ListTagsResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_list_tags(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListTagsRequest,
+) -> bindings::response::Response {
+    process_list_tags_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing list tags request: {e}");
+            ListTagsResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_list_tags_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListTagsRequest,
+) -> Result<ListTagsResponse, ListTagsError> {
+    let ListTagsRequest { access_token } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "list tags",
+        access_token_validator,
+        access_token,
+        ListTagsResponse(Err(StorageError::InvalidCredentials)),
+    );
+
+    trace!("listing tags for user \"{username}\"");
+    Ok(ListTagsResponse(Ok(note_storage.list_tags(&username).await?)))
+}
+
+#[derive(Debug, Error)]
+enum ListTagsError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
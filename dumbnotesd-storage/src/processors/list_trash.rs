@@ -0,0 +1,51 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::list_trash::{ListTrashRequest, ListTrashResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_list_trash(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListTrashRequest,
+) -> bindings::response::Response {
+    process_list_trash_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing list trash request: {e}");
+            ListTrashResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_list_trash_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ListTrashRequest,
+) -> Result<ListTrashResponse, ListTrashError> {
+    let ListTrashRequest { access_token } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "list trash",
+        access_token_validator,
+        access_token,
+        ListTrashResponse(Err(StorageError::InvalidCredentials)),
+    );
+
+    trace!("listing the trash for user \"{username}\"");
+    Ok(ListTrashResponse(Ok(note_storage.list_trash(&username).await?)))
+}
+
+#[derive(Debug, Error)]
+enum ListTrashError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
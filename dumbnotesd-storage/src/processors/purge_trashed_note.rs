@@ -0,0 +1,74 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use data::{AuditEvent, AuditEventKind, AuditOutcome};
+use dumbnotes::audit_log::AuditLog;
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::purge_trashed_note::{PurgeTrashedNoteRequest, PurgeTrashedNoteResponse};
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+use thiserror::Error;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_purge_trashed_note(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: PurgeTrashedNoteRequest,
+) -> bindings::response::Response {
+    process_purge_trashed_note_impl(
+        note_storage,
+        access_token_validator,
+        audit_log,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing trashed note purge request: {e}");
+            PurgeTrashedNoteResponse(Some(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_purge_trashed_note_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    audit_log: &AuditLog,
+    request: PurgeTrashedNoteRequest,
+) -> Result<PurgeTrashedNoteResponse, PurgeTrashedNoteError> {
+    let PurgeTrashedNoteRequest { access_token, note_id } = request;
+
+    let AccessTokenData { session_id, username, .. } = check_access_token!(
+        "purge trashed note",
+        access_token_validator,
+        access_token,
+        PurgeTrashedNoteResponse(Some(StorageError::InvalidCredentials)),
+    );
+
+    trace!("purging note \"{note_id}\" from the trash for user \"{username}\"");
+    let result = note_storage.purge_trashed_note(&username, note_id).await;
+    let outcome = match result {
+        Ok(()) => AuditOutcome::Success,
+        Err(_) => AuditOutcome::Failure,
+    };
+    audit_log
+        .record(
+            &AuditEvent {
+                session_id: Some(session_id),
+                note_id: Some(note_id),
+                ..AuditEvent::now(username, AuditEventKind::NotePurge, outcome)
+            }
+        )
+        .await;
+    match result {
+        Ok(()) => Ok(PurgeTrashedNoteResponse(None)),
+        Err(SE::NoteNotFound) => Ok(PurgeTrashedNoteResponse(Some(StorageError::NotFound))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Error)]
+enum PurgeTrashedNoteError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
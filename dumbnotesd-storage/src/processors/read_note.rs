@@ -1,4 +1,5 @@
 use access_token::{AccessTokenData, AccessTokenValidator};
+use data::ShareAccess;
 use dumbnotes::check_access_token;
 use log::{error, trace};
 use storage_ipc_data::model::read_note::{ReadNoteRequest, ReadNoteResponse};
@@ -31,7 +32,7 @@ async fn process_read_note_impl(
     access_token_validator: &AccessTokenValidator,
     request: ReadNoteRequest,
 ) -> Result<ReadNoteResponse, ReadNoteError> {
-    let ReadNoteRequest { access_token, note_id } = request;
+    let ReadNoteRequest { access_token, note_id, owner } = request;
 
     let AccessTokenData { username, .. } = check_access_token!(
         "read note",
@@ -41,7 +42,13 @@ async fn process_read_note_impl(
     );
 
     trace!("reading note \"{note_id}\" for user \"{username}\"");
-    match note_storage.read_note(&username, note_id).await {
+    let result = async {
+        let owner = note_storage
+            .resolve_note_access(&username, owner, note_id, ShareAccess::ReadOnly)
+            .await?;
+        note_storage.read_versioned_note(&owner, note_id).await
+    }.await;
+    match result {
         Ok(note) => Ok(ReadNoteResponse(Ok(note))),
         Err(SE::TooBig) => Ok(ReadNoteResponse(Err(StorageError::TooBig))),
         Err(SE::NoteNotFound) => Ok(ReadNoteResponse(Err(StorageError::NotFound))),
@@ -0,0 +1,58 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::read_note_revision::{ReadNoteRevisionRequest, ReadNoteRevisionResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_read_note_revision(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ReadNoteRevisionRequest,
+) -> bindings::response::Response {
+    process_read_note_revision_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing read note revision request: {e}");
+            ReadNoteRevisionResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_read_note_revision_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: ReadNoteRevisionRequest,
+) -> Result<ReadNoteRevisionResponse, ReadNoteRevisionError> {
+    let ReadNoteRevisionRequest { access_token, note_id, revision } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "read note revision",
+        access_token_validator,
+        access_token,
+        ReadNoteRevisionResponse(Err(StorageError::InvalidCredentials)),
+    );
+
+    trace!(
+        "reading revision {revision} of note \"{note_id}\" for user \"{username}\""
+    );
+    match note_storage.read_revision(&username, note_id, revision).await {
+        Ok(note) => Ok(ReadNoteRevisionResponse(Ok(note))),
+        Err(SE::TooBig) => Ok(ReadNoteRevisionResponse(Err(StorageError::TooBig))),
+        Err(SE::NoteNotFound) => Ok(ReadNoteRevisionResponse(Err(StorageError::NotFound))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Error)]
+enum ReadNoteRevisionError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
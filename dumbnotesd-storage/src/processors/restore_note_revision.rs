@@ -0,0 +1,67 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::restore_note_revision::{RestoreNoteRevisionRequest, RestoreNoteRevisionResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+use crate::user_quotas::{UserQuotas, UserQuotasError};
+
+pub async fn process_restore_note_revision(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    user_quotas: &UserQuotas,
+    request: RestoreNoteRevisionRequest,
+) -> bindings::response::Response {
+    process_restore_note_revision_impl(
+        note_storage,
+        access_token_validator,
+        user_quotas,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing restore note revision request: {e}");
+            RestoreNoteRevisionResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_restore_note_revision_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    user_quotas: &UserQuotas,
+    request: RestoreNoteRevisionRequest,
+) -> Result<RestoreNoteRevisionResponse, RestoreNoteRevisionError> {
+    let RestoreNoteRevisionRequest { access_token, note_id, revision } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "restore note revision",
+        access_token_validator,
+        access_token,
+        RestoreNoteRevisionResponse(Err(StorageError::InvalidCredentials)),
+    );
+    let quota = user_quotas.get_user_quota(&username).await?;
+
+    trace!(
+        "restoring revision {revision} of note \"{note_id}\" for user \"{username}\""
+    );
+    match note_storage.restore_revision(&username, note_id, revision, quota).await {
+        Ok(restored) => Ok(RestoreNoteRevisionResponse(Ok(restored))),
+        Err(SE::TooBig) => Ok(RestoreNoteRevisionResponse(Err(StorageError::TooBig))),
+        Err(SE::QuotaExceeded) => Ok(RestoreNoteRevisionResponse(Err(StorageError::QuotaExceeded))),
+        Err(SE::NoteNotFound) => Ok(RestoreNoteRevisionResponse(Err(StorageError::NotFound))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Error)]
+enum RestoreNoteRevisionError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+
+    #[error("user quota lookup error: {0}")]
+    UserQuotas(#[from] UserQuotasError),
+}
@@ -0,0 +1,65 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::restore_trashed_note::{RestoreTrashedNoteRequest, RestoreTrashedNoteResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+use crate::user_quotas::{UserQuotas, UserQuotasError};
+
+pub async fn process_restore_trashed_note(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    user_quotas: &UserQuotas,
+    request: RestoreTrashedNoteRequest,
+) -> bindings::response::Response {
+    process_restore_trashed_note_impl(
+        note_storage,
+        access_token_validator,
+        user_quotas,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing restore trashed note request: {e}");
+            RestoreTrashedNoteResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_restore_trashed_note_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    user_quotas: &UserQuotas,
+    request: RestoreTrashedNoteRequest,
+) -> Result<RestoreTrashedNoteResponse, RestoreTrashedNoteError> {
+    let RestoreTrashedNoteRequest { access_token, note_id } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "restore trashed note",
+        access_token_validator,
+        access_token,
+        RestoreTrashedNoteResponse(Err(StorageError::InvalidCredentials)),
+    );
+    let quota = user_quotas.get_user_quota(&username).await?;
+
+    trace!("restoring note \"{note_id}\" from the trash for user \"{username}\"");
+    match note_storage.restore_trashed_note(&username, note_id, quota).await {
+        Ok(restored) => Ok(RestoreTrashedNoteResponse(Ok(restored))),
+        Err(SE::NoteExists) => Ok(RestoreTrashedNoteResponse(Err(StorageError::AlreadyExists))),
+        Err(SE::NoteNotFound) => Ok(RestoreTrashedNoteResponse(Err(StorageError::NotFound))),
+        Err(SE::QuotaExceeded) => Ok(RestoreTrashedNoteResponse(Err(StorageError::QuotaExceeded))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Error)]
+enum RestoreTrashedNoteError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+
+    #[error("user quota lookup error: {0}")]
+    UserQuotas(#[from] UserQuotasError),
+}
@@ -0,0 +1,58 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::revoke_note_share::{RevokeNoteShareRequest, RevokeNoteShareResponse};
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+use thiserror::Error;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_revoke_note_share(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: RevokeNoteShareRequest,
+) -> bindings::response::Response {
+    process_revoke_note_share_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing revoke note share request: {e}");
+            RevokeNoteShareResponse(Some(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_revoke_note_share_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: RevokeNoteShareRequest,
+) -> Result<RevokeNoteShareResponse, RevokeNoteShareError> {
+    let RevokeNoteShareRequest { access_token, note_id, username: grantee } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "revoke note share",
+        access_token_validator,
+        access_token,
+        RevokeNoteShareResponse(Some(StorageError::InvalidCredentials)),
+    );
+
+    trace!(
+        "revoking the share of note \"{note_id}\" of user \"{username}\" \
+            with user \"{grantee}\""
+    );
+    match note_storage.revoke_note_share(&username, note_id, &grantee).await {
+        Ok(()) => Ok(RevokeNoteShareResponse(None)),
+        Err(SE::NoteNotFound) => Ok(RevokeNoteShareResponse(Some(StorageError::NotFound))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Error)]
+enum RevokeNoteShareError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
@@ -0,0 +1,55 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::search_notes::{SearchNotesRequest, SearchNotesResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+
+pub async fn process_search_notes(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: SearchNotesRequest,
+) -> bindings::response::Response {
+    process_search_notes_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing search notes request: {e}");
+            SearchNotesResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_search_notes_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    request: SearchNotesRequest,
+) -> Result<SearchNotesResponse, SearchNotesError> {
+    let SearchNotesRequest { access_token, query } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "search notes",
+        access_token_validator,
+        access_token,
+        SearchNotesResponse(Err(StorageError::InvalidCredentials)),
+    );
+
+    trace!("searching notes with {query:?} for user \"{username}\"");
+    match note_storage.search_notes(&username, &query).await {
+        Ok(page) => Ok(SearchNotesResponse(Ok(page))),
+        Err(SE::TooBig) => Ok(SearchNotesResponse(Err(StorageError::TooBig))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Error)]
+enum SearchNotesError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+}
@@ -0,0 +1,76 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use access_token::{AccessTokenData, AccessTokenValidator};
+use async_stream::stream;
+use dumbnotes::check_access_token;
+use futures::stream::{self, BoxStream, StreamExt};
+use log::{debug, trace, warn};
+use storage_ipc_data::model::subscribe_note_events::{NoteEventResponse, SubscribeNoteEventsRequest};
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+use time::OffsetDateTime;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::storage::NoteStorage;
+
+pub async fn process_subscribe_note_events(
+    note_storage: &Arc<NoteStorage>,
+    access_token_validator: &AccessTokenValidator,
+    request: SubscribeNoteEventsRequest,
+) -> BoxStream<'static, bindings::response::Response> {
+    let Ok(responses) = process_subscribe_note_events_impl(
+        note_storage,
+        access_token_validator,
+        request,
+    ).await;
+    responses.map(bindings::response::Response::from).boxed()
+}
+
+async fn process_subscribe_note_events_impl(
+    note_storage: &Arc<NoteStorage>,
+    access_token_validator: &AccessTokenValidator,
+    request: SubscribeNoteEventsRequest,
+) -> Result<BoxStream<'static, NoteEventResponse>, Infallible> {
+    let SubscribeNoteEventsRequest { access_token } = request;
+
+    let AccessTokenData { username, expires_at, .. } = check_access_token!(
+        "subscribe note events",
+        access_token_validator,
+        access_token,
+        stream::iter([NoteEventResponse::Error(StorageError::InvalidCredentials)]).boxed(),
+    );
+
+    trace!("subscribing to note events for user \"{username}\"");
+    let mut events = note_storage.subscribe_events();
+    let expiry = tokio::time::sleep(
+        (expires_at - OffsetDateTime::now_utc())
+            .try_into()
+            .unwrap_or_default()
+    );
+    Ok(
+        stream! {
+            yield NoteEventResponse::Subscribed;
+            tokio::pin!(expiry);
+            loop {
+                tokio::select! {
+                    _ = &mut expiry => {
+                        debug!("ending note events for user \"{username}\": token expired");
+                        break
+                    },
+                    event = events.recv() => match event {
+                        Ok((event_username, event)) => if event_username == username {
+                            yield NoteEventResponse::Event(event)
+                        },
+                        Err(RecvError::Lagged(count)) => {
+                            // the subscriber has to resync anyway
+                            warn!("note events subscriber for user \"{username}\" missed {count} events");
+                            break
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        }.boxed()
+    )
+}
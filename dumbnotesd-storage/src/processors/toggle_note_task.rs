@@ -0,0 +1,68 @@
+use access_token::{AccessTokenData, AccessTokenValidator};
+use dumbnotes::check_access_token;
+use log::{error, trace};
+use storage_ipc_data::model::toggle_note_task::{ToggleNoteTaskRequest, ToggleNoteTaskResponse};
+use thiserror::Error;
+use storage_ipc_data::bindings;
+use bindings::StorageError;
+
+use crate::StorageError as SE;
+use crate::storage::NoteStorage;
+use crate::user_quotas::{UserQuotas, UserQuotasError};
+
+pub async fn process_toggle_note_task(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    user_quotas: &UserQuotas,
+    request: ToggleNoteTaskRequest,
+) -> bindings::response::Response {
+    process_toggle_note_task_impl(
+        note_storage,
+        access_token_validator,
+        user_quotas,
+        request,
+    ).await
+        .unwrap_or_else(|e| {
+            error!("error processing toggle note task request: {e}");
+            ToggleNoteTaskResponse(Err(StorageError::InternalError))
+        })
+        .into()
+}
+
+async fn process_toggle_note_task_impl(
+    note_storage: &NoteStorage,
+    access_token_validator: &AccessTokenValidator,
+    user_quotas: &UserQuotas,
+    request: ToggleNoteTaskRequest,
+) -> Result<ToggleNoteTaskResponse, ToggleNoteTaskError> {
+    let ToggleNoteTaskRequest { access_token, note_id, task_index } = request;
+
+    let AccessTokenData { username, .. } = check_access_token!(
+        "toggle note task",
+        access_token_validator,
+        access_token,
+        ToggleNoteTaskResponse(Err(StorageError::InvalidCredentials)),
+    );
+    let quota = user_quotas.get_user_quota(&username).await?;
+
+    trace!(
+        "toggling task {task_index} of note \"{note_id}\" for user \"{username}\""
+    );
+    match note_storage.toggle_task(&username, note_id, task_index, quota).await {
+        Ok(note) => Ok(ToggleNoteTaskResponse(Ok(note))),
+        Err(SE::TooBig) => Ok(ToggleNoteTaskResponse(Err(StorageError::TooBig))),
+        Err(SE::QuotaExceeded) => Ok(ToggleNoteTaskResponse(Err(StorageError::QuotaExceeded))),
+        Err(SE::NoteNotFound | SE::TaskNotFound) =>
+            Ok(ToggleNoteTaskResponse(Err(StorageError::NotFound))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Error)]
+enum ToggleNoteTaskError {
+    #[error("note storage error: {0}")]
+    NoteStorage(#[from] SE),
+
+    #[error("user quota lookup error: {0}")]
+    UserQuotas(#[from] UserQuotasError),
+}
@@ -1,4 +1,5 @@
 use access_token::{AccessTokenData, AccessTokenValidator};
+use data::ShareAccess;
 use dumbnotes::check_access_token;
 use log::{error, trace};
 use storage_ipc_data::bindings::StorageError;
@@ -6,21 +7,24 @@ use storage_ipc_data::model::write_note::{WriteNoteRequest, WriteNoteResponse};
 use thiserror::Error;
 
 use crate::storage::NoteStorage;
+use crate::user_quotas::{UserQuotas, UserQuotasError};
 use crate::StorageError as SE;
 
 pub async fn process_write_note(
     note_storage: &NoteStorage,
     access_token_validator: &AccessTokenValidator,
+    user_quotas: &UserQuotas,
     request: WriteNoteRequest,
 ) -> storage_ipc_data::bindings::response::Response {
     process_write_note_impl(
         note_storage,
         access_token_validator,
+        user_quotas,
         request,
     ).await
         .unwrap_or_else(|e| {
             error!("error processing read note request: {e}");
-            WriteNoteResponse(Some(StorageError::InternalError))
+            WriteNoteResponse::Error(StorageError::InternalError)
         })
         .into()
 }
@@ -28,23 +32,42 @@ pub async fn process_write_note(
 async fn process_write_note_impl(
     note_storage: &NoteStorage,
     access_token_validator: &AccessTokenValidator,
+    user_quotas: &UserQuotas,
     request: WriteNoteRequest,
 ) -> Result<WriteNoteResponse, WriteNoteError> {
-    let WriteNoteRequest { access_token, note } = request;
+    let WriteNoteRequest { access_token, note, base_version, owner } = request;
 
     let AccessTokenData { username, .. } = check_access_token!(
         "write note",
         access_token_validator,
         access_token,
-        WriteNoteResponse(Some(StorageError::InvalidCredentials)),
+        WriteNoteResponse::Error(StorageError::InvalidCredentials),
     );
 
     trace!("writing note \"{note:?}\" for user \"{username}\"");
-    match note_storage.write_note(&username, &note).await {
-        Ok(()) => Ok(WriteNoteResponse(None)),
-        Err(SE::TooBig) => Ok(WriteNoteResponse(Some(StorageError::TooBig))),
-        Err(SE::NoteNotFound) => Ok(WriteNoteResponse(Some(StorageError::NotFound))),
-        Err(e) => Err(e.into()),
+    let owner = match note_storage
+        .resolve_note_access(&username, owner, note.metadata.id, ShareAccess::ReadWrite)
+        .await
+    {
+        Ok(owner) => owner,
+        Err(e) => return map_storage_error(e),
+    };
+    // the writes of a shared note count against the owner's quota
+    let quota = user_quotas.get_user_quota(&owner).await?;
+    match note_storage.write_note(&owner, &username, &note, base_version, quota).await {
+        Ok((metadata, version)) => Ok(WriteNoteResponse::Written(metadata, version)),
+        Err(e) => map_storage_error(e),
+    }
+}
+
+fn map_storage_error(e: SE) -> Result<WriteNoteResponse, WriteNoteError> {
+    match e {
+        SE::Conflict(current) => Ok(WriteNoteResponse::Conflict(*current)),
+        SE::TooBig => Ok(WriteNoteResponse::Error(StorageError::TooBig)),
+        SE::QuotaExceeded => Ok(WriteNoteResponse::Error(StorageError::QuotaExceeded)),
+        SE::NoteNotFound => Ok(WriteNoteResponse::Error(StorageError::NotFound)),
+        SE::Forbidden => Ok(WriteNoteResponse::Error(StorageError::Forbidden)),
+        e => Err(e.into()),
     }
 }
 
@@ -52,4 +75,7 @@ async fn process_write_note_impl(
 enum WriteNoteError {
     #[error("note storage error: {0}")]
     NoteStorage(#[from] SE),
+
+    #[error("user quota lookup error: {0}")]
+    UserQuotas(#[from] UserQuotasError),
 }
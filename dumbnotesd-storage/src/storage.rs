@@ -1,4 +1,8 @@
+#[cfg(test)] mod tests;
+
 use futures::future::join_all;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
 use log::{debug, error, trace};
 use std::ffi::OsString;
 use std::io::ErrorKind;
@@ -10,26 +14,47 @@ use std::str::FromStr;
 use time::UtcDateTime;
 use tokio::io;
 use tokio::io::AsyncReadExt;
+use tokio::sync::{broadcast, Mutex};
 use uuid::fmt::Hyphenated;
 use uuid::Uuid;
 
-use data::{Note, NoteInfo, NoteMetadata};
+use data::{Note, NoteEvent, NoteInfo, NoteListOrder, NoteListPage, NoteListQuery, NoteMetadata, NoteVersion, ShareAccess, UserQuota, UsernameString, VersionedNote, MAX_NOTE_TAGS};
+use crate::storage::encryption::ENCRYPTED_NOTE_OVERHEAD;
 use crate::storage::errors::StorageError;
 use crate::util::StrExt;
 use util::send_fut_lifetime_workaround;
 
-use crate::app_constants::NOTES_DIRECTORY_PATH;
+use crate::app_constants::NOTE_EVENTS_CAPACITY;
+use dumbnotes::bin_constants::NOTES_DIRECTORY_PATH;
 use crate::storage::io_trait::OpenFile;
+use crate::storage::page_limits::PageLimits;
+use crate::storage::tasks::summarize_tasks;
 use data::UsernameStr;
 use io_trait::NoteStorageIo;
 use io_trait::ProductionNoteStorageIo;
 use unix::errors::CheckAccessError;
 
+mod archive_time;
+mod encryption;
 mod io_trait;
+mod journal;
+mod page_limits;
+mod quota;
+mod revisions;
+mod search;
+mod shares;
+mod tags;
+mod tasks;
+mod trash;
 pub mod errors;
 
+pub use encryption::NoteCipher;
+pub use quota::QuotaLimits;
+pub use revisions::RevisionLimits;
+
 const HYPHENED_UUID_SIZE: usize = 36;
 const TMP_FILENAME_INFIX: &str = ".tmp.";
+const MIN_NOTE_MTIME: i64 = 0;
 
 pub type NoteStorage = NoteStorageImpl<ProductionNoteStorageIo>;
 
@@ -40,6 +65,15 @@ pub struct NoteStorageImpl<Io: NoteStorageIo> {
     basedir: PathBuf,
     max_note_len: u64,
     max_note_name_len: u64,
+    page_limits: PageLimits,
+    revision_limits: RevisionLimits,
+    default_quota: QuotaLimits,
+    cipher: Option<NoteCipher>,
+    write_lock: Mutex<()>,
+    // storage size and note count by user, only changed with the write
+    // lock held, see `quota.rs`
+    usage_cache: std::sync::Mutex<HashMap<UsernameString, (u64, u32)>>,
+    events: broadcast::Sender<(UsernameString, NoteEvent)>,
 }
 
 impl NoteStorage {
@@ -47,11 +81,17 @@ impl NoteStorage {
         data_directory: impl AsRef<Path>,
         max_note_size: u64,
         max_note_name_size: u64,
+        revision_limits: RevisionLimits,
+        default_quota: QuotaLimits,
+        cipher: Option<NoteCipher>,
     ) -> Result<NoteStorage, StorageError> {
         Self::new_internal(
             Self::get_notes_dir(data_directory),
             max_note_size,
             max_note_name_size,
+            revision_limits,
+            default_quota,
+            cipher,
             ProductionNoteStorageIo::new(),
         ).await
     }
@@ -67,6 +107,9 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
         notes_dir: PathBuf,
         max_note_size: u64,
         max_note_name_size: u64,
+        revision_limits: RevisionLimits,
+        default_quota: QuotaLimits,
+        cipher: Option<NoteCipher>,
         io: Io,
     ) -> Result<NoteStorageImpl<Io>, StorageError> {
         debug!(
@@ -84,6 +127,13 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
             basedir: notes_dir,
             max_note_len: max_note_size,
             max_note_name_len: max_note_name_size,
+            page_limits: PageLimits::new(max_note_name_size),
+            revision_limits,
+            default_quota,
+            cipher,
+            write_lock: Mutex::new(()),
+            usage_cache: std::sync::Mutex::new(HashMap::new()),
+            events: broadcast::Sender::new(NOTE_EVENTS_CAPACITY),
         })
     }
 
@@ -92,11 +142,33 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
         username: &UsernameStr,
         note_id: Uuid,
     ) -> Result<Note, StorageError> {
+        Ok(self.read_versioned_note(username, note_id).await?.note)
+    }
+
+    /// Reads the note along with the version the edits of it are based on
+    pub async fn read_versioned_note(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+    ) -> Result<VersionedNote, StorageError> {
         let path = self.get_note_path(username, note_id);
         debug!(
             "reading note {note_id} for user \"{username}\" at \"{}\"",
             path.display(),
         );
+        let mut versioned = self.read_note_file(username, path, note_id).await?;
+        versioned.note.tags = self.read_note_tags(username, note_id).await?;
+        Ok(versioned)
+    }
+
+    // the note comes without the tags, they're kept in a file of their own,
+    // which the revisions don't have
+    async fn read_note_file(
+        &self,
+        username: &UsernameStr,
+        path: impl AsRef<Path> + Send,
+        note_id: Uuid,
+    ) -> Result<VersionedNote, StorageError> {
         let file = self.io
             .open_file(path)
             .await
@@ -104,10 +176,15 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
                 ErrorKind::NotFound => StorageError::NoteNotFound,
                 _ => StorageError::Io(e),
             })?;
-        if file.size > self.max_note_len {
+        let max_file_size = match self.cipher {
+            Some(_) => self.max_note_len + ENCRYPTED_NOTE_OVERHEAD,
+            None => self.max_note_len,
+        };
+        if file.size > max_file_size {
             return Err(StorageError::TooBig);
         }
-        let contents = read_limited_utf8_lossy(self.max_note_len, file.file)
+        let contents = self
+            .read_note_data(username, note_id, file.file, self.max_note_len)
             .await?;
         let (name, contents) = contents.split_once('\n')
             .unwrap_or((&contents, ""));
@@ -119,29 +196,92 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
             return Err(StorageError::TooBig);
         }
         Ok(
-            Note {
-                metadata: NoteMetadata {
-                    id: note_id,
-                    mtime: UtcDateTime::from_unix_timestamp(file.mtime)?,
+            VersionedNote {
+                note: Note {
+                    metadata: NoteMetadata {
+                        id: note_id,
+                        mtime: UtcDateTime::from_unix_timestamp(file.mtime)?,
+                    },
+                    name: name.nonblank_to_some(),
+                    tags: BTreeSet::new(),
+                    contents: contents.to_owned(),
+                },
+                version: NoteVersion {
+                    ino: file.ino,
+                    ctime: file.ctime,
+                    ctime_nsec: file.ctime_nsec,
                 },
-                name: name.nonblank_to_some(),
-                contents: contents.to_owned(),
             }
         )
     }
 
-    // TODO: write mtime
+    // the names in the listings are cut to the configured length
+    fn truncate_note_name<'a>(&self, name: &'a str) -> &'a str {
+        &name[..name.floor_char_boundary(self.max_note_name_len as usize)]
+    }
+
+    /// Returns the metadata and the version the note was stored with, the
+    /// mtime may differ from the requested one after clamping, see
+    /// [`Self::clamp_mtime`]. The writer needs write access to the note
+    /// when it's shared with them.
     pub async fn write_note(
         &self,
         username: &UsernameStr,
+        writer: &UsernameStr,
         note: &Note,
-    ) -> Result<(), StorageError> {
+        base_version: Option<NoteVersion>,
+        quota: UserQuota,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageError> {
+        if note.tags.len() > MAX_NOTE_TAGS {
+            return Err(StorageError::TooBig)
+        }
         let filename = self.get_note_path(username, note.metadata.id);
         debug!(
             "writing note {} for user \"{username}\" to \"{}\"",
             note.metadata.id,
             filename.display(),
         );
+        let _write_guard = self.write_lock.lock().await;
+        self
+            .check_note_access(writer, username, note.metadata.id, ShareAccess::ReadWrite)
+            .await?;
+        let current_mtime = self
+            .check_base_version(username, note.metadata.id, base_version)
+            .await?;
+        self
+            .track_usage(
+                username,
+                Some(note.metadata.id),
+                self.write_note_locked(username, note, current_mtime, quota),
+            )
+            .await
+    }
+
+    // must be called with the write lock held
+    async fn write_note_locked(
+        &self,
+        username: &UsernameStr,
+        note: &Note,
+        current_mtime: Option<i64>,
+        quota: UserQuota,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageError> {
+        let filename = self.get_note_path(username, note.metadata.id);
+        let metadata = NoteMetadata {
+            id: note.metadata.id,
+            mtime: Self::clamp_mtime(note.metadata.mtime, UtcDateTime::now()),
+        };
+        if metadata.mtime != note.metadata.mtime {
+            debug!(
+                "clamped mtime {} of note {} for user \"{username}\" to {}",
+                note.metadata.mtime,
+                note.metadata.id,
+                metadata.mtime,
+            );
+        }
+        let data = self
+            .encode_note_file(username, note.metadata.id, format_note(note))?;
+        self.check_quota(username, quota, note.metadata.id, data.len() as u64)
+            .await?;
         let tmp_filename = self
             .get_note_tmp_path(username, note.metadata.id);
         trace!(
@@ -149,15 +289,26 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
             note.metadata.id,
             tmp_filename.display(),
         );
-        self.io.write_file(&tmp_filename, format_note(note)).await?;
+        if current_mtime.is_some() {
+            self.archive_revision(username, note.metadata.id).await?;
+        }
+        self.write_note_tags(username, note.metadata.id, &note.tags).await?;
+        self.io.write_file(&tmp_filename, data).await?;
         trace!(
-            "renaming tmp file \"{}\" for note \"{}\"",
+            "setting mtime and renaming tmp file \"{}\" for note \"{}\"",
             tmp_filename.display(),
             note.metadata.id,
         );
-        if let Err(e) = self.io.rename_file(&tmp_filename, &filename).await {
+        let result = match self.io
+            .set_file_mtime(&tmp_filename, metadata.mtime.unix_timestamp())
+            .await
+        {
+            Ok(()) => self.io.rename_file(&tmp_filename, &filename).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
             error!(
-                "failed to rename tmp file \"{}\" for note {}: {e}",
+                "failed to finalize tmp file \"{}\" for note {}: {e}",
                 tmp_filename.display(),
                 note.metadata.id,
             );
@@ -170,40 +321,191 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
             }
             return Err(e.into())
         }
-        Ok(())
+        if current_mtime.is_some() {
+            self.prune_revisions(username, note.metadata.id).await;
+        }
+        self.update_search_index(username, note.metadata.id, Some(note)).await;
+        self.journal_write(username, metadata).await;
+        let info = NoteInfo {
+            metadata,
+            name: note.name.clone(),
+            tags: note.tags.clone(),
+            tasks: Some(summarize_tasks(&note.contents)),
+        };
+        self.publish_event(
+            username,
+            match current_mtime {
+                None => NoteEvent::Created(info),
+                Some(_) => NoteEvent::Updated(info),
+            },
+        );
+        let (_, version) = self
+            .stat_note(username, note.metadata.id)
+            .await?
+            .ok_or(StorageError::NoteNotFound)?;
+        Ok((metadata, version))
+    }
+
+    /// Returns the mtime of the current version of the note. Without
+    /// a base version the write is unconditional, overwriting an existing
+    /// note as the clients predating the conflict checks expect.
+    async fn check_base_version(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+        base_version: Option<NoteVersion>,
+    ) -> Result<Option<i64>, StorageError> {
+        let current = self.stat_note(username, note_id).await?;
+        trace!(
+            "note {note_id} for user \"{username}\" has mtime and version \
+                {current:?}, base version is {base_version:?}"
+        );
+        match (current, base_version) {
+            (current, None) => Ok(current.map(|(mtime, _)| mtime)),
+            (None, Some(_)) => Err(StorageError::NoteNotFound),
+            (Some((mtime, version)), Some(base))
+                if version == base => Ok(Some(mtime)),
+            (Some(_), Some(_)) => {
+                debug!(
+                    "rejecting a stale write of note {note_id} \
+                        for user \"{username}\""
+                );
+                Err(
+                    StorageError::Conflict(
+                        Box::new(self.read_versioned_note(username, note_id).await?)
+                    )
+                )
+            },
+        }
     }
 
+    // The version of a note is the identity and the change time of its
+    // file, which every write replaces; the client's mtime can't tell
+    // the versions apart, being set by the clients and only to a second.
+    async fn stat_note(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+    ) -> Result<Option<(i64, NoteVersion)>, StorageError> {
+        match self.io.metadata(self.get_note_path(username, note_id)).await {
+            Ok(metadata) => Ok(
+                Some((
+                    metadata.mtime,
+                    NoteVersion {
+                        ino: metadata.ino,
+                        ctime: metadata.ctime,
+                        ctime_nsec: metadata.ctime_nsec,
+                    },
+                ))
+            ),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Client clocks can't be trusted: timestamps from the future are pulled
+    // back to the server time, and pre-epoch ones are raised to the epoch.
+    // Versions may share an mtime, the conflicts are told by their
+    // versions, see [`Self::stat_note`].
+    fn clamp_mtime(mtime: UtcDateTime, now: UtcDateTime) -> UtcDateTime {
+        let clamped = mtime.unix_timestamp()
+            .clamp(MIN_NOTE_MTIME, now.unix_timestamp());
+        // between the epoch and the server time
+        UtcDateTime::from_unix_timestamp(clamped)
+            .expect("clamped mtime is out of range")
+    }
+
+    /// Lists a page of notes, the limit is capped so that the details
+    /// of the page fit in a response, see [`PageLimits`]
     pub async fn list_notes(
         &self,
         username: &UsernameStr,
+        query: &NoteListQuery,
+    ) -> Result<NoteListPage<NoteMetadata>, StorageError> {
+        debug!("listing notes for user \"{username}\" with {query:?}");
+        let max_limit = self.page_limits.note_list;
+        let limit = query.limit
+            .map_or(max_limit, |limit| limit.min(max_limit))
+            as usize;
+        let tagged = match &query.tag {
+            Some(tag) => Some(self.find_tagged_notes(username, tag).await?),
+            None => None,
+        };
+        // one more to know whether there's a next page
+        let mut notes = match query.order {
+            NoteListOrder::OldestFirst => self
+                .scan_notes(username, query, tagged.as_ref(), limit + 1, |key| key)
+                .await?,
+            NoteListOrder::NewestFirst => self
+                .scan_notes(username, query, tagged.as_ref(), limit + 1, Reverse)
+                .await?,
+        };
+        let next = if notes.len() > limit {
+            notes.truncate(limit);
+            notes.last().copied()
+        } else {
+            None
+        };
+        Ok(NoteListPage { notes, next })
+    }
+
+    // Keeps only the first `limit` notes in the order given by `order_key`
+    // while reading the directory, so that memory use doesn't depend
+    // on the number of notes
+    async fn scan_notes<K: Ord>(
+        &self,
+        username: &UsernameStr,
+        query: &NoteListQuery,
+        tagged: Option<&HashSet<Uuid>>,
+        limit: usize,
+        order_key: impl Fn((i64, Uuid)) -> K,
     ) -> Result<Vec<NoteMetadata>, StorageError> {
-        debug!("listing notes for user \"{username}\"");
         // TODO: reimplement with `scandir()` (needs an async implementation)
-        //  and a data limit
+        let after = query.after
+            .map(|nm| order_key((nm.mtime.unix_timestamp(), nm.id)));
+        let modified_since = query.modified_since
+            .map(UtcDateTime::unix_timestamp);
         let mut read = self.io.read_dir(self.get_user_dir(username)).await?;
-        let mut ret = Vec::new();
+        let mut heap = BinaryHeap::with_capacity(limit);
         while let Some(entry) = read.next_entry().await? {
             trace!("read dir entry \"{entry:?}\" for user \"{username}\"");
-            if let Some(uuid) = Self::try_extract_uuid(entry.file_name()) {
-                trace!(
-                    "dir entry \"{entry:?}\" for user \"{username}\" \
-                        accepted with id {uuid}"
-                );
-                ret.push(
-                    NoteMetadata { 
-                        id: uuid,
-                        mtime: UtcDateTime
-                            ::from_unix_timestamp(
-                                entry.metadata().await
-                                    .map(|nm| nm.mtime())?
-                            )
+            let Some(uuid) = Self::try_extract_uuid(entry.file_name()) else {
+                continue
+            };
+            if tagged.is_some_and(|ids| !ids.contains(&uuid)) {
+                continue
+            }
+            let mtime = entry.metadata().await?.mtime();
+            if modified_since.is_some_and(|since| mtime < since) {
+                continue
+            }
+            let key = order_key((mtime, uuid));
+            if after.as_ref().is_some_and(|after| key <= *after) {
+                continue
+            }
+            trace!(
+                "dir entry \"{entry:?}\" for user \"{username}\" \
+                    accepted with id {uuid}"
+            );
+            if heap.len() < limit {
+                heap.push((key, mtime, uuid));
+            } else if heap.peek().is_some_and(|(top, _, _)| key < *top) {
+                heap.pop();
+                heap.push((key, mtime, uuid));
+            }
+        }
+        Ok(
+            heap.into_sorted_vec()
+                .into_iter()
+                .map(|(_, mtime, id)|
+                    NoteMetadata {
+                        id,
+                        mtime: UtcDateTime::from_unix_timestamp(mtime)
                             .unwrap_or(UtcDateTime::MIN),
                     }
                 )
-            }
-        }
-        ret.sort_by_key(|nm| nm.mtime);
-        Ok(ret)
+                .collect()
+        )
     }
 
     pub async fn get_note_details(
@@ -212,6 +514,17 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
         notes: impl IntoIterator<Item=NoteMetadata>,
     ) -> Result<Vec<Option<NoteInfo>>, StorageError> {
         debug!("getting note details for user \"{username}\"");
+        let notes = notes.into_iter().collect::<Vec<_>>();
+        if notes.is_empty() {
+            return Ok(Vec::new())
+        }
+        let task_summaries = self.get_task_summaries(username)
+            .await
+            .map(Some)
+            .unwrap_or_else(|e| {
+                error!("failed to get the task summaries for user \"{username}\": {e}");
+                None
+            });
         Ok(
             send_fut_lifetime_workaround(join_all(
                 notes.into_iter()
@@ -235,7 +548,7 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
                             "open note {} for user \"{username}\", reading",
                             nm.id,
                         );
-                        let buf = send_fut_lifetime_workaround(read_limited_utf8_lossy(self.max_note_name_len, file))
+                        let buf = send_fut_lifetime_workaround(self.read_note_data(username, nm.id, file, self.max_note_name_len))
                             .await
                             .map(Some)
                             .unwrap_or_else(|e| {
@@ -248,14 +561,28 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
                         let name = buf.split_once('\n')
                             .map(|(name, _)| name)
                             .unwrap_or(&buf);
+                        let name = self.truncate_note_name(name);
                         trace!(
                             "parsed note title \"{name}\" of note {} for user \"{username}\"",
                             nm.id,
                         );
+                        let tags = send_fut_lifetime_workaround(self.read_note_tags(username, nm.id))
+                            .await
+                            .unwrap_or_else(|e| {
+                                error!(
+                                    "failed to read tags of note {} for user \"{username}\": {e}",
+                                    nm.id,
+                                );
+                                BTreeSet::new()
+                            });
                         Some(
                             NoteInfo {
                                 metadata: nm,
                                 name: name.nonblank_to_some(),
+                                tags,
+                                tasks: task_summaries
+                                    .as_ref()
+                                    .and_then(|tasks| tasks.get(&nm.id).copied()),
                             }
                         )
                     })
@@ -263,22 +590,15 @@ impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
         )
     }
 
-    pub async fn delete_note(
-        &self,
-        username: &UsernameStr,
-        id: Uuid,
-    ) -> Result<(), StorageError> {
-        debug!("deleting note {id} for user \"{username}\"");
-            self.io
-                .remove_file(self.get_note_path(username, id))
-                .await
-                .map_err(|e|
-                    if e.kind() == ErrorKind::NotFound {
-                        StorageError::NoteNotFound
-                    } else {
-                        e.into()
-                    }
-                )
+    /// Events of all users, a lagging receiver misses them
+    pub fn subscribe_events(&self) -> broadcast::Receiver<(UsernameString, NoteEvent)> {
+        self.events.subscribe()
+    }
+
+    fn publish_event(&self, username: &UsernameStr, event: NoteEvent) {
+        trace!("publishing event {event:?} for user \"{username}\"");
+        // fails only when nobody's listening
+        let _ = self.events.send((username.to_owned(), event));
     }
 
     fn get_user_dir(&self, username: &UsernameStr) -> PathBuf {
@@ -320,13 +640,15 @@ async fn read_limited_utf8_lossy<R: io::AsyncRead + Unpin + Send>(
     limit: u64,
     reader: R,
 ) -> Result<String, io::Error> {
-    // TODO: reimplement manually to log trimming and lossy conversions
     let mut buf = Vec::with_capacity(limit as usize);
     io::BufReader::new(reader).take(limit).read_to_end(&mut buf).await?;
-    Ok(
-        String::from_utf8_lossy(&buf)
-            .replace(std::char::REPLACEMENT_CHARACTER, "")
-    )
+    Ok(utf8_lossy(&buf))
+}
+
+fn utf8_lossy(buf: &[u8]) -> String {
+    // TODO: reimplement manually to log trimming and lossy conversions
+    String::from_utf8_lossy(buf)
+        .replace(std::char::REPLACEMENT_CHARACTER, "")
 }
 
 fn format_note(note: &Note) -> String {
@@ -0,0 +1,89 @@
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use log::error;
+use tokio::io::AsyncReadExt;
+
+use crate::storage::errors::StorageError;
+use crate::storage::io_trait::NoteStorageIo;
+use crate::storage::NoteStorageImpl;
+
+const ARCHIVE_TIME_PREFIX: &str = ".archived-at.";
+const MAX_ARCHIVE_TIME_FILE_LEN: u64 = 64;
+
+// The ctime of a trashed note or a revision tells when it was archived.
+// Replacing the file, as the encryption migration does, would reset it,
+// so the original time is then kept in `.archived-at.<file name>` next
+// to the file, along with the ctime the new file got. The recorded time
+// holds only as long as the file's ctime stays the same: a note trashed
+// again after a restore, or a version relinked, gets archived anew.
+#[allow(private_bounds)]
+impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
+    /// Returns when the trashed note or revision at the path was archived
+    pub(super) async fn read_archive_time(
+        &self,
+        path: &Path,
+    ) -> Result<i64, StorageError> {
+        let metadata = self.io.metadata(path).await?;
+        let archive_time_path = get_archive_time_path(path);
+        let file = match self.io.open_file(&archive_time_path).await {
+            Ok(file) => file.file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(metadata.ctime),
+            Err(e) => return Err(e.into()),
+        };
+        let mut data = String::new();
+        file.take(MAX_ARCHIVE_TIME_FILE_LEN).read_to_string(&mut data).await?;
+        let Some([archived_at, ctime, ctime_nsec]) = data
+            .split_whitespace()
+            .map(|value| value.parse::<i64>().ok())
+            .collect::<Option<Vec<_>>>()
+            .and_then(|values| <[i64; 3]>::try_from(values).ok())
+        else {
+            error!(
+                "archive time file \"{}\" is corrupted",
+                archive_time_path.display(),
+            );
+            return Ok(metadata.ctime)
+        };
+        if (ctime, ctime_nsec) != (metadata.ctime, metadata.ctime_nsec) {
+            return Ok(metadata.ctime)
+        }
+        Ok(archived_at)
+    }
+
+    /// Records the archive time of the file at the path, to be called once
+    /// the file won't change anymore
+    pub(super) async fn write_archive_time(
+        &self,
+        path: &Path,
+        archived_at: i64,
+    ) -> Result<(), StorageError> {
+        let metadata = self.io.metadata(path).await?;
+        self.io
+            .write_file(
+                get_archive_time_path(path),
+                format!("{archived_at} {} {}\n", metadata.ctime, metadata.ctime_nsec),
+            )
+            .await?;
+        Ok(())
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn remove_archive_time(
+        &self,
+        path: &Path,
+    ) -> Result<(), StorageError> {
+        match self.io.remove_file(get_archive_time_path(path)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+pub(super) fn get_archive_time_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(ARCHIVE_TIME_PREFIX);
+    name.push(path.file_name().unwrap_or_default());
+    path.with_file_name(name)
+}
@@ -0,0 +1,450 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce};
+use data::{UsernameStr, UsernameString};
+use dumbnotes::bin_constants::NOTE_ENCRYPTION_KEY_LENGTH;
+use log::{debug, error, info, trace};
+use rand::Rng;
+use thiserror::Error;
+use tokio::io;
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+use crate::app_constants::{REVISIONS_DIRECTORY_PATH, TAGS_DIRECTORY_PATH};
+use crate::storage::errors::StorageError;
+use crate::storage::io_trait::NoteStorageIo;
+use crate::storage::{read_limited_utf8_lossy, utf8_lossy, NoteStorageImpl, HYPHENED_UUID_SIZE, TMP_FILENAME_INFIX};
+
+// An encrypted note file is the magic, the format version, the nonce and
+// the ciphertext with the tag appended. Anything not starting with the
+// magic is a legacy plaintext note, which can't start with 0xff being
+// valid UTF-8.
+const ENCRYPTED_NOTE_MAGIC: &[u8] = b"\xffDNE";
+const ENCRYPTED_NOTE_VERSION: u8 = 1;
+const NONCE_SIZE: usize = 24;
+const TAG_SIZE: usize = 16;
+const ENCRYPTED_NOTE_HEADER_SIZE: usize =
+    ENCRYPTED_NOTE_MAGIC.len() + 1 + NONCE_SIZE;
+pub(super) const ENCRYPTED_NOTE_OVERHEAD: u64 =
+    (ENCRYPTED_NOTE_HEADER_SIZE + TAG_SIZE) as u64;
+
+pub struct NoteCipher {
+    cipher: XChaCha20Poly1305,
+}
+
+#[derive(Debug, Error)]
+pub enum NoteKeyError {
+    #[error("note encryption key has length {0}, expected {NOTE_ENCRYPTION_KEY_LENGTH}")]
+    InvalidLength(usize),
+}
+
+impl NoteCipher {
+    pub fn new(key: &[u8]) -> Result<NoteCipher, NoteKeyError> {
+        Ok(
+            NoteCipher {
+                cipher: XChaCha20Poly1305::new_from_slice(key)
+                    .map_err(|_| NoteKeyError::InvalidLength(key.len()))?,
+            }
+        )
+    }
+
+    // the ciphertext is bound to the file it belongs to by the aad, so that
+    // files can't be swapped between notes or users unnoticed
+    fn encrypt(
+        &self,
+        aad: &str,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, StorageError> {
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::rng().fill_bytes(&mut nonce);
+        let ciphertext = self.cipher
+            .encrypt(
+                &XNonce::from(nonce),
+                Payload { msg: plaintext, aad: aad.as_bytes() },
+            )
+            .map_err(|_| StorageError::Encryption)?;
+        let mut ret = Vec::with_capacity(
+            ENCRYPTED_NOTE_HEADER_SIZE + ciphertext.len()
+        );
+        ret.extend_from_slice(ENCRYPTED_NOTE_MAGIC);
+        ret.push(ENCRYPTED_NOTE_VERSION);
+        ret.extend_from_slice(&nonce);
+        ret.extend_from_slice(&ciphertext);
+        Ok(ret)
+    }
+
+    fn decrypt(
+        &self,
+        aad: &str,
+        data: &[u8],
+    ) -> Result<Vec<u8>, StorageError> {
+        let rest = data.strip_prefix(ENCRYPTED_NOTE_MAGIC)
+            .ok_or(StorageError::Decryption)?;
+        let (nonce, ciphertext) = match rest.split_first() {
+            Some((&ENCRYPTED_NOTE_VERSION, rest)) if rest.len() >= NONCE_SIZE =>
+                rest.split_at(NONCE_SIZE),
+            Some((&version, _)) if version != ENCRYPTED_NOTE_VERSION =>
+                return Err(StorageError::UnsupportedNoteFormat(version)),
+            _ => return Err(StorageError::Decryption),
+        };
+        self.cipher
+            .decrypt(
+                &XNonce::try_from(nonce).map_err(|_| StorageError::Decryption)?,
+                Payload { msg: ciphertext, aad: aad.as_bytes() },
+            )
+            .map_err(|_| StorageError::Decryption)
+    }
+}
+
+impl Debug for NoteCipher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("NoteCipher")
+    }
+}
+
+#[allow(private_bounds)]
+impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
+    /// Encrypts the legacy plaintext note files of all users in place,
+    /// including the trash, the revisions and the tags, returning their
+    /// number. Plaintext search indexes are dropped to be rebuilt.
+    /// Replacing the files resets their ctime, so the archive times of
+    /// trashed notes and revisions get recorded, and files sharing
+    /// an inode, like a note and its newest revision, stay linked.
+    pub async fn encrypt_plaintext_notes(&self) -> Result<u64, StorageError> {
+        let Some(ref cipher) = self.cipher else {
+            return Err(StorageError::EncryptionKeyMissing)
+        };
+        let _write_guard = self.write_lock.lock().await;
+        let mut count = 0;
+        let mut read = self.io.read_dir(&self.basedir).await?;
+        while let Some(entry) = read.next_entry().await? {
+            let Some(username) = entry.file_name()
+                .to_str()
+                .and_then(|name| UsernameString::from_str(name).ok())
+            else {
+                trace!("skipping notes dir entry \"{entry:?}\"");
+                continue
+            };
+            if !entry.file_type().await?.is_dir() {
+                continue
+            }
+            let user_count = self
+                .encrypt_user_plaintext_notes(cipher, &username)
+                .await?;
+            if user_count > 0 {
+                info!("encrypted {user_count} note files of user \"{username}\"");
+            }
+            count += user_count;
+        }
+        // the encrypted files are larger
+        self.forget_cached_usage();
+        Ok(count)
+    }
+
+    async fn encrypt_user_plaintext_notes(
+        &self,
+        cipher: &NoteCipher,
+        username: &UsernameStr,
+    ) -> Result<u64, StorageError> {
+        // live notes aren't archived
+        let mut files = self.list_note_files(self.get_user_dir(username))
+            .await?
+            .into_iter()
+            .map(|(note_id, path)| (note_id, path, false))
+            .collect::<Vec<_>>();
+        files.extend(
+            self.list_note_files(self.get_trash_dir(username))
+                .await?
+                .into_iter()
+                .map(|(note_id, path)| (note_id, path, true))
+        );
+        for (note_id, revisions_dir) in self
+            .list_note_files(
+                self.get_user_dir(username).join(REVISIONS_DIRECTORY_PATH),
+            )
+            .await?
+        {
+            let mut read = self.io.read_dir(&revisions_dir).await?;
+            while let Some(entry) = read.next_entry().await? {
+                if entry.file_name().to_str()
+                    .is_some_and(|name| name.parse::<i64>().is_ok())
+                {
+                    files.push((note_id, entry.path(), true));
+                }
+            }
+        }
+        // read up front, replacing a file changes the ctime of the other
+        // files sharing its inode
+        let mut note_files = Vec::with_capacity(files.len());
+        for (note_id, path, archived) in files {
+            let ino = self.io.metadata(&path).await?.ino;
+            let archived_at = match archived {
+                true => Some(self.read_archive_time(&path).await?),
+                false => None,
+            };
+            note_files.push((note_id, path, ino, archived_at));
+        }
+        let mut encrypted = HashMap::<(Uuid, u64), PathBuf>::new();
+        let mut archive_times = Vec::new();
+        let mut count = 0;
+        for (note_id, path, ino, archived_at) in note_files {
+            let replaced = match encrypted.get(&(note_id, ino)) {
+                Some(encrypted_path) => {
+                    self.replace_with_link(encrypted_path, &path).await?;
+                    true
+                },
+                None => {
+                    let aad = make_note_aad(username, note_id);
+                    let replaced = self
+                        .encrypt_plaintext_file(cipher, &aad, &path)
+                        .await?;
+                    if replaced {
+                        encrypted.insert((note_id, ino), path.clone());
+                    }
+                    replaced
+                },
+            };
+            if !replaced {
+                continue
+            }
+            count += 1;
+            if let Some(archived_at) = archived_at {
+                archive_times.push((path, archived_at));
+            }
+        }
+        // the ctimes are final once all the links are made
+        for (path, archived_at) in archive_times {
+            self.write_archive_time(&path, archived_at).await?;
+        }
+        let mut tags_files = self.list_note_files(self.get_tags_dir(username)).await?;
+        tags_files.extend(
+            self.list_note_files(
+                self.get_trash_dir(username).join(TAGS_DIRECTORY_PATH),
+            ).await?
+        );
+        for (note_id, path) in tags_files {
+            let aad = make_user_file_aad(username, &make_tags_file_kind(note_id));
+            if self.encrypt_plaintext_file(cipher, &aad, &path).await? {
+                count += 1;
+            }
+        }
+        self.remove_plaintext_search_index(username).await?;
+        Ok(count)
+    }
+
+    async fn encrypt_plaintext_file(
+        &self,
+        cipher: &NoteCipher,
+        aad: &str,
+        path: &Path,
+    ) -> Result<bool, StorageError> {
+        let file = self.io.open_file(path).await?;
+        let mtime = file.mtime;
+        let mut data = Vec::with_capacity(file.size as usize);
+        io::BufReader::new(file.file).read_to_end(&mut data).await?;
+        if is_encrypted(&data) {
+            return Ok(false)
+        }
+        trace!("encrypting file \"{}\"", path.display());
+        let tmp_path = self.make_migration_tmp_path(path);
+        self.io
+            .write_file(&tmp_path, cipher.encrypt(aad, &data)?)
+            .await?;
+        let result = match self.io.set_file_mtime(&tmp_path, mtime).await {
+            Ok(()) => self.io.rename_file(&tmp_path, path).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            if let Err(e) = self.io.remove_file(&tmp_path).await {
+                error!(
+                    "failed to remove tmp file \"{}\": {e}",
+                    tmp_path.display(),
+                );
+            }
+            return Err(e.into())
+        }
+        Ok(true)
+    }
+
+    async fn replace_with_link(
+        &self,
+        from: &Path,
+        path: &Path,
+    ) -> Result<(), StorageError> {
+        trace!(
+            "linking encrypted file \"{}\" to \"{}\"",
+            from.display(),
+            path.display(),
+        );
+        let tmp_path = self.make_migration_tmp_path(path);
+        self.io.hard_link_file(from, &tmp_path).await?;
+        if let Err(e) = self.io.rename_file(&tmp_path, path).await {
+            if let Err(e) = self.io.remove_file(&tmp_path).await {
+                error!(
+                    "failed to remove tmp file \"{}\": {e}",
+                    tmp_path.display(),
+                );
+            }
+            return Err(e.into())
+        }
+        Ok(())
+    }
+
+    fn make_migration_tmp_path(&self, path: &Path) -> PathBuf {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(TMP_FILENAME_INFIX);
+        tmp_path.push(self.io.generate_uuid().hyphenated().to_string());
+        PathBuf::from(tmp_path)
+    }
+
+    async fn remove_plaintext_search_index(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<(), StorageError> {
+        let path = self.get_search_index_path(username);
+        let mut header = Vec::with_capacity(ENCRYPTED_NOTE_MAGIC.len());
+        match self.io.open_file(&path).await {
+            Ok(file) => file.file
+                .take(ENCRYPTED_NOTE_MAGIC.len() as u64)
+                .read_to_end(&mut header)
+                .await?,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        if !is_encrypted(&header) {
+            trace!("removing the plaintext search index of user \"{username}\"");
+            self.io.remove_file(&path).await?;
+        }
+        Ok(())
+    }
+
+    async fn list_note_files(
+        &self,
+        dir: PathBuf,
+    ) -> Result<Vec<(Uuid, PathBuf)>, StorageError> {
+        let mut read = match self.io.read_dir(&dir).await {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut ret = Vec::new();
+        while let Some(entry) = read.next_entry().await? {
+            if entry.file_name().len() != HYPHENED_UUID_SIZE {
+                continue
+            }
+            if let Some(id) = Self::try_extract_uuid(entry.file_name()) {
+                ret.push((id, entry.path()));
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Encrypts the formatted note if a key is configured
+    pub(super) fn encode_note_file(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+        formatted: String,
+    ) -> Result<Vec<u8>, StorageError> {
+        match self.cipher {
+            Some(ref cipher) => cipher.encrypt(
+                &make_note_aad(username, note_id),
+                formatted.as_bytes(),
+            ),
+            None => Ok(formatted.into_bytes()),
+        }
+    }
+
+    /// Encrypts a file of the user other than a note if a key is
+    /// configured, `kind` being its path relative to the user dir
+    pub(super) fn encode_user_file(
+        &self,
+        username: &UsernameStr,
+        kind: &str,
+        data: String,
+    ) -> Result<Vec<u8>, StorageError> {
+        match self.cipher {
+            Some(ref cipher) => cipher.encrypt(
+                &make_user_file_aad(username, kind),
+                data.as_bytes(),
+            ),
+            None => Ok(data.into_bytes()),
+        }
+    }
+
+    /// Decrypts a file of the user other than a note, passing legacy
+    /// plaintext files through
+    pub(super) fn decode_user_file(
+        &self,
+        username: &UsernameStr,
+        kind: &str,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, StorageError> {
+        if !is_encrypted(&data) {
+            return Ok(data)
+        }
+        let Some(ref cipher) = self.cipher else {
+            debug!(
+                "file \"{kind}\" of user \"{username}\" is encrypted, \
+                    but no key is configured"
+            );
+            return Err(StorageError::EncryptionKeyMissing)
+        };
+        cipher.decrypt(&make_user_file_aad(username, kind), &data)
+    }
+
+    /// Reads up to `limit` bytes of the note, decrypting it if needed
+    pub(super) async fn read_note_data<R: io::AsyncRead + Unpin + Send>(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+        reader: R,
+        limit: u64,
+    ) -> Result<String, StorageError> {
+        let mut reader = io::BufReader::new(reader);
+        let mut data = Vec::with_capacity(ENCRYPTED_NOTE_HEADER_SIZE);
+        (&mut reader)
+            .take(ENCRYPTED_NOTE_HEADER_SIZE as u64)
+            .read_to_end(&mut data)
+            .await?;
+        if !is_encrypted(&data) {
+            return Ok(read_limited_utf8_lossy(limit, data.as_slice().chain(reader)).await?)
+        }
+        let Some(ref cipher) = self.cipher else {
+            debug!(
+                "note {note_id} for user \"{username}\" is encrypted, \
+                    but no key is configured"
+            );
+            return Err(StorageError::EncryptionKeyMissing)
+        };
+        reader
+            .take(self.max_note_len + ENCRYPTED_NOTE_OVERHEAD)
+            .read_to_end(&mut data)
+            .await?;
+        let plaintext = cipher.decrypt(&make_note_aad(username, note_id), &data)?;
+        Ok(utf8_lossy(&plaintext[..plaintext.len().min(limit as usize)]))
+    }
+}
+
+pub(super) fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(ENCRYPTED_NOTE_MAGIC)
+}
+
+pub(super) fn make_tags_file_kind(note_id: Uuid) -> String {
+    // the same in the trash, the file moves along with the note
+    format!("{TAGS_DIRECTORY_PATH}/{}", note_id.hyphenated())
+}
+
+fn make_note_aad(username: &UsernameStr, note_id: Uuid) -> String {
+    format!("{username}/{}", note_id.hyphenated())
+}
+
+// can't clash with the notes, their ids don't start with a dot
+fn make_user_file_aad(username: &UsernameStr, kind: &str) -> String {
+    format!("{username}/{kind}")
+}
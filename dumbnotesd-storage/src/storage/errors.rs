@@ -1,3 +1,4 @@
+use data::VersionedNote;
 use thiserror::Error;
 use time::error::ComponentRange;
 use tokio::io::Error as IoError;
@@ -20,6 +21,36 @@ pub enum StorageError {
     #[error("note not found")]
     NoteNotFound,
 
+    #[error("user not found")]
+    UserNotFound,
+
+    #[error("not permitted by the note's shares")]
+    Forbidden,
+
+    #[error("task not found in the note")]
+    TaskNotFound,
+
+    #[error("note was modified since the base version")]
+    Conflict(Box<VersionedNote>),
+
+    #[error("note already exists")]
+    NoteExists,
+
+    #[error("storage quota exceeded")]
+    QuotaExceeded,
+
+    #[error("note is encrypted, but no encryption key is configured")]
+    EncryptionKeyMissing,
+
+    #[error("failed to encrypt note")]
+    Encryption,
+
+    #[error("failed to decrypt note")]
+    Decryption,
+
+    #[error("unsupported note file format version {0}")]
+    UnsupportedNoteFormat(u8),
+
     #[error(transparent)]
     CheckAccessError(CheckAccessError),
 }
@@ -2,8 +2,9 @@ use util::make_uuid;
 use async_trait::async_trait;
 use std::os::unix::prelude::*;
 use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
 use libc::{gid_t, uid_t};
-use tokio::{fs, io};
+use tokio::{fs, io, task};
 use uuid::Uuid;
 use unix::check_dir_rw_access;
 use unix::errors::CheckAccessError;
@@ -29,6 +30,12 @@ pub trait NoteStorageIo: Send + Sync
         data: impl AsRef<[u8]> + Send,
     ) -> io::Result<()>;
 
+    async fn set_file_mtime(
+        &self,
+        path: impl AsRef<Path> + Send,
+        mtime: i64,
+    ) -> io::Result<()>;
+
     async fn rename_file(
         &self,
         from: impl AsRef<Path> + Send,
@@ -39,6 +46,22 @@ pub trait NoteStorageIo: Send + Sync
         &self,
         path: impl AsRef<Path> + Send,
     ) -> io::Result<()>;
+
+    async fn hard_link_file(
+        &self,
+        from: impl AsRef<Path> + Send,
+        to: impl AsRef<Path> + Send,
+    ) -> io::Result<()>;
+
+    async fn create_dir_all(
+        &self,
+        path: impl AsRef<Path> + Send,
+    ) -> io::Result<()>;
+
+    async fn remove_dir_all(
+        &self,
+        path: impl AsRef<Path> + Send,
+    ) -> io::Result<()>;
     
     // TODO: get ReadDir behind a facade to make it properly testable
     async fn read_dir(
@@ -58,12 +81,20 @@ pub struct Metadata {
     pub is_dir: bool,
     pub uid: uid_t,
     pub gid: gid_t,
+    pub mtime: i64,
+    pub size: u64,
+    pub ino: u64,
+    pub ctime: i64,
+    pub ctime_nsec: i64,
 }
 
 pub struct OpenFile<F: io::AsyncRead + Unpin + Send + Sync> {
     pub file: F,
     pub size: u64,
     pub mtime: i64,
+    pub ino: u64,
+    pub ctime: i64,
+    pub ctime_nsec: i64,
 }
 
 pub struct ProductionNoteStorageIo;
@@ -85,6 +116,11 @@ impl NoteStorageIo for ProductionNoteStorageIo {
             is_dir: meta.is_dir(),
             uid: meta.uid(),
             gid: meta.gid(),
+            mtime: meta.mtime(),
+            size: meta.len(),
+            ino: meta.ino(),
+            ctime: meta.ctime(),
+            ctime_nsec: meta.ctime_nsec(),
         })
     }
 
@@ -99,6 +135,9 @@ impl NoteStorageIo for ProductionNoteStorageIo {
                 file,
                 size: metadata.len(),
                 mtime: metadata.mtime(),
+                ino: metadata.ino(),
+                ctime: metadata.ctime(),
+                ctime_nsec: metadata.ctime_nsec(),
             }
         )
     } }
@@ -111,6 +150,23 @@ impl NoteStorageIo for ProductionNoteStorageIo {
         fs::write(path, data).await
     }
 
+    async fn set_file_mtime(
+        &self,
+        path: impl AsRef<Path> + Send,
+        mtime: i64,
+    ) -> io::Result<()> {
+        let path = path.as_ref().to_owned();
+        let mtime = UNIX_EPOCH
+            .checked_add(Duration::from_secs(mtime.max(0) as u64))
+            .ok_or(io::Error::from(io::ErrorKind::InvalidInput))?;
+        task::spawn_blocking(move ||
+            std::fs::File::options()
+                .write(true)
+                .open(path)?
+                .set_modified(mtime)
+        ).await?
+    }
+
     async fn rename_file(
         &self,
         from: impl AsRef<Path> + Send,
@@ -125,6 +181,28 @@ impl NoteStorageIo for ProductionNoteStorageIo {
     ) -> io::Result<()> {
         fs::remove_file(path).await
     }
+
+    async fn hard_link_file(
+        &self,
+        from: impl AsRef<Path> + Send,
+        to: impl AsRef<Path> + Send,
+    ) -> io::Result<()> {
+        fs::hard_link(from, to).await
+    }
+
+    async fn create_dir_all(
+        &self,
+        path: impl AsRef<Path> + Send,
+    ) -> io::Result<()> {
+        fs::create_dir_all(path).await
+    }
+
+    async fn remove_dir_all(
+        &self,
+        path: impl AsRef<Path> + Send,
+    ) -> io::Result<()> {
+        fs::remove_dir_all(path).await
+    }
     
     async fn read_dir(
         &self,
@@ -0,0 +1,372 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use data::{
+    NoteChanges, NoteInfo, NoteListQuery, NoteMetadata, NoteTombstone, SyncToken,
+    UsernameStr,
+};
+use log::{debug, error, trace};
+use time::UtcDateTime;
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+use crate::app_constants::{JOURNAL_PATH, JOURNAL_TOMBSTONE_MAX_AGE};
+use crate::storage::errors::StorageError;
+use crate::storage::io_trait::NoteStorageIo;
+use crate::storage::NoteStorageImpl;
+
+const JOURNAL_HEADER: &str = "dumbnotes journal 1";
+const JOURNAL_TMP_SUFFIX: &str = ".tmp";
+
+// The change journal of a user lives in `<user dir>/.journal`, recording
+// the latest write or deletion of every note with an increasing sequence
+// number. Older entries for the same note are dropped on every append,
+// and so are tombstones older than `JOURNAL_TOMBSTONE_MAX_AGE`, raising
+// the floor below which sync tokens are no longer usable. A missing or
+// unreadable journal gets rebuilt from the note files with a new
+// generation, forcing clients to resync from scratch.
+#[derive(Debug)]
+struct Journal {
+    generation: Uuid,
+    floor: u64,
+    last_seq: u64,
+    entries: Vec<JournalEntry>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct JournalEntry {
+    seq: u64,
+    kind: ChangeKind,
+    id: Uuid,
+    time: i64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ChangeKind {
+    Written,
+    Deleted,
+}
+
+#[allow(private_bounds)]
+impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
+    /// Lists the changes since `since`, or all the notes if the token is
+    /// absent or no longer usable
+    pub async fn list_changes(
+        &self,
+        username: &UsernameStr,
+        since: Option<SyncToken>,
+        limit: Option<u32>,
+    ) -> Result<NoteChanges, StorageError> {
+        debug!("listing changes for user \"{username}\" since {since:?}");
+        let journal = match self.load_journal(username).await? {
+            Some(journal) => journal,
+            None => self.rebuild_journal(username).await?,
+        };
+        let max_limit = self.page_limits.note_list;
+        let limit = limit
+            .map_or(max_limit, |limit| limit.min(max_limit))
+            as usize;
+        // a full listing needs the tombstones from its start on, for
+        // the notes deleted after being listed on an earlier page
+        let (from_seq, snapshot_start) = match since {
+            Some(token) if token.generation == journal.generation
+                && token.seq <= journal.last_seq
+                && token.snapshot_start.unwrap_or(token.seq) >= journal.floor
+                => (token.seq, token.snapshot_start),
+            Some(token) => {
+                trace!("sync token {token:?} is no longer usable");
+                (0, Some(journal.last_seq))
+            },
+            None => (0, Some(journal.last_seq)),
+        };
+        let mut entries = journal.entries
+            .iter()
+            .filter(|entry| entry.seq > from_seq)
+            .filter(|entry| entry.kind == ChangeKind::Written
+                || snapshot_start.is_none_or(|start| entry.seq > start)
+            )
+            .collect::<Vec<_>>();
+        let more = entries.len() > limit;
+        entries.truncate(limit);
+        let seq = match more {
+            true => entries.last().map(|entry| entry.seq).unwrap_or(from_seq),
+            false => journal.last_seq,
+        };
+        let mut ret = NoteChanges {
+            updated: Vec::new(),
+            deleted: Vec::new(),
+            sync_token: SyncToken {
+                generation: journal.generation,
+                seq,
+                snapshot_start: snapshot_start.filter(|_| more),
+            },
+            more,
+            reset: snapshot_start.is_some(),
+        };
+        for entry in entries {
+            match entry.kind {
+                ChangeKind::Written => {
+                    let name = match self
+                        .read_note_name(
+                            username,
+                            entry.id,
+                            self.get_note_path(username, entry.id),
+                        )
+                        .await
+                    {
+                        Ok(name) => name,
+                        Err(StorageError::Io(e)) if e.kind() == ErrorKind::NotFound => {
+                            trace!("skipping journaled but missing note {}", entry.id);
+                            continue
+                        },
+                        Err(e) => return Err(e),
+                    };
+                    ret.updated.push(
+                        NoteInfo {
+                            metadata: NoteMetadata {
+                                id: entry.id,
+                                mtime: UtcDateTime::from_unix_timestamp(entry.time)?,
+                            },
+                            name,
+                            tags: self.read_note_tags(username, entry.id).await?,
+                            tasks: None,
+                        }
+                    );
+                },
+                ChangeKind::Deleted => ret.deleted.push(
+                    NoteTombstone {
+                        id: entry.id,
+                        deleted_at: UtcDateTime::from_unix_timestamp(entry.time)?,
+                    }
+                ),
+            }
+        }
+        Ok(ret)
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn journal_write(
+        &self,
+        username: &UsernameStr,
+        metadata: NoteMetadata,
+    ) {
+        self.append_journal(
+            username,
+            metadata.id,
+            ChangeKind::Written,
+            metadata.mtime.unix_timestamp(),
+        ).await
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn journal_deletion(
+        &self,
+        username: &UsernameStr,
+        tombstone: NoteTombstone,
+    ) {
+        self.append_journal(
+            username,
+            tombstone.id,
+            ChangeKind::Deleted,
+            tombstone.deleted_at.unix_timestamp(),
+        ).await
+    }
+
+    async fn append_journal(
+        &self,
+        username: &UsernameStr,
+        id: Uuid,
+        kind: ChangeKind,
+        time: i64,
+    ) {
+        let result: Result<(), StorageError> = async {
+            // a missing journal gets rebuilt on the next listing
+            let Some(mut journal) = self.load_journal(username).await? else {
+                return Ok(())
+            };
+            journal.push(id, kind, time);
+            journal.expire_tombstones(
+                (UtcDateTime::now() - JOURNAL_TOMBSTONE_MAX_AGE).unix_timestamp(),
+            );
+            self.save_journal(username, &journal).await
+        }.await;
+        if let Err(e) = result {
+            error!(
+                "failed to update the change journal for user \"{username}\", \
+                    dropping it: {e}"
+            );
+            match self.io.remove_file(self.get_journal_path(username)).await {
+                Ok(()) => {},
+                Err(e) if e.kind() == ErrorKind::NotFound => {},
+                Err(e) => error!(
+                    "failed to remove the change journal for user \"{username}\": {e}"
+                ),
+            }
+        }
+    }
+
+    async fn load_journal(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Option<Journal>, StorageError> {
+        let mut file = match self.io
+            .open_file(self.get_journal_path(username))
+            .await
+        {
+            Ok(file) => file.file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut data = String::new();
+        match file.read_to_string(&mut data).await {
+            Ok(_) => {},
+            Err(e) if e.kind() == ErrorKind::InvalidData => {
+                error!("change journal for user \"{username}\" is not valid utf-8");
+                return Ok(None)
+            },
+            Err(e) => return Err(e.into()),
+        }
+        let journal = Journal::parse(&data);
+        if journal.is_none() {
+            error!("change journal for user \"{username}\" is corrupted");
+        }
+        Ok(journal)
+    }
+
+    async fn rebuild_journal(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Journal, StorageError> {
+        let _write_guard = self.write_lock.lock().await;
+        // might have been rebuilt while waiting for the lock
+        if let Some(journal) = self.load_journal(username).await? {
+            return Ok(journal)
+        }
+        debug!("rebuilding the change journal for user \"{username}\"");
+        let mut journal = Journal {
+            generation: self.io.generate_uuid(),
+            floor: 0,
+            last_seq: 0,
+            entries: Vec::new(),
+        };
+        let mut query = NoteListQuery::default();
+        loop {
+            let page = self.list_notes(username, &query).await?;
+            for metadata in page.notes {
+                journal.push(
+                    metadata.id,
+                    ChangeKind::Written,
+                    metadata.mtime.unix_timestamp(),
+                );
+            }
+            match page.next {
+                Some(next) => query.after = Some(next),
+                None => break,
+            }
+        }
+        self
+            .track_usage(username, None, self.save_journal(username, &journal))
+            .await?;
+        Ok(journal)
+    }
+
+    // must be called with the write lock held
+    async fn save_journal(
+        &self,
+        username: &UsernameStr,
+        journal: &Journal,
+    ) -> Result<(), StorageError> {
+        let path = self.get_journal_path(username);
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(JOURNAL_TMP_SUFFIX);
+        self.io.write_file(&tmp_path, journal.format()).await?;
+        self.io.rename_file(&tmp_path, &path).await?;
+        Ok(())
+    }
+
+    pub(super) fn get_journal_path(&self, username: &UsernameStr) -> PathBuf {
+        self.get_user_dir(username).join(JOURNAL_PATH)
+    }
+}
+
+impl Journal {
+    fn parse(data: &str) -> Option<Journal> {
+        let mut lines = data.lines();
+        let mut header = lines.next()?
+            .strip_prefix(JOURNAL_HEADER)?
+            .strip_prefix(' ')?
+            .split(' ');
+        let generation = Uuid::try_parse(header.next()?).ok()?;
+        let floor = header.next()?.parse().ok()?;
+        let last_seq = header.next()?.parse().ok()?;
+        if header.next().is_some() {
+            return None
+        }
+        let mut entries = Vec::new();
+        for line in lines {
+            let mut fields = line.split(' ');
+            let entry = JournalEntry {
+                seq: fields.next()?.parse().ok()?,
+                kind: match fields.next()? {
+                    "w" => ChangeKind::Written,
+                    "d" => ChangeKind::Deleted,
+                    _ => return None,
+                },
+                id: Uuid::try_parse(fields.next()?).ok()?,
+                time: fields.next()?.parse().ok()?,
+            };
+            if fields.next().is_some() || entry.seq > last_seq {
+                return None
+            }
+            entries.push(entry);
+        }
+        if !entries.is_sorted_by_key(|entry| entry.seq) {
+            return None
+        }
+        Some(Journal { generation, floor, last_seq, entries })
+    }
+
+    fn format(&self) -> String {
+        let mut ret = format!(
+            "{JOURNAL_HEADER} {} {} {}\n",
+            self.generation.hyphenated(),
+            self.floor,
+            self.last_seq,
+        );
+        for entry in &self.entries {
+            ret.push_str(
+                &format!(
+                    "{} {} {} {}\n",
+                    entry.seq,
+                    match entry.kind {
+                        ChangeKind::Written => "w",
+                        ChangeKind::Deleted => "d",
+                    },
+                    entry.id.hyphenated(),
+                    entry.time,
+                )
+            );
+        }
+        ret
+    }
+
+    // only the latest entry for every note is kept
+    fn push(&mut self, id: Uuid, kind: ChangeKind, time: i64) {
+        self.last_seq += 1;
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.push(JournalEntry { seq: self.last_seq, kind, id, time });
+    }
+
+    fn expire_tombstones(&mut self, oldest_allowed: i64) {
+        let floor = &mut self.floor;
+        self.entries.retain(|entry| {
+            let expired = entry.kind == ChangeKind::Deleted
+                && entry.time < oldest_allowed;
+            if expired {
+                *floor = (*floor).max(entry.seq);
+            }
+            !expired
+        });
+    }
+}
+
@@ -0,0 +1,134 @@
+use data::{MAX_NOTE_TAGS, MAX_TAG_LEN};
+use dumbnotes::bin_constants::IPC_STORAGE_MESSAGE_MAX_SIZE;
+use prost::Message;
+use storage_ipc_data::bindings;
+
+use crate::app_constants::{MAX_NOTE_LIST_LIMIT, MAX_NOTE_SEARCH_LIMIT};
+use crate::storage::search::{MAX_QUERY_TERMS, MAX_SNIPPET_LEN};
+
+// takes the most bytes to encode
+const WIDEST_CHAR: char = '\u{10ffff}';
+// the page is nested at most three messages deep in the response, and
+// their length prefixes grow by up to 4 bytes each as it fills up
+const LENGTH_PREFIXES_GROWTH: usize = 3 * 4;
+
+/// The most notes a page may have for the response to fit in a storage
+/// IPC message, whatever the notes are
+#[derive(Clone, Copy, Debug)]
+pub(super) struct PageLimits {
+    /// Applies to the note changes too, and to the note details, which are
+    /// fetched a listed page at a time
+    pub note_list: u32,
+    pub note_search: u32,
+}
+
+impl PageLimits {
+    pub fn new(max_note_name_len: u64) -> PageLimits {
+        let note_list = fit_page(
+            &max_note_info(max_note_name_len),
+            [
+                bindings::response::Response::GetNoteDetails(
+                    bindings::GetNoteDetailsResponse {
+                        response: Some(
+                            bindings::get_note_details_response::Response::NotesInfo(
+                                bindings::MaybeNotesInfo::default()
+                            )
+                        ),
+                    }
+                ),
+                bindings::response::Response::ListNoteChanges(
+                    bindings::ListNoteChangesResponse {
+                        response: Some(
+                            bindings::list_note_changes_response::Response::Changes(
+                                bindings::NoteChanges {
+                                    updated: Vec::new(),
+                                    deleted: Vec::new(),
+                                    sync_token: Some(
+                                        bindings::SyncToken {
+                                            generation: vec![u8::MAX; 16],
+                                            seq: u64::MAX,
+                                            snapshot_start: Some(u64::MAX),
+                                        }
+                                    ),
+                                    more: true,
+                                    reset: true,
+                                }
+                            )
+                        ),
+                    }
+                ),
+            ],
+        );
+        let note_search = fit_page(
+            &max_search_result(max_note_name_len),
+            [
+                bindings::response::Response::SearchNotes(
+                    bindings::SearchNotesResponse {
+                        response: Some(
+                            bindings::search_notes_response::Response::Results(
+                                bindings::NoteSearchResults::default()
+                            )
+                        ),
+                        next: Some(max_note_metadata()),
+                    }
+                ),
+            ],
+        );
+        PageLimits {
+            note_list: note_list.min(MAX_NOTE_LIST_LIMIT),
+            note_search: note_search.min(MAX_NOTE_SEARCH_LIMIT),
+        }
+    }
+}
+
+// how many entries fit in the largest of the empty responses
+fn fit_page<const N: usize>(
+    entry: &impl Message,
+    empty_responses: [bindings::response::Response; N],
+) -> u32 {
+    let response_len = empty_responses
+        .into_iter()
+        .map(|response|
+            bindings::Response {
+                command_id: u64::MAX,
+                response: Some(response),
+            }.encoded_len()
+        )
+        .max()
+        .unwrap_or(0);
+    // the field tag of a repeated message takes a byte
+    let entry_len = 1 + prost::length_delimiter_len(entry.encoded_len()) + entry.encoded_len();
+    let available = IPC_STORAGE_MESSAGE_MAX_SIZE
+        .saturating_sub(response_len + LENGTH_PREFIXES_GROWTH);
+    // at least a note per page for the listings to make progress,
+    // even if an unreasonable note name length doesn't fit
+    (available / entry_len).clamp(1, u32::MAX as usize) as u32
+}
+
+pub(super) fn max_note_metadata() -> bindings::NoteMetadata {
+    bindings::NoteMetadata {
+        id: vec![u8::MAX; 16],
+        mtime: i64::MIN,
+    }
+}
+
+pub(super) fn max_note_info(max_note_name_len: u64) -> bindings::NoteInfo {
+    bindings::NoteInfo {
+        metadata: Some(max_note_metadata()),
+        name: Some("x".repeat(max_note_name_len as usize)),
+        tags: vec![WIDEST_CHAR.to_string().repeat(MAX_TAG_LEN); MAX_NOTE_TAGS],
+        tasks: Some(
+            bindings::TaskSummary {
+                done: u32::MAX,
+                total: u32::MAX,
+            }
+        ),
+    }
+}
+
+pub(super) fn max_search_result(max_note_name_len: u64) -> bindings::NoteSearchResult {
+    bindings::NoteSearchResult {
+        info: Some(max_note_info(max_note_name_len)),
+        snippets: vec![WIDEST_CHAR.to_string().repeat(MAX_SNIPPET_LEN); MAX_QUERY_TERMS],
+    }
+}
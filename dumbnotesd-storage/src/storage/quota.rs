@@ -0,0 +1,313 @@
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::os::unix::prelude::*;
+use std::path::Path;
+
+use data::{StorageUsage, UserQuota, UsernameStr};
+use log::{debug, error, trace};
+use uuid::Uuid;
+
+use crate::app_constants::{REVISIONS_DIRECTORY_PATH, TAGS_DIRECTORY_PATH};
+use crate::storage::archive_time::get_archive_time_path;
+use crate::storage::errors::StorageError;
+use crate::storage::io_trait::NoteStorageIo;
+use crate::storage::{NoteStorageImpl, HYPHENED_UUID_SIZE};
+
+#[derive(Clone, Copy, Debug)]
+pub struct QuotaLimits {
+    /// Zero means unlimited
+    pub max_storage_size: u64,
+    /// Zero means unlimited
+    pub max_notes: u32,
+}
+
+// The storage size counts every file kept for the user: the live and
+// the trashed notes, as the trash gets purged only after a while, their
+// revisions, and the tags, shares, archive time, search index and change
+// journal files.
+// Hard links are counted once, a revision sharing its data with the note
+// it was archived from. The note count only counts the live notes.
+// Writes that don't grow the note always go through, even though the
+// replaced version is kept as a revision, so that a user over a lowered
+// quota can still shrink their notes.
+//
+// The usage of a user is measured by walking their files on the first
+// quota check, then kept in `usage_cache` and updated with the difference
+// each change makes to the files of the note it touches and to the search
+// index and journal, measured before and after the change.
+#[allow(private_bounds)]
+impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
+    pub async fn get_usage(
+        &self,
+        username: &UsernameStr,
+        quota: UserQuota,
+    ) -> Result<StorageUsage, StorageError> {
+        debug!("measuring storage usage of user \"{username}\"");
+        let limits = self.resolve_quota(quota);
+        // not cached from here, that has to be done with the write lock held
+        let cached = self.usage_cache.lock().unwrap().get(username).copied();
+        let (storage_size, notes) = match cached {
+            Some(usage) => usage,
+            None => self.measure_usage(username).await?,
+        };
+        Ok(
+            StorageUsage {
+                storage_size,
+                notes,
+                max_storage_size: Some(limits.max_storage_size)
+                    .filter(|&limit| limit > 0),
+                max_notes: Some(limits.max_notes).filter(|&limit| limit > 0),
+            }
+        )
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn check_quota(
+        &self,
+        username: &UsernameStr,
+        quota: UserQuota,
+        note_id: Uuid,
+        new_size: u64,
+    ) -> Result<(), StorageError> {
+        let limits = self.resolve_quota(quota);
+        if limits.max_storage_size == 0 && limits.max_notes == 0 {
+            return Ok(())
+        }
+        let current_size = match self.io
+            .metadata(self.get_note_path(username, note_id))
+            .await
+        {
+            Ok(metadata) => Some(metadata.size),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let (storage_size, notes) = self.cached_usage(username).await?;
+        // the replaced version stays as a revision unless those are off
+        let new_storage_size = match self.revision_limits.max_count {
+            0 => storage_size.saturating_sub(current_size.unwrap_or(0)),
+            _ => storage_size,
+        } + new_size;
+        if limits.max_storage_size > 0
+            && new_size > current_size.unwrap_or(0)
+            && new_storage_size > limits.max_storage_size
+        {
+            debug!(
+                "storage quota of user \"{username}\" exceeded: \
+                    {new_storage_size} > {}",
+                limits.max_storage_size,
+            );
+            return Err(StorageError::QuotaExceeded)
+        }
+        if limits.max_notes > 0 && current_size.is_none() && notes >= limits.max_notes {
+            debug!(
+                "note quota of user \"{username}\" exceeded: {}",
+                limits.max_notes,
+            );
+            return Err(StorageError::QuotaExceeded)
+        }
+        Ok(())
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn check_note_count_quota(
+        &self,
+        username: &UsernameStr,
+        quota: UserQuota,
+    ) -> Result<(), StorageError> {
+        let limits = self.resolve_quota(quota);
+        if limits.max_notes == 0 {
+            return Ok(())
+        }
+        let (_, notes) = self.cached_usage(username).await?;
+        if notes >= limits.max_notes {
+            debug!(
+                "note quota of user \"{username}\" exceeded: {}",
+                limits.max_notes,
+            );
+            return Err(StorageError::QuotaExceeded)
+        }
+        Ok(())
+    }
+
+    /// Runs a change to the note's files, or only to the search index
+    /// and journal without a note, updating the cached usage of the user
+    // must be called with the write lock held
+    pub(super) async fn track_usage<T>(
+        &self,
+        username: &UsernameStr,
+        note_id: Option<Uuid>,
+        change: impl Future<Output=Result<T, StorageError>> + Send,
+    ) -> Result<T, StorageError> {
+        // the usage may get cached by the quota checks of the change,
+        // those come before it modifies anything
+        let before = self.measure_note_files(username, note_id).await;
+        let result = change.await;
+        let after = self.measure_note_files(username, note_id).await;
+        let mut usage_cache = self.usage_cache.lock().unwrap();
+        match (before, after, usage_cache.get_mut(username)) {
+            (Ok(before), Ok(after), Some((storage_size, notes))) => {
+                *storage_size = (*storage_size + after.0).saturating_sub(before.0);
+                *notes = (*notes + after.1).saturating_sub(before.1);
+            },
+            (Err(e), _, Some(_)) | (_, Err(e), Some(_)) => {
+                error!(
+                    "failed to measure the usage change of user \"{username}\", \
+                        dropping the cached usage: {e}"
+                );
+                usage_cache.remove(username);
+            },
+            _ => {},
+        }
+        result
+    }
+
+    pub(super) fn forget_cached_usage(&self) {
+        self.usage_cache.lock().unwrap().clear();
+    }
+
+    // must be called with the write lock held
+    async fn cached_usage(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<(u64, u32), StorageError> {
+        if let Some(&usage) = self.usage_cache.lock().unwrap().get(username) {
+            return Ok(usage)
+        }
+        let usage = self.measure_usage(username).await?;
+        self.usage_cache.lock().unwrap().insert(username.to_owned(), usage);
+        Ok(usage)
+    }
+
+    fn resolve_quota(&self, quota: UserQuota) -> QuotaLimits {
+        QuotaLimits {
+            max_storage_size: quota.max_storage_size
+                .unwrap_or(self.default_quota.max_storage_size),
+            max_notes: quota.max_notes.unwrap_or(self.default_quota.max_notes),
+        }
+    }
+
+    /// Returns the storage size and the number of live notes
+    async fn measure_usage(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<(u64, u32), StorageError> {
+        let mut inodes = HashSet::new();
+        // includes the search index and the journal
+        let (live_size, notes) = self
+            .measure_dir(self.get_user_dir(username), &mut inodes)
+            .await?;
+        let (trash_size, _) = self
+            .measure_dir(self.get_trash_dir(username), &mut inodes)
+            .await?;
+        let mut extra_size = 0;
+        for dir in [
+            self.get_trash_dir(username).join(TAGS_DIRECTORY_PATH),
+            self.get_tags_dir(username),
+            self.get_shares_dir(username),
+        ] {
+            extra_size += self.measure_dir(dir, &mut inodes).await?.0;
+        }
+        let revisions_dir = self.get_user_dir(username).join(REVISIONS_DIRECTORY_PATH);
+        let mut read = match self.io.read_dir(revisions_dir).await {
+            Ok(read) => Some(read),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let mut revisions_size = 0;
+        while let Some(entry) = match read.as_mut() {
+            Some(read) => read.next_entry().await?,
+            None => None,
+        } {
+            revisions_size += self.measure_dir(entry.path(), &mut inodes).await?.0;
+        }
+        trace!(
+            "user \"{username}\" has {notes} notes taking {live_size} bytes, \
+                {trash_size} bytes in the trash, {revisions_size} bytes \
+                of revisions and {extra_size} bytes of tags and shares"
+        );
+        Ok((live_size + trash_size + revisions_size + extra_size, notes))
+    }
+
+    /// Returns the size of the files a change to the note may touch,
+    /// and whether it is a live note
+    async fn measure_note_files(
+        &self,
+        username: &UsernameStr,
+        note_id: Option<Uuid>,
+    ) -> Result<(u64, u32), StorageError> {
+        let mut inodes = HashSet::new();
+        let (mut size, mut notes) = (0, 0);
+        let mut paths = vec![
+            self.get_search_index_path(username),
+            self.get_journal_path(username),
+        ];
+        if let Some(id) = note_id {
+            let note_path = self.get_note_path(username, id);
+            if let Some(note_size) = self.measure_file(note_path, &mut inodes).await? {
+                size += note_size;
+                notes = 1;
+            }
+            paths.extend([
+                self.get_trashed_note_path(username, id),
+                get_archive_time_path(&self.get_trashed_note_path(username, id)),
+                self.get_note_tags_path(username, id),
+                self.get_trashed_note_tags_path(username, id),
+                self.get_note_shares_path(username, id),
+            ]);
+            size += self
+                .measure_dir(self.get_revisions_dir(username, id), &mut inodes)
+                .await?
+                .0;
+        }
+        for path in paths {
+            size += self.measure_file(path, &mut inodes).await?.unwrap_or(0);
+        }
+        Ok((size, notes))
+    }
+
+    /// Returns `None` if the file doesn't exist, and zero if its inode
+    /// has been seen
+    async fn measure_file(
+        &self,
+        path: impl AsRef<Path> + Send,
+        inodes: &mut HashSet<u64>,
+    ) -> Result<Option<u64>, StorageError> {
+        match self.io.metadata(path).await {
+            Ok(metadata) if inodes.insert(metadata.ino) => Ok(Some(metadata.size)),
+            Ok(_) => Ok(Some(0)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Returns the size of the regular files in the directory, skipping
+    /// the inodes already seen, and the number of note files among them
+    async fn measure_dir(
+        &self,
+        path: impl AsRef<Path> + Send,
+        inodes: &mut HashSet<u64>,
+    ) -> Result<(u64, u32), StorageError> {
+        let mut read = match self.io.read_dir(path).await {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok((0, 0)),
+            Err(e) => return Err(e.into()),
+        };
+        let (mut size, mut count) = (0, 0);
+        while let Some(entry) = read.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if !metadata.is_file() {
+                continue
+            }
+            if inodes.insert(metadata.ino()) {
+                size += metadata.len();
+            }
+            // tmp files of notes being written start with the note id too
+            if entry.file_name().len() == HYPHENED_UUID_SIZE
+                && Self::try_extract_uuid(entry.file_name()).is_some()
+            {
+                count += 1;
+            }
+        }
+        Ok((size, count))
+    }
+}
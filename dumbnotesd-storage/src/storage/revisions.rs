@@ -0,0 +1,313 @@
+use std::collections::BTreeSet;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use data::{Note, NoteInfo, NoteMetadata, NoteRevisionInfo, NoteVersion, UserQuota, UsernameStr};
+use log::{debug, error, trace};
+use time::{Duration, UtcDateTime};
+use uuid::Uuid;
+
+use crate::app_constants::REVISIONS_DIRECTORY_PATH;
+use crate::storage::errors::StorageError;
+use crate::storage::io_trait::NoteStorageIo;
+use crate::storage::NoteStorageImpl;
+use crate::util::StrExt;
+
+#[derive(Clone, Copy, Debug)]
+pub struct RevisionLimits {
+    /// Zero disables the revision history
+    pub max_count: u32,
+    pub max_age: Duration,
+}
+
+// Revisions of a note are kept as hard links to its previous versions
+// in `<user dir>/.revisions/<note id>/<revision>`. The link is made right
+// before the version gets replaced, so the file's ctime tells when it
+// became a revision, unless recorded otherwise. The revision is assigned
+// then too, as the archive time in nanoseconds, kept above the newest
+// one's so that it only increases: the version's mtime comes from the
+// client and may repeat. Revisions from before were named after their
+// mtime in seconds, they sort as the oldest.
+struct RevisionEntry {
+    revision: i64,
+    mtime: i64,
+    archived_at: i64,
+    ino: u64,
+}
+
+#[allow(private_bounds)]
+impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
+    /// The most recently archived first
+    pub async fn list_revisions(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+    ) -> Result<Vec<NoteRevisionInfo>, StorageError> {
+        debug!("listing revisions of note {note_id} for user \"{username}\"");
+        match self.io.metadata(self.get_note_path(username, note_id)).await {
+            Ok(_) => {},
+            Err(e) if e.kind() == ErrorKind::NotFound =>
+                return Err(StorageError::NoteNotFound),
+            Err(e) => return Err(e.into()),
+        }
+        let mut ret = Vec::new();
+        for entry in self.read_revision_entries(username, note_id).await? {
+            let path = self.get_revision_path(username, note_id, entry.revision);
+            let name = match self.read_note_name(username, note_id, &path).await {
+                Ok(name) => name,
+                Err(e) => {
+                    error!(
+                        "failed to read revision {} of note {note_id} \
+                            for user \"{username}\": {e}",
+                        entry.revision,
+                    );
+                    continue
+                },
+            };
+            ret.push(
+                NoteRevisionInfo {
+                    revision: entry.revision,
+                    archived_at: UtcDateTime::from_unix_timestamp(entry.archived_at)?,
+                    info: NoteInfo {
+                        metadata: NoteMetadata {
+                            id: note_id,
+                            mtime: UtcDateTime::from_unix_timestamp(entry.mtime)?,
+                        },
+                        name,
+                        // tags aren't kept with revisions
+                        tags: BTreeSet::new(),
+                        tasks: None,
+                    },
+                }
+            );
+        }
+        Ok(ret)
+    }
+
+    pub async fn read_revision(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+        revision: i64,
+    ) -> Result<Note, StorageError> {
+        let path = self.get_revision_path(username, note_id, revision);
+        debug!(
+            "reading revision {revision} of note {note_id} for user \
+                \"{username}\" at \"{}\"",
+            path.display(),
+        );
+        Ok(self.read_note_file(username, path, note_id).await?.note)
+    }
+
+    /// Writes the revision's contents as the new version of the note,
+    /// the replaced version becomes a revision itself
+    pub async fn restore_revision(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+        revision: i64,
+        quota: UserQuota,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageError> {
+        debug!(
+            "restoring revision {revision} of note {note_id} \
+                for user \"{username}\""
+        );
+        let _write_guard = self.write_lock.lock().await;
+        let mut note = self
+            .read_note_file(
+                username,
+                self.get_revision_path(username, note_id, revision),
+                note_id,
+            )
+            .await?
+            .note;
+        let current_mtime = match self.io
+            .metadata(self.get_note_path(username, note_id))
+            .await
+        {
+            Ok(metadata) => Some(metadata.mtime),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        note.metadata.mtime = UtcDateTime::now();
+        note.tags = self.read_note_tags(username, note_id).await?;
+        self
+            .track_usage(
+                username,
+                Some(note_id),
+                self.write_note_locked(username, &note, current_mtime, quota),
+            )
+            .await
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn archive_revision(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+    ) -> Result<(), StorageError> {
+        if self.revision_limits.max_count == 0 {
+            return Ok(())
+        }
+        let note_path = self.get_note_path(username, note_id);
+        let note_ino = self.io.metadata(&note_path).await?.ino;
+        let entries = self.read_revision_entries(username, note_id).await?;
+        if entries.first().is_some_and(|newest| newest.ino == note_ino) {
+            trace!(
+                "current version of note {note_id} for user \"{username}\" \
+                    is archived already by a write that failed"
+            );
+            return Ok(())
+        }
+        let now = UtcDateTime::now().unix_timestamp_nanos() as i64;
+        let revision = entries
+            .first()
+            .map_or(now, |newest| now.max(newest.revision + 1));
+        trace!(
+            "archiving the current version of note {note_id} for user \
+                \"{username}\" as revision {revision}"
+        );
+        self.io
+            .create_dir_all(self.get_revisions_dir(username, note_id))
+            .await?;
+        let path = self.get_revision_path(username, note_id, revision);
+        match self.io.hard_link_file(&note_path, &path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                match self.io.metadata(&path).await?.ino == note_ino {
+                    true => Ok(()),
+                    false => Err(e.into()),
+                }
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn prune_revisions(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+    ) {
+        let entries = match self.read_revision_entries(username, note_id).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!(
+                    "failed to list revisions of note {note_id} \
+                        for user \"{username}\": {e}"
+                );
+                return
+            },
+        };
+        let oldest_allowed = (UtcDateTime::now() - self.revision_limits.max_age)
+            .unix_timestamp();
+        for (i, entry) in entries.into_iter().enumerate() {
+            if i < self.revision_limits.max_count as usize
+                && entry.archived_at >= oldest_allowed
+            {
+                continue
+            }
+            trace!(
+                "pruning revision {} of note {note_id} for user \"{username}\"",
+                entry.revision,
+            );
+            let path = self.get_revision_path(username, note_id, entry.revision);
+            let result = match self.io.remove_file(&path).await {
+                Ok(()) => self.remove_archive_time(&path).await,
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = result {
+                error!(
+                    "failed to prune revision \"{}\" of note {note_id} \
+                        for user \"{username}\": {e}",
+                    path.display(),
+                );
+            }
+        }
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn remove_revisions(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+    ) -> Result<(), StorageError> {
+        match self.io
+            .remove_dir_all(self.get_revisions_dir(username, note_id))
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // the most recently archived first
+    async fn read_revision_entries(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+    ) -> Result<Vec<RevisionEntry>, StorageError> {
+        let mut read = match self.io
+            .read_dir(self.get_revisions_dir(username, note_id))
+            .await
+        {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut ret = Vec::new();
+        while let Some(entry) = read.next_entry().await? {
+            let Some(revision) = entry.file_name()
+                .to_str()
+                .and_then(|name| name.parse::<i64>().ok())
+            else {
+                trace!("skipping revision dir entry \"{entry:?}\"");
+                continue
+            };
+            let path = entry.path();
+            let metadata = self.io.metadata(&path).await?;
+            ret.push(
+                RevisionEntry {
+                    revision,
+                    mtime: metadata.mtime,
+                    archived_at: self.read_archive_time(&path).await?,
+                    ino: metadata.ino,
+                }
+            );
+        }
+        ret.sort_by_key(|entry| std::cmp::Reverse(entry.revision));
+        Ok(ret)
+    }
+
+    pub(super) async fn read_note_name(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+        path: impl AsRef<Path> + Send,
+    ) -> Result<Option<String>, StorageError> {
+        let file = self.io.open_file(path).await?;
+        let buf = self
+            .read_note_data(username, note_id, file.file, self.max_note_name_len)
+            .await?;
+        let name = buf.split_once('\n')
+            .map(|(name, _)| name)
+            .unwrap_or(&buf);
+        Ok(self.truncate_note_name(name).nonblank_to_some())
+    }
+
+    pub(super) fn get_revisions_dir(&self, username: &UsernameStr, note_id: Uuid) -> PathBuf {
+        self.get_user_dir(username)
+            .join(REVISIONS_DIRECTORY_PATH)
+            .join(note_id.hyphenated().to_string())
+    }
+
+    fn get_revision_path(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+        revision: i64,
+    ) -> PathBuf {
+        self.get_revisions_dir(username, note_id).join(revision.to_string())
+    }
+}
@@ -0,0 +1,451 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::ErrorKind;
+use std::ops::Bound;
+use std::path::PathBuf;
+
+use data::{Note, NoteInfo, NoteListPage, NoteListQuery, NoteMetadata, NoteSearchQuery, NoteSearchResult, TaskSummary, UsernameStr};
+use log::{debug, error, trace};
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+use time::UtcDateTime;
+
+use crate::app_constants::SEARCH_INDEX_PATH;
+use crate::storage::encryption::is_encrypted;
+use crate::storage::errors::StorageError;
+use crate::storage::io_trait::NoteStorageIo;
+use crate::storage::NoteStorageImpl;
+use crate::storage::tasks::summarize_tasks;
+
+const INDEX_HEADER: &str = "dumbnotes search index 2";
+const INDEX_TMP_SUFFIX: &str = ".tmp";
+const MAX_TERM_LEN: usize = 32;
+pub(super) const MAX_QUERY_TERMS: usize = 16;
+// shorter terms only match whole words, not to match most of the notes
+const MIN_PREFIX_TERM_LEN: usize = 3;
+const SNIPPET_CONTEXT_LEN: usize = 40;
+// in characters, the matched word is cut like the terms, with ellipses around
+pub(super) const MAX_SNIPPET_LEN: usize = 2 * SNIPPET_CONTEXT_LEN + MAX_TERM_LEN + 2;
+
+// The index of a user's notes lives in `<user dir>/.search-index`, mapping
+// lowercased words to the ids of the notes containing them, one word
+// per line, then after an empty line the task summaries of the notes,
+// one note per line, and is encrypted like the notes when a key is
+// configured. It's a cache: whenever it's missing or unreadable, or still
+// in plaintext with a key configured, it gets rebuilt from the note files
+// on the next search or listing.
+#[derive(Debug, Default)]
+struct SearchIndex {
+    terms: BTreeMap<String, BTreeSet<Uuid>>,
+    // kept here so that the listings don't read the whole notes
+    tasks: BTreeMap<Uuid, TaskSummary>,
+}
+
+#[allow(private_bounds)]
+impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
+    /// Finds a page of the notes containing all the query's words as word
+    /// prefixes, newest first, the limit is capped so that the page fits
+    /// in a response, see [`PageLimits`](super::page_limits::PageLimits)
+    pub async fn search_notes(
+        &self,
+        username: &UsernameStr,
+        query: &NoteSearchQuery,
+    ) -> Result<NoteListPage<NoteSearchResult>, StorageError> {
+        debug!("searching notes for user \"{username}\" with {query:?}");
+        if query.query.len() as u64 > self.max_note_name_len {
+            return Err(StorageError::TooBig)
+        }
+        let max_limit = self.page_limits.note_search;
+        let limit = query.limit
+            .map_or(max_limit, |limit| limit.min(max_limit))
+            as usize;
+        let terms = tokenize(&query.query)
+            .into_iter()
+            .map(|(_, token)| normalize_term(token))
+            .collect::<BTreeSet<_>>();
+        if terms.len() > MAX_QUERY_TERMS {
+            return Err(StorageError::TooBig)
+        }
+        let index = match self.load_search_index(username).await? {
+            Some(index) => index,
+            None => self.rebuild_search_index(username).await?,
+        };
+        let mut found: Option<BTreeSet<Uuid>> = None;
+        for term in &terms {
+            let ids = index.find(term);
+            found = Some(
+                match found {
+                    Some(found) => found.intersection(&ids).copied().collect(),
+                    None => ids,
+                }
+            );
+        }
+        // only the mtimes are needed to order the candidates, so that
+        // no more notes than the page takes are read
+        let after = query.after
+            .map(|nm| Reverse((nm.mtime.unix_timestamp(), nm.id)));
+        let mut candidates = Vec::new();
+        for id in found.unwrap_or_default() {
+            let mtime = match self.io.metadata(self.get_note_path(username, id)).await {
+                Ok(metadata) => metadata.mtime,
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    trace!("skipping indexed but missing note {id}");
+                    continue
+                },
+                Err(e) => return Err(e.into()),
+            };
+            let key = Reverse((mtime, id));
+            if after.is_some_and(|after| key <= after) {
+                continue
+            }
+            candidates.push(key);
+        }
+        candidates.sort_unstable();
+        let mut ret = Vec::new();
+        // one more to know whether there's a next page
+        for Reverse((mtime, id)) in candidates {
+            if ret.len() > limit {
+                break
+            }
+            let note = match self.read_note(username, id).await {
+                Ok(note) => note,
+                Err(StorageError::NoteNotFound) => {
+                    trace!("skipping indexed but missing note {id}");
+                    continue
+                },
+                Err(e) => return Err(e),
+            };
+            // the index may be stale, the note has the final say
+            let Some(snippets) = make_snippets(&note, &terms) else {
+                trace!("skipping indexed but not matching note {id}");
+                continue
+            };
+            ret.push(
+                NoteSearchResult {
+                    info: NoteInfo {
+                        // the listing order holds even if the note has
+                        // been written since
+                        metadata: NoteMetadata {
+                            id,
+                            mtime: UtcDateTime::from_unix_timestamp(mtime)?,
+                        },
+                        tasks: Some(summarize_tasks(&note.contents)),
+                        name: note.name
+                            .as_deref()
+                            .map(|name| self.truncate_note_name(name).to_owned()),
+                        tags: note.tags,
+                    },
+                    snippets,
+                }
+            );
+        }
+        let next = if ret.len() > limit {
+            ret.truncate(limit);
+            ret.last().map(|result| result.info.metadata)
+        } else {
+            None
+        };
+        Ok(NoteListPage { notes: ret, next })
+    }
+
+    /// The task summaries of all the user's notes
+    pub(super) async fn get_task_summaries(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<BTreeMap<Uuid, TaskSummary>, StorageError> {
+        let index = match self.load_search_index(username).await? {
+            Some(index) => index,
+            None => self.rebuild_search_index(username).await?,
+        };
+        Ok(index.tasks)
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn update_search_index(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+        note: Option<&Note>,
+    ) {
+        let result: Result<(), StorageError> = async {
+            // a missing index gets built from scratch on the next search
+            let Some(mut index) = self.load_search_index(username).await? else {
+                return Ok(())
+            };
+            index.remove(note_id);
+            if let Some(note) = note {
+                index.insert(note);
+            }
+            self.save_search_index(username, &index).await
+        }.await;
+        if let Err(e) = result {
+            error!(
+                "failed to update the search index for user \"{username}\", \
+                    dropping it: {e}"
+            );
+            match self.io.remove_file(self.get_search_index_path(username)).await {
+                Ok(()) => {},
+                Err(e) if e.kind() == ErrorKind::NotFound => {},
+                Err(e) => error!(
+                    "failed to remove the search index for user \"{username}\": {e}"
+                ),
+            }
+        }
+    }
+
+    async fn load_search_index(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Option<SearchIndex>, StorageError> {
+        let mut file = match self.io
+            .open_file(self.get_search_index_path(username))
+            .await
+        {
+            Ok(file) => file.file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).await?;
+        if self.cipher.is_some() && !is_encrypted(&data) {
+            debug!("search index for user \"{username}\" is not encrypted");
+            return Ok(None)
+        }
+        let data = match self.decode_user_file(username, SEARCH_INDEX_PATH, data) {
+            Ok(data) => data,
+            Err(StorageError::EncryptionKeyMissing) =>
+                return Err(StorageError::EncryptionKeyMissing),
+            Err(e) => {
+                error!("failed to decrypt the search index for user \"{username}\": {e}");
+                return Ok(None)
+            },
+        };
+        let Ok(data) = String::from_utf8(data) else {
+            error!("search index for user \"{username}\" is not valid utf-8");
+            return Ok(None)
+        };
+        let index = SearchIndex::parse(&data);
+        if index.is_none() {
+            error!("search index for user \"{username}\" is corrupted");
+        }
+        Ok(index)
+    }
+
+    async fn rebuild_search_index(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<SearchIndex, StorageError> {
+        let _write_guard = self.write_lock.lock().await;
+        // might have been rebuilt while waiting for the lock
+        if let Some(index) = self.load_search_index(username).await? {
+            return Ok(index)
+        }
+        debug!("rebuilding the search index for user \"{username}\"");
+        let mut index = SearchIndex::default();
+        let mut query = NoteListQuery::default();
+        loop {
+            let page = self.list_notes(username, &query).await?;
+            for metadata in page.notes {
+                match self.read_note(username, metadata.id).await {
+                    Ok(note) => index.insert(&note),
+                    Err(e) => error!(
+                        "failed to index note {} for user \"{username}\": {e}",
+                        metadata.id,
+                    ),
+                }
+            }
+            match page.next {
+                Some(next) => query.after = Some(next),
+                None => break,
+            }
+        }
+        self
+            .track_usage(username, None, self.save_search_index(username, &index))
+            .await?;
+        Ok(index)
+    }
+
+    // must be called with the write lock held
+    async fn save_search_index(
+        &self,
+        username: &UsernameStr,
+        index: &SearchIndex,
+    ) -> Result<(), StorageError> {
+        let path = self.get_search_index_path(username);
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(INDEX_TMP_SUFFIX);
+        let data = self.encode_user_file(username, SEARCH_INDEX_PATH, index.format())?;
+        self.io.write_file(&tmp_path, data).await?;
+        self.io.rename_file(&tmp_path, &path).await?;
+        Ok(())
+    }
+
+    pub(super) fn get_search_index_path(&self, username: &UsernameStr) -> PathBuf {
+        self.get_user_dir(username).join(SEARCH_INDEX_PATH)
+    }
+}
+
+impl SearchIndex {
+    fn parse(data: &str) -> Option<SearchIndex> {
+        let mut lines = data.lines();
+        if lines.next()? != INDEX_HEADER {
+            return None
+        }
+        let mut terms = BTreeMap::new();
+        for line in lines.by_ref().take_while(|line| !line.is_empty()) {
+            let (term, ids) = line.split_once('\t')?;
+            let ids = ids
+                .split(' ')
+                .map(|id| Uuid::try_parse(id).ok())
+                .collect::<Option<BTreeSet<_>>>()?;
+            terms.insert(term.to_owned(), ids);
+        }
+        let mut tasks = BTreeMap::new();
+        for line in lines {
+            let (id, summary) = line.split_once('\t')?;
+            let (done, total) = summary.split_once(' ')?;
+            tasks.insert(
+                Uuid::try_parse(id).ok()?,
+                TaskSummary {
+                    done: done.parse().ok()?,
+                    total: total.parse().ok()?,
+                },
+            );
+        }
+        Some(SearchIndex { terms, tasks })
+    }
+
+    fn format(&self) -> String {
+        let mut ret = String::from(INDEX_HEADER);
+        ret.push('\n');
+        for (term, ids) in &self.terms {
+            ret.push_str(term);
+            let mut separator = '\t';
+            for id in ids {
+                ret.push(separator);
+                ret.push_str(&id.hyphenated().to_string());
+                separator = ' ';
+            }
+            ret.push('\n');
+        }
+        ret.push('\n');
+        for (id, TaskSummary { done, total }) in &self.tasks {
+            ret.push_str(&format!("{}\t{done} {total}\n", id.hyphenated()));
+        }
+        ret
+    }
+
+    fn insert(&mut self, note: &Note) {
+        let text = [note.name.as_deref().unwrap_or(""), &note.contents];
+        for (_, token) in text.into_iter().flat_map(tokenize) {
+            self.terms
+                .entry(normalize_term(token))
+                .or_default()
+                .insert(note.metadata.id);
+        }
+        self.tasks.insert(note.metadata.id, summarize_tasks(&note.contents));
+    }
+
+    fn remove(&mut self, note_id: Uuid) {
+        self.terms.retain(|_, ids| {
+            ids.remove(&note_id);
+            !ids.is_empty()
+        });
+        self.tasks.remove(&note_id);
+    }
+
+    // the notes having a word matching the term, see `matches_term()`
+    fn find(&self, term: &str) -> BTreeSet<Uuid> {
+        self.terms
+            .range::<str, _>((Bound::Included(term), Bound::Unbounded))
+            .take_while(|(word, _)| matches_term(word, term))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect()
+    }
+}
+
+// words are runs of alphanumeric characters, returned with their offsets
+fn tokenize(text: &str) -> Vec<(usize, &str)> {
+    let mut ret = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                ret.push((s, &text[s..i]));
+                start = None;
+            },
+            _ => {},
+        }
+    }
+    if let Some(s) = start {
+        ret.push((s, &text[s..]));
+    }
+    ret
+}
+
+fn normalize_term(token: &str) -> String {
+    token.chars().flat_map(char::to_lowercase).take(MAX_TERM_LEN).collect()
+}
+
+// a normalized word matches a term it starts with, or only the same term
+// when it's too short to be a prefix
+fn matches_term(word: &str, term: &str) -> bool {
+    match term.chars().count() < MIN_PREFIX_TERM_LEN {
+        true => word == term,
+        false => word.starts_with(term),
+    }
+}
+
+// a snippet per query term, preferring the contents over the name;
+// `None` if any of the terms doesn't match
+fn make_snippets(note: &Note, terms: &BTreeSet<String>) -> Option<Vec<String>> {
+    let texts = [note.contents.as_str(), note.name.as_deref().unwrap_or("")];
+    let mut ret = Vec::new();
+    for term in terms {
+        let snippet = texts.iter().find_map(|text|
+            tokenize(text)
+                .into_iter()
+                .find(|(_, token)| matches_term(&normalize_term(token), term))
+                .map(|(start, token)| {
+                    let end = token
+                        .char_indices()
+                        .nth(MAX_TERM_LEN)
+                        .map_or(token.len(), |(i, _)| i);
+                    make_snippet(text, start, start + end)
+                })
+        )?;
+        if !ret.contains(&snippet) {
+            ret.push(snippet);
+        }
+    }
+    Some(ret)
+}
+
+fn make_snippet(text: &str, start: usize, end: usize) -> String {
+    let snippet_start = text[..start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_LEN - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let snippet_end = text[end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT_LEN)
+        .map(|(i, _)| end + i)
+        .unwrap_or(text.len());
+    let mut ret = String::new();
+    if snippet_start > 0 {
+        ret.push('…');
+    }
+    ret.extend(
+        text[snippet_start..snippet_end]
+            .chars()
+            .map(|c| if c.is_whitespace() { ' ' } else { c })
+    );
+    if snippet_end < text.len() {
+        ret.push('…');
+    }
+    ret
+}
@@ -0,0 +1,370 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use data::{NoteMetadata, NoteShare, ShareAccess, SharedNoteInfo, UsernameStr, UsernameString};
+use log::{debug, error, trace, warn};
+use time::UtcDateTime;
+use uuid::Uuid;
+
+use dumbnotes::bin_constants::SHARES_DIRECTORY_PATH;
+use crate::storage::errors::StorageError;
+use crate::storage::io_trait::NoteStorageIo;
+use crate::storage::{read_limited_utf8_lossy, NoteStorageImpl};
+
+const SHARES_TMP_PREFIX: &str = ".tmp.";
+const MAX_SHARES_FILE_LEN: u64 = 64 * 1024;
+const MAX_NOTE_SHARES: usize = 256;
+
+// Shares of a note are kept in `<owner dir>/.shares/<note id>`, a
+// `<username> <read|write>` line per user. The file stays in place while
+// the note is in the trash, the access checks requiring a live note.
+
+#[allow(private_bounds)]
+impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
+    /// Shares the note with another user, replacing the access granted
+    /// to them earlier
+    pub async fn grant_note_share(
+        &self,
+        owner: &UsernameStr,
+        note_id: Uuid,
+        share: NoteShare,
+    ) -> Result<(), StorageError> {
+        debug!(
+            "sharing note {note_id} of user \"{owner}\" with user \"{}\" as {:?}",
+            share.username,
+            share.access,
+        );
+        if *share.username == *owner {
+            return Err(StorageError::Forbidden)
+        }
+        let _write_guard = self.write_lock.lock().await;
+        self.check_note_exists(owner, note_id).await?;
+        if share.username.is_empty() {
+            return Err(StorageError::UserNotFound)
+        }
+        match self.io.metadata(self.get_user_dir(&share.username)).await {
+            Ok(metadata) if metadata.is_dir => {},
+            Ok(_) => return Err(StorageError::UserNotFound),
+            Err(e) if e.kind() == ErrorKind::NotFound =>
+                return Err(StorageError::UserNotFound),
+            Err(e) => return Err(e.into()),
+        }
+        let mut shares = self.read_note_shares(owner, note_id).await?;
+        let count = shares.len();
+        match shares.iter_mut().find(|s| s.username == share.username) {
+            Some(existing) => existing.access = share.access,
+            None if count >= MAX_NOTE_SHARES => return Err(StorageError::TooBig),
+            None => shares.push(share),
+        }
+        self
+            .track_usage(
+                owner,
+                Some(note_id),
+                self.write_note_shares(owner, note_id, &shares),
+            )
+            .await
+    }
+
+    pub async fn revoke_note_share(
+        &self,
+        owner: &UsernameStr,
+        note_id: Uuid,
+        username: &UsernameStr,
+    ) -> Result<(), StorageError> {
+        debug!(
+            "revoking the share of note {note_id} of user \"{owner}\" \
+                with user \"{username}\""
+        );
+        let _write_guard = self.write_lock.lock().await;
+        let mut shares = self.read_note_shares(owner, note_id).await?;
+        let count = shares.len();
+        shares.retain(|share| *share.username != *username);
+        if shares.len() == count {
+            return Err(StorageError::NoteNotFound)
+        }
+        self
+            .track_usage(
+                owner,
+                Some(note_id),
+                self.write_note_shares(owner, note_id, &shares),
+            )
+            .await
+    }
+
+    pub async fn list_note_shares(
+        &self,
+        owner: &UsernameStr,
+        note_id: Uuid,
+    ) -> Result<Vec<NoteShare>, StorageError> {
+        debug!("listing shares of note {note_id} of user \"{owner}\"");
+        self.check_note_exists(owner, note_id).await?;
+        self.read_note_shares(owner, note_id).await
+    }
+
+    /// Lists the notes of the other users shared with the user,
+    /// the most recently modified first
+    pub async fn list_shared_notes(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Vec<SharedNoteInfo>, StorageError> {
+        debug!("listing notes shared with user \"{username}\"");
+        let mut shared = Vec::new();
+        let mut read = self.io.read_dir(&self.basedir).await?;
+        while let Some(entry) = read.next_entry().await? {
+            let Some(owner) = entry.file_name()
+                .to_str()
+                .and_then(|name| UsernameString::from_str(name).ok())
+                .filter(|owner| **owner != *username)
+            else {
+                trace!("skipping notes dir entry \"{entry:?}\"");
+                continue
+            };
+            if !entry.file_type().await?.is_dir() {
+                continue
+            }
+            match self.find_notes_shared_by(&owner, username).await {
+                Ok(notes) if notes.is_empty() => {},
+                Ok(notes) => shared.push((owner, notes)),
+                Err(e) => error!(
+                    "failed to read the shares of user \"{owner}\": {e}"
+                ),
+            }
+        }
+        let mut ret = Vec::new();
+        for (owner, notes) in shared {
+            let details = self
+                .get_note_details(&owner, notes.iter().map(|(metadata, _)| *metadata))
+                .await?;
+            ret.extend(
+                notes.into_iter()
+                    .zip(details)
+                    .filter_map(|((_, access), info)| Some(
+                        SharedNoteInfo {
+                            owner: owner.clone(),
+                            access,
+                            info: info?,
+                        }
+                    ))
+            );
+        }
+        ret.sort_by_key(|note| std::cmp::Reverse(note.info.metadata.mtime));
+        Ok(ret)
+    }
+
+    /// Checks the access of the user to a note of the owner, which is
+    /// the user themselves when not set. Returns the owner.
+    pub async fn resolve_note_access(
+        &self,
+        username: &UsernameStr,
+        owner: Option<UsernameString>,
+        note_id: Uuid,
+        access: ShareAccess,
+    ) -> Result<UsernameString, StorageError> {
+        let owner = owner.unwrap_or_else(|| username.to_owned());
+        self.check_note_access(username, &owner, note_id, access).await?;
+        Ok(owner)
+    }
+
+    // the changes of a shared note check the access again with the write
+    // lock held, as the share may have been revoked in the meantime
+    pub(super) async fn check_note_access(
+        &self,
+        username: &UsernameStr,
+        owner: &UsernameStr,
+        note_id: Uuid,
+        access: ShareAccess,
+    ) -> Result<(), StorageError> {
+        if *owner == *username {
+            return Ok(())
+        }
+        let shares = self.read_note_shares(owner, note_id).await?;
+        let Some(share) = shares
+            .iter()
+            .find(|share| *share.username == *username)
+        else {
+            debug!(
+                "note {note_id} of user \"{owner}\" isn't shared \
+                    with user \"{username}\""
+            );
+            return Err(StorageError::NoteNotFound)
+        };
+        if share.access < access {
+            debug!(
+                "note {note_id} of user \"{owner}\" is shared with \
+                    user \"{username}\" as {:?}, {access:?} needed",
+                share.access,
+            );
+            return Err(StorageError::Forbidden)
+        }
+        self.check_note_exists(owner, note_id).await
+    }
+
+    async fn find_notes_shared_by(
+        &self,
+        owner: &UsernameStr,
+        username: &UsernameStr,
+    ) -> Result<Vec<(NoteMetadata, ShareAccess)>, StorageError> {
+        let mut read = match self.io
+            .read_dir(self.get_shares_dir(owner))
+            .await
+        {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut ret = Vec::new();
+        while let Some(entry) = read.next_entry().await? {
+            let Some(id) = Self::try_extract_uuid(entry.file_name()) else {
+                trace!("skipping shares dir entry \"{entry:?}\"");
+                continue
+            };
+            let shares = match self.read_note_shares(owner, id).await {
+                Ok(shares) => shares,
+                Err(e) => {
+                    error!("failed to read shares of note {id} of user \"{owner}\": {e}");
+                    continue
+                },
+            };
+            let Some(share) = shares
+                .into_iter()
+                .find(|share| *share.username == *username)
+            else {
+                continue
+            };
+            // trashed notes aren't listed
+            let mtime = match self.io.metadata(self.get_note_path(owner, id)).await {
+                Ok(metadata) => metadata.mtime,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            ret.push((
+                NoteMetadata { id, mtime: UtcDateTime::from_unix_timestamp(mtime)? },
+                share.access,
+            ));
+        }
+        Ok(ret)
+    }
+
+    async fn check_note_exists(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+    ) -> Result<(), StorageError> {
+        match self.io.metadata(self.get_note_path(username, note_id)).await {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(StorageError::NoteNotFound),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn read_note_shares(
+        &self,
+        owner: &UsernameStr,
+        note_id: Uuid,
+    ) -> Result<Vec<NoteShare>, StorageError> {
+        let file = match self.io
+            .open_file(self.get_note_shares_path(owner, note_id))
+            .await
+        {
+            Ok(file) => file.file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let data = read_limited_utf8_lossy(MAX_SHARES_FILE_LEN, file).await?;
+        let shares = data
+            .lines()
+            .filter_map(|line| {
+                let share = parse_share_line(line);
+                if share.is_none() {
+                    warn!(
+                        "skipping invalid share {line:?} of note {note_id} \
+                            of user \"{owner}\""
+                    );
+                }
+                share
+            })
+            .collect();
+        Ok(shares)
+    }
+
+    // must be called with the write lock held
+    async fn write_note_shares(
+        &self,
+        owner: &UsernameStr,
+        note_id: Uuid,
+        shares: &[NoteShare],
+    ) -> Result<(), StorageError> {
+        let path = self.get_note_shares_path(owner, note_id);
+        if shares.is_empty() {
+            return self.remove_note_shares_file(path).await
+        }
+        let mut data = String::new();
+        for share in shares {
+            data.push_str(&share.username);
+            data.push(' ');
+            data.push_str(match share.access {
+                ShareAccess::ReadOnly => "read",
+                ShareAccess::ReadWrite => "write",
+            });
+            data.push('\n');
+        }
+        let shares_dir = self.get_shares_dir(owner);
+        self.io.create_dir_all(&shares_dir).await?;
+        let tmp_path = shares_dir.join(
+            SHARES_TMP_PREFIX.to_owned() + &self.io.generate_uuid().hyphenated().to_string()
+        );
+        self.io.write_file(&tmp_path, data).await?;
+        if let Err(e) = self.io.rename_file(&tmp_path, &path).await {
+            if let Err(e) = self.io.remove_file(&tmp_path).await {
+                error!(
+                    "failed to remove tmp shares file \"{}\": {e}",
+                    tmp_path.display(),
+                );
+            }
+            return Err(e.into())
+        }
+        Ok(())
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn remove_note_shares(
+        &self,
+        owner: &UsernameStr,
+        note_id: Uuid,
+    ) -> Result<(), StorageError> {
+        self.remove_note_shares_file(self.get_note_shares_path(owner, note_id))
+            .await
+    }
+
+    async fn remove_note_shares_file(&self, path: PathBuf) -> Result<(), StorageError> {
+        match self.io.remove_file(path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub(super) fn get_note_shares_path(&self, owner: &UsernameStr, note_id: Uuid) -> PathBuf {
+        self.get_shares_dir(owner).join(note_id.hyphenated().to_string())
+    }
+
+    pub(super) fn get_shares_dir(&self, owner: &UsernameStr) -> PathBuf {
+        self.get_user_dir(owner).join(SHARES_DIRECTORY_PATH)
+    }
+}
+
+fn parse_share_line(line: &str) -> Option<NoteShare> {
+    // usernames may contain spaces
+    let (username, access) = line.rsplit_once(' ')?;
+    Some(
+        NoteShare {
+            username: UsernameString::from_str(username).ok()?,
+            access: match access {
+                "read" => ShareAccess::ReadOnly,
+                "write" => ShareAccess::ReadWrite,
+                _ => return None,
+            },
+        }
+    )
+}
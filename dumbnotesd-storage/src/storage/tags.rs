@@ -0,0 +1,201 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use data::{is_valid_tag, TagCount, UsernameStr, MAX_NOTE_TAGS, MAX_TAG_LEN};
+use log::{debug, error, trace, warn};
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+use crate::app_constants::TAGS_DIRECTORY_PATH;
+use crate::storage::encryption::{make_tags_file_kind, ENCRYPTED_NOTE_OVERHEAD};
+use crate::storage::errors::StorageError;
+use crate::storage::io_trait::NoteStorageIo;
+use crate::storage::{utf8_lossy, NoteStorageImpl};
+
+const TAGS_TMP_PREFIX: &str = ".tmp.";
+// up to 4 bytes per char and a newline for every tag
+const MAX_TAGS_FILE_LEN: u64 = (MAX_NOTE_TAGS * (MAX_TAG_LEN * 4 + 1)) as u64;
+
+// Tags of a note are kept in `<user dir>/.tags/<note id>`, one per line,
+// leaving the note file itself as it is, and are encrypted like the notes
+// when a key is configured. The file moves along with the
+// note to `<user dir>/.trash/.tags/<note id>` on deletion, and notes
+// without tags have none.
+#[allow(private_bounds)]
+impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
+    /// Lists the tags in use with the number of notes having them,
+    /// sorted by tag
+    pub async fn list_tags(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Vec<TagCount>, StorageError> {
+        debug!("listing tags for user \"{username}\"");
+        let mut counts = BTreeMap::<String, u32>::new();
+        for (_, tags) in self.read_all_tags(username).await? {
+            for tag in tags {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+        Ok(
+            counts.into_iter()
+                .map(|(tag, count)| TagCount { tag, count })
+                .collect()
+        )
+    }
+
+    pub(super) async fn find_tagged_notes(
+        &self,
+        username: &UsernameStr,
+        tag: &str,
+    ) -> Result<HashSet<Uuid>, StorageError> {
+        Ok(
+            self.read_all_tags(username)
+                .await?
+                .into_iter()
+                .filter(|(_, tags)| tags.contains(tag))
+                .map(|(id, _)| id)
+                .collect()
+        )
+    }
+
+    async fn read_all_tags(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Vec<(Uuid, BTreeSet<String>)>, StorageError> {
+        let mut read = match self.io
+            .read_dir(self.get_tags_dir(username))
+            .await
+        {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut ret = Vec::new();
+        while let Some(entry) = read.next_entry().await? {
+            let Some(id) = Self::try_extract_uuid(entry.file_name()) else {
+                trace!("skipping tags dir entry \"{entry:?}\"");
+                continue
+            };
+            match self.read_tags_file(username, id, entry.path()).await {
+                Ok(tags) => ret.push((id, tags)),
+                Err(e) => error!(
+                    "failed to read tags of note {id} for user \"{username}\": {e}"
+                ),
+            }
+        }
+        Ok(ret)
+    }
+
+    pub(super) async fn read_note_tags(
+        &self,
+        username: &UsernameStr,
+        id: Uuid,
+    ) -> Result<BTreeSet<String>, StorageError> {
+        self.read_tags_file(username, id, self.get_note_tags_path(username, id))
+            .await
+    }
+
+    pub(super) async fn read_tags_file(
+        &self,
+        username: &UsernameStr,
+        id: Uuid,
+        path: impl AsRef<Path> + Send,
+    ) -> Result<BTreeSet<String>, StorageError> {
+        let file = match self.io.open_file(path).await {
+            Ok(file) => file.file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(BTreeSet::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut data = Vec::new();
+        file.take(MAX_TAGS_FILE_LEN + ENCRYPTED_NOTE_OVERHEAD)
+            .read_to_end(&mut data)
+            .await?;
+        let mut data = self.decode_user_file(username, &make_tags_file_kind(id), data)?;
+        data.truncate(MAX_TAGS_FILE_LEN as usize);
+        let data = utf8_lossy(&data);
+        Ok(
+            data.lines()
+                .filter(|tag| is_valid_tag(tag))
+                .map(str::to_owned)
+                .collect()
+        )
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn write_note_tags(
+        &self,
+        username: &UsernameStr,
+        id: Uuid,
+        tags: &BTreeSet<String>,
+    ) -> Result<(), StorageError> {
+        let path = self.get_note_tags_path(username, id);
+        let mut data = String::new();
+        for tag in tags {
+            if !is_valid_tag(tag) {
+                warn!("dropping invalid tag {tag:?} of note {id} for user \"{username}\"");
+                continue
+            }
+            data.push_str(tag);
+            data.push('\n');
+        }
+        if data.is_empty() {
+            return self.remove_tags_file(&path).await
+        }
+        let tags_dir = self.get_tags_dir(username);
+        self.io.create_dir_all(&tags_dir).await?;
+        let tmp_path = tags_dir.join(
+            TAGS_TMP_PREFIX.to_owned() + &self.io.generate_uuid().hyphenated().to_string()
+        );
+        let data = self.encode_user_file(username, &make_tags_file_kind(id), data)?;
+        self.io.write_file(&tmp_path, data).await?;
+        if let Err(e) = self.io.rename_file(&tmp_path, &path).await {
+            if let Err(e) = self.io.remove_file(&tmp_path).await {
+                error!(
+                    "failed to remove tmp tags file \"{}\": {e}",
+                    tmp_path.display(),
+                );
+            }
+            return Err(e.into())
+        }
+        Ok(())
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn move_tags_file(
+        &self,
+        from: PathBuf,
+        to: PathBuf,
+    ) -> Result<(), StorageError> {
+        match self.io.metadata(&from).await {
+            Ok(_) => {},
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+        if let Some(parent) = to.parent() {
+            self.io.create_dir_all(parent).await?;
+        }
+        self.io.rename_file(from, to).await?;
+        Ok(())
+    }
+
+    // must be called with the write lock held
+    pub(super) async fn remove_tags_file(
+        &self,
+        path: impl AsRef<Path> + Send,
+    ) -> Result<(), StorageError> {
+        match self.io.remove_file(path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub(super) fn get_note_tags_path(&self, username: &UsernameStr, id: Uuid) -> PathBuf {
+        self.get_tags_dir(username).join(id.hyphenated().to_string())
+    }
+
+    pub(super) fn get_tags_dir(&self, username: &UsernameStr) -> PathBuf {
+        self.get_user_dir(username).join(TAGS_DIRECTORY_PATH)
+    }
+}
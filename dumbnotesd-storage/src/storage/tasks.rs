@@ -0,0 +1,56 @@
+use data::{TaskSummary, UserQuota, UsernameStr, VersionedNote};
+use log::debug;
+use markdown_renderer::find_tasks;
+use time::UtcDateTime;
+use uuid::Uuid;
+
+use crate::storage::errors::StorageError;
+use crate::storage::io_trait::NoteStorageIo;
+use crate::storage::NoteStorageImpl;
+
+#[allow(private_bounds)]
+impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
+    /// Flips the checkbox of the task list item with the index
+    /// in [`find_tasks`] order, returns the note as written
+    pub async fn toggle_task(
+        &self,
+        username: &UsernameStr,
+        note_id: Uuid,
+        task_index: u32,
+        quota: UserQuota,
+    ) -> Result<VersionedNote, StorageError> {
+        debug!(
+            "toggling task {task_index} of note {note_id} for user \"{username}\""
+        );
+        let _write_guard = self.write_lock.lock().await;
+        let mut note = self.read_note(username, note_id).await?;
+        let task = find_tasks(&note.contents)
+            .into_iter()
+            .nth(task_index as usize)
+            .ok_or(StorageError::TaskNotFound)?;
+        // the marker is `[ ]`, `[x]` or `[X]`
+        note.contents.replace_range(
+            task.marker.start + 1..task.marker.end - 1,
+            if task.checked { " " } else { "x" },
+        );
+        let current_mtime = note.metadata.mtime.unix_timestamp();
+        note.metadata.mtime = UtcDateTime::now();
+        let (metadata, version) = self
+            .track_usage(
+                username,
+                Some(note_id),
+                self.write_note_locked(username, &note, Some(current_mtime), quota),
+            )
+            .await?;
+        note.metadata = metadata;
+        Ok(VersionedNote { note, version })
+    }
+}
+
+pub(super) fn summarize_tasks(contents: &str) -> TaskSummary {
+    let tasks = find_tasks(contents);
+    TaskSummary {
+        done: tasks.iter().filter(|task| task.checked).count() as u32,
+        total: tasks.len() as u32,
+    }
+}
@@ -0,0 +1,99 @@
+use super::*;
+
+fn clamp(mtime: i64, now: i64) -> i64 {
+    NoteStorage::clamp_mtime(
+        UtcDateTime::from_unix_timestamp(mtime).unwrap(),
+        UtcDateTime::from_unix_timestamp(now).unwrap(),
+    ).unix_timestamp()
+}
+
+#[test]
+fn client_mtime_kept_when_valid() {
+    assert_eq!(clamp(1000, 5000), 1000);
+    assert_eq!(clamp(0, 5000), 0);
+    assert_eq!(clamp(5000, 5000), 5000);
+}
+
+#[test]
+fn client_mtime_clamped_to_now() {
+    assert_eq!(clamp(9000, 5000), 5000);
+    assert_eq!(clamp(-10, 5000), 0);
+}
+
+fn encoded_response_len(response: storage_ipc_data::bindings::response::Response) -> usize {
+    use prost::Message;
+    storage_ipc_data::bindings::Response {
+        command_id: u64::MAX,
+        response: Some(response),
+    }.encoded_len()
+}
+
+#[test]
+fn full_pages_fit_in_a_response() {
+    use dumbnotes::bin_constants::IPC_STORAGE_MESSAGE_MAX_SIZE;
+    use storage_ipc_data::bindings;
+    use page_limits::{max_note_info, max_note_metadata, max_search_result};
+
+    // the default and a generous one
+    for max_note_name_len in [256, 4096] {
+        let limits = PageLimits::new(max_note_name_len);
+        let note_details = bindings::response::Response::GetNoteDetails(
+            bindings::GetNoteDetailsResponse {
+                response: Some(
+                    bindings::get_note_details_response::Response::NotesInfo(
+                        bindings::MaybeNotesInfo {
+                            notes_info: vec![
+                                bindings::MaybeNoteInfo {
+                                    note_info: Some(max_note_info(max_note_name_len)),
+                                };
+                                limits.note_list as usize
+                            ],
+                        }
+                    )
+                ),
+            }
+        );
+        assert!(encoded_response_len(note_details) <= IPC_STORAGE_MESSAGE_MAX_SIZE);
+        let note_changes = bindings::response::Response::ListNoteChanges(
+            bindings::ListNoteChangesResponse {
+                response: Some(
+                    bindings::list_note_changes_response::Response::Changes(
+                        bindings::NoteChanges {
+                            updated: vec![
+                                max_note_info(max_note_name_len);
+                                limits.note_list as usize
+                            ],
+                            deleted: Vec::new(),
+                            sync_token: Some(
+                                bindings::SyncToken {
+                                    generation: vec![u8::MAX; 16],
+                                    seq: u64::MAX,
+                                    snapshot_start: Some(u64::MAX),
+                                }
+                            ),
+                            more: true,
+                            reset: true,
+                        }
+                    )
+                ),
+            }
+        );
+        assert!(encoded_response_len(note_changes) <= IPC_STORAGE_MESSAGE_MAX_SIZE);
+        let search_results = bindings::response::Response::SearchNotes(
+            bindings::SearchNotesResponse {
+                response: Some(
+                    bindings::search_notes_response::Response::Results(
+                        bindings::NoteSearchResults {
+                            results: vec![
+                                max_search_result(max_note_name_len);
+                                limits.note_search as usize
+                            ],
+                        }
+                    )
+                ),
+                next: Some(max_note_metadata()),
+            }
+        );
+        assert!(encoded_response_len(search_results) <= IPC_STORAGE_MESSAGE_MAX_SIZE);
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::BTreeSet;
+use std::io::ErrorKind;
+use std::os::unix::prelude::*;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use data::{NoteEvent, NoteInfo, NoteMetadata, NoteTombstone, NoteVersion, ShareAccess, UserQuota, UsernameStr, UsernameString};
+use log::{debug, error, trace};
+use time::{Duration, UtcDateTime};
+use uuid::Uuid;
+
+use crate::app_constants::{TAGS_DIRECTORY_PATH, TRASH_DIRECTORY_PATH};
+use crate::storage::errors::StorageError;
+use crate::storage::io_trait::NoteStorageIo;
+use crate::storage::NoteStorageImpl;
+use crate::storage::tasks::summarize_tasks;
+
+// Deleted notes are moved to `<user dir>/.trash/<note id>`, keeping their
+// revisions until purged. The rename updates the file's ctime, which
+// tells when the note was deleted, unless recorded otherwise.
+#[allow(private_bounds)]
+impl<Io: NoteStorageIo> NoteStorageImpl<Io> {
+    /// Moves the note to the trash, replacing an earlier deleted version
+    /// with the same id. The writer needs write access to the note when
+    /// it's shared with them.
+    pub async fn delete_note(
+        &self,
+        username: &UsernameStr,
+        writer: &UsernameStr,
+        id: Uuid,
+    ) -> Result<(), StorageError> {
+        debug!("moving note {id} for user \"{username}\" to the trash");
+        let _write_guard = self.write_lock.lock().await;
+        self
+            .check_note_access(writer, username, id, ShareAccess::ReadWrite)
+            .await?;
+        self
+            .track_usage(username, Some(id), self.delete_note_locked(username, id))
+            .await
+    }
+
+    // must be called with the write lock held
+    async fn delete_note_locked(
+        &self,
+        username: &UsernameStr,
+        id: Uuid,
+    ) -> Result<(), StorageError> {
+        self.io
+            .create_dir_all(self.get_trash_dir(username))
+            .await?;
+        self.io
+            .rename_file(
+                self.get_note_path(username, id),
+                self.get_trashed_note_path(username, id),
+            )
+            .await
+            .map_err(|e|
+                if e.kind() == ErrorKind::NotFound {
+                    StorageError::NoteNotFound
+                } else {
+                    StorageError::from(e)
+                }
+            )?;
+        // the tags and archive time of an earlier deleted version go with it
+        self.remove_archive_time(&self.get_trashed_note_path(username, id))
+            .await?;
+        self.remove_tags_file(self.get_trashed_note_tags_path(username, id))
+            .await?;
+        self
+            .move_tags_file(
+                self.get_note_tags_path(username, id),
+                self.get_trashed_note_tags_path(username, id),
+            )
+            .await?;
+        let tombstone = NoteTombstone {
+            id,
+            deleted_at: UtcDateTime::now(),
+        };
+        self.update_search_index(username, id, None).await;
+        self.journal_deletion(username, tombstone).await;
+        self.publish_event(username, NoteEvent::Deleted(tombstone));
+        Ok(())
+    }
+
+    pub async fn list_trash(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<Vec<NoteInfo>, StorageError> {
+        debug!("listing the trash for user \"{username}\"");
+        let mut read = match self.io
+            .read_dir(self.get_trash_dir(username))
+            .await
+        {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut ret = Vec::new();
+        while let Some(entry) = read.next_entry().await? {
+            let Some(id) = Self::try_extract_uuid(entry.file_name()) else {
+                trace!("skipping trash dir entry \"{entry:?}\"");
+                continue
+            };
+            let name = match self.read_note_name(username, id, entry.path()).await {
+                Ok(name) => name,
+                Err(e) => {
+                    error!(
+                        "failed to read trashed note {id} \
+                            for user \"{username}\": {e}"
+                    );
+                    continue
+                },
+            };
+            let tags = self
+                .read_tags_file(username, id, self.get_trashed_note_tags_path(username, id))
+                .await
+                .unwrap_or_else(|e| {
+                    error!(
+                        "failed to read tags of trashed note {id} \
+                            for user \"{username}\": {e}"
+                    );
+                    BTreeSet::new()
+                });
+            ret.push(
+                NoteInfo {
+                    metadata: NoteMetadata {
+                        id,
+                        mtime: UtcDateTime::from_unix_timestamp(
+                            entry.metadata().await?.mtime(),
+                        )?,
+                    },
+                    name,
+                    tags,
+                    tasks: None,
+                }
+            );
+        }
+        ret.sort_by_key(|info| info.metadata.mtime);
+        Ok(ret)
+    }
+
+    /// Fails if a note with the same id has been created since the deletion
+    pub async fn restore_trashed_note(
+        &self,
+        username: &UsernameStr,
+        id: Uuid,
+        quota: UserQuota,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageError> {
+        debug!("restoring note {id} for user \"{username}\" from the trash");
+        let _write_guard = self.write_lock.lock().await;
+        let trashed_path = self.get_trashed_note_path(username, id);
+        let metadata = match self.io.metadata(&trashed_path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == ErrorKind::NotFound =>
+                return Err(StorageError::NoteNotFound),
+            Err(e) => return Err(e.into()),
+        };
+        match self.io.metadata(self.get_note_path(username, id)).await {
+            Ok(_) => return Err(StorageError::NoteExists),
+            Err(e) if e.kind() == ErrorKind::NotFound => {},
+            Err(e) => return Err(e.into()),
+        }
+        self.check_note_count_quota(username, quota).await?;
+        self
+            .track_usage(
+                username,
+                Some(id),
+                self.restore_trashed_note_locked(username, id, metadata.mtime),
+            )
+            .await
+    }
+
+    // must be called with the write lock held
+    async fn restore_trashed_note_locked(
+        &self,
+        username: &UsernameStr,
+        id: Uuid,
+        mtime: i64,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageError> {
+        self.io
+            .rename_file(
+                self.get_trashed_note_path(username, id),
+                self.get_note_path(username, id),
+            )
+            .await?;
+        let (_, version) = self.stat_note(username, id)
+            .await?
+            .ok_or(StorageError::NoteNotFound)?;
+        self.remove_archive_time(&self.get_trashed_note_path(username, id))
+            .await?;
+        self
+            .move_tags_file(
+                self.get_trashed_note_tags_path(username, id),
+                self.get_note_tags_path(username, id),
+            )
+            .await?;
+        let (name, tags, tasks) = match self.read_note(username, id).await {
+            Ok(note) => {
+                self.update_search_index(username, id, Some(&note)).await;
+                (note.name, note.tags, Some(summarize_tasks(&note.contents)))
+            },
+            Err(e) => {
+                error!(
+                    "failed to read restored note {id} for user \"{username}\" \
+                        for indexing: {e}"
+                );
+                (None, BTreeSet::new(), None)
+            },
+        };
+        let metadata = NoteMetadata {
+            id,
+            mtime: UtcDateTime::from_unix_timestamp(mtime)?,
+        };
+        self.journal_write(username, metadata).await;
+        self.publish_event(username, NoteEvent::Created(NoteInfo { metadata, name, tags, tasks }));
+        Ok((metadata, version))
+    }
+
+    /// Removes the note from the trash along with its revisions
+    pub async fn purge_trashed_note(
+        &self,
+        username: &UsernameStr,
+        id: Uuid,
+    ) -> Result<(), StorageError> {
+        debug!("purging note {id} for user \"{username}\" from the trash");
+        let _write_guard = self.write_lock.lock().await;
+        self
+            .track_usage(username, Some(id), self.purge_trashed_note_locked(username, id))
+            .await
+    }
+
+    /// Purges the notes deleted longer than `retention` ago for all users
+    pub async fn purge_expired_trash(
+        &self,
+        retention: Duration,
+    ) -> Result<(), StorageError> {
+        debug!("purging trash entries older than {retention}");
+        let oldest_allowed = (UtcDateTime::now() - retention).unix_timestamp();
+        let mut read = self.io.read_dir(&self.basedir).await?;
+        while let Some(entry) = read.next_entry().await? {
+            let Some(username) = entry.file_name()
+                .to_str()
+                .and_then(|name| UsernameString::from_str(name).ok())
+            else {
+                trace!("skipping notes dir entry \"{entry:?}\"");
+                continue
+            };
+            if !entry.file_type().await?.is_dir() {
+                continue
+            }
+            if let Err(e) = self
+                .purge_user_trash_before(&username, oldest_allowed)
+                .await
+            {
+                error!("failed to purge the trash for user \"{username}\": {e}");
+            }
+        }
+        Ok(())
+    }
+
+    async fn purge_user_trash_before(
+        &self,
+        username: &UsernameStr,
+        oldest_allowed: i64,
+    ) -> Result<(), StorageError> {
+        let _write_guard = self.write_lock.lock().await;
+        let mut read = match self.io
+            .read_dir(self.get_trash_dir(username))
+            .await
+        {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = read.next_entry().await? {
+            let Some(id) = Self::try_extract_uuid(entry.file_name()) else {
+                continue
+            };
+            if self.read_archive_time(&entry.path()).await? >= oldest_allowed {
+                continue
+            }
+            trace!("purging expired note {id} for user \"{username}\"");
+            self
+                .track_usage(username, Some(id), self.purge_trashed_note_locked(username, id))
+                .await?;
+        }
+        Ok(())
+    }
+
+    // must be called with the write lock held
+    async fn purge_trashed_note_locked(
+        &self,
+        username: &UsernameStr,
+        id: Uuid,
+    ) -> Result<(), StorageError> {
+        self.io
+            .remove_file(self.get_trashed_note_path(username, id))
+            .await
+            .map_err(|e|
+                if e.kind() == ErrorKind::NotFound {
+                    StorageError::NoteNotFound
+                } else {
+                    e.into()
+                }
+            )?;
+        self.remove_archive_time(&self.get_trashed_note_path(username, id))
+            .await?;
+        self.remove_tags_file(self.get_trashed_note_tags_path(username, id))
+            .await?;
+        self.remove_note_shares(username, id).await?;
+        self.remove_revisions(username, id).await
+    }
+
+    pub(super) fn get_trash_dir(&self, username: &UsernameStr) -> PathBuf {
+        self.get_user_dir(username).join(TRASH_DIRECTORY_PATH)
+    }
+
+    pub(super) fn get_trashed_note_path(&self, username: &UsernameStr, id: Uuid) -> PathBuf {
+        self.get_trash_dir(username).join(id.hyphenated().to_string())
+    }
+
+    pub(super) fn get_trashed_note_tags_path(&self, username: &UsernameStr, id: Uuid) -> PathBuf {
+        self.get_trash_dir(username)
+            .join(TAGS_DIRECTORY_PATH)
+            .join(id.hyphenated().to_string())
+    }
+}
@@ -0,0 +1,69 @@
+use auth_ipc_data::bindings;
+use auth_ipc_data::model::get_user_quota::{GetUserQuotaRequest, GetUserQuotaResponse};
+use data::{UserQuota, UsernameStr};
+use dumbnotes::bin_constants::IPC_MESSAGE_MAX_SIZE;
+use dumbnotes::gen_proto_ipc_wrappers;
+use dumbnotes::ipc::caller::{Caller, CallerError, CallerImpl};
+use log::trace;
+use protobuf_common::ProtobufRequestError;
+use thiserror::Error;
+use tokio::net::UnixStream;
+
+type ProductionCaller = CallerImpl<
+    bindings::response::Response,
+    bindings::Response,
+    Response,
+>;
+
+gen_proto_ipc_wrappers!(
+    bindings::Response[response] | bindings::response::Response => pub Response,
+    bindings::Command[command] | bindings::command::Command => pub Command,
+);
+
+/// Looks the quotas up in the auth daemon on every use, so that
+/// changes to the user db apply right away
+pub struct UserQuotas {
+    caller: ProductionCaller,
+}
+
+impl UserQuotas {
+    pub async fn new(auth_socket: UnixStream) -> Self {
+        let (caller, _) = ProductionCaller
+            ::new(auth_socket, IPC_MESSAGE_MAX_SIZE)
+            .await;
+        UserQuotas { caller }
+    }
+
+    /// The overrides of the configured quota for the user
+    pub async fn get_user_quota(
+        &self,
+        username: &UsernameStr,
+    ) -> Result<UserQuota, UserQuotasError> {
+        trace!("looking up the quota of user \"{username}\"");
+        let response: GetUserQuotaResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::GetUserQuota(
+                        GetUserQuotaRequest {
+                            username: username.to_owned(),
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        response.0.map_err(|_| UserQuotasError::AuthDaemonInternalError)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum UserQuotasError {
+    #[error("calling the auth daemon failed: {0}")]
+    Caller(#[from] CallerError),
+
+    #[error("auth daemon internal error")]
+    AuthDaemonInternalError,
+
+    #[error(transparent)]
+    ProtobufError(#[from] ProtobufRequestError),
+}
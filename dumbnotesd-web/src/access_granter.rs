@@ -0,0 +1,332 @@
+use access_token::{AccessTokenData, AccessTokenValidator, AccessTokenValidatorError};
+use auth_ipc_data::model::successful_login::SuccessfulLogin;
+use data::{SessionKind, UsernameStr};
+use dumbnotes::bin_constants::IPC_MESSAGE_MAX_SIZE;
+use dumbnotes::gen_proto_ipc_wrappers;
+use dumbnotes::ipc::data::IpcOutput;
+use tokio::sync::oneshot;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+use async_trait::async_trait;
+use log::{debug, error, trace};
+use tokio::net::UnixStream;
+use dumbnotes::ipc::caller::{Caller, CallerImpl};
+use auth_ipc_data::model::login::{LoginFailure, LoginRequest, LoginResponse};
+use auth_ipc_data::model::logout::{LogoutRequest, LogoutResponse};
+use auth_ipc_data::model::check_session::{CheckSessionRequest, CheckSessionResponse};
+use auth_ipc_data::bindings::{self, LoginError, LogoutError, SessionsError};
+use uuid::Uuid;
+
+use crate::app_constants::{LIVE_SESSIONS_CACHE_CAPACITY, SESSION_CHECK_CACHE_TIME};
+
+mod errors;
+mod model;
+
+pub use errors::AccessGranterError;
+pub use model::{KnownSession, LoginResult, SessionInfo};
+
+#[async_trait]
+pub trait AccessGranter: Send + Sync + 'static {
+    /// Only accepts the tokens of web sessions
+    async fn check_user_access(
+        &self,
+        access_token: &str,
+    ) -> Result<SessionInfo, AccessGranterError>;
+
+    /// Not cached, as it guards the state-changing requests
+    async fn check_xsrf_token(
+        &self,
+        session: &KnownSession,
+        xsrf_token: &[u8],
+    ) -> Result<bool, AccessGranterError>;
+
+    async fn login_user(
+        &self,
+        username: &UsernameStr,
+        password: &str,
+        second_factor: Option<&str>,
+        client_label: Option<&str>,
+        client_address: Option<IpAddr>,
+    ) -> Result<LoginResult, AccessGranterError>;
+
+    async fn logout_user(
+        &self,
+        session: &KnownSession,
+        xsrf_token: &[u8],
+    ) -> Result<(), AccessGranterError>;
+}
+
+pub struct AccessGranterImpl<
+    Command: Send + Sync + 'static,
+    CommandContainer: prost::Message + 'static,
+    CommandWrapper: IpcOutput<Command, CommandContainer>,
+    Response: Send + Sync + 'static,
+    C: Caller<Command, CommandContainer, CommandWrapper, Response>,
+> {
+    access_token_validator: AccessTokenValidator,
+    caller: C,
+    // when each session was last confirmed alive
+    live_sessions: Mutex<HashMap<Uuid, Instant>>,
+    _phantom: PhantomData<(Command, CommandContainer, CommandWrapper, Response)>,
+}
+
+type ProductionCaller = CallerImpl<
+    bindings::response::Response,
+    bindings::Response,
+    Response,
+>;
+
+pub type ProductionAccessGranter = AccessGranterImpl<
+    bindings::command::Command,
+    bindings::Command,
+    Command,
+    bindings::response::Response,
+    ProductionCaller,
+>;
+
+gen_proto_ipc_wrappers!(
+    bindings::Response[response] | bindings::response::Response => pub Response,
+    bindings::Command[command] | bindings::command::Command => pub Command,
+);
+
+impl ProductionAccessGranter {
+    pub async fn new(
+        access_token_validator: AccessTokenValidator,
+        auth_socket: UnixStream,
+    ) -> (Self, oneshot::Receiver<()>) {
+        let (caller, shutdown_notice) = ProductionCaller
+            ::new(auth_socket, IPC_MESSAGE_MAX_SIZE)
+            .await;
+        (
+            AccessGranterImpl {
+                access_token_validator,
+                caller,
+                live_sessions: Mutex::new(HashMap::new()),
+                _phantom: Default::default(),
+            },
+            shutdown_notice,
+        )
+    }
+}
+
+#[async_trait]
+impl<
+    C: Caller<
+        bindings::command::Command,
+        bindings::Command,
+        Command,
+        bindings::response::Response
+    >,
+> AccessGranter for AccessGranterImpl<bindings::command::Command, bindings::Command, Command, bindings::response::Response, C> {
+    async fn check_user_access(
+        &self,
+        access_token: &str,
+    ) -> Result<SessionInfo, AccessGranterError> {
+        trace!("authenticating user by token {access_token}");
+        match self.access_token_validator.check_access_token(access_token) {
+            Ok(parsed_token) => {
+                let session = into_known_session(access_token, parsed_token)?;
+                if self.check_session(&session, None).await? {
+                    Ok(SessionInfo::Valid(session))
+                } else {
+                    Err(AccessGranterError::InvalidToken)
+                }
+            },
+            Err(AccessTokenValidatorError::InvalidToken(_)) =>
+                Err(AccessGranterError::InvalidToken),
+            Err(AccessTokenValidatorError::ExpiredToken(parsed_token)) => Ok(
+                SessionInfo::Expired(
+                    into_known_session(access_token, *parsed_token)?
+                )
+            ),
+        }
+    }
+
+    async fn check_xsrf_token(
+        &self,
+        session: &KnownSession,
+        xsrf_token: &[u8],
+    ) -> Result<bool, AccessGranterError> {
+        self.check_session(session, Some(xsrf_token)).await
+    }
+
+    async fn login_user(
+        &self,
+        username: &UsernameStr,
+        password: &str,
+        second_factor: Option<&str>,
+        client_label: Option<&str>,
+        client_address: Option<IpAddr>,
+    ) -> Result<LoginResult, AccessGranterError> {
+        debug!("logging user \"{username}\" in");
+        let response: LoginResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::Login(
+                        LoginRequest {
+                            username: username.to_owned(),
+                            password: password.to_owned(),
+                            session_kind: SessionKind::Web,
+                            client_label: client_label.map(str::to_owned),
+                            second_factor: second_factor.map(str::to_owned),
+                            client_address,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        match response.0 {
+            Ok(SuccessfulLogin::Web { access_token, xsrf_token }) => {
+                let expires_at = match self.access_token_validator.check_access_token(&access_token) {
+                    Ok(parsed_token) => parsed_token.expires_at,
+                    Err(e) => {
+                        error!("received an unusable access token: {e}");
+                        return Err(AccessGranterError::AuthDaemonInternalError)
+                    },
+                };
+                Ok(
+                    LoginResult {
+                        access_token,
+                        xsrf_token,
+                        expires_at,
+                    }
+                )
+            },
+
+            Ok(_) => {
+                error!("received invalid login response");
+                Err(AccessGranterError::AuthDaemonInternalError)
+            },
+
+            Err(LoginFailure { error, retry_after }) => Err(
+                match error {
+                    LoginError::LoginInvalidCredentials => AccessGranterError::InvalidCredentials,
+                    LoginError::LoginSecondFactorRequired => AccessGranterError::SecondFactorRequired,
+                    LoginError::LoginTooManyAttempts => match retry_after {
+                        Some(retry_after) => AccessGranterError::TooManyAttempts { retry_after },
+                        None => {
+                            error!("received a lockout without its duration");
+                            AccessGranterError::AuthDaemonInternalError
+                        },
+                    },
+                    LoginError::LoginInternalError => AccessGranterError::AuthDaemonInternalError,
+                }
+            )
+        }
+    }
+
+    async fn logout_user(
+        &self,
+        session: &KnownSession,
+        xsrf_token: &[u8],
+    ) -> Result<(), AccessGranterError> {
+        trace!("deleting session {}", session.session_id);
+        let response: LogoutResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::Logout(
+                        LogoutRequest {
+                            access_token: session.raw_token.clone(),
+                            xsrf_token: Some(xsrf_token.to_owned()),
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        self.live_sessions.lock().unwrap().remove(&session.session_id);
+        match response.0 {
+            Some(error) => Err(
+                match error {
+                    LogoutError::LogoutInvalidCredentials => AccessGranterError::InvalidToken,
+                    LogoutError::LogoutInternalError => AccessGranterError::AuthDaemonInternalError,
+                }
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<
+    C: Caller<
+        bindings::command::Command,
+        bindings::Command,
+        Command,
+        bindings::response::Response
+    >,
+> AccessGranterImpl<bindings::command::Command, bindings::Command, Command, bindings::response::Response, C> {
+    async fn check_session(
+        &self,
+        session: &KnownSession,
+        xsrf_token: Option<&[u8]>,
+    ) -> Result<bool, AccessGranterError> {
+        let is_cached = xsrf_token.is_none() && self.live_sessions
+            .lock()
+            .unwrap()
+            .get(&session.session_id)
+            .is_some_and(|checked_at| checked_at.elapsed() < SESSION_CHECK_CACHE_TIME);
+        if is_cached {
+            return Ok(true)
+        }
+
+        trace!("checking session {} of user \"{}\"", session.session_id, session.username);
+        let response: CheckSessionResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::CheckSession(
+                        CheckSessionRequest {
+                            username: session.username.clone(),
+                            session_id: session.session_id,
+                            xsrf_token: xsrf_token.map(<[u8]>::to_vec),
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        let is_alive = response.0.map_err(map_sessions_error)?;
+        let mut live_sessions = self.live_sessions.lock().unwrap();
+        if is_alive {
+            if live_sessions.len() >= LIVE_SESSIONS_CACHE_CAPACITY {
+                live_sessions.retain(|_, checked_at| {
+                    checked_at.elapsed() < SESSION_CHECK_CACHE_TIME
+                });
+            }
+            live_sessions.insert(session.session_id, Instant::now());
+        } else if xsrf_token.is_none() {
+            debug!("session {} of user \"{}\" was revoked", session.session_id, session.username);
+            live_sessions.remove(&session.session_id);
+        }
+        Ok(is_alive)
+    }
+}
+
+fn into_known_session(
+    access_token: &str,
+    parsed_token: AccessTokenData,
+) -> Result<KnownSession, AccessGranterError> {
+    if parsed_token.session_kind != SessionKind::Web {
+        debug!("rejecting a token of non-web session {}", parsed_token.session_id);
+        return Err(AccessGranterError::InvalidToken)
+    }
+    Ok(
+        KnownSession {
+            raw_token: access_token.to_owned(),
+            session_id: parsed_token.session_id,
+            username: parsed_token.username,
+            expires_at: parsed_token.expires_at,
+        }
+    )
+}
+
+fn map_sessions_error(error: SessionsError) -> AccessGranterError {
+    match error {
+        SessionsError::SessionsInvalidCredentials |
+        SessionsError::SessionsNotFound => AccessGranterError::InvalidToken,
+        SessionsError::SessionsInternalError => AccessGranterError::AuthDaemonInternalError,
+    }
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+use thiserror::Error;
+use dumbnotes::ipc::caller::CallerError;
+use protobuf_common::ProtobufRequestError;
+
+#[derive(Debug, Error)]
+pub enum AccessGranterError {
+    #[error("invalid token")]
+    InvalidToken,
+
+    #[error("invalid credentials")]
+    InvalidCredentials,
+
+    #[error("second authentication factor required")]
+    SecondFactorRequired,
+
+    #[error("too many failed logins, retry after {retry_after:?}")]
+    TooManyAttempts {
+        retry_after: Duration,
+    },
+
+    #[error("calling the auth daemon failed")]
+    Caller(#[from] CallerError),
+
+    #[error("auth daemon internal error")]
+    AuthDaemonInternalError,
+
+    #[error(transparent)]
+    ProtobufError(#[from] ProtobufRequestError),
+}
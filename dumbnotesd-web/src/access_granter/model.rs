@@ -0,0 +1,23 @@
+use time::OffsetDateTime;
+use uuid::Uuid;
+use data::UsernameString;
+
+pub struct LoginResult {
+    pub access_token: String,
+    pub xsrf_token: Vec<u8>,
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Debug)]
+pub enum SessionInfo {
+    Valid(KnownSession),
+    Expired(KnownSession),
+}
+
+#[derive(Debug)]
+pub struct KnownSession {
+    pub raw_token: String,
+    pub session_id: Uuid,
+    pub username: UsernameString,
+    pub expires_at: OffsetDateTime,
+}
@@ -1,2 +1,13 @@
+use std::time::Duration;
+
 pub const WEB_PREFIX: &str = "/";
 pub const DEFAULT_WEB_PORT: u16 = 8082;
+pub const LOGIN_PATH: &str = "/login";
+pub const NOTE_LIST_PAGE_SIZE: u32 = 50;
+/// Sessions revoked from another client keep working here for at most
+/// this long
+pub const SESSION_CHECK_CACHE_TIME: Duration = Duration::from_secs(5);
+pub const LIVE_SESSIONS_CACHE_CAPACITY: usize = 1024;
+pub const ACCESS_TOKEN_COOKIE: &str = "access_token";
+pub const XSRF_TOKEN_COOKIE: &str = "xsrf_token";
+pub const XSRF_TOKEN_HEADER: &str = "X-XSRF-Token";
@@ -2,60 +2,598 @@ mod static_content;
 mod language;
 mod translator;
 mod authentication_guard;
+mod htmx;
+mod note_list_cursor;
+mod note_version;
+mod user_agent;
 
 use askama::Template;
-use rocket::response::Redirect;
-use rocket::{Build, Rocket, delete, get, post, routes};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use data::{is_valid_tag, Note, NoteInfo, NoteListOrder, NoteListQuery, NoteMetadata, UsernameString, VersionedNote, MAX_NOTE_TAGS, MAX_TAG_LEN, TaskSummary};
+use log::{debug, error};
+use markdown_renderer::{render_markdown, render_markdown_with_task_inputs};
+use rocket::form::{Form, FromForm};
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::{Build, Request, Rocket, State, catch, catchers, delete, get, post, routes};
 use rocket::response::content::RawHtml;
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::str::FromStr;
+use storage_ipc_sdk::StorageAccessor;
+use storage_ipc_sdk::errors::StorageAccessorError;
+use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, UtcDateTime};
+use util::send_fut_lifetime_workaround;
 use uuid::Uuid;
-use crate::app_constants::WEB_PREFIX;
+use crate::access_granter::{AccessGranter, AccessGranterError, LoginResult};
+use crate::app_constants::{ACCESS_TOKEN_COOKIE, LOGIN_PATH, NOTE_LIST_PAGE_SIZE, WEB_PREFIX, XSRF_TOKEN_COOKIE};
+use crate::routes::authentication_guard::Authenticated;
+use crate::routes::htmx::{HtmxRedirect, HtmxReplaceUrl, HtmxRequest};
 use crate::routes::language::BestLanguage;
+use crate::routes::note_list_cursor::NoteListCursor;
+use crate::routes::note_version::NoteVersionParam;
 use crate::routes::static_content::WebStaticContentRocketBuildExt;
 use crate::routes::translator::{t, Translator};
+use crate::routes::user_agent::UserAgent;
 
 #[derive(Debug, Template)]
 #[template(path = "login.html")]
 struct LoginPage {
     t: Translator,
+    username: String,
+    error: Option<String>,
 }
 
-// #[get("/login")]
-// fn authenticated_login_redirect(
-//     _auth: Authenticated,
-// ) -> Redirect {
-//     Redirect::temporary(format!("{WEB_PREFIX}/login"))
-// }
+#[derive(Debug, Template)]
+#[template(path = "login_form.html")]
+struct LoginFormFragment {
+    t: Translator,
+    username: String,
+    error: Option<String>,
+}
+
+#[derive(Debug, Template)]
+#[template(path = "notes.html")]
+struct NotesPage {
+    t: Translator,
+    username: UsernameString,
+    xsrf_token: String,
+    notes: Vec<NoteListEntry>,
+    next: Option<NoteListCursor>,
+}
+
+#[derive(Debug, Template)]
+#[template(path = "note_list_items.html")]
+struct NoteListItemsFragment {
+    t: Translator,
+    notes: Vec<NoteListEntry>,
+    next: Option<NoteListCursor>,
+}
+
+#[derive(Debug, Template)]
+#[template(path = "note.html")]
+struct NotePage {
+    t: Translator,
+    username: UsernameString,
+    xsrf_token: String,
+    note: NoteView,
+    message: Option<String>,
+}
+
+#[derive(Debug, Template)]
+#[template(path = "note_saved.html")]
+struct NoteSavedFragment {
+    t: Translator,
+    note: NoteView,
+    message: Option<String>,
+}
+
+#[derive(Debug, Template)]
+#[template(path = "note_task_toggled.html")]
+struct NoteTaskToggledFragment {
+    t: Translator,
+    note: NoteView,
+    message: Option<String>,
+}
+
+#[derive(Debug)]
+struct NoteListEntry {
+    id: Uuid,
+    name: Option<String>,
+    tags: BTreeSet<String>,
+    tasks: Option<TaskSummary>,
+    mtime: DisplayTime,
+}
+
+impl From<NoteInfo> for NoteListEntry {
+    fn from(value: NoteInfo) -> Self {
+        NoteListEntry {
+            id: value.metadata.id,
+            name: value.name,
+            tags: value.tags,
+            tasks: value.tasks,
+            mtime: value.metadata.mtime.into(),
+        }
+    }
+}
+
+/// A note as edited in the form
+#[derive(Debug)]
+struct NoteView {
+    id: Uuid,
+    name: String,
+    /// Separated by commas
+    tags: String,
+    contents: String,
+    rendered: String,
+    /// `None` until the note is saved
+    mtime: Option<DisplayTime>,
+    /// The stored version the edits are based on, `None` until the note
+    /// is saved
+    version: Option<NoteVersionParam>,
+}
+
+impl From<VersionedNote> for NoteView {
+    fn from(VersionedNote { note: value, version }: VersionedNote) -> Self {
+        NoteView {
+            id: value.metadata.id,
+            name: value.name.unwrap_or_default(),
+            tags: value.tags
+                .into_iter()
+                .collect::<Vec<_>>()
+                .join(", "),
+            rendered: render_note_contents(value.metadata.id, &value.contents, true),
+            contents: value.contents,
+            mtime: Some(value.metadata.mtime.into()),
+            version: Some(NoteVersionParam(version)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct DisplayTime {
+    time: UtcDateTime,
+    /// For the `datetime` attribute
+    rfc3339: String,
+    display: String,
+}
+
+impl From<UtcDateTime> for DisplayTime {
+    fn from(value: UtcDateTime) -> Self {
+        DisplayTime {
+            time: value,
+            rfc3339: value.format(&Rfc3339).unwrap_or_default(),
+            display: format!("{} {:02}:{:02}", value.date(), value.hour(), value.minute()),
+        }
+    }
+}
+
+#[derive(Debug, FromForm)]
+struct LoginForm {
+    username: String,
+    password: String,
+    second_factor: Option<String>,
+}
 
 #[get("/login")]
 fn login_page(
+    auth: Option<Authenticated>,
+    language: BestLanguage,
+) -> Result<RawHtml<String>, HtmxRedirect> {
+    if auth.is_some() {
+        return Err(HtmxRedirect(WEB_PREFIX))
+    }
+    Ok(
+        RawHtml(
+            LoginPage {
+                t: language.0.into(),
+                username: String::new(),
+                error: None,
+            }
+                .render()
+                .unwrap()
+        )
+    )
+}
+
+#[get("/?<after>")]
+async fn root(
+    auth: Authenticated,
+    language: BestLanguage,
+    htmx: HtmxRequest,
+    after: Option<&str>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+) -> Result<RawHtml<String>, Status> {
+    let after = after
+        .map(NoteListCursor::from_str)
+        .transpose()
+        .map_err(|_| Status::BadRequest)?;
+    let page = note_storage
+        .get_users_notes(
+            auth.session.raw_token,
+            NoteListQuery {
+                limit: Some(NOTE_LIST_PAGE_SIZE),
+                after: after.map(|cursor| cursor.0),
+                order: NoteListOrder::NewestFirst,
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|e| storage_error_status("listing notes", e))?;
+    let t = language.0.into();
+    let notes = page.notes
+        .into_iter()
+        .map(NoteListEntry::from)
+        .collect();
+    let next = page.next.map(NoteListCursor);
+    let page = if htmx.0 {
+        NoteListItemsFragment { t, notes, next }.render()
+    } else {
+        NotesPage {
+            t,
+            username: auth.session.username,
+            xsrf_token: auth.xsrf_token,
+            notes,
+            next,
+        }.render()
+    };
+    Ok(RawHtml(page.unwrap()))
+}
+
+#[post("/login", data = "<form>")]
+async fn login_submit(
+    form: Form<LoginForm>,
+    language: BestLanguage,
+    htmx: HtmxRequest,
+    user_agent: UserAgent,
+    client_address: Option<IpAddr>,
+    cookies: &CookieJar<'_>,
+    access_granter: &State<Box<dyn AccessGranter>>,
+) -> Result<HtmxRedirect, RawHtml<String>> {
+    let t: Translator = language.0.into();
+    let LoginForm { username, password, second_factor } = form.into_inner();
+    let second_factor = second_factor.filter(|code| !code.is_empty());
+    let result = match UsernameString::from_str(&username) {
+        Ok(parsed_username) => access_granter
+            .login_user(
+                &parsed_username,
+                &password,
+                second_factor.as_deref(),
+                user_agent.0.as_deref(),
+                client_address,
+            )
+            .await,
+        Err(_) => Err(AccessGranterError::InvalidCredentials),
+    };
+    match result {
+        Ok(LoginResult { access_token, xsrf_token, expires_at }) => {
+            cookies.add(session_cookie(ACCESS_TOKEN_COOKIE, access_token, expires_at));
+            cookies.add(
+                session_cookie(
+                    XSRF_TOKEN_COOKIE,
+                    Base64UrlUnpadded::encode_string(&xsrf_token),
+                    expires_at,
+                )
+            );
+            Ok(HtmxRedirect(WEB_PREFIX))
+        },
+        Err(e) => {
+            let error = match e {
+                AccessGranterError::InvalidToken |
+                AccessGranterError::InvalidCredentials
+                => t!(t, "login.error.invalid_credentials"),
+
+                AccessGranterError::SecondFactorRequired
+                => t!(t, "login.error.second_factor_required"),
+
+                AccessGranterError::TooManyAttempts { retry_after }
+                => t!(t, "login.error.too_many_attempts", seconds = retry_after.as_secs().max(1)),
+
+                AccessGranterError::Caller(_) |
+                AccessGranterError::AuthDaemonInternalError |
+                AccessGranterError::ProtobufError(_)
+                => {
+                    error!("error logging user \"{username}\" in: {e}");
+                    t!(t, "login.error.internal")
+                },
+            }.into_owned();
+            let page = if htmx.0 {
+                LoginFormFragment { t, username, error: Some(error) }.render()
+            } else {
+                LoginPage { t, username, error: Some(error) }.render()
+            };
+            Err(RawHtml(page.unwrap()))
+        },
+    }
+}
+
+#[post("/logout")]
+async fn logout(
+    auth: Authenticated,
+    cookies: &CookieJar<'_>,
+    access_granter: &State<Box<dyn AccessGranter>>,
+) -> Result<HtmxRedirect, Status> {
+    let xsrf_token = auth.decode_xsrf_token()
+        .ok_or(Status::Forbidden)?;
+    match access_granter.logout_user(&auth.session, &xsrf_token).await {
+        Ok(()) | Err(AccessGranterError::InvalidToken) => {
+            remove_session_cookies(cookies);
+            Ok(HtmxRedirect(LOGIN_PATH))
+        },
+        Err(e) => {
+            error!("error logging out session {}: {e}", auth.session.session_id);
+            Err(Status::InternalServerError)
+        },
+    }
+}
+
+#[catch(401)]
+fn catch_unauthorized(request: &Request<'_>) -> HtmxRedirect {
+    let cookies = request.cookies();
+    if cookies.get(ACCESS_TOKEN_COOKIE).is_some() || cookies.get(XSRF_TOKEN_COOKIE).is_some() {
+        remove_session_cookies(cookies);
+    }
+    HtmxRedirect(LOGIN_PATH)
+}
+
+fn session_cookie(
+    name: &'static str,
+    value: String,
+    expires_at: OffsetDateTime,
+) -> Cookie<'static> {
+    Cookie::build((name, value))
+        .path(WEB_PREFIX)
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .expires(expires_at)
+        .build()
+}
+
+fn remove_session_cookies(cookies: &CookieJar<'_>) {
+    cookies.remove(Cookie::build(ACCESS_TOKEN_COOKIE).path(WEB_PREFIX));
+    cookies.remove(Cookie::build(XSRF_TOKEN_COOKIE).path(WEB_PREFIX));
+}
+
+#[get("/notes/new")]
+fn new_note(
+    auth: Authenticated,
     language: BestLanguage,
 ) -> RawHtml<String> {
     RawHtml(
-        LoginPage { t: language.0.into() }
+        NotePage {
+            t: language.0.into(),
+            username: auth.session.username,
+            xsrf_token: auth.xsrf_token,
+            note: NoteView {
+                id: Uuid::new_v4(),
+                name: String::new(),
+                tags: String::new(),
+                contents: String::new(),
+                rendered: String::new(),
+                mtime: None,
+                version: None,
+            },
+            message: None,
+        }
             .render()
             .unwrap()
     )
 }
 
-#[get("/")]
-fn root() -> RawHtml<&'static str> {
-    todo!()
-}
-#[post("/")]
-fn login_submit() -> RawHtml<&'static str> {
-    todo!()
-}
 #[get("/notes/<note_id>")]
-fn one_note_view(note_id: Uuid) -> RawHtml<&'static str> {
-    todo!()
+async fn one_note_view(
+    auth: Authenticated,
+    language: BestLanguage,
+    note_id: Uuid,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+) -> Result<RawHtml<String>, Status> {
+    let note =
+        send_fut_lifetime_workaround(
+            note_storage.get_note(auth.session.raw_token, note_id, None)
+        )
+        .await
+        .map_err(|e| storage_error_status("fetching note", e))?;
+    Ok(
+        RawHtml(
+            NotePage {
+                t: language.0.into(),
+                username: auth.session.username,
+                xsrf_token: auth.xsrf_token,
+                note: note.into(),
+                message: None,
+            }
+                .render()
+                .unwrap()
+        )
+    )
 }
-#[post("/notes/<note_id>")]
-fn save_note(note_id: Uuid, /* TODO */) -> RawHtml<&'static str> {
-    todo!()
+
+#[derive(Debug, FromForm)]
+struct NoteForm {
+    name: String,
+    tags: String,
+    contents: String,
+    /// The displayed modification time, empty for the notes not saved yet
+    mtime: Option<i64>,
+    /// Empty for the notes not saved yet
+    base_version: String,
+}
+
+#[post("/notes/<note_id>", data = "<form>")]
+async fn save_note(
+    auth: Authenticated,
+    language: BestLanguage,
+    note_id: Uuid,
+    form: Form<NoteForm>,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+) -> Result<HtmxReplaceUrl<RawHtml<String>>, Status> {
+    let t: Translator = language.0.into();
+    let NoteForm { name, tags, contents, mtime, base_version } = form.into_inner();
+    let mtime = mtime
+        .map(UtcDateTime::from_unix_timestamp)
+        .transpose()
+        .map_err(|_| Status::BadRequest)?;
+    let base_version = Some(base_version)
+        .filter(|version| !version.is_empty())
+        .map(|version| version.parse::<NoteVersionParam>())
+        .transpose()
+        .map_err(|_| Status::BadRequest)?;
+    let mut note = NoteView {
+        id: note_id,
+        name,
+        tags,
+        contents,
+        rendered: String::new(),
+        mtime: mtime.map(DisplayTime::from),
+        version: base_version,
+    };
+    let mut is_saved = false;
+
+    let message = match parse_tags(&note.tags) {
+        Some(tags) => {
+            let result = note_storage
+                .write_note(
+                    auth.session.raw_token,
+                    Note {
+                        metadata: NoteMetadata {
+                            id: note_id,
+                            mtime: UtcDateTime::now(),
+                        },
+                        name: Some(note.name.clone()).filter(|name| !name.is_empty()),
+                        tags,
+                        contents: note.contents.clone(),
+                    },
+                    base_version.map(|version| version.0),
+                    None,
+                )
+                .await;
+            match result {
+                Ok((metadata, version)) => {
+                    note.mtime = Some(metadata.mtime.into());
+                    note.version = Some(NoteVersionParam(version));
+                    is_saved = true;
+                    t!(t, "note.saved")
+                },
+                Err(StorageAccessorError::Conflict(current)) => {
+                    debug!(
+                        "stale write of note {note_id} for user \"{}\"",
+                        auth.session.username,
+                    );
+                    // saving again overwrites the current version
+                    note.mtime = Some(current.note.metadata.mtime.into());
+                    note.version = Some(NoteVersionParam(current.version));
+                    t!(t, "note.error.conflict")
+                },
+                Err(StorageAccessorError::NotFound) => {
+                    note.mtime = None;
+                    note.version = None;
+                    t!(t, "note.error.not_found")
+                },
+                Err(StorageAccessorError::TooBig) => t!(t, "note.error.too_big"),
+                Err(StorageAccessorError::QuotaExceeded) => t!(t, "note.error.quota_exceeded"),
+                Err(StorageAccessorError::InvalidCredentials) => return Err(Status::Unauthorized),
+                Err(e) => {
+                    error!("error writing note: {e}");
+                    t!(t, "note.error.internal")
+                },
+            }
+        },
+        None => t!(
+            t,
+            "note.error.invalid_tags",
+            max_tags = MAX_NOTE_TAGS,
+            max_length = MAX_TAG_LEN,
+        ),
+    }.into_owned();
+    note.rendered = render_note_contents(note_id, &note.contents, is_saved);
+
+    Ok(
+        HtmxReplaceUrl(
+            RawHtml(
+                NoteSavedFragment {
+                    t,
+                    note,
+                    message: Some(message),
+                }
+                    .render()
+                    .unwrap()
+            ),
+            format!("/notes/{note_id}"),
+        )
+    )
 }
+
 #[delete("/notes/<note_id>")]
-fn delete_note(note_id: Uuid) -> RawHtml<&'static str> {
-    todo!()
+async fn delete_note(
+    auth: Authenticated,
+    note_id: Uuid,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+) -> Result<HtmxRedirect, Status> {
+    note_storage
+        .delete_note(auth.session.raw_token, note_id, None)
+        .await
+        .map_err(|e| storage_error_status("deleting note", e))?;
+    Ok(HtmxRedirect(WEB_PREFIX))
+}
+
+#[post("/notes/<note_id>/tasks/<task_index>/toggle")]
+async fn toggle_note_task(
+    auth: Authenticated,
+    language: BestLanguage,
+    note_id: Uuid,
+    task_index: u32,
+    note_storage: &State<Box<dyn StorageAccessor>>,
+) -> Result<RawHtml<String>, Status> {
+    let note = note_storage
+        .toggle_note_task(auth.session.raw_token, note_id, task_index)
+        .await
+        .map_err(|e| storage_error_status("toggling note task", e))?;
+    Ok(
+        RawHtml(
+            NoteTaskToggledFragment {
+                t: language.0.into(),
+                note: note.into(),
+                message: None,
+            }
+                .render()
+                .unwrap()
+        )
+    )
+}
+
+// The task checkboxes stay disabled unless the contents are what's stored,
+// as the task indices would be off otherwise
+fn render_note_contents(note_id: Uuid, contents: &str, is_saved: bool) -> String {
+    if !is_saved {
+        return render_markdown(contents)
+    }
+    render_markdown_with_task_inputs(contents, |index| format!(
+        "hx-post=\"/notes/{note_id}/tasks/{index}/toggle\" \
+            hx-target=\"#note-rendered\" hx-swap=\"outerHTML\""
+    ))
+}
+
+/// `None` when any of the comma separated tags is invalid
+fn parse_tags(tags: &str) -> Option<BTreeSet<String>> {
+    let tags = tags
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| is_valid_tag(tag).then(|| tag.to_owned()))
+        .collect::<Option<BTreeSet<_>>>()?;
+    (tags.len() <= MAX_NOTE_TAGS).then_some(tags)
+}
+
+fn storage_error_status(action: &str, e: StorageAccessorError) -> Status {
+    match e {
+        StorageAccessorError::NotFound => Status::NotFound,
+        // the session was revoked since it was checked
+        StorageAccessorError::InvalidCredentials => Status::Unauthorized,
+        e => {
+            error!("error {action}: {e}");
+            Status::InternalServerError
+        },
+    }
 }
 
 pub trait WebRocketBuildExt {
@@ -70,6 +608,20 @@ impl WebRocketBuildExt for Rocket<Build> {
                 WEB_PREFIX,
                 routes![
                     login_page,
+                    login_submit,
+                    root,
+                    logout,
+                    new_note,
+                    one_note_view,
+                    save_note,
+                    delete_note,
+                    toggle_note_task,
+                ]
+            )
+            .register(
+                WEB_PREFIX,
+                catchers![
+                    catch_unauthorized,
                 ]
             )
     }
@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use base64ct::{Base64UrlUnpadded, Encoding};
+use log::{error, info, warn};
+use rocket::{Request, State};
+use rocket::http::{Method, Status};
+use rocket::outcome::try_outcome;
+use rocket::request::{FromRequest, Outcome};
+use crate::access_granter::{AccessGranter, AccessGranterError, KnownSession, SessionInfo};
+use crate::app_constants::{ACCESS_TOKEN_COOKIE, XSRF_TOKEN_COOKIE, XSRF_TOKEN_HEADER};
+
+/// Fails with `Status::Unauthorized` when there's no usable session,
+/// the requests other than `GET` also need the xsrf token header
+#[derive(Debug)]
+pub struct Authenticated {
+    pub session: KnownSession,
+    /// As stored in the cookie, to be sent back by the pages
+    pub xsrf_token: String,
+}
+
+impl Authenticated {
+    pub fn decode_xsrf_token(&self) -> Option<Vec<u8>> {
+        Base64UrlUnpadded::decode_vec(&self.xsrf_token).ok()
+    }
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for Authenticated {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let cookies = request.cookies();
+        let (Some(access_token), Some(xsrf_token)) = (
+            cookies.get(ACCESS_TOKEN_COOKIE),
+            cookies.get(XSRF_TOKEN_COOKIE),
+        ) else {
+            return Outcome::Error((Status::Unauthorized, ()))
+        };
+        let access_granter = try_outcome!(request.guard::<&State<Box<dyn AccessGranter>>>().await);
+        let session = match access_granter.check_user_access(access_token.value()).await {
+            Ok(SessionInfo::Valid(session)) => session,
+            Ok(SessionInfo::Expired(session)) => {
+                info!(
+                    "session {} of user \"{}\" has expired",
+                    session.session_id,
+                    session.username,
+                );
+                return Outcome::Error((Status::Unauthorized, ()))
+            },
+            Err(AccessGranterError::InvalidToken) => {
+                return Outcome::Error((Status::Unauthorized, ()))
+            },
+            Err(e) => {
+                error!("authentication system failed: {e}");
+                return Outcome::Error((Status::InternalServerError, ()))
+            },
+        };
+
+        if !matches!(request.method(), Method::Get | Method::Head) {
+            let header_token = request.headers()
+                .get_one(XSRF_TOKEN_HEADER)
+                .and_then(|header| Base64UrlUnpadded::decode_vec(header).ok());
+            let is_valid = match header_token {
+                Some(header_token) => match access_granter
+                    .check_xsrf_token(&session, &header_token)
+                    .await
+                {
+                    Ok(is_valid) => is_valid,
+                    Err(e) => {
+                        error!("authentication system failed: {e}");
+                        return Outcome::Error((Status::InternalServerError, ()))
+                    },
+                },
+                None => false,
+            };
+            if !is_valid {
+                warn!(
+                    "rejecting {} {} of session {}: missing or invalid xsrf token",
+                    request.method(),
+                    request.uri(),
+                    session.session_id,
+                );
+                return Outcome::Error((Status::Forbidden, ()))
+            }
+        }
+
+        Outcome::Success(
+            Authenticated {
+                session,
+                xsrf_token: xsrf_token.value().to_owned(),
+            }
+        )
+    }
+}
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use rocket::{Request, Response};
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::{self, Redirect, Responder};
+
+const HX_REQUEST_HEADER: &str = "HX-Request";
+const HX_REDIRECT_HEADER: &str = "HX-Redirect";
+const HX_REPLACE_URL_HEADER: &str = "HX-Replace-Url";
+
+/// Whether the request was made by htmx, expecting a page fragment
+#[derive(Debug)]
+pub struct HtmxRequest(pub bool);
+
+#[async_trait]
+impl<'r> FromRequest<'r> for HtmxRequest {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(HtmxRequest(is_htmx_request(request)))
+    }
+}
+
+fn is_htmx_request(request: &Request<'_>) -> bool {
+    request.headers().get_one(HX_REQUEST_HEADER) == Some("true")
+}
+
+/// Makes htmx load the whole page instead of swapping in the response,
+/// a plain `303 See Other` for the requests made without it
+#[derive(Debug)]
+pub struct HtmxRedirect(pub &'static str);
+
+impl<'r> Responder<'r, 'static> for HtmxRedirect {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        if is_htmx_request(request) {
+            Response::build()
+                .status(Status::NoContent)
+                .raw_header(HX_REDIRECT_HEADER, self.0)
+                .ok()
+        } else {
+            Redirect::to(self.0).respond_to(request)
+        }
+    }
+}
+
+/// Makes htmx show the url in the address bar after swapping in the
+/// response
+#[derive(Debug)]
+pub struct HtmxReplaceUrl<R>(pub R, pub String);
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for HtmxReplaceUrl<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        Response::build_from(self.0.respond_to(request)?)
+            .raw_header(HX_REPLACE_URL_HEADER, self.1)
+            .ok()
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+use std::str::FromStr;
+use data::NoteMetadata;
+use time::UtcDateTime;
+use uuid::Uuid;
+
+/// Position in the note list, in the same format as the API's
+#[derive(Clone, Copy, Debug)]
+pub struct NoteListCursor(pub NoteMetadata);
+
+#[derive(Debug)]
+pub struct InvalidNoteListCursor;
+
+impl fmt::Display for NoteListCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.0.mtime.unix_timestamp(), self.0.id.simple())
+    }
+}
+
+impl FromStr for NoteListCursor {
+    type Err = InvalidNoteListCursor;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mtime, id) = s.split_once('.').ok_or(InvalidNoteListCursor)?;
+        Ok(
+            NoteListCursor(
+                NoteMetadata {
+                    id: Uuid::try_parse(id).map_err(|_| InvalidNoteListCursor)?,
+                    mtime: mtime.parse::<i64>()
+                        .ok()
+                        .and_then(|v| UtcDateTime::from_unix_timestamp(v).ok())
+                        .ok_or(InvalidNoteListCursor)?,
+                }
+            )
+        )
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt;
+use std::str::FromStr;
+use data::NoteVersion;
+
+/// Version a note edit is based on, in the same format as the API's
+#[derive(Clone, Copy, Debug)]
+pub struct NoteVersionParam(pub NoteVersion);
+
+#[derive(Debug)]
+pub struct InvalidNoteVersion;
+
+impl fmt::Display for NoteVersionParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.0.ino, self.0.ctime, self.0.ctime_nsec)
+    }
+}
+
+impl FromStr for NoteVersionParam {
+    type Err = InvalidNoteVersion;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, '.');
+        let mut next = || parts.next().ok_or(InvalidNoteVersion);
+        Ok(
+            NoteVersionParam(
+                NoteVersion {
+                    ino: next()?.parse().map_err(|_| InvalidNoteVersion)?,
+                    ctime: next()?.parse().map_err(|_| InvalidNoteVersion)?,
+                    ctime_nsec: next()?.parse().map_err(|_| InvalidNoteVersion)?,
+                }
+            )
+        )
+    }
+}
@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+use rocket::Request;
+use rocket::http::hyper::header;
+use rocket::request::{FromRequest, Outcome};
+
+#[derive(Debug)]
+pub struct UserAgent(pub Option<String>);
+
+#[async_trait]
+impl<'r> FromRequest<'r> for UserAgent {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(
+            UserAgent(
+                request.headers()
+                    .get_one(header::USER_AGENT.as_str())
+                    .map(str::to_owned)
+            )
+        )
+    }
+}
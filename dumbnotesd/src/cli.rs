@@ -19,12 +19,17 @@ pub struct CliConfig {
     #[cfg(debug_assertions)]
     #[arg(long, default_value_t = false)]
     pub no_fork: bool,
+
+    /// Encrypt the plaintext note files in place with the configured key
+    /// and exit, runs in the foreground
+    #[arg(long, default_value_t = false)]
+    pub encrypt_notes: bool,
 }
 
 impl CliConfig {
     #[cfg(not(debug_assertions))]
     pub fn is_daemonizing(&self) -> bool {
-        !self.no_daemonize
+        !self.no_daemonize && !self.encrypt_notes
     }
 
     #[cfg(debug_assertions)]
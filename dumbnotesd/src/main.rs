@@ -2,8 +2,8 @@ use std::{ffi::{OsStr, OsString}, ops::Not, os::fd::AsRawFd, process::ExitStatus
 
 use boolean_enums::gen_boolean_enum;
 use clap::{crate_name, Parser};
-use dumbnotes::{config::{app_config::AppConfig, read::read_app_config}, ipc::socket::create_socket_pair, sandbox::user_group::{clear_supplementary_groups, set_user_and_group}};
-use dumbnotesd::{app_constants::{EXTRA_SHUTDOWN_TIMEOUT, SHUTDOWN_TIMEOUT}, cli::CliConfig, exec_path::{get_apid_executable_path, get_authd_executable_path, get_storaged_executable_path, get_webd_executable_path}, kill_with_timeout::{KillWithTimeoutChildExt, SendTerm}, launch_sub::{launch_sub, launch_sub_with_sockets}};
+use dumbnotes::{config::{app_config::AppConfig, read::read_app_config}, ipc::socket::create_socket_pair, sandbox::user_group::{clear_supplementary_groups, get_user_and_group, set_user_and_group}};
+use dumbnotesd::{app_constants::{EXTRA_SHUTDOWN_TIMEOUT, SHUTDOWN_TIMEOUT}, cli::CliConfig, exec_path::{get_apid_executable_path, get_authd_executable_path, get_storaged_executable_path, get_webd_executable_path}, kill_with_timeout::{KillWithTimeoutChildExt, SendTerm}, launch_sub::{launch_sub, launch_sub_with_sockets, IsDaemonizing}};
 use futures::{FutureExt, Stream, future::{join_all, select_all}};
 use socket2::Socket;
 use tap::Pipe;
@@ -47,6 +47,10 @@ async fn async_main(cli_config: CliConfig, is_root: IsRoot) {
 
     info!("{} starting up", crate_name!());
 
+    if cli_config.encrypt_notes {
+        encrypt_notes(&read_config(&cli_config), is_root).await
+    }
+
     if !cli_config.is_daemonizing() && is_root.into() {
         error_exit!("daemonizing is required when launching from root")
     }
@@ -54,17 +58,7 @@ async fn async_main(cli_config: CliConfig, is_root: IsRoot) {
         error_exit!("cannot be daemonizing from a non-root user")
     }
 
-    if !cli_config.config_file.exists() {
-        error_exit!(
-            "configuration file at {} does not exist",
-            cli_config.config_file.display()
-        )
-    }
-
-    let app_config = read_app_config(&cli_config.config_file)
-        .unwrap_or_else(|e|
-            error_exit!("failed to read the config file: {e}")
-        );
+    let app_config = read_config(&cli_config);
 
     if !app_config.is_api_enabled && !app_config.is_web_enabled {
         error_exit!("all network servers are disabled in the configuration")
@@ -143,6 +137,59 @@ async fn async_main(cli_config: CliConfig, is_root: IsRoot) {
 }
 gen_boolean_enum!(IsRoot);
 
+fn read_config(cli_config: &CliConfig) -> AppConfig {
+    if !cli_config.config_file.exists() {
+        error_exit!(
+            "configuration file at {} does not exist",
+            cli_config.config_file.display()
+        )
+    }
+
+    read_app_config(&cli_config.config_file)
+        .unwrap_or_else(|e|
+            error_exit!("failed to read the config file: {e}")
+        )
+}
+
+async fn encrypt_notes(app_config: &AppConfig, is_root: IsRoot) -> ! {
+    if app_config.note_encryption_key.is_none() {
+        error_exit!("note_encryption_key is not set in the configuration")
+    }
+    let user_group = app_config.storage_user_group.as_deref()
+        .filter(|_| is_root.into())
+        .map(|user_group|
+            get_user_and_group(user_group)
+                .unwrap_or_else(|e|
+                    error_exit!("failed to get the storage user and group: {e}")
+                )
+        );
+    let storaged_path = get_storaged_executable_path()
+        .unwrap_or_else(|e|
+            error_exit!("failed to get storaged executable path: {e}")
+        );
+    let status = launch_sub(
+        storaged_path,
+        None,
+        IsDaemonizing::No,
+        |command| {
+            add_storaged_args(command, app_config);
+            command.arg("--encrypt-notes");
+            if let Some((uid, gid)) = user_group {
+                command.uid(uid).gid(gid);
+            }
+        },
+    ).await
+        .unwrap_or_else(|e| error_exit!("failed to launch storaged: {e}"))
+        .wait()
+        .await
+        .unwrap_or_else(|e| error_exit!("failed to wait for storaged: {e}"));
+    if !status.success() {
+        error_exit!("encrypting the notes failed with status {status}")
+    }
+    info!("finished encrypting the notes");
+    std::process::exit(0)
+}
+
 async fn intercept_singals() -> impl Stream<Item=()> {
     let int_signal = signal(SignalKind::interrupt())
         .unwrap_or_else(|e|
@@ -189,6 +236,7 @@ async fn spawn_children(
     let mut sockets = Vec::new();
     let api_socket_to_auth = store_pair(&mut sockets, app_config.is_api_enabled);
     let web_socket_to_auth = store_pair(&mut sockets, app_config.is_web_enabled);
+    let storage_socket_to_auth = store_pair(&mut sockets, true).unwrap();
     let auth_child = launch_sub_with_sockets(
         authd_path,
         app_config.authd_user_group.as_str_ref(),
@@ -197,8 +245,34 @@ async fn spawn_children(
         |command: &mut Command| {
             command
                 .arg(path_arg("private-key-file", &app_config.jwt_private_key))
+                .arg(path_arg("public-key-file", &app_config.jwt_public_key))
                 .arg(path_arg("data-directory", &app_config.data_directory))
                 .arg(path_arg("user-db-path", &app_config.user_db))
+                .arg(
+                    format!(
+                        "--login-lockout-threshold={}",
+                        app_config.login_lockout_threshold,
+                    )
+                )
+                .arg(
+                    format!(
+                        "--address-login-lockout-threshold={}",
+                        app_config.address_login_lockout_threshold,
+                    )
+                )
+                .arg(
+                    format!(
+                        "--login-lockout-seconds={}",
+                        app_config.login_lockout_seconds,
+                    )
+                )
+                .arg(
+                    format!(
+                        "--max-login-lockout-seconds={}",
+                        app_config.max_login_lockout_seconds,
+                    )
+                )
+                .args(audit_log_args(app_config))
                 .arg(
                     format!(
                         "--hasher-config={}",
@@ -221,26 +295,20 @@ async fn spawn_children(
         );
     let api_socket_to_storage = store_pair(&mut sockets, app_config.is_api_enabled);
     let web_socket_to_storage = store_pair(&mut sockets, app_config.is_web_enabled);
+    set_cloexec(&storage_socket_to_auth, false);
     let storage_child = launch_sub_with_sockets(
         storaged_path,
         app_config.storage_user_group.as_str_ref(),
         sockets.iter().map(AsRawFd::as_raw_fd),
         cli_config.is_daemonizing().into(),
         |command| {
-            command
-                .arg(path_arg("public-key-file", &app_config.jwt_public_key))
-                .arg(path_arg("data-directory", &app_config.data_directory))
-                .arg(format!("--max-note-len={}", &app_config.max_note_size))
-                .arg(
-                    format!(
-                        "--max-note-name-len={}",
-                        app_config.max_note_name_size,
-                    )
-                );
+            add_storaged_args(command, app_config);
+            command.arg(socket_arg("auth-socket-fd", &storage_socket_to_auth));
         },
     ).await
         .unwrap_or_else(|e| error_exit!("failed to launch storaged: {e}"));
     drop(sockets);
+    drop(storage_socket_to_auth);
 
     let api_child = if app_config.is_api_enabled {
         let api_socket_to_auth = api_socket_to_auth.unwrap();
@@ -329,6 +397,61 @@ async fn spawn_children(
     }
 }
 
+fn add_storaged_args(command: &mut Command, app_config: &AppConfig) {
+    command
+        .arg(path_arg("public-key-file", &app_config.jwt_public_key))
+        .arg(path_arg("data-directory", &app_config.data_directory))
+        .arg(format!("--max-note-len={}", &app_config.max_note_size))
+        .arg(
+            format!(
+                "--max-note-name-len={}",
+                app_config.max_note_name_size,
+            )
+        )
+        .arg(
+            format!(
+                "--max-note-revisions={}",
+                app_config.max_note_revisions,
+            )
+        )
+        .arg(
+            format!(
+                "--max-note-revision-age-days={}",
+                app_config.max_note_revision_age_days,
+            )
+        )
+        .arg(
+            format!(
+                "--trash-retention-days={}",
+                app_config.trash_retention_days,
+            )
+        )
+        .arg(
+            format!(
+                "--max-user-storage-size={}",
+                app_config.max_user_storage_size,
+            )
+        )
+        .arg(
+            format!(
+                "--max-user-notes={}",
+                app_config.max_user_notes,
+            )
+        )
+        .args(audit_log_args(app_config));
+    if let Some(ref note_encryption_key) = app_config.note_encryption_key {
+        command.arg(path_arg("note-key-file", note_encryption_key));
+    }
+}
+
+fn audit_log_args(app_config: &AppConfig) -> [OsString; 3] {
+    [
+        path_arg("audit-log-directory", &app_config.audit_log_directory),
+        format!("--audit-log-max-size={}", app_config.audit_log_max_size).into(),
+        format!("--audit-log-max-files={}", app_config.audit_log_max_files).into(),
+    ]
+}
+
 struct Spawns {
     daemons: Vec<Child>,
     servers: Vec<Child>,
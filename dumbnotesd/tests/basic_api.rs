@@ -1,5 +1,6 @@
 //! Happy path tests
 
+use std::collections::BTreeSet;
 use std::error::Error;
 use std::str::FromStr;
 use data::UsernameString;
@@ -59,6 +60,8 @@ fn create_read_delete_check_deletion() -> Result<(), Box<dyn Error>> {
                 name: Some("a title".to_string()),
                 mtime,
                 contents: "of a note".to_string(),
+                base_version: None,
+                tags: BTreeSet::new(),
             },
         )?;
 
@@ -70,11 +73,10 @@ fn create_read_delete_check_deletion() -> Result<(), Box<dyn Error>> {
             access_token.as_deref(),
         )?
         .try_into()?;
-    assert_eq!(read_note.0.metadata.id, note_id);
-    // TODO: check when implemented
-    // assert_eq!(read_note.0.metadata.mtime, mtime);
-    assert_eq!(read_note.0.name.as_deref(), Some("a title"));
-    assert_eq!(read_note.0.contents, "of a note");
+    assert_eq!(read_note.note.metadata.id, note_id);
+    assert_eq!(read_note.note.metadata.mtime, mtime);
+    assert_eq!(read_note.note.name.as_deref(), Some("a title"));
+    assert_eq!(read_note.note.contents, "of a note");
 
     RQ
         .delete_pb_successfully::<(), ()>(
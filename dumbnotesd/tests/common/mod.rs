@@ -4,7 +4,7 @@ use assert_fs::{TempDir, prelude::PathChild};
 use data::UsernameStr;
 use reqwest::{IntoUrl, Method, StatusCode, blocking::Response, header::WWW_AUTHENTICATE};
 use tap::{Pipe, Tap};
-use test_utils::{BackgroundReader, ChildKillOnDropExt, DAEMON_BIN_PATH, DAEMON_BIN_PATHS, Faketime, KillOnDropChild, LOCAL_PORT, RQ, ReqwestBuilderProtoExt, ReqwestClientExt, new_configured_command_with_env};
+use test_utils::{BackgroundReader, ChildKillOnDropExt, DAEMON_BIN_PATH, DAEMON_BIN_PATHS, Faketime, KillOnDropChild, LOCAL_PORT, LOCAL_WEB_PORT, RQ, ReqwestBuilderProtoExt, ReqwestClientExt, new_configured_command_with_env};
 use unix::ChildKillTermExt;
 
 pub const ROCKET_STARTED_STRING: &str = "Rocket has launched from";
@@ -20,6 +20,15 @@ pub fn spawn_daemon(
     Ok((child, reader))
 }
 
+/// Waits for both the API and the web rockets
+pub fn spawn_daemon_with_web(
+    dir: &TempDir,
+) -> Result<(KillOnDropChild, BackgroundReader<ChildStderr>), Box<dyn Error>> {
+    let (child, mut reader) = spawn_daemon(dir)?;
+    reader.wait_until(ROCKET_STARTED_STRING)?;
+    Ok((child, reader))
+}
+
 pub fn spawn_daemon_faketime(
     dir: &TempDir,
 ) -> Result<(KillOnDropChild, BackgroundReader<ChildStderr>, Faketime), Box<dyn Error>> {
@@ -124,6 +133,13 @@ pub fn url(endpoint: &str) -> String {
     )
 }
 
+pub fn web_url(path: &str) -> String {
+    format!(
+        "http://localhost:{}/{path}",
+        LOCAL_WEB_PORT.with(Clone::clone),
+    )
+}
+
 pub fn login(
     username: impl AsRef<UsernameStr>,
     password: impl AsRef<str>,
@@ -155,6 +171,8 @@ pub fn call_login(
             LoginRequest {
                 username: username.as_ref().to_owned(),
                 secret,
+                client_label: None,
+                second_factor: None,
             }
         )?
         .try_into()
@@ -232,6 +250,26 @@ where
     )
 }
 
+pub fn assert_http_put_error<I>(
+    url: impl IntoUrl,
+    auth_token: Option<&str>,
+    body: impl Into<I>,
+    error_code: StatusCode,
+    www_authenticate: Option<Unauthorized>,
+) -> Result<Response, Box<dyn Error>>
+where
+    I: prost::Message,
+{
+    assert_http_error(
+        Method::PUT,
+        url,
+        auth_token,
+        body,
+        error_code,
+        www_authenticate,
+    )
+}
+
 pub fn assert_www_authenticate(
     response: &Response,
     error: Unauthorized,
@@ -298,6 +336,8 @@ fn assert_login_error_impl(
         LoginRequest {
             username: username.as_ref().to_owned(),
             secret: secret,
+            client_label: None,
+            second_factor: None,
         },
         status,
         www_authenticate,
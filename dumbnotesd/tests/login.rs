@@ -1,15 +1,19 @@
-use std::{error::Error, str::FromStr, thread::sleep, time::{Duration, SystemTime}};
+use std::{collections::BTreeSet, error::Error, fs, os::unix::fs::PermissionsExt, str::FromStr, thread::sleep, time::{Duration, SystemTime}};
 
 use access_token_data::SESSION_ID_JWT_CLAIM_NAME;
-use api_data::{bindings, http::status::Unauthorized, model::{LoginRequest, LoginRequestSecret, LoginResponse}};
+use api_data::{bindings, http::status::Unauthorized, model::{ActivityResponse, AppPasswordCreateRequest, AppPasswordCreateResponse, AppPasswordListResponse, LoginRequest, LoginRequestSecret, LoginResponse, NoteWriteRequest, PasswordChangeRequest, SessionListResponse}};
 use cfg_or_panic::cfg_or_panic;
-use data::UsernameString;
+use data::{AppPasswordInfo, AuditEvent, AuditEventKind, AuditOutcome, Scope, SessionDescription, SessionKind, UsernameString};
+use dumbnotes::totp;
 use josekit::jwt::JwtPayload;
-use reqwest::StatusCode;
+use reqwest::{Method, StatusCode};
+use assert_fs::prelude::*;
 use tap::Tap;
-use test_utils::{RQ, data::MOCK_JWT_KEY_VERIFIER, setup_basic_config_with_keys_and_data};
+use time::UtcDateTime;
+use uuid::Uuid;
+use test_utils::{GEN_BIN_PATH, RQ, ReqwestBuilderProtoExt, ReqwestClientExt, new_configured_command, data::MOCK_JWT_KEY_VERIFIER, setup_basic_config_with_keys_and_data};
 
-use crate::common::{assert_http_get_error, assert_http_post_error, assert_login_error, assert_maybe_www_authenticate, assert_refresh_error, call_login, login, logout, refresh_token, shutdown_assert_no_errors, shutdown_assert_no_errors_except, spawn_daemon, spawn_daemon_faketime, url};
+use crate::common::{assert_http_error, assert_http_get_error, assert_http_post_error, assert_login_error, assert_maybe_www_authenticate, assert_refresh_error, call_login, login, logout, refresh_token, shutdown_assert_no_errors, shutdown_assert_no_errors_except, spawn_daemon, spawn_daemon_faketime, url};
 
 mod common;
 
@@ -51,6 +55,79 @@ fn wrong_password() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn login_throttling() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    for _ in 0..10 {
+        assert_login_error(
+            &username,
+            "1234",
+            StatusCode::UNAUTHORIZED,
+            Some(Unauthorized::InvalidToken),
+        )?;
+    }
+    // even the right password is refused now
+    let response = assert_http_post_error::<bindings::LoginRequest>(
+        url("login"),
+        None,
+        LoginRequest {
+            username: username.clone(),
+            secret: LoginRequestSecret::Password("123".to_string()),
+            client_label: None,
+            second_factor: None,
+        },
+        StatusCode::TOO_MANY_REQUESTS,
+        None,
+    )?;
+    let retry_after: u64 = response
+        .headers()
+        .get("Retry-After")
+        .ok_or("no Retry-After header")?
+        .to_str()?
+        .parse()?;
+    assert!((1..=30).contains(&retry_after));
+
+    // other users from the same address aren't locked out yet
+    login(UsernameString::from_str("abcdef")?, "012")?;
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn login_throttling_ignores_address_header() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let login_from = |username: &str, password: &str, address: &str| {
+        RQ.post(url("login"))
+            .header("X-Real-IP", address)
+            .pb_body::<bindings::LoginRequest>(
+                LoginRequest {
+                    username: UsernameString::from_str(username)?,
+                    secret: LoginRequestSecret::Password(password.to_string()),
+                    client_label: None,
+                    second_factor: None,
+                }
+            )
+            .send()
+            .map_err(Box::<dyn Error>::from)
+    };
+    for i in 0..20 {
+        let response = login_from(&format!("nobody{i}"), "123", &format!("10.0.0.{i}"))?;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    // the failures count against the peer address, not the claimed ones
+    let response = login_from("abc", "123", "10.0.1.1")?;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
 #[test]
 fn wrong_refresh_token() -> Result<(), Box<dyn Error>> {
     let dir = setup_basic_config_with_keys_and_data();
@@ -151,6 +228,593 @@ fn renewed_jwt_valid() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn change_password() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    // the auth daemon writes a writable user db in place
+    fs::set_permissions(
+        dir.child("etc/dumbnotes/private"),
+        fs::Permissions::from_mode(0o700),
+    )?;
+    fs::set_permissions(
+        dir.child("etc/dumbnotes/private/users.toml"),
+        fs::Permissions::from_mode(0o600),
+    )?;
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let login_1 = login(&username, "123")?;
+    let login_2 = login(&username, "123")?;
+
+    for (old_password, new_password, status) in [
+        ("1234", "456", StatusCode::FORBIDDEN),
+        ("123", "", StatusCode::BAD_REQUEST),
+    ] {
+        assert_http_post_error::<bindings::PasswordChangeRequest>(
+            url("account/password"),
+            Some(&login_1.access_token),
+            PasswordChangeRequest {
+                old_password: old_password.to_string(),
+                new_password: new_password.to_string(),
+                revoke_other_sessions: true,
+            },
+            status,
+            None,
+        )?;
+    }
+    refresh_token(&username, &login_2.refresh_token)?;
+
+    RQ.post_pb_successfully::<bindings::PasswordChangeRequest, ()>(
+        url("account/password"),
+        Some(&login_1.access_token),
+        PasswordChangeRequest {
+            old_password: "123".to_string(),
+            new_password: "456".to_string(),
+            revoke_other_sessions: true,
+        },
+    )?;
+    assert_login_error(
+        &username,
+        "123",
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+    login(&username, "456")?;
+    login(UsernameString::from_str("abcdef")?, "012")?;
+    refresh_token(&username, &login_1.refresh_token)?;
+    assert_refresh_error(
+        &username,
+        &login_2.refresh_token,
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn second_factor_login() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    fs::set_permissions(
+        dir.child("etc/dumbnotes/private"),
+        fs::Permissions::from_mode(0o700),
+    )?;
+    let user_db = dir.child("etc/dumbnotes/private/users.toml");
+    fs::set_permissions(&user_db, fs::Permissions::from_mode(0o600))?;
+    let secret = totp::generate_secret();
+    let recovery_code = "abcde-fghij";
+    let recovery_code_hash = totp::hash_recovery_code(recovery_code);
+    let users = fs::read_to_string(&user_db)?.replacen(
+        "\n\n",
+        &format!(
+            "\ntotp_secret = \"{secret}\"\nrecovery_codes = [\"{recovery_code_hash}\"]\n\n",
+        ),
+        1,
+    );
+    user_db.write_str(&users)?;
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+    let code = totp::generate_code(&secret, now)?;
+    let stale_code = totp::generate_code(&secret, now - 600)?;
+    assert_login_error(
+        &username,
+        "123",
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::SecondFactorRequired),
+    )?;
+    for (password, second_factor) in [
+        ("1234", code.as_str()),
+        ("123", stale_code.as_str()),
+        ("123", "bcdef-ghijk"),
+    ] {
+        assert_second_factor_login_error(&username, password, second_factor)?;
+    }
+
+    second_factor_login_request(&username, "123", &code)?;
+    assert_second_factor_login_error(&username, "123", &code)?;
+    second_factor_login_request(&username, "123", "ABCDE FGHIJ")?;
+    assert_second_factor_login_error(&username, "123", recovery_code)?;
+    assert!(!fs::read_to_string(&user_db)?.contains(&recovery_code_hash));
+
+    // the second factor is per user
+    login(UsernameString::from_str("abcdef")?, "012")?;
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+fn second_factor_login_request(
+    username: &UsernameString,
+    password: &str,
+    second_factor: &str,
+) -> Result<(), Box<dyn Error>> {
+    RQ.post_pb_successfully::<bindings::LoginRequest, bindings::LoginResponse>(
+        url("login"),
+        None,
+        LoginRequest {
+            username: username.clone(),
+            secret: LoginRequestSecret::Password(password.to_string()),
+            client_label: None,
+            second_factor: Some(second_factor.to_string()),
+        },
+    )?;
+    Ok(())
+}
+
+fn assert_second_factor_login_error(
+    username: &UsernameString,
+    password: &str,
+    second_factor: &str,
+) -> Result<(), Box<dyn Error>> {
+    assert_http_post_error::<bindings::LoginRequest>(
+        url("login"),
+        None,
+        LoginRequest {
+            username: username.clone(),
+            secret: LoginRequestSecret::Password(password.to_string()),
+            client_label: None,
+            second_factor: Some(second_factor.to_string()),
+        },
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn list_and_revoke_sessions() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let labeled_login: LoginResponse = RQ
+        .post_pb_successfully::<bindings::LoginRequest, bindings::LoginResponse>(
+            url("login"),
+            None,
+            LoginRequest {
+                username: username.clone(),
+                secret: LoginRequestSecret::Password("123".to_string()),
+                client_label: Some("phone\n".to_string()),
+                second_factor: None,
+            },
+        )?
+        .try_into()?;
+    let other_login = login(&username, "123")?;
+    let other_user_login = login(UsernameString::from_str("abcdef")?, "012")?;
+
+    let sessions = list_sessions(&labeled_login.access_token)?;
+    assert_eq!(sessions.len(), 2);
+    let (current, other): (Vec<_>, Vec<_>) = sessions
+        .into_iter()
+        .partition(|session| session.is_current);
+    assert_eq!(current[0].client_label.as_deref(), Some("phone"));
+    assert_eq!(current[0].kind, SessionKind::Api);
+    assert!(current[0].expires_at > current[0].created_at);
+    let other_user_session = list_sessions(&other_user_login.access_token)?
+        .remove(0);
+
+    assert_http_error::<()>(
+        Method::DELETE,
+        url(&format!("sessions/{}", other_user_session.session_id)),
+        Some(&labeled_login.access_token),
+        (),
+        StatusCode::NOT_FOUND,
+        None,
+    )?;
+    RQ.delete_pb_successfully::<(), ()>(
+        url(&format!("sessions/{}", other[0].session_id)),
+        Some(&labeled_login.access_token),
+        (),
+    )?;
+    assert_refresh_error(
+        &username,
+        &other_login.refresh_token,
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+    assert_eq!(list_sessions(&labeled_login.access_token)?.len(), 1);
+
+    RQ.delete_pb_successfully::<(), ()>(
+        url("sessions"),
+        Some(&labeled_login.access_token),
+        (),
+    )?;
+    assert_refresh_error(
+        &username,
+        &labeled_login.refresh_token,
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+    assert_http_get_error::<()>(
+        url("sessions"),
+        Some(&labeled_login.access_token),
+        (),
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+    refresh_token(
+        UsernameString::from_str("abcdef")?,
+        &other_user_login.refresh_token,
+    )?;
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn account_activity() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    assert_login_error(
+        &username,
+        "1234",
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+    let access_token = login(&username, "123")?.access_token;
+    login(UsernameString::from_str("abcdef")?, "012")?;
+    let note_id = Uuid::new_v4();
+    let note_url = url(&format!("notes/{note_id}"));
+    RQ.put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+        note_url.clone(),
+        Some(&access_token),
+        NoteWriteRequest {
+            mtime: UtcDateTime::now(),
+            name: None,
+            contents: "note".to_string(),
+            base_version: None,
+            tags: BTreeSet::new(),
+        },
+    )?;
+    RQ.delete_pb_successfully::<(), ()>(note_url, Some(&access_token), ())?;
+
+    let activity: ActivityResponse = RQ
+        .get_pb_successfully::<bindings::ActivityResponse>(
+            url("account/activity"),
+            Some(&access_token),
+        )?
+        .try_into()?;
+    assert_eq!(activity.events.len(), 3);
+    assert!(activity.events.iter().all(|event| event.username == username));
+    assert!(
+        activity.events.windows(2).all(|events| events[0].timestamp >= events[1].timestamp)
+    );
+    let find_event = |kind, outcome| activity.events
+        .iter()
+        .find(|event| event.event == kind && event.outcome == outcome);
+    assert!(find_event(AuditEventKind::Login, AuditOutcome::Failure).is_some());
+    assert!(
+        find_event(AuditEventKind::Login, AuditOutcome::Success)
+            .is_some_and(|event| event.session_id.is_some())
+    );
+    assert_eq!(
+        find_event(AuditEventKind::NoteDeletion, AuditOutcome::Success)
+            .and_then(|event| event.note_id),
+        Some(note_id),
+    );
+    let limited: ActivityResponse = RQ
+        .get_pb_successfully::<bindings::ActivityResponse>(
+            url("account/activity?limit=1"),
+            Some(&access_token),
+        )?
+        .try_into()?;
+    assert_eq!(limited.events.len(), 1);
+
+    let read_audit_log = |name: &str| -> Result<Vec<AuditEvent>, Box<dyn Error>> {
+        fs::read_to_string(dir.child("var/log/dumbnotes").child(name))?
+            .lines()
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    };
+    assert_eq!(read_audit_log("auth.jsonl")?.len(), 3);
+    assert_eq!(read_audit_log("storage.jsonl")?.len(), 1);
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn revoked_access_token_rejected() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let logged_out = login(&username, "123")?;
+    let revoked = login(&username, "123")?;
+    let remaining = login(&username, "123")?;
+    for login in [&logged_out, &revoked, &remaining] {
+        RQ.get_pb_successfully::<bindings::NoteListResponse>(
+            url("notes"),
+            Some(&login.access_token),
+        )?;
+    }
+
+    logout(&logged_out.access_token)?;
+    let revoked_id = list_sessions(&revoked.access_token)?
+        .into_iter()
+        .find(|session| session.is_current)
+        .expect("missing current session")
+        .session_id;
+    RQ.delete_pb_successfully::<(), ()>(
+        url(&format!("sessions/{revoked_id}")),
+        Some(&remaining.access_token),
+        (),
+    )?;
+
+    for login in [&logged_out, &revoked] {
+        assert_http_get_error::<()>(
+            url("notes"),
+            Some(&login.access_token),
+            (),
+            StatusCode::UNAUTHORIZED,
+            Some(Unauthorized::InvalidToken),
+        )?;
+    }
+    RQ.get_pb_successfully::<bindings::NoteListResponse>(
+        url("notes"),
+        Some(&remaining.access_token),
+    )?;
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn jwt_key_rotation() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    fs::set_permissions(
+        dir.child("etc/dumbnotes/private"),
+        fs::Permissions::from_mode(0o700),
+    )?;
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let mut logins = vec![login(&username, "123")?];
+    // the keys are replaced by new files, the second rotation checks
+    // that they are still watched after the first one
+    for _ in 0..2 {
+        let result = new_configured_command(&GEN_BIN_PATH, &dir)
+            .arg("--rotate-jwt-key")
+            .spawn()?
+            .wait()?;
+        assert!(result.success(), "status: {result}");
+        // the file watcher debounce, then the new private key activation delay
+        sleep(Duration::from_secs(40));
+        logins.push(login(&username, "123")?);
+    }
+    let key_ids = logins
+        .iter()
+        .map(|login| get_key_id(&login.access_token))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(key_ids[0], None);
+    assert!(key_ids[1].is_some() && key_ids[2].is_some());
+    assert_ne!(key_ids[1], key_ids[2]);
+
+    // the tokens signed with the retired keys are still accepted
+    for login in &logins {
+        RQ.get_pb_successfully::<bindings::NoteListResponse>(
+            url("notes"),
+            Some(&login.access_token),
+        )?;
+    }
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn app_password_scopes() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    fs::set_permissions(
+        dir.child("etc/dumbnotes/private/users.toml"),
+        fs::Permissions::from_mode(0o600),
+    )?;
+    fs::set_permissions(
+        dir.child("etc/dumbnotes/private"),
+        fs::Permissions::from_mode(0o700),
+    )?;
+    let username = UsernameString::from_str("abc")?;
+    let output = new_configured_command(&GEN_BIN_PATH, &dir)
+        .args(["add-app-password", "abc", "backup", "--scope", "notes:read"])
+        .output()?;
+    assert!(output.status.success(), "status: {}", output.status);
+    let app_password = String::from_utf8(output.stdout)?.trim().to_owned();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let limited = login(&username, &app_password)?;
+    RQ.get_pb_successfully::<bindings::NoteListResponse>(
+        url("notes"),
+        Some(&limited.access_token),
+    )?;
+    for (method, endpoint) in [
+        (Method::DELETE, "notes/00000000-0000-0000-0000-000000000000"),
+        (Method::GET, "sessions"),
+    ] {
+        assert_http_error::<()>(
+            method,
+            url(endpoint),
+            Some(&limited.access_token),
+            (),
+            StatusCode::UNAUTHORIZED,
+            Some(Unauthorized::InsufficientScope),
+        )?;
+    }
+    // the refreshed tokens keep the scopes
+    let refreshed = refresh_token(&username, &limited.refresh_token)?;
+    assert_http_get_error::<()>(
+        url("sessions"),
+        Some(&refreshed.access_token),
+        (),
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InsufficientScope),
+    )?;
+    let full = login(&username, "123")?;
+    assert_eq!(list_sessions(&full.access_token)?.len(), 2);
+
+    let result = new_configured_command(&GEN_BIN_PATH, &dir)
+        .args(["remove-app-password", "abc", "backup"])
+        .spawn()?
+        .wait()?;
+    assert!(result.success(), "status: {result}");
+    // the file watcher debounce
+    sleep(Duration::from_secs(15));
+    assert_refresh_error(
+        &username,
+        &refreshed.refresh_token,
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+    assert_login_error(
+        &username,
+        &app_password,
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn manage_app_passwords() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    fs::set_permissions(
+        dir.child("etc/dumbnotes/private/users.toml"),
+        fs::Permissions::from_mode(0o600),
+    )?;
+    fs::set_permissions(
+        dir.child("etc/dumbnotes/private"),
+        fs::Permissions::from_mode(0o700),
+    )?;
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let full = login(&username, "123")?;
+    let request = AppPasswordCreateRequest {
+        name: "backup".to_string(),
+        scopes: BTreeSet::from([Scope::NotesRead]),
+    };
+    let created: AppPasswordCreateResponse = RQ
+        .post_pb_successfully::<bindings::AppPasswordCreateRequest, bindings::AppPasswordCreateResponse>(
+            url("account/app-passwords"),
+            Some(&full.access_token),
+            request.clone(),
+        )?
+        .try_into()?;
+    assert_http_post_error::<bindings::AppPasswordCreateRequest>(
+        url("account/app-passwords"),
+        Some(&full.access_token),
+        request,
+        StatusCode::CONFLICT,
+        None,
+    )?;
+    let listed: AppPasswordListResponse = RQ
+        .get_pb_successfully::<bindings::AppPasswordListResponse>(
+            url("account/app-passwords"),
+            Some(&full.access_token),
+        )?
+        .try_into()?;
+    assert_eq!(
+        listed.app_passwords,
+        vec![
+            AppPasswordInfo {
+                name: "backup".to_string(),
+                scopes: BTreeSet::from([Scope::NotesRead]),
+            },
+        ],
+    );
+
+    let limited = login(&username, &created.password)?;
+    RQ.get_pb_successfully::<bindings::NoteListResponse>(
+        url("notes"),
+        Some(&limited.access_token),
+    )?;
+    assert_http_get_error::<()>(
+        url("account/app-passwords"),
+        Some(&limited.access_token),
+        (),
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InsufficientScope),
+    )?;
+
+    RQ.delete_pb_successfully::<(), ()>(
+        url("account/app-passwords/backup"),
+        Some(&full.access_token),
+        (),
+    )?;
+    assert_http_error::<()>(
+        Method::DELETE,
+        url("account/app-passwords/backup"),
+        Some(&full.access_token),
+        (),
+        StatusCode::NOT_FOUND,
+        None,
+    )?;
+    assert_login_error(
+        &username,
+        &created.password,
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+    assert_http_get_error::<()>(
+        url("notes"),
+        Some(&limited.access_token),
+        (),
+        StatusCode::UNAUTHORIZED,
+        Some(Unauthorized::InvalidToken),
+    )?;
+    list_sessions(&full.access_token)?;
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+fn get_key_id(access_token: &str) -> Result<Option<String>, Box<dyn Error>> {
+    Ok(
+        josekit::jwt::decode_header(access_token)?
+            .claim("kid")
+            .and_then(|kid| kid.as_str())
+            .map(str::to_owned)
+    )
+}
+
+fn list_sessions(
+    access_token: &str,
+) -> Result<Vec<SessionDescription>, Box<dyn Error>> {
+    let response: SessionListResponse = RQ
+        .get_pb_successfully::<bindings::SessionListResponse>(
+            url("sessions"),
+            Some(access_token),
+        )?
+        .try_into()?;
+    Ok(response.sessions)
+}
+
 fn request_jwt(
     secret: LoginRequestSecret,
 ) -> Result<(JwtPayload, Vec<u8>), Box<dyn Error>> {
@@ -186,6 +850,8 @@ fn login_sending_auth_header() -> Result<(), Box<dyn Error>> {
         LoginRequest {
             username: username.clone(),
             secret: LoginRequestSecret::Password("123".to_string()),
+            client_label: None,
+            second_factor: None,
         },
         StatusCode::FORBIDDEN,
         None,
@@ -196,6 +862,8 @@ fn login_sending_auth_header() -> Result<(), Box<dyn Error>> {
         LoginRequest {
             username: username.clone(),
             secret: LoginRequestSecret::Password("123".to_string()),
+            client_label: None,
+            second_factor: None,
         },
         StatusCode::FORBIDDEN,
         None,
@@ -228,6 +896,8 @@ fn renew_sending_auth_header() -> Result<(), Box<dyn Error>> {
             secret: LoginRequestSecret::RefreshToken(
                 login.refresh_token,
             ),
+            client_label: None,
+            second_factor: None,
         },
         StatusCode::FORBIDDEN,
         None,
@@ -240,6 +910,8 @@ fn renew_sending_auth_header() -> Result<(), Box<dyn Error>> {
             secret: LoginRequestSecret::RefreshToken(
                 "123".as_bytes().to_owned()
             ),
+            client_label: None,
+            second_factor: None,
         },
         StatusCode::FORBIDDEN,
         None,
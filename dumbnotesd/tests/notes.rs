@@ -0,0 +1,1425 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::fs::Permissions;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use api_data::bindings;
+use api_data::http::query::NoteVersionParam;
+use api_data::model::{NoteChangesResponse, NoteListResponse, NoteResponse, NoteRevisionListResponse, NoteSearchResponse, NoteShareListResponse, NoteShareRequest, NoteWriteRequest, NoteWriteResponse, SharedNoteListResponse, TagListResponse, UsageResponse};
+use data::{NoteRevisionInfo, NoteSearchResult, NoteShare, ShareAccess, StorageUsage, TagCount, TaskSummary, UsernameString};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::{Method, StatusCode, Url};
+use test_utils::{RQ, ReqwestClientExt, ReqwestResponseProtoExt, setup_basic_config_with_keys_and_data};
+use time::{Duration, UtcDateTime};
+use uuid::Uuid;
+
+use crate::common::{assert_http_error, assert_http_get_error, assert_http_post_error, assert_http_put_error, login, logout, new_command, shutdown_assert_no_errors, spawn_daemon, url};
+
+mod common;
+
+#[test]
+fn stale_write_conflicts() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let note_id = Uuid::new_v4();
+    let mtime = UtcDateTime::from_unix_timestamp(1234567)?;
+    let note_url = url(&format!("notes/{note_id}"));
+
+    let access_token = Some(login(username, "123")?.access_token);
+
+    let first: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+            make_write_request("first", mtime, None),
+        )?
+        .try_into()?;
+
+    // without a base version the write overwrites the note unconditionally
+    let second: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+            make_write_request("second", mtime, None),
+        )?
+        .try_into()?;
+    assert_ne!(second.version, first.version);
+
+    // both writes came with the same client mtime, the versions still differ
+    let current: NoteResponse = assert_http_put_error::<bindings::NoteWriteRequest>(
+        note_url.clone(),
+        access_token.as_deref(),
+        make_write_request("third", mtime, Some(first.version)),
+        StatusCode::CONFLICT,
+        None,
+    )?
+        .read_pb::<bindings::NoteResponse>()?
+        .try_into()?;
+    assert_eq!(current.note.metadata.id, note_id);
+    assert_eq!(current.note.metadata.mtime, second.metadata.mtime);
+    assert_eq!(current.note.contents, "second");
+    assert_eq!(current.version, Some(second.version));
+
+    let written: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+            make_write_request(
+                "third",
+                mtime + Duration::seconds(10),
+                current.version,
+            ),
+        )?
+        .try_into()?;
+    assert_eq!(written.metadata.mtime, mtime + Duration::seconds(10));
+
+    let read_note: NoteResponse = RQ
+        .get_pb_successfully::<bindings::NoteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(read_note.note.contents, "third");
+    assert_eq!(read_note.version, Some(written.version));
+
+    // the mtimes from a clock ahead are pulled back to the server time,
+    // the next version may keep the current one's
+    let future_mtime = UtcDateTime::now() + Duration::days(1);
+    let at_now: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+            make_write_request("fourth", future_mtime, Some(written.version)),
+        )?
+        .try_into()?;
+    let also_at_now: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+            make_write_request("fifth", future_mtime, Some(at_now.version)),
+        )?
+        .try_into()?;
+    assert!(also_at_now.metadata.mtime >= at_now.metadata.mtime);
+    assert!(also_at_now.metadata.mtime <= UtcDateTime::now());
+    let current: NoteResponse = assert_http_put_error::<bindings::NoteWriteRequest>(
+        note_url.clone(),
+        access_token.as_deref(),
+        make_write_request("sixth", future_mtime, Some(at_now.version)),
+        StatusCode::CONFLICT,
+        None,
+    )?
+        .read_pb::<bindings::NoteResponse>()?
+        .try_into()?;
+    assert_eq!(current.note.contents, "fifth");
+    assert_eq!(current.note.metadata.mtime, also_at_now.metadata.mtime);
+    assert_eq!(current.version, Some(also_at_now.version));
+
+    assert_http_put_error::<bindings::NoteWriteRequest>(
+        url(&format!("notes/{}", Uuid::new_v4())),
+        access_token.as_deref(),
+        make_write_request("fourth", mtime, Some(written.version)),
+        StatusCode::NOT_FOUND,
+        None,
+    )?;
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn client_mtime_persisted_and_clamped() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(username, "123")?.access_token);
+
+    let past_id = Uuid::new_v4();
+    let past_mtime = UtcDateTime::from_unix_timestamp(1234567)?;
+    let written: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            url(&format!("notes/{past_id}")),
+            access_token.as_deref(),
+            make_write_request("past", past_mtime, None),
+        )?
+        .try_into()?;
+    assert_eq!(written.metadata.mtime, past_mtime);
+
+    let future_id = Uuid::new_v4();
+    let future_mtime = UtcDateTime::now() + Duration::days(365);
+    let written: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            url(&format!("notes/{future_id}")),
+            access_token.as_deref(),
+            make_write_request("future", future_mtime, None),
+        )?
+        .try_into()?;
+    assert!(written.metadata.mtime <= UtcDateTime::now());
+    let clamped_mtime = written.metadata.mtime;
+
+    let pre_epoch_id = Uuid::new_v4();
+    let written: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            url(&format!("notes/{pre_epoch_id}")),
+            access_token.as_deref(),
+            make_write_request(
+                "pre-epoch",
+                UtcDateTime::from_unix_timestamp(-1000)?,
+                None,
+            ),
+        )?
+        .try_into()?;
+    assert_eq!(written.metadata.mtime, UtcDateTime::UNIX_EPOCH);
+
+    let list: NoteListResponse = RQ
+        .get_pb_successfully::<bindings::NoteListResponse>(
+            url("notes"),
+            access_token.as_deref(),
+        )?
+        .try_into()?;
+    let mtimes = list.notes_info
+        .iter()
+        .map(|info| (info.metadata.id, info.metadata.mtime))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        mtimes,
+        vec![
+            (pre_epoch_id, UtcDateTime::UNIX_EPOCH),
+            (past_id, past_mtime),
+            (future_id, clamped_mtime),
+        ],
+    );
+
+    let read_note: NoteResponse = RQ
+        .get_pb_successfully::<bindings::NoteResponse>(
+            url(&format!("notes/{past_id}")),
+            access_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(read_note.note.metadata.mtime, past_mtime);
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn revisions_list_read_restore() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(username, "123")?.access_token);
+    let note_id = Uuid::new_v4();
+    let note_url = url(&format!("notes/{note_id}"));
+
+    let mut base_version = None;
+    let mut versions = Vec::new();
+    // the mtimes come from the client and may repeat
+    for (contents, mtime) in [("first", 1000), ("second", 2000), ("third", 1000), ("fourth", 3000)] {
+        let written: NoteWriteResponse = RQ
+            .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+                note_url.clone(),
+                access_token.as_deref(),
+                make_write_request(
+                    contents,
+                    UtcDateTime::from_unix_timestamp(mtime)?,
+                    base_version,
+                ),
+            )?
+            .try_into()?;
+        base_version = Some(written.version);
+        versions.push(written.metadata.mtime);
+    }
+
+    let revisions = get_revisions(access_token.as_deref(), note_id)?;
+    assert_eq!(
+        revisions.iter().map(|revision| revision.info.metadata.mtime).collect::<Vec<_>>(),
+        vec![versions[2], versions[1], versions[0]],
+    );
+    assert!(revisions.is_sorted_by(|a, b| a.revision > b.revision));
+    for (revision, contents) in revisions.iter().zip(["third", "second", "first"]) {
+        let read_revision: NoteResponse = RQ
+            .get_pb_successfully::<bindings::NoteResponse>(
+                url(&format!("notes/{note_id}/revisions/{}", revision.revision)),
+                access_token.as_deref(),
+            )?
+            .try_into()?;
+        assert_eq!(read_revision.note.contents, contents);
+    }
+
+    let first_revision = revisions[2].revision;
+
+    let restored: NoteWriteResponse = RQ
+        .post_pb_successfully::<(), bindings::NoteWriteResponse>(
+            url(&format!("notes/{note_id}/revisions/{first_revision}/restore")),
+            access_token.as_deref(),
+            (),
+        )?
+        .try_into()?;
+    assert!(restored.metadata.mtime > versions[3]);
+
+    let read_note: NoteResponse = RQ
+        .get_pb_successfully::<bindings::NoteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(read_note.note.contents, "first");
+    assert_eq!(read_note.note.metadata.mtime, restored.metadata.mtime);
+
+    let revisions = get_revisions(access_token.as_deref(), note_id)?;
+    assert_eq!(revisions.len(), 4);
+    assert_eq!(revisions[0].info.metadata.mtime, versions[3]);
+
+    let status = RQ
+        .get(url(&format!("notes/{note_id}/revisions/12345")))
+        .bearer_auth(access_token.as_deref().unwrap())
+        .send()?
+        .status();
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn trash_restore_purge() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(username, "123")?.access_token);
+    let note_id = Uuid::new_v4();
+    let note_url = url(&format!("notes/{note_id}"));
+    let mtime = UtcDateTime::from_unix_timestamp(1234567)?;
+
+    let written: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+            make_write_request("first", mtime, None),
+        )?
+        .try_into()?;
+    let written: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+            make_write_request("second", mtime, Some(written.version)),
+        )?
+        .try_into()?;
+
+    RQ.delete_pb_successfully::<(), ()>(note_url.clone(), access_token.as_deref(), ())?;
+    assert!(get_notes(access_token.as_deref(), "notes")?.is_empty());
+    assert_eq!(get_notes(access_token.as_deref(), "trash")?, vec![note_id]);
+
+    let restored: NoteWriteResponse = RQ
+        .post_pb_successfully::<(), bindings::NoteWriteResponse>(
+            url(&format!("trash/{note_id}/restore")),
+            access_token.as_deref(),
+            (),
+        )?
+        .try_into()?;
+    assert_eq!(restored.metadata.mtime, written.metadata.mtime);
+    assert!(get_notes(access_token.as_deref(), "trash")?.is_empty());
+    let read_note: NoteResponse = RQ
+        .get_pb_successfully::<bindings::NoteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(read_note.note.contents, "second");
+    assert_eq!(get_revisions(access_token.as_deref(), note_id)?.len(), 1);
+
+    RQ.delete_pb_successfully::<(), ()>(note_url.clone(), access_token.as_deref(), ())?;
+    RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            access_token.as_deref(),
+            make_write_request("recreated", mtime, None),
+        )?;
+    assert_http_post_error::<()>(
+        url(&format!("trash/{note_id}/restore")),
+        access_token.as_deref(),
+        (),
+        StatusCode::CONFLICT,
+        None,
+    )?;
+
+    RQ.delete_pb_successfully::<(), ()>(
+        url(&format!("trash/{note_id}")),
+        access_token.as_deref(),
+        (),
+    )?;
+    assert!(get_notes(access_token.as_deref(), "trash")?.is_empty());
+    assert_http_post_error::<()>(
+        url(&format!("trash/{note_id}/restore")),
+        access_token.as_deref(),
+        (),
+        StatusCode::NOT_FOUND,
+        None,
+    )?;
+    assert!(get_revisions(access_token.as_deref(), note_id)?.is_empty());
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn search_notes() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(username, "123")?.access_token);
+    let mtime = UtcDateTime::from_unix_timestamp(1234567)?;
+    let groceries_id = Uuid::new_v4();
+    let work_id = Uuid::new_v4();
+    for (id, name, contents) in [
+        (groceries_id, "Groceries", "buy milk and Bread\nalso eggs"),
+        (work_id, "Work", "fix the bread machine"),
+    ] {
+        RQ
+            .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+                url(&format!("notes/{id}")),
+                access_token.as_deref(),
+                NoteWriteRequest {
+                    mtime,
+                    name: Some(name.to_string()),
+                    contents: contents.to_string(),
+                    base_version: None,
+                    tags: BTreeSet::new(),
+                },
+            )?;
+    }
+
+    let results = search(access_token.as_deref(), "bread")?;
+    let mut ids = results.iter().map(|r| r.info.metadata.id).collect::<Vec<_>>();
+    ids.sort();
+    let mut expected = vec![groceries_id, work_id];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    let results = search(access_token.as_deref(), "BRE mach")?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].info.metadata.id, work_id);
+    assert_eq!(results[0].info.name.as_deref(), Some("Work"));
+    assert_eq!(results[0].snippets, vec!["fix the bread machine"]);
+
+    assert_eq!(search(access_token.as_deref(), "groceries eggs")?.len(), 1);
+    assert!(search(access_token.as_deref(), "bread cheese")?.is_empty());
+    assert!(search(access_token.as_deref(), "")?.is_empty());
+    // too short to be a prefix
+    assert!(search(access_token.as_deref(), "fi")?.is_empty());
+    assert_eq!(search(access_token.as_deref(), "ALSO")?.len(), 1);
+
+    // same mtime, so the newest first means the greatest id first
+    let first_page = search_page(
+        access_token.as_deref(),
+        &[("q", "bread"), ("limit", "1")],
+    )?;
+    assert_eq!(first_page.results.len(), 1);
+    assert_eq!(first_page.results[0].info.metadata.id, groceries_id.max(work_id));
+    let cursor = first_page.next_cursor.expect("no cursor for the next page").to_string();
+    let second_page = search_page(
+        access_token.as_deref(),
+        &[("q", "bread"), ("limit", "1"), ("cursor", &cursor)],
+    )?;
+    assert_eq!(second_page.results.len(), 1);
+    assert_eq!(second_page.results[0].info.metadata.id, groceries_id.min(work_id));
+    assert!(second_page.next_cursor.is_none());
+
+    RQ.delete_pb_successfully::<(), ()>(
+        url(&format!("notes/{work_id}")),
+        access_token.as_deref(),
+        (),
+    )?;
+    let results = search(access_token.as_deref(), "bread")?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].info.metadata.id, groceries_id);
+
+    let index_path = dir.path().join("var/dumbnotes/notes/abc/.search-index");
+    std::fs::remove_file(&index_path)?;
+    let results = search(access_token.as_deref(), "milk")?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].info.metadata.id, groceries_id);
+    assert!(index_path.exists());
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn list_notes_paginated() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(username, "123")?.access_token);
+    let mut notes = Vec::new();
+    for mtime in [3000, 1000, 2000, 2000, 5000] {
+        let id = Uuid::new_v4();
+        let mtime = UtcDateTime::from_unix_timestamp(mtime)?;
+        RQ
+            .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+                url(&format!("notes/{id}")),
+                access_token.as_deref(),
+                make_write_request("", mtime, None),
+            )?;
+        notes.push((mtime, id));
+    }
+    notes.sort();
+    let oldest_first = notes.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+
+    let page = list_notes(access_token.as_deref(), &[])?;
+    assert_eq!(page.notes_info.len(), notes.len());
+    assert!(page.next_cursor.is_none());
+
+    for (order, expected) in [
+        ("oldest", oldest_first.clone()),
+        ("newest", oldest_first.iter().rev().copied().collect()),
+    ] {
+        let mut listed = Vec::new();
+        let mut cursor = None;
+        loop {
+            let mut params = vec![("limit", "2".to_string()), ("order", order.to_string())];
+            if let Some(cursor) = cursor {
+                params.push(("cursor", cursor));
+            }
+            let page = list_notes(access_token.as_deref(), &params)?;
+            assert!(page.notes_info.len() <= 2);
+            listed.extend(page.notes_info.iter().map(|info| info.metadata.id));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next.to_string()),
+                None => break,
+            }
+        }
+        assert_eq!(listed, expected);
+    }
+
+    let page = list_notes(
+        access_token.as_deref(),
+        &[("modified_since", "2000".to_string())],
+    )?;
+    assert_eq!(page.notes_info.len(), 4);
+
+    for (name, value) in [("cursor", "garbage"), ("order", "sideways"), ("limit", "-1")] {
+        let status = RQ
+            .get(Url::parse_with_params(&url("notes"), [(name, value)])?)
+            .bearer_auth(access_token.as_deref().unwrap())
+            .send()?
+            .status();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn tags_filter_and_count() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(username, "123")?.access_token);
+    let mtime = UtcDateTime::from_unix_timestamp(1234567)?;
+    let urgent_id = Uuid::new_v4();
+    let work_id = Uuid::new_v4();
+    let untagged_id = Uuid::new_v4();
+    for (id, tags) in [
+        (urgent_id, vec!["work", "urgent"]),
+        (work_id, vec!["work", "work"]),
+        (untagged_id, vec![]),
+    ] {
+        RQ
+            .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+                url(&format!("notes/{id}")),
+                access_token.as_deref(),
+                NoteWriteRequest {
+                    tags: tags.into_iter().map(str::to_string).collect(),
+                    ..make_write_request("contents", mtime, None)
+                },
+            )?;
+    }
+
+    let read_note: NoteResponse = RQ
+        .get_pb_successfully::<bindings::NoteResponse>(
+            url(&format!("notes/{urgent_id}")),
+            access_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(
+        read_note.note.tags,
+        BTreeSet::from(["urgent".to_string(), "work".to_string()]),
+    );
+    let note_file = dir.path().join(format!("var/dumbnotes/notes/abc/{urgent_id}"));
+    assert_eq!(std::fs::read_to_string(note_file)?, "\ncontents");
+
+    let tagged = |tag: &str| -> Result<BTreeSet<Uuid>, Box<dyn Error>> {
+        Ok(
+            list_notes(access_token.as_deref(), &[("tag", tag.to_string())])?
+                .notes_info
+                .into_iter()
+                .map(|info| info.metadata.id)
+                .collect()
+        )
+    };
+    assert_eq!(tagged("work")?, BTreeSet::from([urgent_id, work_id]));
+    assert_eq!(tagged("urgent")?, BTreeSet::from([urgent_id]));
+    assert!(tagged("Work")?.is_empty());
+    assert_eq!(list_tags(access_token.as_deref())?, [("urgent".to_string(), 1), ("work".to_string(), 2)]);
+
+    RQ.delete_pb_successfully::<(), ()>(
+        url(&format!("notes/{work_id}")),
+        access_token.as_deref(),
+        (),
+    )?;
+    assert_eq!(list_tags(access_token.as_deref())?, [("urgent".to_string(), 1), ("work".to_string(), 1)]);
+    let trashed: NoteListResponse = RQ
+        .get_pb_successfully::<bindings::NoteListResponse>(
+            url("trash"),
+            access_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(trashed.notes_info[0].tags, BTreeSet::from(["work".to_string()]));
+    RQ.post_pb_successfully::<(), bindings::NoteWriteResponse>(
+        url(&format!("trash/{work_id}/restore")),
+        access_token.as_deref(),
+        (),
+    )?;
+    assert_eq!(tagged("work")?, BTreeSet::from([urgent_id, work_id]));
+
+    for tags in [vec![""], vec![" padded"], vec!["new\nline"]] {
+        assert_http_put_error::<bindings::NoteWriteRequest>(
+            url(&format!("notes/{}", Uuid::new_v4())),
+            access_token.as_deref(),
+            NoteWriteRequest {
+                tags: tags.into_iter().map(str::to_string).collect(),
+                ..make_write_request("", mtime, None)
+            },
+            StatusCode::BAD_REQUEST,
+            None,
+        )?;
+    }
+    let status = RQ
+        .get(Url::parse_with_params(&url("notes"), [("tag", "")])?)
+        .bearer_auth(access_token.as_deref().unwrap())
+        .send()?
+        .status();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn storage_quota_enforced() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let config_path = dir.path().join("etc/dumbnotes/dumbnotes.toml");
+    let config = std::fs::read_to_string(&config_path)?;
+    std::fs::write(&config_path, config + "max_user_storage_size = 120\n")?;
+    let user_db_path = dir.path().join("etc/dumbnotes/private/users.toml");
+    std::fs::set_permissions(&user_db_path, Permissions::from_mode(0o600))?;
+    let user_db = std::fs::read_to_string(&user_db_path)?
+        .replace("username = \"abc\"\n", "username = \"abc\"\nmax_notes = 2\n");
+    std::fs::write(&user_db_path, user_db)?;
+    std::fs::set_permissions(&user_db_path, Permissions::from_mode(0o400))?;
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(username, "123")?.access_token);
+    let mtime = UtcDateTime::from_unix_timestamp(1234567)?;
+    let first_id = Uuid::new_v4();
+    let second_id = Uuid::new_v4();
+    let third_id = Uuid::new_v4();
+    let write = |id: Uuid, contents: &str, base_version: Option<NoteVersionParam>| {
+        RQ
+            .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+                url(&format!("notes/{id}")),
+                access_token.as_deref(),
+                make_write_request(contents, mtime, base_version),
+            )
+    };
+    let assert_quota_exceeded = |id: Uuid, contents: &str, base_version: Option<NoteVersionParam>| {
+        assert_http_put_error::<bindings::NoteWriteRequest>(
+            url(&format!("notes/{id}")),
+            access_token.as_deref(),
+            make_write_request(contents, mtime, base_version),
+            StatusCode::INSUFFICIENT_STORAGE,
+            None,
+        )
+    };
+    let get_usage = || -> Result<StorageUsage, Box<dyn Error>> {
+        let usage: UsageResponse = RQ
+            .get_pb_successfully::<bindings::UsageResponse>(
+                url("account/usage"),
+                access_token.as_deref(),
+            )?
+            .try_into()?;
+        Ok(usage.0)
+    };
+
+    let first: NoteWriteResponse = write(first_id, &"a".repeat(10), None)?.try_into()?;
+    write(second_id, &"b".repeat(10), None)?;
+    assert_eq!(
+        get_usage()?,
+        StorageUsage {
+            storage_size: 22,
+            notes: 2,
+            max_storage_size: Some(120),
+            max_notes: Some(2),
+        },
+    );
+    assert_quota_exceeded(third_id, "", None)?;
+    assert_quota_exceeded(first_id, &"a".repeat(200), Some(first.version))?;
+    write(first_id, &"a".repeat(80), Some(first.version))?;
+
+    RQ.delete_pb_successfully::<(), ()>(
+        url(&format!("notes/{second_id}")),
+        access_token.as_deref(),
+        (),
+    )?;
+    // the replaced version of the first note is kept as a revision
+    assert_eq!(get_usage()?.storage_size, 103);
+    assert_eq!(get_usage()?.notes, 1);
+    assert_quota_exceeded(third_id, &"c".repeat(20), None)?;
+    write(third_id, "", None)?;
+    assert_http_post_error::<()>(
+        url(&format!("trash/{second_id}/restore")),
+        access_token.as_deref(),
+        (),
+        StatusCode::INSUFFICIENT_STORAGE,
+        None,
+    )?;
+    assert_eq!(get_usage()?.notes, 2);
+    RQ.delete_pb_successfully::<(), ()>(
+        url(&format!("trash/{second_id}")),
+        access_token.as_deref(),
+        (),
+    )?;
+    assert_eq!(get_usage()?.storage_size, 93);
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn notes_encrypted_at_rest() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let user_dir = dir.path().join("var/dumbnotes/notes/abc");
+    let legacy_id = Uuid::new_v4();
+    let legacy_path = user_dir.join(legacy_id.to_string());
+    std::fs::write(&legacy_path, "legacy\nplaintext contents")?;
+    std::fs::create_dir(user_dir.join(".tags"))?;
+    std::fs::write(user_dir.join(format!(".tags/{legacy_id}")), "oldtag\n")?;
+    let revision_path = user_dir.join(format!(".revisions/{legacy_id}/1234"));
+    std::fs::create_dir_all(revision_path.parent().unwrap())?;
+    std::fs::hard_link(&legacy_path, &revision_path)?;
+    let trashed_id = Uuid::new_v4();
+    let trashed_path = user_dir.join(format!(".trash/{trashed_id}"));
+    std::fs::create_dir(user_dir.join(".trash"))?;
+    std::fs::write(&trashed_path, "trashed\nplaintext leftovers")?;
+    let key_path = dir.path().join("etc/dumbnotes/private/note_key.b64");
+    std::fs::write(&key_path, "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=\n")?;
+    std::fs::set_permissions(&key_path, Permissions::from_mode(0o400))?;
+    let config_path = dir.path().join("etc/dumbnotes/dumbnotes.toml");
+    let config = std::fs::read_to_string(&config_path)?;
+    std::fs::write(
+        &config_path,
+        config + &format!("note_encryption_key = \"{}\"\n", key_path.display()),
+    )?;
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(&username, "123")?.access_token);
+    let read_note = |id: Uuid| -> Result<NoteResponse, Box<dyn Error>> {
+        Ok(
+            RQ
+                .get_pb_successfully::<bindings::NoteResponse>(
+                    url(&format!("notes/{id}")),
+                    access_token.as_deref(),
+                )?
+                .try_into()?
+        )
+    };
+    let legacy_note = read_note(legacy_id)?;
+    assert_eq!(legacy_note.note.name.as_deref(), Some("legacy"));
+    assert_eq!(legacy_note.note.contents, "plaintext contents");
+    assert_eq!(legacy_note.note.tags, BTreeSet::from(["oldtag".to_string()]));
+
+    let note_id = Uuid::new_v4();
+    RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            url(&format!("notes/{note_id}")),
+            access_token.as_deref(),
+            NoteWriteRequest {
+                tags: BTreeSet::from(["hidden".to_string()]),
+                ..make_write_request("secret contents", UtcDateTime::from_unix_timestamp(1234567)?, None)
+            },
+        )?;
+    let stored = std::fs::read(user_dir.join(note_id.to_string()))?;
+    assert_eq!(stored[0], 0xff);
+    assert!(!String::from_utf8_lossy(&stored).contains("secret"));
+    assert_eq!(read_note(note_id)?.note.contents, "secret contents");
+    assert_eq!(read_note(note_id)?.note.tags, BTreeSet::from(["hidden".to_string()]));
+    assert_eq!(list_notes(access_token.as_deref(), &[])?.notes_info.len(), 2);
+    assert_eq!(search(access_token.as_deref(), "secret")?.len(), 1);
+    assert!(user_dir.join(".search-index").exists());
+    for word in ["secret", "hidden"] {
+        assert_eq!(find_files_containing(&user_dir, word)?, Vec::<PathBuf>::new());
+    }
+    shutdown_assert_no_errors(&mut child, reader)?;
+
+    let archived = [
+        (&trashed_path, std::fs::metadata(&trashed_path)?.ctime()),
+        (&revision_path, std::fs::metadata(&revision_path)?.ctime()),
+    ];
+    // for the migration to get a later ctime
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let output = new_command(&dir).arg("--encrypt-notes").output()?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stored = std::fs::read(&legacy_path)?;
+    assert_eq!(stored[0], 0xff);
+    assert_eq!(
+        std::fs::metadata(&revision_path)?.ino(),
+        std::fs::metadata(&legacy_path)?.ino(),
+    );
+    for (path, ctime) in archived {
+        assert_ne!(std::fs::metadata(path)?.ctime(), ctime);
+        let archive_time_path = path.with_file_name(
+            format!(".archived-at.{}", path.file_name().unwrap().display()),
+        );
+        let recorded = std::fs::read_to_string(archive_time_path)?;
+        assert_eq!(recorded.split(' ').next(), Some(ctime.to_string().as_str()));
+    }
+    for word in ["plaintext", "oldtag", "secret"] {
+        assert_eq!(find_files_containing(&user_dir, word)?, Vec::<PathBuf>::new());
+    }
+
+    let (mut child, reader) = spawn_daemon(&dir)?;
+    let access_token = Some(login(&username, "123")?.access_token);
+    let migrated: NoteResponse = RQ
+        .get_pb_successfully::<bindings::NoteResponse>(
+            url(&format!("notes/{legacy_id}")),
+            access_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(migrated.note.name, legacy_note.note.name);
+    assert_eq!(migrated.note.contents, legacy_note.note.contents);
+    assert_eq!(migrated.note.metadata.mtime, legacy_note.note.metadata.mtime);
+    assert_eq!(migrated.note.tags, legacy_note.note.tags);
+    let revision: NoteResponse = RQ
+        .get_pb_successfully::<bindings::NoteResponse>(
+            url(&format!("notes/{legacy_id}/revisions/1234")),
+            access_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(revision.note.contents, legacy_note.note.contents);
+    assert_eq!(get_notes(access_token.as_deref(), "trash")?, vec![trashed_id]);
+    let found = search(access_token.as_deref(), "plaintext")?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].info.metadata.id, legacy_id);
+    assert_eq!(find_files_containing(&user_dir, "plaintext")?, Vec::<PathBuf>::new());
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn note_changes_since_token() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(username, "123")?.access_token);
+    let mtime = UtcDateTime::from_unix_timestamp(1234567)?;
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        let id = Uuid::new_v4();
+        RQ
+            .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+                url(&format!("notes/{id}")),
+                access_token.as_deref(),
+                make_write_request("", mtime, None),
+            )?;
+        ids.push(id);
+    }
+
+    // without a token, the full listing comes in pages
+    let mut listed = Vec::new();
+    let mut since = None;
+    loop {
+        let mut params = vec![("limit", "2".to_string())];
+        if let Some(since) = since {
+            params.push(("since", since));
+        }
+        let changes = list_changes(access_token.as_deref(), &params)?;
+        assert!(changes.reset);
+        assert!(changes.deleted.is_empty());
+        listed.extend(changes.updated.iter().map(|info| info.metadata.id));
+        since = Some(changes.sync_token.to_string());
+        if !changes.more {
+            break
+        }
+    }
+    listed.sort();
+    let mut expected = ids.clone();
+    expected.sort();
+    assert_eq!(listed, expected);
+    let since = since.unwrap();
+
+    let changes = list_changes(access_token.as_deref(), &[("since", since.clone())])?;
+    assert!(!changes.reset && !changes.more);
+    assert!(changes.updated.is_empty() && changes.deleted.is_empty());
+    assert_eq!(changes.sync_token.to_string(), since);
+
+    let written: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            url(&format!("notes/{}", ids[0])),
+            access_token.as_deref(),
+            make_write_request("edited", mtime + Duration::seconds(1), None),
+        )?
+        .try_into()?;
+    RQ.delete_pb_successfully::<(), ()>(
+        url(&format!("notes/{}", ids[1])),
+        access_token.as_deref(),
+        (),
+    )?;
+    let changes = list_changes(access_token.as_deref(), &[("since", since.clone())])?;
+    assert!(!changes.reset && !changes.more);
+    assert_eq!(changes.updated.len(), 1);
+    assert_eq!(changes.updated[0].metadata.id, ids[0]);
+    assert_eq!(changes.updated[0].metadata.mtime, written.metadata.mtime);
+    assert_eq!(
+        changes.deleted.iter().map(|tombstone| tombstone.id).collect::<Vec<_>>(),
+        vec![ids[1]],
+    );
+
+    RQ
+        .post_pb_successfully::<(), bindings::NoteWriteResponse>(
+            url(&format!("trash/{}/restore", ids[1])),
+            access_token.as_deref(),
+            (),
+        )?;
+    let changes = list_changes(
+        access_token.as_deref(),
+        &[("since", changes.sync_token.to_string())],
+    )?;
+    assert_eq!(
+        changes.updated.iter().map(|info| info.metadata.id).collect::<Vec<_>>(),
+        vec![ids[1]],
+    );
+    assert!(changes.deleted.is_empty());
+
+    // a token from another journal is no longer usable
+    let foreign = format!("{}.1", Uuid::new_v4().simple());
+    let changes = list_changes(access_token.as_deref(), &[("since", foreign)])?;
+    assert!(changes.reset);
+    assert_eq!(changes.updated.len(), 3);
+
+    let status = RQ
+        .get(Url::parse_with_params(&url("notes/changes"), [("since", "garbage")])?)
+        .bearer_auth(access_token.as_deref().unwrap())
+        .send()?
+        .status();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn note_deleted_during_full_listing() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(username, "123")?.access_token);
+    let mtime = UtcDateTime::from_unix_timestamp(1234567)?;
+    for _ in 0..3 {
+        RQ
+            .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+                url(&format!("notes/{}", Uuid::new_v4())),
+                access_token.as_deref(),
+                make_write_request("", mtime, None),
+            )?;
+    }
+
+    let first_page = list_changes(access_token.as_deref(), &[("limit", "2".to_string())])?;
+    assert!(first_page.reset && first_page.more);
+    assert_eq!(first_page.updated.len(), 2);
+    let deleted_id = first_page.updated[0].metadata.id;
+    RQ.delete_pb_successfully::<(), ()>(
+        url(&format!("notes/{deleted_id}")),
+        access_token.as_deref(),
+        (),
+    )?;
+
+    let second_page = list_changes(
+        access_token.as_deref(),
+        &[("limit", "2".to_string()), ("since", first_page.sync_token.to_string())],
+    )?;
+    assert!(second_page.reset && !second_page.more);
+    assert_eq!(second_page.updated.len(), 1);
+    assert_eq!(
+        second_page.deleted.iter().map(|tombstone| tombstone.id).collect::<Vec<_>>(),
+        vec![deleted_id],
+    );
+
+    let changes = list_changes(
+        access_token.as_deref(),
+        &[("since", second_page.sync_token.to_string())],
+    )?;
+    assert!(!changes.reset && !changes.more);
+    assert!(changes.updated.is_empty() && changes.deleted.is_empty());
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn note_events_streamed_until_logout() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = login(username, "123")?.access_token;
+    let note_id = Uuid::new_v4();
+    let note_url = url(&format!("notes/{note_id}"));
+    let mtime = UtcDateTime::from_unix_timestamp(1234567)?;
+
+    let mut events = BufReader::new(
+        RQ
+            .get(url("notes/events"))
+            .bearer_auth(&access_token)
+            .send()?
+            .error_for_status()?
+    );
+
+    let written: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            Some(&access_token),
+            make_write_request("first", mtime, None),
+        )?
+        .try_into()?;
+    assert_eq!(read_event(&mut events)?, Some(("created".into(), note_id.to_string())));
+    RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            Some(&access_token),
+            make_write_request("second", mtime + Duration::seconds(1), Some(written.version)),
+        )?;
+    assert_eq!(read_event(&mut events)?, Some(("updated".into(), note_id.to_string())));
+    RQ.delete_pb_successfully::<(), ()>(note_url, Some(&access_token), ())?;
+    assert_eq!(read_event(&mut events)?, Some(("deleted".into(), note_id.to_string())));
+
+    logout(&access_token)?;
+    assert_eq!(read_event(&mut events)?, None);
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+// the event name and data of the next server-sent event, `None` at the end
+#[test]
+fn note_rendered_as_html() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = login(username, "123")?.access_token;
+    let note_url = url(&format!("notes/{}", Uuid::new_v4()));
+    RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            note_url.clone(),
+            Some(&access_token),
+            make_write_request(
+                "# Title\n\n- [x] done\n\n<script>x()</script>\n\n[link](javascript:x())\n",
+                UtcDateTime::from_unix_timestamp(1234567)?,
+                None,
+            ),
+        )?;
+    let expected = "<h1>Title</h1>\n\
+        <ul>\n<li><input disabled=\"\" type=\"checkbox\" checked=\"\"/>\ndone</li>\n</ul>\n\
+        <p>&lt;script&gt;x()&lt;/script&gt;\n</p>\n\
+        <p>link</p>\n";
+
+    let response = RQ.get(format!("{note_url}?format=html"))
+        .bearer_auth(&access_token)
+        .send()?
+        .error_for_status()?;
+    assert!(response.headers()[CONTENT_TYPE].to_str()?.starts_with("text/html"));
+    assert_eq!(response.text()?, expected);
+
+    let response = RQ.get(&note_url)
+        .bearer_auth(&access_token)
+        .header(ACCEPT, "text/html,application/xhtml+xml;q=0.9")
+        .send()?
+        .error_for_status()?;
+    assert_eq!(response.text()?, expected);
+
+    let read_note: NoteResponse = RQ
+        .get_pb_successfully::<bindings::NoteResponse>(
+            format!("{note_url}?format=protobuf"),
+            Some(&access_token),
+        )?
+        .try_into()?;
+    assert!(read_note.note.contents.starts_with("# Title"));
+
+    let response = RQ.get(format!("{note_url}?format=pdf"))
+        .bearer_auth(&access_token)
+        .send()?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn toggle_note_tasks() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let username = UsernameString::from_str("abc")?;
+    let access_token = Some(login(username, "123")?.access_token);
+    let note_id = Uuid::new_v4();
+    let written: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            url(&format!("notes/{note_id}")),
+            access_token.as_deref(),
+            make_write_request(
+                "- [ ] milk\n- [X] bread\n\n```\n- [ ] not a task\n```\n",
+                UtcDateTime::from_unix_timestamp(1234567)?,
+                None,
+            ),
+        )?
+        .try_into()?;
+    let task_summary = || -> Result<Option<TaskSummary>, Box<dyn Error>> {
+        Ok(list_notes(access_token.as_deref(), &[])?.notes_info[0].tasks)
+    };
+    assert_eq!(task_summary()?, Some(TaskSummary { done: 1, total: 2 }));
+
+    let toggle = |index: u32| RQ
+        .post_pb_successfully::<(), bindings::NoteResponse>(
+            url(&format!("notes/{note_id}/tasks/{index}/toggle")),
+            access_token.as_deref(),
+            (),
+        );
+    let toggled: NoteResponse = toggle(0)?.try_into()?;
+    assert_eq!(toggled.note.contents, "- [x] milk\n- [X] bread\n\n```\n- [ ] not a task\n```\n");
+    assert!(toggled.note.metadata.mtime > written.metadata.mtime);
+    assert_eq!(task_summary()?, Some(TaskSummary { done: 2, total: 2 }));
+
+    let toggled: NoteResponse = toggle(1)?.try_into()?;
+    assert_eq!(toggled.note.contents, "- [x] milk\n- [ ] bread\n\n```\n- [ ] not a task\n```\n");
+    assert_eq!(task_summary()?, Some(TaskSummary { done: 1, total: 2 }));
+
+    assert_http_post_error::<()>(
+        url(&format!("notes/{note_id}/tasks/2/toggle")),
+        access_token.as_deref(),
+        (),
+        StatusCode::NOT_FOUND,
+        None,
+    )?;
+    assert_http_post_error::<()>(
+        url(&format!("notes/{}/tasks/0/toggle", Uuid::new_v4())),
+        access_token.as_deref(),
+        (),
+        StatusCode::NOT_FOUND,
+        None,
+    )?;
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+#[test]
+fn share_notes_between_users() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    let (mut child, reader) = spawn_daemon(&dir)?;
+
+    let owner_token = Some(login(UsernameString::from_str("abc")?, "123")?.access_token);
+    let other_token = Some(login(UsernameString::from_str("abcdef")?, "012")?.access_token);
+    let note_id = Uuid::new_v4();
+    let mtime = UtcDateTime::from_unix_timestamp(1234567)?;
+    let shared_url = url(&format!("notes/{note_id}?owner=abc"));
+    let share_url = url(&format!("notes/{note_id}/shares/abcdef"));
+    let written: NoteWriteResponse = RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            url(&format!("notes/{note_id}")),
+            owner_token.as_deref(),
+            make_write_request("shared", mtime, None),
+        )?
+        .try_into()?;
+    let share = |access: ShareAccess| RQ
+        .put_pb_successfully::<bindings::NoteShareRequest, ()>(
+            share_url.clone(),
+            owner_token.as_deref(),
+            NoteShareRequest { access },
+        );
+    let list_shared = || -> Result<SharedNoteListResponse, Box<dyn Error>> {
+        Ok(
+            RQ
+                .get_pb_successfully::<bindings::SharedNoteListResponse>(
+                    url("shared"),
+                    other_token.as_deref(),
+                )?
+                .try_into()?
+        )
+    };
+
+    assert_http_get_error::<()>(
+        shared_url.clone(),
+        other_token.as_deref(),
+        (),
+        StatusCode::NOT_FOUND,
+        None,
+    )?;
+    share(ShareAccess::ReadOnly)?;
+    assert_http_put_error::<bindings::NoteShareRequest>(
+        url(&format!("notes/{note_id}/shares/nobody")),
+        owner_token.as_deref(),
+        NoteShareRequest { access: ShareAccess::ReadOnly },
+        StatusCode::NOT_FOUND,
+        None,
+    )?;
+    assert_http_put_error::<bindings::NoteShareRequest>(
+        url(&format!("notes/{note_id}/shares/abc")),
+        owner_token.as_deref(),
+        NoteShareRequest { access: ShareAccess::ReadOnly },
+        StatusCode::BAD_REQUEST,
+        None,
+    )?;
+    let shares: NoteShareListResponse = RQ
+        .get_pb_successfully::<bindings::NoteShareListResponse>(
+            url(&format!("notes/{note_id}/shares")),
+            owner_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(
+        shares.shares,
+        vec![
+            NoteShare {
+                username: UsernameString::from_str("abcdef")?,
+                access: ShareAccess::ReadOnly,
+            },
+        ],
+    );
+
+    let shared = list_shared()?.notes_info;
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared[0].owner, UsernameString::from_str("abc")?);
+    assert_eq!(shared[0].access, ShareAccess::ReadOnly);
+    assert_eq!(shared[0].info.metadata.id, note_id);
+    let read_note: NoteResponse = RQ
+        .get_pb_successfully::<bindings::NoteResponse>(
+            shared_url.clone(),
+            other_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(read_note.note.contents, "shared");
+    assert_http_put_error::<bindings::NoteWriteRequest>(
+        shared_url.clone(),
+        other_token.as_deref(),
+        make_write_request("edited", mtime, Some(written.version)),
+        StatusCode::FORBIDDEN,
+        None,
+    )?;
+    assert_http_error::<()>(
+        Method::DELETE,
+        shared_url.clone(),
+        other_token.as_deref(),
+        (),
+        StatusCode::FORBIDDEN,
+        None,
+    )?;
+
+    share(ShareAccess::ReadWrite)?;
+    RQ
+        .put_pb_successfully::<bindings::NoteWriteRequest, bindings::NoteWriteResponse>(
+            shared_url.clone(),
+            other_token.as_deref(),
+            make_write_request("edited", mtime, Some(written.version)),
+        )?;
+    let read_note: NoteResponse = RQ
+        .get_pb_successfully::<bindings::NoteResponse>(
+            url(&format!("notes/{note_id}")),
+            owner_token.as_deref(),
+        )?
+        .try_into()?;
+    assert_eq!(read_note.note.contents, "edited");
+    assert_eq!(get_notes(owner_token.as_deref(), "notes")?, vec![note_id]);
+    assert!(get_notes(other_token.as_deref(), "notes")?.is_empty());
+
+    RQ.delete_pb_successfully::<(), ()>(share_url.clone(), owner_token.as_deref(), ())?;
+    assert_http_error::<()>(
+        Method::DELETE,
+        share_url.clone(),
+        owner_token.as_deref(),
+        (),
+        StatusCode::NOT_FOUND,
+        None,
+    )?;
+    assert_http_get_error::<()>(
+        shared_url.clone(),
+        other_token.as_deref(),
+        (),
+        StatusCode::NOT_FOUND,
+        None,
+    )?;
+    assert!(list_shared()?.notes_info.is_empty());
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+    Ok(())
+}
+
+fn read_event(events: &mut impl BufRead) -> Result<Option<(String, String)>, Box<dyn Error>> {
+    let (mut event, mut data) = (None, None);
+    loop {
+        let mut line = String::new();
+        if events.read_line(&mut line)? == 0 {
+            return Ok(None)
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if let (Some(event), Some(data)) = (event.take(), data.take()) {
+                return Ok(Some((event, data)))
+            }
+        } else if let Some(value) = line.strip_prefix("event:") {
+            event = Some(value.trim_start().to_owned());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data = Some(value.trim_start().to_owned());
+        }
+    }
+}
+
+fn find_files_containing(
+    dir: &Path,
+    needle: &str,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut ret = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            ret.extend(find_files_containing(&path, needle)?);
+        } else if String::from_utf8_lossy(&std::fs::read(&path)?).contains(needle) {
+            ret.push(path);
+        }
+    }
+    Ok(ret)
+}
+
+fn list_changes(
+    access_token: Option<&str>,
+    params: &[(&str, String)],
+) -> Result<NoteChangesResponse, Box<dyn Error>> {
+    Ok(
+        RQ
+            .get(Url::parse_with_params(&url("notes/changes"), params)?)
+            .bearer_auth(access_token.unwrap())
+            .send()?
+            .error_for_status()?
+            .read_pb::<bindings::NoteChangesResponse>()?
+            .try_into()?
+    )
+}
+
+fn list_tags(
+    access_token: Option<&str>,
+) -> Result<Vec<(String, u32)>, Box<dyn Error>> {
+    let response: TagListResponse = RQ
+        .get_pb_successfully::<bindings::TagListResponse>(
+            url("tags"),
+            access_token,
+        )?
+        .try_into()?;
+    Ok(
+        response.tags
+            .into_iter()
+            .map(|TagCount { tag, count }| (tag, count))
+            .collect()
+    )
+}
+
+fn list_notes(
+    access_token: Option<&str>,
+    params: &[(&str, String)],
+) -> Result<NoteListResponse, Box<dyn Error>> {
+    Ok(
+        RQ
+            .get(Url::parse_with_params(&url("notes"), params)?)
+            .bearer_auth(access_token.unwrap())
+            .send()?
+            .error_for_status()?
+            .read_pb::<bindings::NoteListResponse>()?
+            .try_into()?
+    )
+}
+
+fn search(
+    access_token: Option<&str>,
+    query: &str,
+) -> Result<Vec<NoteSearchResult>, Box<dyn Error>> {
+    Ok(search_page(access_token, &[("q", query)])?.results)
+}
+
+fn search_page(
+    access_token: Option<&str>,
+    params: &[(&str, &str)],
+) -> Result<NoteSearchResponse, Box<dyn Error>> {
+    Ok(
+        RQ
+            .get(Url::parse_with_params(&url("notes/search"), params)?)
+            .bearer_auth(access_token.unwrap())
+            .send()?
+            .error_for_status()?
+            .read_pb::<bindings::NoteSearchResponse>()?
+            .try_into()?
+    )
+}
+
+fn get_notes(
+    access_token: Option<&str>,
+    endpoint: &str,
+) -> Result<Vec<Uuid>, Box<dyn Error>> {
+    let list: NoteListResponse = RQ
+        .get_pb_successfully::<bindings::NoteListResponse>(
+            url(endpoint),
+            access_token,
+        )?
+        .try_into()?;
+    Ok(list.notes_info.into_iter().map(|info| info.metadata.id).collect())
+}
+
+fn get_revisions(
+    access_token: Option<&str>,
+    note_id: Uuid,
+) -> Result<Vec<NoteRevisionInfo>, Box<dyn Error>> {
+    let list: NoteRevisionListResponse = RQ
+        .get_pb_successfully::<bindings::NoteRevisionListResponse>(
+            url(&format!("notes/{note_id}/revisions")),
+            access_token,
+        )?
+        .try_into()?;
+    Ok(list.revisions)
+}
+
+fn make_write_request(
+    contents: &str,
+    mtime: UtcDateTime,
+    base_version: Option<NoteVersionParam>,
+) -> NoteWriteRequest {
+    NoteWriteRequest {
+        mtime,
+        name: None,
+        contents: contents.to_string(),
+        base_version,
+        tags: BTreeSet::new(),
+    }
+}
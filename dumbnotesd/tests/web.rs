@@ -0,0 +1,240 @@
+use std::error::Error;
+use std::sync::LazyLock;
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{CONTENT_TYPE, COOKIE, LOCATION, SET_COOKIE};
+use reqwest::redirect::Policy;
+use test_utils::{enable_web, setup_basic_config_with_keys_and_data};
+use uuid::Uuid;
+use crate::common::shutdown_assert_no_errors;
+use crate::common::spawn_daemon_with_web;
+use crate::common::web_url;
+
+mod common;
+
+static WEB_RQ: LazyLock<Client> = LazyLock::new(||
+    Client::builder()
+        .redirect(Policy::none())
+        .build()
+        .unwrap()
+);
+
+fn post_login(username: &str, password: &str) -> RequestBuilder {
+    WEB_RQ.post(web_url("login"))
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(format!("username={username}&password={password}&second_factor="))
+}
+
+fn assert_redirect(response: &Response, location: &str) {
+    assert!(response.status().is_redirection(), "unexpected status {}", response.status());
+    assert_eq!(response.headers()[LOCATION], location);
+}
+
+/// Returns the cookie header and the xsrf token
+fn web_login(username: &str, password: &str) -> Result<(String, String), Box<dyn Error>> {
+    let response = post_login(username, password).send()?;
+    assert_redirect(&response, "/");
+    let cookies = session_cookies(&response);
+    assert_eq!(cookies.len(), 2);
+    let xsrf_token = cookies.iter()
+        .find_map(|cookie| cookie.strip_prefix("xsrf_token="))
+        .unwrap()
+        .to_owned();
+    Ok((cookies.join("; "), xsrf_token))
+}
+
+fn post_note(
+    cookie_header: &str,
+    xsrf_token: &str,
+    note_id: Uuid,
+    body: &str,
+) -> Result<String, Box<dyn Error>> {
+    WEB_RQ.post(web_url(&format!("notes/{note_id}")))
+        .header(COOKIE, cookie_header)
+        .header("HX-Request", "true")
+        .header("X-XSRF-Token", xsrf_token)
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(body.to_owned())
+        .send()?
+        .error_for_status()?
+        .text()
+        .map_err(Into::into)
+}
+
+/// `name=value` pairs of the cookies set by the response
+fn session_cookies(response: &Response) -> Vec<String> {
+    response.headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .map(|header| {
+            let header = header.to_str().unwrap();
+            assert!(header.contains("HttpOnly"), "cookie isn't HttpOnly: {header}");
+            assert!(header.contains("SameSite=Strict"), "cookie isn't SameSite=Strict: {header}");
+            header.split(';').next().unwrap().to_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn login_logout() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    enable_web(&dir);
+    let (mut child, reader) = spawn_daemon_with_web(&dir)?;
+
+    let response = WEB_RQ.get(web_url("")).send()?;
+    assert_redirect(&response, "/login");
+
+    let response = post_login("abc", "wrong")
+        .header("HX-Request", "true")
+        .send()?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(SET_COOKIE).is_none());
+    let body = response.text()?;
+    assert!(body.starts_with("<form id=\"login-form\""));
+    assert!(body.contains("Wrong username or password."));
+
+    let (cookie_header, xsrf_token) = web_login("abc", "123")?;
+
+    let response = WEB_RQ.get(web_url("login"))
+        .header(COOKIE, &cookie_header)
+        .send()?;
+    assert_redirect(&response, "/");
+
+    let body = WEB_RQ.get(web_url(""))
+        .header(COOKIE, &cookie_header)
+        .send()?
+        .error_for_status()?
+        .text()?;
+    assert!(body.contains("Logged in as abc"));
+    assert!(body.contains(&xsrf_token));
+
+    let response = WEB_RQ.post(web_url("logout"))
+        .header(COOKIE, &cookie_header)
+        .header("HX-Request", "true")
+        .send()?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = WEB_RQ.post(web_url("logout"))
+        .header(COOKIE, &cookie_header)
+        .header("HX-Request", "true")
+        .header("X-XSRF-Token", "AAAAAAAAAAAAAAAAAAAAAA")
+        .send()?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = WEB_RQ.post(web_url("logout"))
+        .header(COOKIE, &cookie_header)
+        .header("HX-Request", "true")
+        .header("X-XSRF-Token", &xsrf_token)
+        .send()?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(response.headers()["HX-Redirect"], "/login");
+
+    let response = WEB_RQ.get(web_url(""))
+        .header(COOKIE, &cookie_header)
+        .send()?;
+    assert_redirect(&response, "/login");
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+
+    Ok(())
+}
+
+#[test]
+fn notes_list_edit_delete() -> Result<(), Box<dyn Error>> {
+    let dir = setup_basic_config_with_keys_and_data();
+    enable_web(&dir);
+    let (mut child, reader) = spawn_daemon_with_web(&dir)?;
+
+    let (cookie_header, xsrf_token) = web_login("abc", "123")?;
+    let get_page = |path: &str| -> Result<Response, Box<dyn Error>> {
+        WEB_RQ.get(web_url(path))
+            .header(COOKIE, &cookie_header)
+            .send()
+            .map_err(Into::into)
+    };
+
+    let body = get_page("")?.error_for_status()?.text()?;
+    assert!(body.contains("There are no notes yet."));
+
+    let body = get_page("notes/new")?.error_for_status()?.text()?;
+    assert!(body.contains("name=\"base_version\" type=\"hidden\" value=\"\""));
+
+    let note_id = Uuid::new_v4();
+    let body = post_note(
+        &cookie_header,
+        &xsrf_token,
+        note_id,
+        "name=a+title&tags=first%2C+second&contents=%3Cb%3Ebold%3C%2Fb%3E&base_version=",
+    )?;
+    assert!(body.starts_with("<form id=\"note-editor\""));
+    assert!(body.contains("Saved."));
+    assert!(body.contains("<article id=\"note-rendered\" hx-swap-oob=\"true\"><p>&lt;b&gt;bold&lt;/b&gt;</p>"));
+    let base_version = body
+        .split_once("name=\"base_version\" type=\"hidden\" value=\"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .map(|(version, _)| version.to_owned())
+        .unwrap();
+    assert!(!base_version.is_empty());
+
+    let body = get_page("")?.error_for_status()?.text()?;
+    assert!(body.contains(&format!("href=\"/notes/{note_id}\"")));
+    assert!(body.contains("a title"));
+    assert!(body.contains("<span class=\"tag\">second</span>"));
+
+    let body = get_page(&format!("notes/{note_id}"))?.error_for_status()?.text()?;
+    assert!(body.contains("&#60;b&#62;bold&#60;/b&#62;"));
+    assert!(body.contains("value=\"first, second\""));
+
+    let body = post_note(
+        &cookie_header,
+        &xsrf_token,
+        note_id,
+        "name=a+title&tags=&contents=stale&base_version=1.1.1",
+    )?;
+    assert!(body.contains("This note was changed elsewhere"));
+    assert!(body.contains(&format!("value=\"{base_version}\"")));
+
+    let body = post_note(
+        &cookie_header,
+        &xsrf_token,
+        note_id,
+        &format!("name=a+title&tags=&contents=-+%5B+%5D+task&base_version={base_version}"),
+    )?;
+    assert!(body.contains("Saved."));
+    let toggle_path = format!("/notes/{note_id}/tasks/0/toggle");
+    assert!(body.contains(&format!("hx-post=\"{toggle_path}\"")));
+    let body = get_page("")?.error_for_status()?.text()?;
+    assert!(body.contains(">0/1</span>"));
+
+    let body = WEB_RQ.post(web_url(&toggle_path[1..]))
+        .header(COOKIE, &cookie_header)
+        .header("HX-Request", "true")
+        .header("X-XSRF-Token", &xsrf_token)
+        .send()?
+        .error_for_status()?
+        .text()?;
+    assert!(body.starts_with("<article id=\"note-rendered\">"));
+    assert!(body.contains("checked=\"\""));
+    assert!(body.contains("- [x] task</textarea>"));
+    let body = get_page("")?.error_for_status()?.text()?;
+    assert!(body.contains(">1/1</span>"));
+
+    let response = WEB_RQ.delete(web_url(&format!("notes/{note_id}")))
+        .header(COOKIE, &cookie_header)
+        .send()?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = WEB_RQ.delete(web_url(&format!("notes/{note_id}")))
+        .header(COOKIE, &cookie_header)
+        .header("HX-Request", "true")
+        .header("X-XSRF-Token", &xsrf_token)
+        .send()?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(response.headers()["HX-Redirect"], "/");
+
+    assert_eq!(get_page(&format!("notes/{note_id}"))?.status(), StatusCode::NOT_FOUND);
+
+    shutdown_assert_no_errors(&mut child, reader)?;
+
+    Ok(())
+}
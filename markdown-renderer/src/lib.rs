@@ -0,0 +1,123 @@
+mod tasks;
+
+use pulldown_cmark::{html, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+
+pub use tasks::{find_tasks, Task};
+
+const LINK_SCHEMES: &[&str] = &["http", "https", "mailto"];
+const IMAGE_SCHEMES: &[&str] = &["http", "https"];
+
+/// Renders note contents to an HTML fragment safe to embed into a page.
+///
+/// Raw HTML is escaped and shown as text, links and images with
+/// disallowed URL schemes are replaced with their text.
+pub fn render_markdown(source: &str) -> String {
+    render(source, None::<fn(usize) -> String>)
+}
+
+/// Like [`render_markdown`], but the task list checkboxes are left enabled,
+/// with the attributes returned by `task_attributes` for the task's index
+/// in [`find_tasks`] order. The attributes are inserted unescaped.
+pub fn render_markdown_with_task_inputs(
+    source: &str,
+    task_attributes: impl Fn(usize) -> String,
+) -> String {
+    render(source, Some(task_attributes))
+}
+
+fn render(source: &str, task_attributes: Option<impl Fn(usize) -> String>) -> String {
+    let mut output = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(
+        &mut output,
+        Sanitizer::new(Parser::new_ext(source, parser_options()), task_attributes),
+    );
+    output
+}
+
+fn parser_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+}
+
+struct Sanitizer<I, F> {
+    events: I,
+    task_attributes: Option<F>,
+    task_count: usize,
+
+    // whether the end tags of the currently open links and images are kept
+    link_stack: Vec<bool>,
+}
+
+impl<I, F: Fn(usize) -> String> Sanitizer<I, F> {
+    fn new(events: I, task_attributes: Option<F>) -> Self {
+        Sanitizer {
+            events,
+            task_attributes,
+            task_count: 0,
+            link_stack: Vec::new(),
+        }
+    }
+
+    fn task_input<'a>(&mut self, checked: bool) -> Event<'a> {
+        let index = self.task_count;
+        self.task_count += 1;
+        match self.task_attributes {
+            Some(ref task_attributes) => Event::Html(CowStr::from(format!(
+                "<input type=\"checkbox\"{} {}/>\n",
+                if checked { " checked=\"\"" } else { "" },
+                task_attributes(index),
+            ))),
+            None => Event::TaskListMarker(checked),
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = Event<'a>>, F: Fn(usize) -> String> Iterator for Sanitizer<I, F> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let event = match self.events.next()? {
+                Event::Html(html) | Event::InlineHtml(html) => Some(Event::Text(html)),
+                Event::Start(Tag::HtmlBlock) => Some(Event::Start(Tag::Paragraph)),
+                Event::End(TagEnd::HtmlBlock) => Some(Event::End(TagEnd::Paragraph)),
+                Event::TaskListMarker(checked) => Some(self.task_input(checked)),
+                Event::Start(tag @ (Tag::Link { .. } | Tag::Image { .. })) => {
+                    let is_allowed = is_allowed_link(&tag);
+                    self.link_stack.push(is_allowed);
+                    is_allowed.then_some(Event::Start(tag))
+                },
+                Event::End(tag @ (TagEnd::Link | TagEnd::Image)) => self.link_stack
+                    .pop()
+                    .unwrap_or(false)
+                    .then_some(Event::End(tag)),
+                event => Some(event),
+            };
+            if event.is_some() {
+                return event
+            }
+        }
+    }
+}
+
+fn is_allowed_link(tag: &Tag) -> bool {
+    match tag {
+        Tag::Link { link_type: LinkType::Email, .. } => true,
+        Tag::Link { dest_url, .. } => is_allowed_url(dest_url, LINK_SCHEMES),
+        Tag::Image { dest_url, .. } => is_allowed_url(dest_url, IMAGE_SCHEMES),
+        _ => false,
+    }
+}
+
+fn is_allowed_url(url: &str, schemes: &[&str]) -> bool {
+    // browsers ignore whitespace and control characters inside the scheme
+    let url: String = url.chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(end) if url[end..].starts_with(':') => schemes.iter()
+            .any(|scheme| url[..end].eq_ignore_ascii_case(scheme)),
+        _ => true,
+    }
+}
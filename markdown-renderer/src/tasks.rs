@@ -0,0 +1,24 @@
+use std::ops::Range;
+
+use pulldown_cmark::{Event, Parser};
+
+use crate::parser_options;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Task {
+    pub checked: bool,
+    /// Byte range of the `[ ]` marker in the source
+    pub marker: Range<usize>,
+}
+
+/// Task list items in document order, the same ones that are rendered
+/// as checkboxes
+pub fn find_tasks(source: &str) -> Vec<Task> {
+    Parser::new_ext(source, parser_options())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::TaskListMarker(checked) => Some(Task { checked, marker: range }),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,98 @@
+use insta::assert_snapshot;
+use markdown_renderer::{render_markdown, render_markdown_with_task_inputs};
+
+#[test]
+fn basic_formatting() {
+    assert_snapshot!(render_markdown(
+        "# Title\n\
+        \n\
+        Some *emphasis*, **strong** and ~~struck~~ text.\n\
+        \n\
+        - one\n\
+        - two\n\
+        \n\
+        | a | b |\n\
+        |---|---|\n\
+        | 1 | 2 |\n"
+    ));
+}
+
+#[test]
+fn code_blocks() {
+    assert_snapshot!(render_markdown(
+        "Inline `<code>`.\n\
+        \n\
+        ```rust\"><script>\n\
+        fn main() { println!(\"<b>\"); }\n\
+        ```\n\
+        \n    \
+            indented <i>\n"
+    ));
+}
+
+#[test]
+fn task_lists() {
+    assert_snapshot!(render_markdown(
+        "- [ ] open\n\
+        - [x] done\n  \
+          - [X] nested\n"
+    ));
+}
+
+#[test]
+fn task_inputs() {
+    assert_snapshot!(render_markdown_with_task_inputs(
+        "- [ ] open\n\
+        - [x] done\n\
+        \n\
+        ```\n\
+        - [ ] not a task\n\
+        ```\n\
+        \n\
+        1. [ ] numbered\n",
+        |index| format!("data-task=\"{index}\""),
+    ));
+}
+
+#[test]
+fn raw_html_is_escaped() {
+    assert_snapshot!(render_markdown(
+        "<script>alert(1)</script>\n\
+        \n\
+        <div onclick=\"x()\">\n\
+        block\n\
+        </div>\n\
+        \n\
+        Inline <img src=x onerror=alert(1)> html &amp; entities.\n"
+    ));
+}
+
+#[test]
+fn links() {
+    assert_snapshot!(render_markdown(
+        "[http](http://example.com/?a=1&b=\"2\" \"a \\\"title\\\"\")\n\
+        [relative](/notes/some)\n\
+        [mail](mailto:a@example.com)\n\
+        <https://example.com/auto>\n\
+        <a@example.com>\n\
+        [js](javascript:alert(1))\n\
+        [JS](JaVaScRiPt:alert(1))\n\
+        [spaced](java&#9;script:alert(1))\n\
+        [data](data:text/html;base64,PHNjcmlwdD4=)\n\
+        [vb](vbscript:msgbox)\n\
+        [nested [js](javascript:x)](https://example.com)\n\
+        \n\
+        [ref]: javascript:alert(1)\n\
+        [reference][ref]\n"
+    ));
+}
+
+#[test]
+fn images() {
+    assert_snapshot!(render_markdown(
+        "![remote *image*](https://example.com/a.png \"title\")\n\
+        ![inline data](data:image/svg+xml;base64,PHN2Zz4=)\n\
+        ![js](javascript:alert(1))\n\
+        [![linked](https://example.com/b.png)](javascript:x)\n"
+    ));
+}
@@ -0,0 +1,17 @@
+use markdown_renderer::{find_tasks, Task};
+
+#[test]
+fn tasks_found_in_document_order() {
+    let source = "- [ ] one\n  - [X] nested\n\n```\n- [ ] code\n```\n\n> 1. [x] quoted\n\n[ ] plain\n";
+    let tasks = find_tasks(source);
+    assert_eq!(
+        tasks,
+        vec![
+            Task { checked: false, marker: 2..5 },
+            Task { checked: true, marker: 14..17 },
+            Task { checked: true, marker: 51..54 },
+        ],
+    );
+    assert_eq!(&source[tasks[1].marker.clone()], "[X]");
+    assert_eq!(&source[tasks[2].marker.clone()], "[x]");
+}
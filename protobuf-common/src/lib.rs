@@ -9,6 +9,11 @@ pub enum MappingError {
         name: &'static str,
     },
 
+    #[error("invalid field value: {name}")]
+    InvalidField {
+        name: &'static str,
+    },
+
     #[error("invalid username: {0}")]
     UsernameParse(#[from] UsernameParseError),
 
@@ -20,6 +25,10 @@ impl MappingError {
     pub fn missing(name: &'static str) -> Self {
         MappingError::MissingField { name }
     }
+
+    pub fn invalid(name: &'static str) -> Self {
+        MappingError::InvalidField { name }
+    }
 }
 
 #[derive(Debug, Error)]
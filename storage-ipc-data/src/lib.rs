@@ -8,8 +8,30 @@ pub mod model {
     pub mod list_notes;
     pub mod get_note_details;
     pub mod delete_note;
+    pub mod list_note_revisions;
+    pub mod read_note_revision;
+    pub mod restore_note_revision;
+    pub mod list_trash;
+    pub mod restore_trashed_note;
+    pub mod purge_trashed_note;
+    pub mod search_notes;
+    pub mod list_note_changes;
+    pub mod subscribe_note_events;
+    pub mod list_tags;
+    pub mod get_usage;
+    pub mod list_activity;
+    pub mod toggle_note_task;
+    pub mod grant_note_share;
+    pub mod revoke_note_share;
+    pub mod list_note_shares;
+    pub mod list_shared_notes;
 
     mod note_metadata;
+    mod note_version;
     mod note_info;
+    mod note_search_result;
+    mod note_revision_info;
     mod note;
+    mod note_share;
+    mod audit_event;
 }
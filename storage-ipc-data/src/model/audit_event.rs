@@ -0,0 +1,84 @@
+use std::net::IpAddr;
+use std::str::FromStr;
+use data::{AuditEvent, AuditEventKind, AuditOutcome, UsernameString};
+use protobuf_common::{MappingError, ProtobufRequestError};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::bindings;
+
+impl TryFrom<bindings::AuditEvent> for AuditEvent {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::AuditEvent) -> Result<Self, Self::Error> {
+        Ok(
+            AuditEvent {
+                timestamp: OffsetDateTime::from_unix_timestamp(value.timestamp)?,
+                username: UsernameString::from_str(&value.username)?,
+                session_id: value.session_id
+                    .map(|session_id| Uuid::from_slice(&session_id))
+                    .transpose()?,
+                client_address: value.client_address
+                    .map(|address| IpAddr::from_str(&address))
+                    .transpose()
+                    .map_err(|_| MappingError::invalid("client_address"))?,
+                event: <_ as TryInto<bindings::AuditEventKind>>
+                    ::try_into(value.event)?
+                    .into(),
+                outcome: match value.success {
+                    true => AuditOutcome::Success,
+                    false => AuditOutcome::Failure,
+                },
+                note_id: value.note_id
+                    .map(|note_id| Uuid::from_slice(&note_id))
+                    .transpose()?,
+            }
+        )
+    }
+}
+
+impl From<AuditEvent> for bindings::AuditEvent {
+    fn from(value: AuditEvent) -> Self {
+        bindings::AuditEvent {
+            timestamp: value.timestamp.unix_timestamp(),
+            username: value.username.into_string(),
+            session_id: value.session_id
+                .map(|session_id| session_id.as_bytes().to_vec()),
+            client_address: value.client_address
+                .map(|address| address.to_string()),
+            event: <_ as Into<bindings::AuditEventKind>>
+                ::into(value.event)
+                .into(),
+            success: value.outcome == AuditOutcome::Success,
+            note_id: value.note_id
+                .map(|note_id| note_id.as_bytes().to_vec()),
+        }
+    }
+}
+
+impl From<AuditEventKind> for bindings::AuditEventKind {
+    fn from(value: AuditEventKind) -> Self {
+        match value {
+            AuditEventKind::Login => bindings::AuditEventKind::Login,
+            AuditEventKind::Logout => bindings::AuditEventKind::Logout,
+            AuditEventKind::TokenRefresh => bindings::AuditEventKind::TokenRefresh,
+            AuditEventKind::PasswordChange => bindings::AuditEventKind::PasswordChange,
+            AuditEventKind::SessionRevocation => bindings::AuditEventKind::SessionRevocation,
+            AuditEventKind::NoteDeletion => bindings::AuditEventKind::NoteDeletion,
+            AuditEventKind::NotePurge => bindings::AuditEventKind::NotePurge,
+        }
+    }
+}
+
+impl From<bindings::AuditEventKind> for AuditEventKind {
+    fn from(value: bindings::AuditEventKind) -> Self {
+        match value {
+            bindings::AuditEventKind::Login => AuditEventKind::Login,
+            bindings::AuditEventKind::Logout => AuditEventKind::Logout,
+            bindings::AuditEventKind::TokenRefresh => AuditEventKind::TokenRefresh,
+            bindings::AuditEventKind::PasswordChange => AuditEventKind::PasswordChange,
+            bindings::AuditEventKind::SessionRevocation => AuditEventKind::SessionRevocation,
+            bindings::AuditEventKind::NoteDeletion => AuditEventKind::NoteDeletion,
+            bindings::AuditEventKind::NotePurge => AuditEventKind::NotePurge,
+        }
+    }
+}
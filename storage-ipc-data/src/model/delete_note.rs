@@ -1,3 +1,6 @@
+use std::str::FromStr;
+
+use data::UsernameString;
 use protobuf_common::{MappingError, ProtobufRequestError};
 use uuid::Uuid;
 use crate::bindings;
@@ -7,6 +10,7 @@ use bindings::StorageError;
 pub struct DeleteNoteRequest {
     pub access_token: String,
     pub note_id: Uuid,
+    pub owner: Option<UsernameString>,
 }
 
 #[derive(Debug)]
@@ -19,6 +23,10 @@ impl TryFrom<bindings::DeleteNoteRequest> for DeleteNoteRequest {
             DeleteNoteRequest {
                 access_token: value.access_token,
                 note_id: Uuid::from_slice(&value.note_id)?,
+                owner: value.owner
+                    .as_deref()
+                    .map(UsernameString::from_str)
+                    .transpose()?,
             }
         )
     }
@@ -44,6 +52,7 @@ impl From<DeleteNoteRequest> for bindings::DeleteNoteRequest {
         bindings::DeleteNoteRequest {
             access_token: value.access_token,
             note_id: value.note_id.into_bytes().to_vec(),
+            owner: value.owner.map(UsernameString::into_string),
         }
     }
 }
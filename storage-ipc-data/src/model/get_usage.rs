@@ -0,0 +1,81 @@
+use data::StorageUsage;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct GetUsageRequest {
+    pub access_token: String,
+}
+
+#[derive(Debug)]
+pub struct GetUsageResponse(
+    pub Result<StorageUsage, bindings::StorageError>
+);
+
+impl TryFrom<bindings::GetUsageRequest> for GetUsageRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::GetUsageRequest) -> Result<Self, Self::Error> {
+        Ok(
+            GetUsageRequest {
+                access_token: value.access_token,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for GetUsageResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::get_usage_response::Response;
+        let value = match value {
+            bindings::response::Response::GetUsage(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            GetUsageResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::Usage(usage) => Ok(
+                        StorageUsage {
+                            storage_size: usage.storage_size,
+                            notes: usage.notes,
+                            max_storage_size: usage.max_storage_size,
+                            max_notes: usage.max_notes,
+                        }
+                    ),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<GetUsageRequest> for bindings::GetUsageRequest {
+    fn from(value: GetUsageRequest) -> Self {
+        bindings::GetUsageRequest {
+            access_token: value.access_token,
+        }
+    }
+}
+
+impl From<GetUsageResponse> for bindings::response::Response {
+    fn from(value: GetUsageResponse) -> Self {
+        use bindings::get_usage_response::Response;
+        bindings::response::Response::GetUsage(
+            bindings::GetUsageResponse {
+                response: Some(
+                    match value.0 {
+                        Ok(usage) => Response::Usage(
+                            bindings::StorageUsage {
+                                storage_size: usage.storage_size,
+                                notes: usage.notes,
+                                max_storage_size: usage.max_storage_size,
+                                max_notes: usage.max_notes,
+                            }
+                        ),
+                        Err(e) => Response::Error(e.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
@@ -0,0 +1,65 @@
+use data::NoteShare;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+use bindings::StorageError;
+
+#[derive(Debug)]
+pub struct GrantNoteShareRequest {
+    pub access_token: String,
+    pub note_id: Uuid,
+    pub share: NoteShare,
+}
+
+#[derive(Debug)]
+pub struct GrantNoteShareResponse(pub Option<StorageError>);
+
+impl TryFrom<bindings::GrantNoteShareRequest> for GrantNoteShareRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::GrantNoteShareRequest) -> Result<Self, Self::Error> {
+        Ok(
+            GrantNoteShareRequest {
+                access_token: value.access_token,
+                note_id: Uuid::from_slice(&value.note_id)?,
+                share: value.share
+                    .ok_or_mapping_error(MappingError::missing("share"))?
+                    .try_into()?,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for GrantNoteShareResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        let value = match value {
+            bindings::response::Response::GrantNoteShare(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            GrantNoteShareResponse(
+                value.error.map(|e| e.try_into()).transpose()?,
+            )
+        )
+    }
+}
+
+impl From<GrantNoteShareRequest> for bindings::GrantNoteShareRequest {
+    fn from(value: GrantNoteShareRequest) -> Self {
+        bindings::GrantNoteShareRequest {
+            access_token: value.access_token,
+            note_id: value.note_id.into_bytes().to_vec(),
+            share: Some(value.share.into()),
+        }
+    }
+}
+
+impl From<GrantNoteShareResponse> for bindings::response::Response {
+    fn from(value: GrantNoteShareResponse) -> Self {
+        bindings::response::Response::GrantNoteShare(
+            bindings::GrantNoteShareResponse {
+                error: value.0.map(StorageError::into)
+            }
+        )
+    }
+}
@@ -0,0 +1,83 @@
+use data::AuditEvent;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct ListActivityRequest {
+    pub access_token: String,
+    pub limit: u32,
+}
+
+/// The newest first
+#[derive(Debug)]
+pub struct ListActivityResponse(
+    pub Result<Vec<AuditEvent>, bindings::StorageError>
+);
+
+impl TryFrom<bindings::ListActivityRequest> for ListActivityRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ListActivityRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ListActivityRequest {
+                access_token: value.access_token,
+                limit: value.limit,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ListActivityResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::list_activity_response::Response;
+        let value = match value {
+            bindings::response::Response::ListActivity(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ListActivityResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::Events(events) => Ok(
+                        events.events
+                            .into_iter()
+                            .map(TryInto::try_into)
+                            .collect::<Result<_, _>>()?
+                    ),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ListActivityRequest> for bindings::ListActivityRequest {
+    fn from(value: ListActivityRequest) -> Self {
+        bindings::ListActivityRequest {
+            access_token: value.access_token,
+            limit: value.limit,
+        }
+    }
+}
+
+impl From<ListActivityResponse> for bindings::response::Response {
+    fn from(value: ListActivityResponse) -> Self {
+        use bindings::list_activity_response::Response;
+        bindings::response::Response::ListActivity(
+            bindings::ListActivityResponse {
+                response: Some(
+                    match value.0 {
+                        Ok(events) => Response::Events(
+                            bindings::AuditEventList {
+                                events: events
+                                    .into_iter()
+                                    .map(Into::into)
+                                    .collect(),
+                            }
+                        ),
+                        Err(e) => Response::Error(e.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
@@ -0,0 +1,158 @@
+use data::{NoteChanges, NoteInfo, NoteTombstone, SyncToken};
+use log::error;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use time::UtcDateTime;
+use uuid::Uuid;
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct ListNoteChangesRequest {
+    pub access_token: String,
+    pub since: Option<SyncToken>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug)]
+pub struct ListNoteChangesResponse(
+    pub Result<NoteChanges, bindings::StorageError>
+);
+
+impl From<SyncToken> for bindings::SyncToken {
+    fn from(value: SyncToken) -> Self {
+        bindings::SyncToken {
+            generation: value.generation.into_bytes().to_vec(),
+            seq: value.seq,
+            snapshot_start: value.snapshot_start,
+        }
+    }
+}
+
+impl TryFrom<bindings::SyncToken> for SyncToken {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::SyncToken) -> Result<Self, Self::Error> {
+        Ok(
+            SyncToken {
+                generation: Uuid::from_slice(&value.generation)?,
+                seq: value.seq,
+                snapshot_start: value.snapshot_start,
+            }
+        )
+    }
+}
+
+impl From<NoteTombstone> for bindings::NoteTombstone {
+    fn from(value: NoteTombstone) -> Self {
+        bindings::NoteTombstone {
+            id: value.id.into_bytes().to_vec(),
+            deleted_at: value.deleted_at.unix_timestamp(),
+        }
+    }
+}
+
+impl TryFrom<bindings::NoteTombstone> for NoteTombstone {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::NoteTombstone) -> Result<Self, Self::Error> {
+        Ok(
+            NoteTombstone {
+                id: Uuid::from_slice(&value.id)?,
+                deleted_at: UtcDateTime::from_unix_timestamp(value.deleted_at)?,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::ListNoteChangesRequest> for ListNoteChangesRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ListNoteChangesRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ListNoteChangesRequest {
+                access_token: value.access_token,
+                since: value.since.map(SyncToken::try_from).transpose()?,
+                limit: value.limit,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ListNoteChangesResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::list_note_changes_response::Response;
+        let value = match value {
+            bindings::response::Response::ListNoteChanges(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ListNoteChangesResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::Changes(changes) => Ok(
+                        NoteChanges {
+                            updated: changes.updated
+                                .into_iter()
+                                .filter_map(|v|
+                                    NoteInfo::try_from(v)
+                                        .inspect_err(|e| error!("failed to parse note info protobuf response: {e}"))
+                                        .ok()
+                                )
+                                .collect(),
+                            deleted: changes.deleted
+                                .into_iter()
+                                .filter_map(|v|
+                                    NoteTombstone::try_from(v)
+                                        .inspect_err(|e| error!("failed to parse note tombstone protobuf response: {e}"))
+                                        .ok()
+                                )
+                                .collect(),
+                            sync_token: changes.sync_token
+                                .ok_or_mapping_error(MappingError::missing("sync_token"))?
+                                .try_into()?,
+                            more: changes.more,
+                            reset: changes.reset,
+                        }
+                    ),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ListNoteChangesRequest> for bindings::ListNoteChangesRequest {
+    fn from(value: ListNoteChangesRequest) -> Self {
+        bindings::ListNoteChangesRequest {
+            access_token: value.access_token,
+            since: value.since.map(bindings::SyncToken::from),
+            limit: value.limit,
+        }
+    }
+}
+
+impl From<ListNoteChangesResponse> for bindings::response::Response {
+    fn from(value: ListNoteChangesResponse) -> Self {
+        use bindings::list_note_changes_response::Response;
+        bindings::response::Response::ListNoteChanges(
+            bindings::ListNoteChangesResponse {
+                response: Some(
+                    match value.0 {
+                        Ok(changes) => Response::Changes(
+                            bindings::NoteChanges {
+                                updated: changes.updated
+                                    .into_iter()
+                                    .map(bindings::NoteInfo::from)
+                                    .collect(),
+                                deleted: changes.deleted
+                                    .into_iter()
+                                    .map(bindings::NoteTombstone::from)
+                                    .collect(),
+                                sync_token: Some(changes.sync_token.into()),
+                                more: changes.more,
+                                reset: changes.reset,
+                            }
+                        ),
+                        Err(e) => Response::Error(e.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
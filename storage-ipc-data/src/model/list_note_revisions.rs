@@ -0,0 +1,88 @@
+use data::NoteRevisionInfo;
+use log::error;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct ListNoteRevisionsRequest {
+    pub access_token: String,
+    pub note_id: Uuid,
+}
+
+#[derive(Debug)]
+pub struct ListNoteRevisionsResponse(
+    pub Result<Vec<NoteRevisionInfo>, bindings::StorageError>
+);
+
+impl TryFrom<bindings::ListNoteRevisionsRequest> for ListNoteRevisionsRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ListNoteRevisionsRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ListNoteRevisionsRequest {
+                access_token: value.access_token,
+                note_id: Uuid::from_slice(&value.note_id)?,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ListNoteRevisionsResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::list_note_revisions_response::Response;
+        let value = match value {
+            bindings::response::Response::ListNoteRevisions(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ListNoteRevisionsResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::RevisionsInfo(revisions) => Ok(
+                        revisions.revisions_info
+                            .into_iter()
+                            .filter_map(|v|
+                                NoteRevisionInfo::try_from(v)
+                                    .inspect_err(|e| error!("failed to parse revision info protobuf response: {e}"))
+                                    .ok()
+                            )
+                            .collect()
+                    ),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ListNoteRevisionsRequest> for bindings::ListNoteRevisionsRequest {
+    fn from(value: ListNoteRevisionsRequest) -> Self {
+        bindings::ListNoteRevisionsRequest {
+            access_token: value.access_token,
+            note_id: value.note_id.into_bytes().to_vec(),
+        }
+    }
+}
+
+impl From<ListNoteRevisionsResponse> for bindings::response::Response {
+    fn from(value: ListNoteRevisionsResponse) -> Self {
+        use bindings::list_note_revisions_response::Response;
+        bindings::response::Response::ListNoteRevisions(
+            bindings::ListNoteRevisionsResponse {
+                response: Some(
+                    match value.0 {
+                        Ok(revisions) => Response::RevisionsInfo(
+                            bindings::NoteRevisionsInfo {
+                                revisions_info: revisions
+                                    .into_iter()
+                                    .map(bindings::NoteRevisionInfo::from)
+                                    .collect(),
+                            }
+                        ),
+                        Err(e) => Response::Error(e.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
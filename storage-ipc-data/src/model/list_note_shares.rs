@@ -0,0 +1,83 @@
+use data::NoteShare;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct ListNoteSharesRequest {
+    pub access_token: String,
+    pub note_id: Uuid,
+}
+
+#[derive(Debug)]
+pub struct ListNoteSharesResponse(
+    pub Result<Vec<NoteShare>, bindings::StorageError>
+);
+
+impl TryFrom<bindings::ListNoteSharesRequest> for ListNoteSharesRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ListNoteSharesRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ListNoteSharesRequest {
+                access_token: value.access_token,
+                note_id: Uuid::from_slice(&value.note_id)?,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ListNoteSharesResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::list_note_shares_response::Response;
+        let value = match value {
+            bindings::response::Response::ListNoteShares(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ListNoteSharesResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::Shares(shares) => Ok(
+                        shares.shares
+                            .into_iter()
+                            .map(NoteShare::try_from)
+                            .collect::<Result<_, _>>()?
+                    ),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ListNoteSharesRequest> for bindings::ListNoteSharesRequest {
+    fn from(value: ListNoteSharesRequest) -> Self {
+        bindings::ListNoteSharesRequest {
+            access_token: value.access_token,
+            note_id: value.note_id.into_bytes().to_vec(),
+        }
+    }
+}
+
+impl From<ListNoteSharesResponse> for bindings::response::Response {
+    fn from(value: ListNoteSharesResponse) -> Self {
+        use bindings::list_note_shares_response::Response;
+        bindings::response::Response::ListNoteShares(
+            bindings::ListNoteSharesResponse {
+                response: Some(
+                    match value.0 {
+                        Ok(shares) => Response::Shares(
+                            bindings::NoteShares {
+                                shares: shares
+                                    .into_iter()
+                                    .map(bindings::NoteShare::from)
+                                    .collect(),
+                            }
+                        ),
+                        Err(e) => Response::Error(e.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
@@ -1,17 +1,19 @@
-use data::NoteMetadata;
+use data::{NoteListOrder, NoteListPage, NoteListQuery, NoteMetadata};
 use log::error;
 use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use time::UtcDateTime;
 use crate::bindings;
 use bindings::StorageError;
 
 #[derive(Debug)]
 pub struct ListNotesRequest {
     pub access_token: String,
+    pub query: NoteListQuery,
 }
 
 #[derive(Debug)]
 pub enum ListNotesResponse {
-    Notes(Vec<NoteMetadata>),
+    Notes(NoteListPage<NoteMetadata>),
     Error(StorageError),
 }
 
@@ -21,6 +23,18 @@ impl TryFrom<bindings::ListNotesRequest> for ListNotesRequest {
         Ok(
             ListNotesRequest {
                 access_token: value.access_token,
+                query: NoteListQuery {
+                    limit: value.limit,
+                    after: value.after.map(NoteMetadata::try_from).transpose()?,
+                    modified_since: value.modified_since
+                        .map(UtcDateTime::from_unix_timestamp)
+                        .transpose()?,
+                    order: match bindings::NoteListOrder::try_from(value.order)? {
+                        bindings::NoteListOrder::OldestFirst => NoteListOrder::OldestFirst,
+                        bindings::NoteListOrder::NewestFirst => NoteListOrder::NewestFirst,
+                    },
+                    tag: value.tag,
+                },
             }
         )
     }
@@ -37,18 +51,21 @@ impl TryFrom<bindings::response::Response> for ListNotesResponse {
         Ok(
             match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
                 Response::NotesInfo(notes_metadata) => ListNotesResponse::Notes(
-                    notes_metadata.notes_metadata
-                        .into_iter()
-                        .filter_map(|v| {
-                            match NoteMetadata::try_from(v) {
-                                Ok(note_metadata) => Some(note_metadata),
-                                Err(e) => {
-                                    error!("failed to parse note metadata protobuf repsonse: {e}");
-                                    None
+                    NoteListPage {
+                        notes: notes_metadata.notes_metadata
+                            .into_iter()
+                            .filter_map(|v| {
+                                match NoteMetadata::try_from(v) {
+                                    Ok(note_metadata) => Some(note_metadata),
+                                    Err(e) => {
+                                        error!("failed to parse note metadata protobuf repsonse: {e}");
+                                        None
+                                    }
                                 }
-                            }
-                        })
-                        .collect()
+                            })
+                            .collect(),
+                        next: value.next.map(NoteMetadata::try_from).transpose()?,
+                    }
                 ),
                 Response::Error(e) => ListNotesResponse::Error(e.try_into()?),
             }
@@ -60,6 +77,15 @@ impl From<ListNotesRequest> for bindings::ListNotesRequest {
     fn from(value: ListNotesRequest) -> Self {
         bindings::ListNotesRequest {
             access_token: value.access_token,
+            limit: value.query.limit,
+            after: value.query.after.map(bindings::NoteMetadata::from),
+            modified_since: value.query.modified_since
+                .map(UtcDateTime::unix_timestamp),
+            order: match value.query.order {
+                NoteListOrder::OldestFirst => bindings::NoteListOrder::OldestFirst,
+                NoteListOrder::NewestFirst => bindings::NoteListOrder::NewestFirst,
+            }.into(),
+            tag: value.query.tag,
         }
     }
 }
@@ -68,20 +94,24 @@ impl From<ListNotesResponse> for bindings::response::Response {
     fn from(value: ListNotesResponse) -> Self {
         use bindings::list_notes_response::Response;
         bindings::response::Response::ListNotes(
-            bindings::ListNotesResponse {
-                response: Some(
-                    match value {
-                        ListNotesResponse::Notes(notes_info) => Response::NotesInfo(
+            match value {
+                ListNotesResponse::Notes(page) => bindings::ListNotesResponse {
+                    response: Some(
+                        Response::NotesInfo(
                             bindings::NotesMetadata {
-                                notes_metadata: notes_info
+                                notes_metadata: page.notes
                                     .into_iter()
                                     .map(bindings::NoteMetadata::from)
                                     .collect(),
                             }
-                        ),
-                        ListNotesResponse::Error(e) => Response::Error(e.into()),
-                    }
-                )
+                        )
+                    ),
+                    next: page.next.map(bindings::NoteMetadata::from),
+                },
+                ListNotesResponse::Error(e) => bindings::ListNotesResponse {
+                    response: Some(Response::Error(e.into())),
+                    next: None,
+                },
             }
         )
     }
@@ -0,0 +1,84 @@
+use data::SharedNoteInfo;
+use log::error;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct ListSharedNotesRequest {
+    pub access_token: String,
+}
+
+#[derive(Debug)]
+pub struct ListSharedNotesResponse(
+    pub Result<Vec<SharedNoteInfo>, bindings::StorageError>
+);
+
+impl TryFrom<bindings::ListSharedNotesRequest> for ListSharedNotesRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ListSharedNotesRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ListSharedNotesRequest {
+                access_token: value.access_token,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ListSharedNotesResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::list_shared_notes_response::Response;
+        let value = match value {
+            bindings::response::Response::ListSharedNotes(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ListSharedNotesResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::NotesInfo(notes) => Ok(
+                        notes.notes_info
+                            .into_iter()
+                            .filter_map(|v|
+                                SharedNoteInfo::try_from(v)
+                                    .inspect_err(|e| error!("failed to parse shared note info protobuf response: {e}"))
+                                    .ok()
+                            )
+                            .collect()
+                    ),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ListSharedNotesRequest> for bindings::ListSharedNotesRequest {
+    fn from(value: ListSharedNotesRequest) -> Self {
+        bindings::ListSharedNotesRequest {
+            access_token: value.access_token,
+        }
+    }
+}
+
+impl From<ListSharedNotesResponse> for bindings::response::Response {
+    fn from(value: ListSharedNotesResponse) -> Self {
+        use bindings::list_shared_notes_response::Response;
+        bindings::response::Response::ListSharedNotes(
+            bindings::ListSharedNotesResponse {
+                response: Some(
+                    match value.0 {
+                        Ok(notes) => Response::NotesInfo(
+                            bindings::SharedNotesInfo {
+                                notes_info: notes
+                                    .into_iter()
+                                    .map(bindings::SharedNoteInfo::from)
+                                    .collect(),
+                            }
+                        ),
+                        Err(e) => Response::Error(e.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
@@ -0,0 +1,79 @@
+use data::TagCount;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct ListTagsRequest {
+    pub access_token: String,
+}
+
+#[derive(Debug)]
+pub struct ListTagsResponse(
+    pub Result<Vec<TagCount>, bindings::StorageError>
+);
+
+impl TryFrom<bindings::ListTagsRequest> for ListTagsRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ListTagsRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ListTagsRequest {
+                access_token: value.access_token,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ListTagsResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::list_tags_response::Response;
+        let value = match value {
+            bindings::response::Response::ListTags(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ListTagsResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::Tags(tags) => Ok(
+                        tags.tags
+                            .into_iter()
+                            .map(|v| TagCount { tag: v.tag, count: v.count })
+                            .collect()
+                    ),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ListTagsRequest> for bindings::ListTagsRequest {
+    fn from(value: ListTagsRequest) -> Self {
+        bindings::ListTagsRequest {
+            access_token: value.access_token,
+        }
+    }
+}
+
+impl From<ListTagsResponse> for bindings::response::Response {
+    fn from(value: ListTagsResponse) -> Self {
+        use bindings::list_tags_response::Response;
+        bindings::response::Response::ListTags(
+            bindings::ListTagsResponse {
+                response: Some(
+                    match value.0 {
+                        Ok(tags) => Response::Tags(
+                            bindings::TagCounts {
+                                tags: tags
+                                    .into_iter()
+                                    .map(|v| bindings::TagCount { tag: v.tag, count: v.count })
+                                    .collect(),
+                            }
+                        ),
+                        Err(e) => Response::Error(e.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
@@ -0,0 +1,84 @@
+use data::NoteInfo;
+use log::error;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct ListTrashRequest {
+    pub access_token: String,
+}
+
+#[derive(Debug)]
+pub struct ListTrashResponse(
+    pub Result<Vec<NoteInfo>, bindings::StorageError>
+);
+
+impl TryFrom<bindings::ListTrashRequest> for ListTrashRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ListTrashRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ListTrashRequest {
+                access_token: value.access_token,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ListTrashResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::list_trash_response::Response;
+        let value = match value {
+            bindings::response::Response::ListTrash(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ListTrashResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::NotesInfo(notes) => Ok(
+                        notes.notes_info
+                            .into_iter()
+                            .filter_map(|v|
+                                NoteInfo::try_from(v)
+                                    .inspect_err(|e| error!("failed to parse trashed note info protobuf response: {e}"))
+                                    .ok()
+                            )
+                            .collect()
+                    ),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ListTrashRequest> for bindings::ListTrashRequest {
+    fn from(value: ListTrashRequest) -> Self {
+        bindings::ListTrashRequest {
+            access_token: value.access_token,
+        }
+    }
+}
+
+impl From<ListTrashResponse> for bindings::response::Response {
+    fn from(value: ListTrashResponse) -> Self {
+        use bindings::list_trash_response::Response;
+        bindings::response::Response::ListTrash(
+            bindings::ListTrashResponse {
+                response: Some(
+                    match value.0 {
+                        Ok(notes) => Response::NotesInfo(
+                            bindings::NotesInfo {
+                                notes_info: notes
+                                    .into_iter()
+                                    .map(bindings::NoteInfo::from)
+                                    .collect(),
+                            }
+                        ),
+                        Err(e) => Response::Error(e.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
@@ -10,6 +10,8 @@ impl From<Note> for bindings::Note {
                 bindings::NoteInfo {
                     metadata: Some(value.metadata.into()),
                     name: value.name,
+                    tags: value.tags.into_iter().collect(),
+                    tasks: None,
                 }
             ),
             contents: value.contents,
@@ -27,6 +29,7 @@ impl TryFrom<bindings::Note> for Note {
             Note {
                 metadata: note_info.metadata,
                 name: note_info.name,
+                tags: note_info.tags,
                 contents: value.contents,
             }
         )
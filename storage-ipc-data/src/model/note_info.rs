@@ -1,4 +1,4 @@
-use data::NoteInfo;
+use data::{NoteInfo, TaskSummary};
 use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
 
 use crate::bindings;
@@ -8,6 +8,8 @@ impl From<NoteInfo> for bindings::NoteInfo {
         bindings::NoteInfo {
             metadata: Some(value.metadata.into()),
             name: value.name,
+            tags: value.tags.into_iter().collect(),
+            tasks: value.tasks.map(Into::into),
         }
     }
 }
@@ -21,7 +23,27 @@ impl TryFrom<bindings::NoteInfo> for NoteInfo {
                     .ok_or_mapping_error(MappingError::missing("metadata"))?
                     .try_into()?,
                 name: value.name,
+                tags: value.tags.into_iter().collect(),
+                tasks: value.tasks.map(Into::into),
             }
         )
     }
 }
+
+impl From<TaskSummary> for bindings::TaskSummary {
+    fn from(value: TaskSummary) -> Self {
+        bindings::TaskSummary {
+            done: value.done,
+            total: value.total,
+        }
+    }
+}
+
+impl From<bindings::TaskSummary> for TaskSummary {
+    fn from(value: bindings::TaskSummary) -> Self {
+        TaskSummary {
+            done: value.done,
+            total: value.total,
+        }
+    }
+}
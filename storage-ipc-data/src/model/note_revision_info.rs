@@ -0,0 +1,30 @@
+use data::NoteRevisionInfo;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use time::UtcDateTime;
+
+use crate::bindings;
+
+impl From<NoteRevisionInfo> for bindings::NoteRevisionInfo {
+    fn from(value: NoteRevisionInfo) -> Self {
+        bindings::NoteRevisionInfo {
+            revision: value.revision,
+            archived_at: value.archived_at.unix_timestamp(),
+            info: Some(value.info.into()),
+        }
+    }
+}
+
+impl TryFrom<bindings::NoteRevisionInfo> for NoteRevisionInfo {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::NoteRevisionInfo) -> Result<Self, Self::Error> {
+        Ok(
+            NoteRevisionInfo {
+                revision: value.revision,
+                archived_at: UtcDateTime::from_unix_timestamp(value.archived_at)?,
+                info: value.info
+                    .ok_or_mapping_error(MappingError::missing("info"))?
+                    .try_into()?,
+            }
+        )
+    }
+}
@@ -0,0 +1,27 @@
+use data::NoteSearchResult;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+
+use crate::bindings;
+
+impl From<NoteSearchResult> for bindings::NoteSearchResult {
+    fn from(value: NoteSearchResult) -> Self {
+        bindings::NoteSearchResult {
+            info: Some(value.info.into()),
+            snippets: value.snippets,
+        }
+    }
+}
+
+impl TryFrom<bindings::NoteSearchResult> for NoteSearchResult {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::NoteSearchResult) -> Result<Self, Self::Error> {
+        Ok(
+            NoteSearchResult {
+                info: value.info
+                    .ok_or_mapping_error(MappingError::missing("info"))?
+                    .try_into()?,
+                snippets: value.snippets,
+            }
+        )
+    }
+}
@@ -0,0 +1,70 @@
+use std::str::FromStr;
+
+use data::{NoteShare, ShareAccess, SharedNoteInfo, UsernameString};
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+
+use crate::bindings;
+
+impl From<NoteShare> for bindings::NoteShare {
+    fn from(value: NoteShare) -> Self {
+        bindings::NoteShare {
+            username: value.username.into_string(),
+            access: bindings::ShareAccess::from(value.access).into(),
+        }
+    }
+}
+
+impl TryFrom<bindings::NoteShare> for NoteShare {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::NoteShare) -> Result<Self, Self::Error> {
+        Ok(
+            NoteShare {
+                username: UsernameString::from_str(&value.username)?,
+                access: bindings::ShareAccess::try_from(value.access)?.into(),
+            }
+        )
+    }
+}
+
+impl From<SharedNoteInfo> for bindings::SharedNoteInfo {
+    fn from(value: SharedNoteInfo) -> Self {
+        bindings::SharedNoteInfo {
+            owner: value.owner.into_string(),
+            access: bindings::ShareAccess::from(value.access).into(),
+            info: Some(value.info.into()),
+        }
+    }
+}
+
+impl TryFrom<bindings::SharedNoteInfo> for SharedNoteInfo {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::SharedNoteInfo) -> Result<Self, Self::Error> {
+        Ok(
+            SharedNoteInfo {
+                owner: UsernameString::from_str(&value.owner)?,
+                access: bindings::ShareAccess::try_from(value.access)?.into(),
+                info: value.info
+                    .ok_or_mapping_error(MappingError::missing("info"))?
+                    .try_into()?,
+            }
+        )
+    }
+}
+
+impl From<ShareAccess> for bindings::ShareAccess {
+    fn from(value: ShareAccess) -> Self {
+        match value {
+            ShareAccess::ReadOnly => bindings::ShareAccess::ReadOnly,
+            ShareAccess::ReadWrite => bindings::ShareAccess::ReadWrite,
+        }
+    }
+}
+
+impl From<bindings::ShareAccess> for ShareAccess {
+    fn from(value: bindings::ShareAccess) -> Self {
+        match value {
+            bindings::ShareAccess::ReadOnly => ShareAccess::ReadOnly,
+            bindings::ShareAccess::ReadWrite => ShareAccess::ReadWrite,
+        }
+    }
+}
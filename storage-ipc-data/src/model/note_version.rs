@@ -0,0 +1,23 @@
+use data::NoteVersion;
+
+use crate::bindings;
+
+impl From<NoteVersion> for bindings::NoteVersion {
+    fn from(value: NoteVersion) -> Self {
+        bindings::NoteVersion {
+            ino: value.ino,
+            ctime: value.ctime,
+            ctime_nsec: value.ctime_nsec,
+        }
+    }
+}
+
+impl From<bindings::NoteVersion> for NoteVersion {
+    fn from(value: bindings::NoteVersion) -> Self {
+        NoteVersion {
+            ino: value.ino,
+            ctime: value.ctime,
+            ctime_nsec: value.ctime_nsec,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use protobuf_common::{MappingError, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+use bindings::StorageError;
+
+#[derive(Debug)]
+pub struct PurgeTrashedNoteRequest {
+    pub access_token: String,
+    pub note_id: Uuid,
+}
+
+#[derive(Debug)]
+pub struct PurgeTrashedNoteResponse(pub Option<StorageError>);
+
+impl TryFrom<bindings::PurgeTrashedNoteRequest> for PurgeTrashedNoteRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::PurgeTrashedNoteRequest) -> Result<Self, Self::Error> {
+        Ok(
+            PurgeTrashedNoteRequest {
+                access_token: value.access_token,
+                note_id: Uuid::from_slice(&value.note_id)?,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for PurgeTrashedNoteResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        let value = match value {
+            bindings::response::Response::PurgeTrashedNote(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            PurgeTrashedNoteResponse(
+                value.error.map(|e| e.try_into()).transpose()?,
+            )
+        )
+    }
+}
+
+impl From<PurgeTrashedNoteRequest> for bindings::PurgeTrashedNoteRequest {
+    fn from(value: PurgeTrashedNoteRequest) -> Self {
+        bindings::PurgeTrashedNoteRequest {
+            access_token: value.access_token,
+            note_id: value.note_id.into_bytes().to_vec(),
+        }
+    }
+}
+
+impl From<PurgeTrashedNoteResponse> for bindings::response::Response {
+    fn from(value: PurgeTrashedNoteResponse) -> Self {
+        bindings::response::Response::PurgeTrashedNote(
+            bindings::PurgeTrashedNoteResponse {
+                error: value.0.map(StorageError::into)
+            }
+        )
+    }
+}
@@ -1,4 +1,6 @@
-use data::Note;
+use std::str::FromStr;
+
+use data::{UsernameString, VersionedNote};
 use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
 use uuid::Uuid;
 use crate::bindings;
@@ -7,11 +9,12 @@ use crate::bindings;
 pub struct ReadNoteRequest {
     pub access_token: String,
     pub note_id: Uuid,
+    pub owner: Option<UsernameString>,
 }
 
 #[derive(Debug)]
 pub struct ReadNoteResponse(
-    pub Result<Note, bindings::StorageError>
+    pub Result<VersionedNote, bindings::StorageError>
 );
 
 impl TryFrom<bindings::ReadNoteRequest> for ReadNoteRequest {
@@ -21,6 +24,10 @@ impl TryFrom<bindings::ReadNoteRequest> for ReadNoteRequest {
             ReadNoteRequest {
                 access_token: value.access_token,
                 note_id: Uuid::from_slice(&value.note_id)?,
+                owner: value.owner
+                    .as_deref()
+                    .map(UsernameString::from_str)
+                    .transpose()?,
             }
         )
     }
@@ -37,7 +44,14 @@ impl TryFrom<bindings::response::Response> for ReadNoteResponse {
         Ok(
             ReadNoteResponse(
                 match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
-                    Response::Note(note) => Ok(note.try_into()?),
+                    Response::Note(note) => Ok(
+                        VersionedNote {
+                            note: note.try_into()?,
+                            version: value.version
+                                .ok_or_mapping_error(MappingError::missing("version"))?
+                                .into(),
+                        }
+                    ),
                     Response::Error(e) => Err(e.try_into()?),
                 }
             )
@@ -50,6 +64,7 @@ impl From<ReadNoteRequest> for bindings::ReadNoteRequest {
         bindings::ReadNoteRequest {
             access_token: value.access_token,
             note_id: value.note_id.into_bytes().to_vec(),
+            owner: value.owner.map(UsernameString::into_string),
         }
     }
 }
@@ -57,13 +72,15 @@ impl From<ReadNoteRequest> for bindings::ReadNoteRequest {
 impl From<ReadNoteResponse> for bindings::response::Response {
     fn from(value: ReadNoteResponse) -> Self {
         bindings::response::Response::ReadNote(
-            bindings::ReadNoteResponse {
-                response: Some(
-                    match value.0 {
-                        Ok(response) => bindings::read_note_response::Response::Note(response.into()),
-                        Err(e) => bindings::read_note_response::Response::Error(e.into()),
-                    }
-                ),
+            match value.0 {
+                Ok(VersionedNote { note, version }) => bindings::ReadNoteResponse {
+                    response: Some(bindings::read_note_response::Response::Note(note.into())),
+                    version: Some(version.into()),
+                },
+                Err(e) => bindings::ReadNoteResponse {
+                    response: Some(bindings::read_note_response::Response::Error(e.into())),
+                    version: None,
+                },
             }
         )
     }
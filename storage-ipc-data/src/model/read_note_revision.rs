@@ -0,0 +1,74 @@
+use data::Note;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct ReadNoteRevisionRequest {
+    pub access_token: String,
+    pub note_id: Uuid,
+    pub revision: i64,
+}
+
+#[derive(Debug)]
+pub struct ReadNoteRevisionResponse(
+    pub Result<Note, bindings::StorageError>
+);
+
+impl TryFrom<bindings::ReadNoteRevisionRequest> for ReadNoteRevisionRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ReadNoteRevisionRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ReadNoteRevisionRequest {
+                access_token: value.access_token,
+                note_id: Uuid::from_slice(&value.note_id)?,
+                revision: value.revision,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ReadNoteRevisionResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::read_note_revision_response::Response;
+        let value = match value {
+            bindings::response::Response::ReadNoteRevision(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ReadNoteRevisionResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::Note(note) => Ok(note.try_into()?),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ReadNoteRevisionRequest> for bindings::ReadNoteRevisionRequest {
+    fn from(value: ReadNoteRevisionRequest) -> Self {
+        bindings::ReadNoteRevisionRequest {
+            access_token: value.access_token,
+            note_id: value.note_id.into_bytes().to_vec(),
+            revision: value.revision,
+        }
+    }
+}
+
+impl From<ReadNoteRevisionResponse> for bindings::response::Response {
+    fn from(value: ReadNoteRevisionResponse) -> Self {
+        use bindings::read_note_revision_response::Response;
+        bindings::response::Response::ReadNoteRevision(
+            bindings::ReadNoteRevisionResponse {
+                response: Some(
+                    match value.0 {
+                        Ok(note) => Response::Note(note.into()),
+                        Err(e) => Response::Error(e.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
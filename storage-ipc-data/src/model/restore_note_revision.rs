@@ -0,0 +1,81 @@
+use data::{NoteMetadata, NoteVersion};
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct RestoreNoteRevisionRequest {
+    pub access_token: String,
+    pub note_id: Uuid,
+    pub revision: i64,
+}
+
+#[derive(Debug)]
+pub struct RestoreNoteRevisionResponse(
+    pub Result<(NoteMetadata, NoteVersion), bindings::StorageError>
+);
+
+impl TryFrom<bindings::RestoreNoteRevisionRequest> for RestoreNoteRevisionRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::RestoreNoteRevisionRequest) -> Result<Self, Self::Error> {
+        Ok(
+            RestoreNoteRevisionRequest {
+                access_token: value.access_token,
+                note_id: Uuid::from_slice(&value.note_id)?,
+                revision: value.revision,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for RestoreNoteRevisionResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::restore_note_revision_response::Response;
+        let value = match value {
+            bindings::response::Response::RestoreNoteRevision(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            RestoreNoteRevisionResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::Metadata(metadata) => Ok((
+                        metadata.try_into()?,
+                        value.version
+                            .ok_or_mapping_error(MappingError::missing("version"))?
+                            .into(),
+                    )),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<RestoreNoteRevisionRequest> for bindings::RestoreNoteRevisionRequest {
+    fn from(value: RestoreNoteRevisionRequest) -> Self {
+        bindings::RestoreNoteRevisionRequest {
+            access_token: value.access_token,
+            note_id: value.note_id.into_bytes().to_vec(),
+            revision: value.revision,
+        }
+    }
+}
+
+impl From<RestoreNoteRevisionResponse> for bindings::response::Response {
+    fn from(value: RestoreNoteRevisionResponse) -> Self {
+        use bindings::restore_note_revision_response::Response;
+        bindings::response::Response::RestoreNoteRevision(
+            match value.0 {
+                Ok((metadata, version)) => bindings::RestoreNoteRevisionResponse {
+                    response: Some(Response::Metadata(metadata.into())),
+                    version: Some(version.into()),
+                },
+                Err(e) => bindings::RestoreNoteRevisionResponse {
+                    response: Some(Response::Error(e.into())),
+                    version: None,
+                },
+            }
+        )
+    }
+}
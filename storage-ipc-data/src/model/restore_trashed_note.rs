@@ -0,0 +1,78 @@
+use data::{NoteMetadata, NoteVersion};
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct RestoreTrashedNoteRequest {
+    pub access_token: String,
+    pub note_id: Uuid,
+}
+
+#[derive(Debug)]
+pub struct RestoreTrashedNoteResponse(
+    pub Result<(NoteMetadata, NoteVersion), bindings::StorageError>
+);
+
+impl TryFrom<bindings::RestoreTrashedNoteRequest> for RestoreTrashedNoteRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::RestoreTrashedNoteRequest) -> Result<Self, Self::Error> {
+        Ok(
+            RestoreTrashedNoteRequest {
+                access_token: value.access_token,
+                note_id: Uuid::from_slice(&value.note_id)?,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for RestoreTrashedNoteResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::restore_trashed_note_response::Response;
+        let value = match value {
+            bindings::response::Response::RestoreTrashedNote(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            RestoreTrashedNoteResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::Metadata(metadata) => Ok((
+                        metadata.try_into()?,
+                        value.version
+                            .ok_or_mapping_error(MappingError::missing("version"))?
+                            .into(),
+                    )),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<RestoreTrashedNoteRequest> for bindings::RestoreTrashedNoteRequest {
+    fn from(value: RestoreTrashedNoteRequest) -> Self {
+        bindings::RestoreTrashedNoteRequest {
+            access_token: value.access_token,
+            note_id: value.note_id.into_bytes().to_vec(),
+        }
+    }
+}
+
+impl From<RestoreTrashedNoteResponse> for bindings::response::Response {
+    fn from(value: RestoreTrashedNoteResponse) -> Self {
+        use bindings::restore_trashed_note_response::Response;
+        bindings::response::Response::RestoreTrashedNote(
+            match value.0 {
+                Ok((metadata, version)) => bindings::RestoreTrashedNoteResponse {
+                    response: Some(Response::Metadata(metadata.into())),
+                    version: Some(version.into()),
+                },
+                Err(e) => bindings::RestoreTrashedNoteResponse {
+                    response: Some(Response::Error(e.into())),
+                    version: None,
+                },
+            }
+        )
+    }
+}
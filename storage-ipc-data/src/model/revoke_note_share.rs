@@ -0,0 +1,65 @@
+use std::str::FromStr;
+
+use data::UsernameString;
+use protobuf_common::{MappingError, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+use bindings::StorageError;
+
+#[derive(Debug)]
+pub struct RevokeNoteShareRequest {
+    pub access_token: String,
+    pub note_id: Uuid,
+    pub username: UsernameString,
+}
+
+#[derive(Debug)]
+pub struct RevokeNoteShareResponse(pub Option<StorageError>);
+
+impl TryFrom<bindings::RevokeNoteShareRequest> for RevokeNoteShareRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::RevokeNoteShareRequest) -> Result<Self, Self::Error> {
+        Ok(
+            RevokeNoteShareRequest {
+                access_token: value.access_token,
+                note_id: Uuid::from_slice(&value.note_id)?,
+                username: UsernameString::from_str(&value.username)?,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for RevokeNoteShareResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        let value = match value {
+            bindings::response::Response::RevokeNoteShare(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            RevokeNoteShareResponse(
+                value.error.map(|e| e.try_into()).transpose()?,
+            )
+        )
+    }
+}
+
+impl From<RevokeNoteShareRequest> for bindings::RevokeNoteShareRequest {
+    fn from(value: RevokeNoteShareRequest) -> Self {
+        bindings::RevokeNoteShareRequest {
+            access_token: value.access_token,
+            note_id: value.note_id.into_bytes().to_vec(),
+            username: value.username.into_string(),
+        }
+    }
+}
+
+impl From<RevokeNoteShareResponse> for bindings::response::Response {
+    fn from(value: RevokeNoteShareResponse) -> Self {
+        bindings::response::Response::RevokeNoteShare(
+            bindings::RevokeNoteShareResponse {
+                error: value.0.map(StorageError::into)
+            }
+        )
+    }
+}
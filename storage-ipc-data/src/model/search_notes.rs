@@ -0,0 +1,100 @@
+use data::{NoteListPage, NoteMetadata, NoteSearchQuery, NoteSearchResult};
+use log::error;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct SearchNotesRequest {
+    pub access_token: String,
+    pub query: NoteSearchQuery,
+}
+
+#[derive(Debug)]
+pub struct SearchNotesResponse(
+    pub Result<NoteListPage<NoteSearchResult>, bindings::StorageError>
+);
+
+impl TryFrom<bindings::SearchNotesRequest> for SearchNotesRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::SearchNotesRequest) -> Result<Self, Self::Error> {
+        Ok(
+            SearchNotesRequest {
+                access_token: value.access_token,
+                query: NoteSearchQuery {
+                    query: value.query,
+                    limit: value.limit,
+                    after: value.after.map(NoteMetadata::try_from).transpose()?,
+                },
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for SearchNotesResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::search_notes_response::Response;
+        let value = match value {
+            bindings::response::Response::SearchNotes(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            SearchNotesResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::Results(results) => Ok(
+                        NoteListPage {
+                            notes: results.results
+                                .into_iter()
+                                .filter_map(|v|
+                                    NoteSearchResult::try_from(v)
+                                        .inspect_err(|e| error!("failed to parse search result protobuf response: {e}"))
+                                        .ok()
+                                )
+                                .collect(),
+                            next: value.next.map(NoteMetadata::try_from).transpose()?,
+                        }
+                    ),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<SearchNotesRequest> for bindings::SearchNotesRequest {
+    fn from(value: SearchNotesRequest) -> Self {
+        bindings::SearchNotesRequest {
+            access_token: value.access_token,
+            query: value.query.query,
+            limit: value.query.limit,
+            after: value.query.after.map(bindings::NoteMetadata::from),
+        }
+    }
+}
+
+impl From<SearchNotesResponse> for bindings::response::Response {
+    fn from(value: SearchNotesResponse) -> Self {
+        use bindings::search_notes_response::Response;
+        bindings::response::Response::SearchNotes(
+            match value.0 {
+                Ok(page) => bindings::SearchNotesResponse {
+                    response: Some(
+                        Response::Results(
+                            bindings::NoteSearchResults {
+                                results: page.notes
+                                    .into_iter()
+                                    .map(bindings::NoteSearchResult::from)
+                                    .collect(),
+                            }
+                        )
+                    ),
+                    next: page.next.map(bindings::NoteMetadata::from),
+                },
+                Err(e) => bindings::SearchNotesResponse {
+                    response: Some(Response::Error(e.into())),
+                    next: None,
+                },
+            }
+        )
+    }
+}
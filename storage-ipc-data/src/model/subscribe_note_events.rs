@@ -0,0 +1,91 @@
+use data::{NoteEvent, NoteTombstone};
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct SubscribeNoteEventsRequest {
+    pub access_token: String,
+}
+
+#[derive(Debug)]
+pub enum NoteEventResponse {
+    Subscribed,
+    Event(NoteEvent),
+    Error(bindings::StorageError),
+}
+
+impl TryFrom<bindings::SubscribeNoteEventsRequest> for SubscribeNoteEventsRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::SubscribeNoteEventsRequest) -> Result<Self, Self::Error> {
+        Ok(
+            SubscribeNoteEventsRequest {
+                access_token: value.access_token,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for NoteEventResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, ProtobufRequestError> {
+        use bindings::note_event_response::Response;
+        use bindings::note_event::Event;
+        let value = match value {
+            bindings::response::Response::NoteEvent(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                Response::Subscribed(_) => NoteEventResponse::Subscribed,
+                Response::Event(event) => NoteEventResponse::Event(
+                    match event.event.ok_or_mapping_error(MappingError::missing("event"))? {
+                        Event::Created(info) => NoteEvent::Created(info.try_into()?),
+                        Event::Updated(info) => NoteEvent::Updated(info.try_into()?),
+                        Event::Deleted(tombstone) => NoteEvent::Deleted(
+                            NoteTombstone::try_from(tombstone)?
+                        ),
+                    }
+                ),
+                Response::Error(e) => NoteEventResponse::Error(e.try_into()?),
+            }
+        )
+    }
+}
+
+impl From<SubscribeNoteEventsRequest> for bindings::SubscribeNoteEventsRequest {
+    fn from(value: SubscribeNoteEventsRequest) -> Self {
+        bindings::SubscribeNoteEventsRequest {
+            access_token: value.access_token,
+        }
+    }
+}
+
+impl From<NoteEventResponse> for bindings::response::Response {
+    fn from(value: NoteEventResponse) -> Self {
+        use bindings::note_event_response::Response;
+        use bindings::note_event::Event;
+        bindings::response::Response::NoteEvent(
+            bindings::NoteEventResponse {
+                response: Some(
+                    match value {
+                        NoteEventResponse::Subscribed => Response::Subscribed(
+                            bindings::Subscribed {}
+                        ),
+                        NoteEventResponse::Event(event) => Response::Event(
+                            bindings::NoteEvent {
+                                event: Some(
+                                    match event {
+                                        NoteEvent::Created(info) => Event::Created(info.into()),
+                                        NoteEvent::Updated(info) => Event::Updated(info.into()),
+                                        NoteEvent::Deleted(tombstone) => Event::Deleted(tombstone.into()),
+                                    }
+                                ),
+                            }
+                        ),
+                        NoteEventResponse::Error(e) => Response::Error(e.into()),
+                    }
+                ),
+            }
+        )
+    }
+}
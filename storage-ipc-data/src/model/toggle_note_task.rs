@@ -0,0 +1,83 @@
+use data::VersionedNote;
+use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
+use uuid::Uuid;
+use crate::bindings;
+
+#[derive(Debug)]
+pub struct ToggleNoteTaskRequest {
+    pub access_token: String,
+    pub note_id: Uuid,
+    pub task_index: u32,
+}
+
+#[derive(Debug)]
+pub struct ToggleNoteTaskResponse(
+    pub Result<VersionedNote, bindings::StorageError>
+);
+
+impl TryFrom<bindings::ToggleNoteTaskRequest> for ToggleNoteTaskRequest {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::ToggleNoteTaskRequest) -> Result<Self, Self::Error> {
+        Ok(
+            ToggleNoteTaskRequest {
+                access_token: value.access_token,
+                note_id: Uuid::from_slice(&value.note_id)?,
+                task_index: value.task_index,
+            }
+        )
+    }
+}
+
+impl TryFrom<bindings::response::Response> for ToggleNoteTaskResponse {
+    type Error = ProtobufRequestError;
+    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+        use bindings::toggle_note_task_response::Response;
+        let value = match value {
+            bindings::response::Response::ToggleNoteTask(value) => value,
+            _ => return Err(MappingError::UnexpectedEnumVariant.into()),
+        };
+        Ok(
+            ToggleNoteTaskResponse(
+                match value.response.ok_or_mapping_error(MappingError::missing("response"))? {
+                    Response::Note(note) => Ok(
+                        VersionedNote {
+                            note: note.try_into()?,
+                            version: value.version
+                                .ok_or_mapping_error(MappingError::missing("version"))?
+                                .into(),
+                        }
+                    ),
+                    Response::Error(e) => Err(e.try_into()?),
+                }
+            )
+        )
+    }
+}
+
+impl From<ToggleNoteTaskRequest> for bindings::ToggleNoteTaskRequest {
+    fn from(value: ToggleNoteTaskRequest) -> Self {
+        bindings::ToggleNoteTaskRequest {
+            access_token: value.access_token,
+            note_id: value.note_id.into_bytes().to_vec(),
+            task_index: value.task_index,
+        }
+    }
+}
+
+impl From<ToggleNoteTaskResponse> for bindings::response::Response {
+    fn from(value: ToggleNoteTaskResponse) -> Self {
+        use bindings::toggle_note_task_response::Response;
+        bindings::response::Response::ToggleNoteTask(
+            match value.0 {
+                Ok(VersionedNote { note, version }) => bindings::ToggleNoteTaskResponse {
+                    response: Some(Response::Note(note.into())),
+                    version: Some(version.into()),
+                },
+                Err(e) => bindings::ToggleNoteTaskResponse {
+                    response: Some(Response::Error(e.into())),
+                    version: None,
+                },
+            }
+        )
+    }
+}
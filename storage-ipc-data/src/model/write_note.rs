@@ -1,4 +1,6 @@
-use data::Note;
+use std::str::FromStr;
+
+use data::{Note, NoteMetadata, NoteVersion, UsernameString, VersionedNote};
 use protobuf_common::{MappingError, OptionExt, ProtobufRequestError};
 
 use crate::bindings::{self, StorageError};
@@ -7,10 +9,16 @@ use crate::bindings::{self, StorageError};
 pub struct WriteNoteRequest {
     pub access_token: String,
     pub note: Note,
+    pub base_version: Option<NoteVersion>,
+    pub owner: Option<UsernameString>,
 }
 
 #[derive(Debug)]
-pub struct WriteNoteResponse(pub Option<StorageError>);
+pub enum WriteNoteResponse {
+    Written(NoteMetadata, NoteVersion),
+    Conflict(VersionedNote),
+    Error(StorageError),
+}
 
 impl TryFrom<bindings::WriteNoteRequest> for WriteNoteRequest {
     type Error = ProtobufRequestError;
@@ -21,6 +29,11 @@ impl TryFrom<bindings::WriteNoteRequest> for WriteNoteRequest {
                 note: value.note
                     .ok_or_mapping_error(MappingError::missing("note"))?
                     .try_into()?,
+                base_version: value.base_version.map(NoteVersion::from),
+                owner: value.owner
+                    .as_deref()
+                    .map(UsernameString::from_str)
+                    .transpose()?,
             }
         )
     }
@@ -28,15 +41,32 @@ impl TryFrom<bindings::WriteNoteRequest> for WriteNoteRequest {
 
 impl TryFrom<bindings::response::Response> for WriteNoteResponse {
     type Error = ProtobufRequestError;
-    fn try_from(value: bindings::response::Response) -> Result<Self, Self::Error> {
+    fn try_from(value: bindings::response::Response) -> Result<Self, ProtobufRequestError> {
         let value = match value {
             bindings::response::Response::WriteNote(value) => value,
             _ => return Err(MappingError::UnexpectedEnumVariant.into()),
         };
+        let version = || value.version
+            .map(NoteVersion::from)
+            .ok_or_mapping_error(MappingError::missing("version"));
         Ok(
-            WriteNoteResponse(
-                value.error.map(|e| e.try_into()).transpose()?,
-            )
+            match value.error.map(StorageError::try_from).transpose()? {
+                None => WriteNoteResponse::Written(
+                    value.metadata
+                        .ok_or_mapping_error(MappingError::missing("metadata"))?
+                        .try_into()?,
+                    version()?,
+                ),
+                Some(StorageError::Conflict) => WriteNoteResponse::Conflict(
+                    VersionedNote {
+                        note: value.current_note
+                            .ok_or_mapping_error(MappingError::missing("current_note"))?
+                            .try_into()?,
+                        version: version()?,
+                    }
+                ),
+                Some(e) => WriteNoteResponse::Error(e),
+            }
         )
     }
 }
@@ -46,6 +76,8 @@ impl From<WriteNoteRequest> for bindings::WriteNoteRequest {
         bindings::WriteNoteRequest {
             access_token: value.access_token,
             note: Some(value.note.into()),
+            base_version: value.base_version.map(NoteVersion::into),
+            owner: value.owner.map(UsernameString::into_string),
         }
     }
 }
@@ -53,8 +85,25 @@ impl From<WriteNoteRequest> for bindings::WriteNoteRequest {
 impl From<WriteNoteResponse> for bindings::response::Response {
     fn from(value: WriteNoteResponse) -> Self {
         bindings::response::Response::WriteNote(
-            bindings::WriteNoteResponse {
-                error: value.0.map(StorageError::into),
+            match value {
+                WriteNoteResponse::Written(metadata, version) => bindings::WriteNoteResponse {
+                    error: None,
+                    current_note: None,
+                    metadata: Some(metadata.into()),
+                    version: Some(version.into()),
+                },
+                WriteNoteResponse::Conflict(VersionedNote { note, version }) => bindings::WriteNoteResponse {
+                    error: Some(StorageError::Conflict.into()),
+                    current_note: Some(note.into()),
+                    metadata: None,
+                    version: Some(version.into()),
+                },
+                WriteNoteResponse::Error(e) => bindings::WriteNoteResponse {
+                    error: Some(e.into()),
+                    current_note: None,
+                    metadata: None,
+                    version: None,
+                },
             }
         )
     }
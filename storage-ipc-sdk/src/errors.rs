@@ -1,5 +1,6 @@
+use data::VersionedNote;
 use dumbnotes::ipc::caller::CallerError;
-use protobuf_common::ProtobufRequestError;
+use protobuf_common::{MappingError, ProtobufRequestError};
 use storage_ipc_data::bindings::StorageError;
 use thiserror::Error;
 
@@ -11,6 +12,18 @@ pub enum StorageAccessorError {
     #[error("note not found")]
     NotFound,
 
+    #[error("note was modified since the base version")]
+    Conflict(Box<VersionedNote>),
+
+    #[error("note already exists")]
+    AlreadyExists,
+
+    #[error("storage quota exceeded")]
+    QuotaExceeded,
+
+    #[error("not permitted by the note's shares")]
+    Forbidden,
+
     #[error("calling the storage daemon failed: {0}")]
     Caller(#[from] CallerError),
 
@@ -31,6 +44,13 @@ impl From<StorageError> for StorageAccessorError {
             StorageError::TooBig => StorageAccessorError::TooBig,
             StorageError::NotFound => StorageAccessorError::NotFound,
             StorageError::InvalidCredentials => StorageAccessorError::InvalidCredentials,
+            StorageError::AlreadyExists => StorageAccessorError::AlreadyExists,
+            StorageError::QuotaExceeded => StorageAccessorError::QuotaExceeded,
+            StorageError::Forbidden => StorageAccessorError::Forbidden,
+            // a conflict is only valid with the current note attached
+            StorageError::Conflict => StorageAccessorError::ProtobufError(
+                MappingError::UnexpectedEnumVariant.into()
+            ),
         }
     }
 }
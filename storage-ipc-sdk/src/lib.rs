@@ -2,11 +2,13 @@ pub mod errors;
 
 use std::marker::PhantomData;
 
-use ::data::{Note, NoteInfo};
+use ::data::{AuditEvent, Note, NoteChanges, NoteEvent, NoteInfo, NoteListPage, NoteListQuery, NoteMetadata, NoteRevisionInfo, NoteSearchQuery, NoteSearchResult, NoteShare, NoteVersion, SharedNoteInfo, StorageUsage, SyncToken, TagCount, UsernameString, VersionedNote};
 use dumbnotes::{bin_constants::IPC_STORAGE_MESSAGE_MAX_SIZE, gen_proto_ipc_wrappers, ipc::{caller::{Caller, CallerImpl}, data::IpcOutput}};
+use futures::stream::{BoxStream, StreamExt};
 use log::{error, warn};
+use protobuf_common::MappingError;
 use rocket::async_trait;
-use storage_ipc_data::{bindings, model::{delete_note::{DeleteNoteRequest, DeleteNoteResponse}, get_note_details::{GetNoteDetailsRequest, GetNoteDetailsResponse}, list_notes::{ListNotesRequest, ListNotesResponse}, read_note::{ReadNoteRequest, ReadNoteResponse}, write_note::{WriteNoteRequest, WriteNoteResponse}}};
+use storage_ipc_data::{bindings, model::{delete_note::{DeleteNoteRequest, DeleteNoteResponse}, get_note_details::{GetNoteDetailsRequest, GetNoteDetailsResponse}, get_usage::{GetUsageRequest, GetUsageResponse}, grant_note_share::{GrantNoteShareRequest, GrantNoteShareResponse}, list_activity::{ListActivityRequest, ListActivityResponse}, list_note_changes::{ListNoteChangesRequest, ListNoteChangesResponse}, list_note_revisions::{ListNoteRevisionsRequest, ListNoteRevisionsResponse}, list_note_shares::{ListNoteSharesRequest, ListNoteSharesResponse}, list_notes::{ListNotesRequest, ListNotesResponse}, list_shared_notes::{ListSharedNotesRequest, ListSharedNotesResponse}, list_tags::{ListTagsRequest, ListTagsResponse}, list_trash::{ListTrashRequest, ListTrashResponse}, purge_trashed_note::{PurgeTrashedNoteRequest, PurgeTrashedNoteResponse}, read_note::{ReadNoteRequest, ReadNoteResponse}, read_note_revision::{ReadNoteRevisionRequest, ReadNoteRevisionResponse}, restore_note_revision::{RestoreNoteRevisionRequest, RestoreNoteRevisionResponse}, restore_trashed_note::{RestoreTrashedNoteRequest, RestoreTrashedNoteResponse}, revoke_note_share::{RevokeNoteShareRequest, RevokeNoteShareResponse}, search_notes::{SearchNotesRequest, SearchNotesResponse}, subscribe_note_events::{NoteEventResponse, SubscribeNoteEventsRequest}, toggle_note_task::{ToggleNoteTaskRequest, ToggleNoteTaskResponse}, write_note::{WriteNoteRequest, WriteNoteResponse}}};
 use tokio::{net::UnixStream, sync::oneshot};
 use uuid::Uuid;
 
@@ -17,25 +19,139 @@ pub trait StorageAccessor: Send + Sync + 'static {
     async fn get_users_notes(
         &self,
         access_token: String,
-    ) -> Result<Vec<NoteInfo>, StorageAccessorError>;
+        query: NoteListQuery,
+    ) -> Result<NoteListPage<NoteInfo>, StorageAccessorError>;
 
+    /// `owner` is set for the notes shared by another user
     async fn get_note(
         &self,
         access_token: String,
         note_id: Uuid,
-    ) -> Result<Note, StorageAccessorError>;
+        owner: Option<UsernameString>,
+    ) -> Result<VersionedNote, StorageAccessorError>;
 
     async fn write_note(
         &self,
         access_token: String,
         note: Note,
-    ) -> Result<(), StorageAccessorError>;
+        base_version: Option<NoteVersion>,
+        owner: Option<UsernameString>,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageAccessorError>;
 
     async fn delete_note(
         &self,
         access_token: String,
         note_id: Uuid,
+        owner: Option<UsernameString>,
+    ) -> Result<(), StorageAccessorError>;
+
+    async fn get_note_revisions(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+    ) -> Result<Vec<NoteRevisionInfo>, StorageAccessorError>;
+
+    async fn get_note_revision(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+        revision: i64,
+    ) -> Result<Note, StorageAccessorError>;
+
+    async fn restore_note_revision(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+        revision: i64,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageAccessorError>;
+
+    /// Flips the checkbox of a task list item, returns the note as written
+    async fn toggle_note_task(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+        task_index: u32,
+    ) -> Result<VersionedNote, StorageAccessorError>;
+
+    /// Replaces the access granted to the user earlier
+    async fn grant_note_share(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+        share: NoteShare,
+    ) -> Result<(), StorageAccessorError>;
+
+    async fn revoke_note_share(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+        username: UsernameString,
+    ) -> Result<(), StorageAccessorError>;
+
+    async fn list_note_shares(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+    ) -> Result<Vec<NoteShare>, StorageAccessorError>;
+
+    /// The notes of the other users shared with the user
+    async fn list_shared_notes(
+        &self,
+        access_token: String,
+    ) -> Result<Vec<SharedNoteInfo>, StorageAccessorError>;
+
+    async fn get_trashed_notes(
+        &self,
+        access_token: String,
+    ) -> Result<Vec<NoteInfo>, StorageAccessorError>;
+
+    async fn restore_trashed_note(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageAccessorError>;
+
+    async fn purge_trashed_note(
+        &self,
+        access_token: String,
+        note_id: Uuid,
     ) -> Result<(), StorageAccessorError>;
+
+    async fn search_notes(
+        &self,
+        access_token: String,
+        query: NoteSearchQuery,
+    ) -> Result<NoteListPage<NoteSearchResult>, StorageAccessorError>;
+
+    async fn get_note_changes(
+        &self,
+        access_token: String,
+        since: Option<SyncToken>,
+        limit: Option<u32>,
+    ) -> Result<NoteChanges, StorageAccessorError>;
+
+    /// Streams the user's note events until the access token expires
+    async fn subscribe_note_events(
+        &self,
+        access_token: String,
+    ) -> Result<BoxStream<'static, Result<NoteEvent, StorageAccessorError>>, StorageAccessorError>;
+
+    async fn list_tags(
+        &self,
+        access_token: String,
+    ) -> Result<Vec<TagCount>, StorageAccessorError>;
+
+    async fn get_usage(
+        &self,
+        access_token: String,
+    ) -> Result<StorageUsage, StorageAccessorError>;
+
+    /// The user's note events from the audit log, the newest first
+    async fn list_activity(
+        &self,
+        access_token: String,
+        limit: u32,
+    ) -> Result<Vec<AuditEvent>, StorageAccessorError>;
 }
 
 pub struct StorageAccessorImpl<
@@ -95,21 +211,23 @@ impl<
     async fn get_users_notes(
         &self,
         access_token: String,
-    ) -> Result<Vec<NoteInfo>, StorageAccessorError> {
+        query: NoteListQuery,
+    ) -> Result<NoteListPage<NoteInfo>, StorageAccessorError> {
         let response: ListNotesResponse = self.caller
             .execute(
                 Command(
                     bindings::command::Command::ListNotes(
                         ListNotesRequest {
                             access_token: access_token.clone(),
+                            query,
                         }.into()
                     )
                 )
             )
             .await?
             .try_into()?;
-        let notes_metadata = match response {
-            ListNotesResponse::Notes(notes_metadata) => notes_metadata,
+        let (notes_metadata, next) = match response {
+            ListNotesResponse::Notes(page) => (page.notes, page.next),
             ListNotesResponse::Error(e) => return Err(e.into()),
         };
         let response: GetNoteDetailsResponse = self.caller
@@ -127,15 +245,18 @@ impl<
             .try_into()?;
         match response {
             GetNoteDetailsResponse::Notes(notes_info) => Ok(
-                notes_info
-                    .into_iter()
-                    .filter_map(|maybe_info| {
-                        if maybe_info.is_none() {
-                            warn!("no info could be read for a note");
-                        }
-                        maybe_info
-                    })
-                    .collect()
+                NoteListPage {
+                    notes: notes_info
+                        .into_iter()
+                        .filter_map(|maybe_info| {
+                            if maybe_info.is_none() {
+                                warn!("no info could be read for a note");
+                            }
+                            maybe_info
+                        })
+                        .collect(),
+                    next,
+                }
             ),
             GetNoteDetailsResponse::Error(e) => {
                 error!("error fetching note info: {e:?}");
@@ -148,7 +269,8 @@ impl<
         &self,
         access_token: String,
         note_id: Uuid,
-    ) -> Result<Note, StorageAccessorError> {
+        owner: Option<UsernameString>,
+    ) -> Result<VersionedNote, StorageAccessorError> {
         let response: ReadNoteResponse = self.caller
             .execute(
                 Command(
@@ -156,6 +278,7 @@ impl<
                         ReadNoteRequest {
                             access_token,
                             note_id,
+                            owner,
                         }.into()
                     )
                 )
@@ -169,7 +292,9 @@ impl<
         &self,
         access_token: String,
         note: Note,
-    ) -> Result<(), StorageAccessorError> {
+        base_version: Option<NoteVersion>,
+        owner: Option<UsernameString>,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageAccessorError> {
         let response: WriteNoteResponse = self.caller
             .execute(
                 Command(
@@ -177,15 +302,19 @@ impl<
                         WriteNoteRequest {
                             access_token,
                             note,
+                            base_version,
+                            owner,
                         }.into()
                     )
                 )
             )
             .await?
             .try_into()?;
-        match response.0 {
-            Some(e) => Err(e.into()),
-            None => Ok(()),
+        match response {
+            WriteNoteResponse::Written(metadata, version) => Ok((metadata, version)),
+            WriteNoteResponse::Conflict(current) =>
+                Err(StorageAccessorError::Conflict(Box::new(current))),
+            WriteNoteResponse::Error(e) => Err(e.into()),
         }
     }
 
@@ -193,6 +322,7 @@ impl<
         &self,
         access_token: String,
         note_id: Uuid,
+        owner: Option<UsernameString>,
     ) -> Result<(), StorageAccessorError> {
         let response: DeleteNoteResponse = self.caller
             .execute(
@@ -201,6 +331,253 @@ impl<
                         DeleteNoteRequest {
                             access_token,
                             note_id,
+                            owner,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        match response.0 {
+            None => Ok(()),
+            Some(e) => Err(e.into()),
+        }
+    }
+
+    async fn get_note_revisions(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+    ) -> Result<Vec<NoteRevisionInfo>, StorageAccessorError> {
+        let response: ListNoteRevisionsResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ListNoteRevisions(
+                        ListNoteRevisionsRequest {
+                            access_token,
+                            note_id,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn get_note_revision(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+        revision: i64,
+    ) -> Result<Note, StorageAccessorError> {
+        let response: ReadNoteRevisionResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ReadNoteRevision(
+                        ReadNoteRevisionRequest {
+                            access_token,
+                            note_id,
+                            revision,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn restore_note_revision(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+        revision: i64,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageAccessorError> {
+        let response: RestoreNoteRevisionResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::RestoreNoteRevision(
+                        RestoreNoteRevisionRequest {
+                            access_token,
+                            note_id,
+                            revision,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn toggle_note_task(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+        task_index: u32,
+    ) -> Result<VersionedNote, StorageAccessorError> {
+        let response: ToggleNoteTaskResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ToggleNoteTask(
+                        ToggleNoteTaskRequest {
+                            access_token,
+                            note_id,
+                            task_index,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn grant_note_share(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+        share: NoteShare,
+    ) -> Result<(), StorageAccessorError> {
+        let response: GrantNoteShareResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::GrantNoteShare(
+                        GrantNoteShareRequest {
+                            access_token,
+                            note_id,
+                            share,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        match response.0 {
+            None => Ok(()),
+            Some(e) => Err(e.into()),
+        }
+    }
+
+    async fn revoke_note_share(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+        username: UsernameString,
+    ) -> Result<(), StorageAccessorError> {
+        let response: RevokeNoteShareResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::RevokeNoteShare(
+                        RevokeNoteShareRequest {
+                            access_token,
+                            note_id,
+                            username,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        match response.0 {
+            None => Ok(()),
+            Some(e) => Err(e.into()),
+        }
+    }
+
+    async fn list_note_shares(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+    ) -> Result<Vec<NoteShare>, StorageAccessorError> {
+        let response: ListNoteSharesResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ListNoteShares(
+                        ListNoteSharesRequest {
+                            access_token,
+                            note_id,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn list_shared_notes(
+        &self,
+        access_token: String,
+    ) -> Result<Vec<SharedNoteInfo>, StorageAccessorError> {
+        let response: ListSharedNotesResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ListSharedNotes(
+                        ListSharedNotesRequest {
+                            access_token,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn get_trashed_notes(
+        &self,
+        access_token: String,
+    ) -> Result<Vec<NoteInfo>, StorageAccessorError> {
+        let response: ListTrashResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ListTrash(
+                        ListTrashRequest {
+                            access_token,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn restore_trashed_note(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+    ) -> Result<(NoteMetadata, NoteVersion), StorageAccessorError> {
+        let response: RestoreTrashedNoteResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::RestoreTrashedNote(
+                        RestoreTrashedNoteRequest {
+                            access_token,
+                            note_id,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn purge_trashed_note(
+        &self,
+        access_token: String,
+        note_id: Uuid,
+    ) -> Result<(), StorageAccessorError> {
+        let response: PurgeTrashedNoteResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::PurgeTrashedNote(
+                        PurgeTrashedNoteRequest {
+                            access_token,
+                            note_id,
                         }.into()
                     )
                 )
@@ -212,4 +589,145 @@ impl<
             Some(e) => Err(e.into()),
         }
     }
+
+    async fn search_notes(
+        &self,
+        access_token: String,
+        query: NoteSearchQuery,
+    ) -> Result<NoteListPage<NoteSearchResult>, StorageAccessorError> {
+        let response: SearchNotesResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::SearchNotes(
+                        SearchNotesRequest {
+                            access_token,
+                            query,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn get_note_changes(
+        &self,
+        access_token: String,
+        since: Option<SyncToken>,
+        limit: Option<u32>,
+    ) -> Result<NoteChanges, StorageAccessorError> {
+        let response: ListNoteChangesResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ListNoteChanges(
+                        ListNoteChangesRequest {
+                            access_token,
+                            since,
+                            limit,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn subscribe_note_events(
+        &self,
+        access_token: String,
+    ) -> Result<BoxStream<'static, Result<NoteEvent, StorageAccessorError>>, StorageAccessorError> {
+        let mut responses = self.caller
+            .subscribe(
+                Command(
+                    bindings::command::Command::SubscribeNoteEvents(
+                        SubscribeNoteEventsRequest {
+                            access_token,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .map(|response| -> Result<NoteEventResponse, StorageAccessorError> {
+                Ok(response?.try_into()?)
+            });
+        match responses.next().await {
+            Some(Ok(NoteEventResponse::Subscribed)) => {},
+            Some(Ok(NoteEventResponse::Error(e))) => return Err(e.into()),
+            Some(Ok(NoteEventResponse::Event(_))) =>
+                return Err(StorageAccessorError::ProtobufError(MappingError::UnexpectedEnumVariant.into())),
+            Some(Err(e)) => return Err(e),
+            None => return Err(StorageAccessorError::StorageDaemonInternalError),
+        }
+        Ok(
+            responses
+                .map(|response| match response? {
+                    NoteEventResponse::Event(event) => Ok(event),
+                    NoteEventResponse::Error(e) => Err(e.into()),
+                    NoteEventResponse::Subscribed =>
+                        Err(StorageAccessorError::ProtobufError(MappingError::UnexpectedEnumVariant.into())),
+                })
+                .boxed()
+        )
+    }
+
+    async fn list_tags(
+        &self,
+        access_token: String,
+    ) -> Result<Vec<TagCount>, StorageAccessorError> {
+        let response: ListTagsResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ListTags(
+                        ListTagsRequest {
+                            access_token,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn get_usage(
+        &self,
+        access_token: String,
+    ) -> Result<StorageUsage, StorageAccessorError> {
+        let response: GetUsageResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::GetUsage(
+                        GetUsageRequest {
+                            access_token,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
+
+    async fn list_activity(
+        &self,
+        access_token: String,
+        limit: u32,
+    ) -> Result<Vec<AuditEvent>, StorageAccessorError> {
+        let response: ListActivityResponse = self.caller
+            .execute(
+                Command(
+                    bindings::command::Command::ListActivity(
+                        ListActivityRequest {
+                            access_token,
+                            limit,
+                        }.into()
+                    )
+                )
+            )
+            .await?
+            .try_into()?;
+        Ok(response.0?)
+    }
 }
@@ -12,10 +12,10 @@ mod faketime;
 
 pub use build_bin::{build_bin, make_path_for_bins, new_configured_command, new_configured_command_with_env};
 pub use build_bin::{AUTHD_BIN_PATH, STORAGED_BIN_PATH, DAEMON_BIN_PATH, DAEMON_BIN_PATHS, GEN_BIN_PATH};
-pub use mock_hierarchy::{setup_basic_config, setup_basic_config_with_keys, setup_basic_config_with_keys_and_data};
+pub use mock_hierarchy::{enable_web, setup_basic_config, setup_basic_config_with_keys, setup_basic_config_with_keys_and_data};
 pub use pty_session::PtySessionExt;
 pub use background_reader::{BackgroundReader, BackgroundReaderError};
 pub use kill_on_drop::{KillOnDropChild, ChildKillOnDropExt};
 pub use reqwest::*;
-pub use ports::LOCAL_PORT;
+pub use ports::{LOCAL_PORT, LOCAL_WEB_PORT};
 pub use faketime::Faketime;
@@ -1,15 +1,15 @@
+use std::fs::OpenOptions;
+use std::io::Write;
 use std::path::Path;
 use assert_fs::prelude::*;
 use assert_fs::TempDir;
 use tap::Tap;
 use unix::chmod;
-use crate::LOCAL_PORT;
+use crate::{LOCAL_PORT, LOCAL_WEB_PORT};
 use crate::data::MOCK_USER_DB_DATA;
 use crate::data::MOCK_USER_DB_STR;
 use crate::data::{MOCK_JWT_PRIVATE_KEY_STR, MOCK_JWT_PUBLIC_KEY_STR, MOCK_PEPPER_STR};
 
-// FIXME: launch the web part too
-
 pub fn setup_basic_config() -> TempDir {
     setup_basic_config_impl(None::<&str>, None::<&str>)
         .tap(|dir|
@@ -30,6 +30,8 @@ pub fn setup_basic_config_impl(
     data_dir.create_dir_all().unwrap();
     let rw_secrets_dir = data_dir.child("private");
     rw_secrets_dir.create_dir_all().unwrap();
+    let audit_log_dir = root.child("var/log/dumbnotes");
+    audit_log_dir.create_dir_all().unwrap();
 
     let data_path_extra = match data_path {
         Some(path) => format!(
@@ -51,6 +53,7 @@ pub fn setup_basic_config_impl(
 jwt_public_key = "{}"
 pepper_path = "{}"
 api_rocket_config = "{}"
+audit_log_directory = "{}"
 api_enabled = true
 {}{}
 "#,
@@ -58,6 +61,7 @@ api_enabled = true
         config_dir.child("jwt_public_key.json").to_str().unwrap(),
         ro_secrets_dir.child("pepper.b64").to_str().unwrap(),
         api_rocket_path.to_str().unwrap(),
+        audit_log_dir.to_str().unwrap(),
         data_path_extra,
         user_db_path_extra,
     );
@@ -119,3 +123,28 @@ pub fn setup_basic_config_with_keys_and_data() -> TempDir {
     chmod(session_db.path(), 0o600).unwrap();
     root
 }
+
+/// Launches the web daemon next to the API one
+pub fn enable_web(root: &TempDir) {
+    let config_dir = root.child("etc/dumbnotes");
+    let web_rocket_path = config_dir.child("dumbnotes.web.rocket.toml");
+    let mut config = OpenOptions::new()
+        .append(true)
+        .open(config_dir.child("dumbnotes.toml"))
+        .unwrap();
+    write!(
+        config,
+        r#"web_rocket_config = "{}"
+web_enabled = true
+"#,
+        web_rocket_path.to_str().unwrap(),
+    ).unwrap();
+    let web_rocket_config = format!(
+        r#"port = {}
+temp_dir = "{}"
+"#,
+        LOCAL_WEB_PORT.with(Clone::clone),
+        root.child("tmp").to_str().unwrap(),
+    );
+    web_rocket_path.write_str(&web_rocket_config).unwrap();
+}
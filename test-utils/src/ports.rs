@@ -1,7 +1,12 @@
 use std::sync::atomic::{AtomicU16, Ordering};
 
 static NEXT_PORT: AtomicU16 = AtomicU16::new(8000);
+static NEXT_WEB_PORT: AtomicU16 = AtomicU16::new(9000);
 
 thread_local! {
     pub static LOCAL_PORT: u16 = NEXT_PORT.fetch_add(1, Ordering::Relaxed);
 }
+
+thread_local! {
+    pub static LOCAL_WEB_PORT: u16 = NEXT_WEB_PORT.fetch_add(1, Ordering::Relaxed);
+}